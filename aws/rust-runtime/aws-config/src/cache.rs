@@ -74,6 +74,13 @@ where
         future.await.map(|(value, _expiry)| value.clone())
     }
 
+    /// Replaces the cached value, e.g. after it was refreshed ahead of its expiration.
+    pub async fn set(&self, value: T, expiry: SystemTime) {
+        let cell = OnceCell::new();
+        let _ = cell.set((value, expiry));
+        *self.value.write().await = cell;
+    }
+
     /// If the value is expired, clears the cache. Otherwise, yields the current value.
     pub async fn yield_or_clear_if_expired(&self, now: SystemTime) -> Option<T> {
         // Short-circuit if the value is not expired
@@ -0,0 +1,529 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Credentials Provider for external process
+//!
+//! The credential process provider runs an external command and parses credentials from its
+//! standard output. It is most commonly configured through a profile in `~/.aws/config`:
+//! ```ini
+//! [profile default]
+//! credential_process = /usr/bin/my-credential-helper --role admin
+//! ```
+//!
+//! The command is run through the platform shell (`sh -c` on Unix, `cmd.exe /C` on Windows) and
+//! MUST write a JSON document to standard output:
+//! ```json
+//! {
+//!   "Version": 1,
+//!   "AccessKeyId": "AKIA...",
+//!   "SecretAccessKey": "...",
+//!   "SessionToken": "...",
+//!   "Expiration": "2021-09-18T03:31:56Z"
+//! }
+//! ```
+//! `SessionToken` and `Expiration` are optional. When `Expiration` is omitted, the credentials are
+//! treated as long-lived.
+//!
+//! # Security
+//! The command and its arguments will be executed as given. Only the program name is ever written
+//! to logs or `Debug` output because arguments frequently contain secrets.
+//!
+//! # Timeouts
+//! The process is killed if it doesn't exit within the [timeout](Builder::timeout). Enforcing the
+//! timeout requires a sleep implementation: if the [`ProviderConfig`] doesn't have one, loading
+//! credentials fails without running the command.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aws_smithy_async::future::timeout::Timeout;
+use aws_smithy_async::rt::sleep::AsyncSleep;
+use aws_smithy_json::deserialize::token::skip_value;
+use aws_smithy_json::deserialize::{json_token_iter, Token};
+use aws_smithy_types::Number;
+use aws_types::credentials::{self, future, CredentialsError, ProvideCredentials};
+use aws_types::os_shim_internal::Process;
+use aws_types::Credentials;
+use tracing::Instrument;
+
+use crate::json_credentials::{parse_expiration, InvalidJsonCredentials};
+use crate::provider_config::ProviderConfig;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const PROVIDER_NAME: &str = "CredentialProcess";
+
+/// External process credentials provider
+///
+/// See the [module](crate::credential_process) documentation for more details.
+///
+/// # Examples
+/// ```rust,no_run
+/// use aws_config::credential_process::CredentialProcessProvider;
+/// use std::time::Duration;
+/// let provider = CredentialProcessProvider::builder()
+///     .command("/usr/bin/my-credential-helper --role admin")
+///     .timeout(Duration::from_secs(10))
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct CredentialProcessProvider {
+    command: CommandWithSensitiveArgs<String>,
+    timeout: Duration,
+    sleep: Option<Arc<dyn AsyncSleep>>,
+    process: Process,
+}
+
+impl CredentialProcessProvider {
+    /// Builder for [`CredentialProcessProvider`]
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Create a [`CredentialProcessProvider`] for `command` using the default configuration
+    pub fn new(command: impl Into<String>) -> Self {
+        Self::builder().command(command).build()
+    }
+
+    async fn credentials(&self) -> credentials::Result {
+        let sleep = self.sleep.as_ref().ok_or_else(|| {
+            CredentialsError::invalid_configuration(
+                "a sleep implementation is required to enforce the credential process timeout",
+            )
+        })?;
+        tracing::debug!(command = %self.command, "loading credentials from external process");
+        let output = Timeout::new(
+            self.process.run(self.command.unredacted()),
+            sleep.sleep(self.timeout),
+        )
+        .await
+        .map_err(|_| {
+            CredentialsError::provider_error(format!(
+                "credential process `{}` did not complete within {:?}",
+                self.command, self.timeout
+            ))
+        })?
+        .map_err(|err| {
+            CredentialsError::provider_error(format!(
+                "failed to spawn credential process `{}`: {}",
+                self.command, err
+            ))
+        })?;
+
+        if !output.success() {
+            tracing::warn!(
+                command = %self.command,
+                stderr = %String::from_utf8_lossy(output.stderr()),
+                "credential process exited unsuccessfully"
+            );
+            let status = match output.status() {
+                Some(code) => format!("exit code {}", code),
+                None => "terminated by signal".to_string(),
+            };
+            return Err(CredentialsError::provider_error(format!(
+                "credential process `{}` exited unsuccessfully ({})",
+                self.command, status
+            )));
+        }
+        let output = std::str::from_utf8(output.stdout()).map_err(|_| {
+            CredentialsError::unhandled("credential process output was not valid UTF-8")
+        })?;
+        parse_credential_process_json_credentials(output).map_err(|err| {
+            CredentialsError::provider_error(format!(
+                "invalid output from credential process `{}`: {}",
+                self.command, err
+            ))
+        })
+    }
+}
+
+impl ProvideCredentials for CredentialProcessProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(self.credentials().instrument(tracing::info_span!(
+            "load_credentials",
+            provider = PROVIDER_NAME
+        )))
+    }
+}
+
+/// Builder for [`CredentialProcessProvider`]
+#[derive(Debug, Default)]
+pub struct Builder {
+    command: Option<String>,
+    timeout: Option<Duration>,
+    provider_config: Option<ProviderConfig>,
+}
+
+impl Builder {
+    /// Override the configuration used for this provider
+    ///
+    /// The sleep implementation of the provider config is used to enforce the timeout. Without
+    /// one, loading credentials fails.
+    pub fn configure(mut self, provider_config: &ProviderConfig) -> Self {
+        self.provider_config = Some(provider_config.clone());
+        self
+    }
+
+    /// The command to run to load credentials
+    ///
+    /// The command is passed to the platform shell unmodified.
+    pub fn command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// Override the maximum amount of time the process may run for
+    ///
+    /// If the process has not exited by the time the timeout elapses, it will be killed and an
+    /// error returned. Defaults to 60 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Create a [`CredentialProcessProvider`] from this builder.
+    ///
+    /// ## Panics
+    /// This method will panic if no command was set.
+    pub fn build(self) -> CredentialProcessProvider {
+        let provider_config = self.provider_config.unwrap_or_default();
+        CredentialProcessProvider {
+            command: CommandWithSensitiveArgs::new(
+                self.command
+                    .expect("a command must be set to build a CredentialProcessProvider"),
+            ),
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            sleep: provider_config.sleep(),
+            process: provider_config.process(),
+        }
+    }
+}
+
+/// Command string whose arguments are redacted from `Debug` and `Display` output
+#[derive(Clone)]
+pub(crate) struct CommandWithSensitiveArgs<T>(T);
+
+impl<T> CommandWithSensitiveArgs<T>
+where
+    T: AsRef<str>,
+{
+    pub(crate) fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub(crate) fn unredacted(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+impl<T> fmt::Display for CommandWithSensitiveArgs<T>
+where
+    T: AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = self.0.as_ref().trim_start();
+        match command.split_once(char::is_whitespace) {
+            Some((program, _args)) => write!(f, "{} ** arguments redacted **", program),
+            None => write!(f, "{}", command),
+        }
+    }
+}
+
+impl<T> fmt::Debug for CommandWithSensitiveArgs<T>
+where
+    T: AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", format!("{}", self))
+    }
+}
+
+/// Deserialize the output of a credential process
+///
+/// Only version `1` of the output format is supported. Keys are case insensitive.
+pub(crate) fn parse_credential_process_json_credentials(
+    credentials_response: &str,
+) -> Result<Credentials, InvalidJsonCredentials> {
+    let mut version = None;
+    let mut access_key_id = None;
+    let mut secret_access_key = None;
+    let mut session_token = None;
+    let mut expiration = None;
+    let mut tokens = json_token_iter(credentials_response.as_bytes()).peekable();
+    if !matches!(tokens.next().transpose()?, Some(Token::StartObject { .. })) {
+        return Err(InvalidJsonCredentials::JsonError(
+            "expected a JSON document starting with `{`".into(),
+        ));
+    }
+    loop {
+        match tokens.next().transpose()? {
+            Some(Token::EndObject { .. }) => break,
+            Some(Token::ObjectKey { key, .. }) => {
+                let key = key.to_unescaped()?;
+                match tokens.peek() {
+                    Some(Ok(Token::ValueNumber { value, .. }))
+                        if key.eq_ignore_ascii_case("Version") =>
+                    {
+                        version = Some(*value)
+                    }
+                    Some(Ok(Token::ValueString { value, .. })) => match key {
+                        c if c.eq_ignore_ascii_case("AccessKeyId") => {
+                            access_key_id = Some(value.to_unescaped()?)
+                        }
+                        c if c.eq_ignore_ascii_case("SecretAccessKey") => {
+                            secret_access_key = Some(value.to_unescaped()?)
+                        }
+                        c if c.eq_ignore_ascii_case("SessionToken") => {
+                            session_token = Some(value.to_unescaped()?)
+                        }
+                        c if c.eq_ignore_ascii_case("Expiration") => {
+                            expiration = Some(value.to_unescaped()?)
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+                skip_value(&mut tokens)?;
+            }
+            other => {
+                return Err(InvalidJsonCredentials::Other(
+                    format!("expected object key, found: {:?}", other).into(),
+                ));
+            }
+        }
+    }
+    if tokens.next().is_some() {
+        return Err(InvalidJsonCredentials::Other(
+            "found more JSON tokens after completing parsing".into(),
+        ));
+    }
+
+    match version {
+        Some(Number::PosInt(1)) => {}
+        Some(other) => {
+            return Err(InvalidJsonCredentials::Other(
+                format!(
+                    "unsupported version `{}`, only version 1 is supported",
+                    other.to_f64()
+                )
+                .into(),
+            ))
+        }
+        None => return Err(InvalidJsonCredentials::MissingField("Version")),
+    }
+    let access_key_id = access_key_id.ok_or(InvalidJsonCredentials::MissingField("AccessKeyId"))?;
+    let secret_access_key =
+        secret_access_key.ok_or(InvalidJsonCredentials::MissingField("SecretAccessKey"))?;
+    let expiration = expiration.as_deref().map(parse_expiration).transpose()?;
+    Ok(Credentials::new(
+        access_key_id,
+        secret_access_key,
+        session_token.map(Cow::into_owned),
+        expiration,
+        PROVIDER_NAME,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::credential_process::{
+        parse_credential_process_json_credentials, CommandWithSensitiveArgs,
+        CredentialProcessProvider,
+    };
+    use crate::json_credentials::InvalidJsonCredentials;
+    use crate::provider_config::ProviderConfig;
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_types::credentials::{CredentialsError, ProvideCredentials};
+    use aws_types::os_shim_internal::{Process, ProcessOutput};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn parse_full_response() {
+        let creds = parse_credential_process_json_credentials(
+            r#"{
+                "Version": 1,
+                "AccessKeyId": "ASIARTESTID",
+                "SecretAccessKey": "TESTSECRETKEY",
+                "SessionToken": "TESTSESSIONTOKEN",
+                "Expiration": "2021-09-18T03:31:56Z"
+            }"#,
+        )
+        .expect("valid response");
+        assert_eq!(creds.access_key_id(), "ASIARTESTID");
+        assert_eq!(creds.secret_access_key(), "TESTSECRETKEY");
+        assert_eq!(creds.session_token(), Some("TESTSESSIONTOKEN"));
+        assert_eq!(
+            creds.expiry(),
+            Some(UNIX_EPOCH + Duration::from_secs(1631935916))
+        );
+    }
+
+    #[test]
+    fn parse_long_lived_credentials() {
+        let creds = parse_credential_process_json_credentials(
+            r#"{ "version": 1, "accessKeyId": "AKIDTEST", "secretAccessKey": "SECRET" }"#,
+        )
+        .expect("session token & expiration are optional");
+        assert_eq!(creds.access_key_id(), "AKIDTEST");
+        assert_eq!(creds.session_token(), None);
+        assert_eq!(creds.expiry(), None);
+    }
+
+    #[test]
+    fn version_is_required() {
+        let err = parse_credential_process_json_credentials(
+            r#"{ "AccessKeyId": "AKIDTEST", "SecretAccessKey": "SECRET" }"#,
+        )
+        .expect_err("no version");
+        assert!(matches!(
+            err,
+            InvalidJsonCredentials::MissingField("Version")
+        ));
+
+        let err = parse_credential_process_json_credentials(
+            r#"{ "Version": 2, "AccessKeyId": "AKIDTEST", "SecretAccessKey": "SECRET" }"#,
+        )
+        .expect_err("unsupported version");
+        assert!(
+            format!("{}", err).contains("unsupported version `2`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn invalid_expiration() {
+        let err = parse_credential_process_json_credentials(
+            r#"{ "Version": 1, "AccessKeyId": "AKID", "SecretAccessKey": "SECRET", "Expiration": "tomorrow" }"#,
+        )
+        .expect_err("invalid date");
+        assert!(format!("{}", err).contains("invalid date"), "{}", err);
+    }
+
+    #[test]
+    fn arguments_are_redacted() {
+        let command = CommandWithSensitiveArgs::new("/usr/bin/helper --secret hunter2");
+        assert_eq!(
+            format!("{}", command),
+            "/usr/bin/helper ** arguments redacted **"
+        );
+        assert!(!format!("{:?}", command).contains("hunter2"));
+        assert_eq!(
+            format!("{}", CommandWithSensitiveArgs::new("helper")),
+            "helper"
+        );
+    }
+
+    fn provider(command: &str, process: Process) -> CredentialProcessProvider {
+        CredentialProcessProvider::builder()
+            .command(command)
+            .configure(
+                &ProviderConfig::empty()
+                    .with_sleep(TokioSleep::new())
+                    .with_process(process),
+            )
+            .build()
+    }
+
+    #[tokio::test]
+    async fn load_credentials_from_process() {
+        let provider = provider(
+            "/usr/bin/helper --role admin",
+            Process::from_slice(&[(
+                "/usr/bin/helper --role admin",
+                ProcessOutput::new(
+                    0,
+                    r#"{ "Version": 1, "AccessKeyId": "ASIARTESTID", "SecretAccessKey": "TESTSECRETKEY", "SessionToken": "TESTSESSIONTOKEN", "Expiration": "2022-05-02T18:36:00Z" }"#,
+                    "",
+                ),
+            )]),
+        );
+        let creds = provider
+            .provide_credentials()
+            .await
+            .expect("valid credentials");
+        assert_eq!(creds.access_key_id(), "ASIARTESTID");
+        assert_eq!(creds.session_token(), Some("TESTSESSIONTOKEN"));
+        assert_eq!(
+            creds.expiry(),
+            Some(UNIX_EPOCH + Duration::from_secs(1651516560))
+        );
+    }
+
+    #[tokio::test]
+    async fn process_failure() {
+        let provider = provider(
+            "/usr/bin/helper",
+            Process::from_slice(&[("/usr/bin/helper", ProcessOutput::new(1, "", "failed"))]),
+        );
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("process failed");
+        assert!(
+            format!("{}", err).contains("exited unsuccessfully (exit code 1)"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn process_not_found() {
+        let provider = provider("/usr/bin/helper", Process::from_slice(&[]));
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("process doesn't exist");
+        assert!(
+            format!("{}", err).contains("failed to spawn credential process"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn process_timeout() {
+        tokio::time::pause();
+        let provider = CredentialProcessProvider::builder()
+            .command("/usr/bin/helper")
+            .timeout(Duration::from_millis(100))
+            .configure(
+                &ProviderConfig::empty()
+                    .with_sleep(TokioSleep::new())
+                    .with_process(Process::never_exits()),
+            )
+            .build();
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("process should time out");
+        assert!(
+            format!("{}", err).contains("did not complete within"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn sleep_impl_is_required() {
+        let provider = CredentialProcessProvider::builder()
+            .command("/usr/bin/helper")
+            .configure(&ProviderConfig::empty().with_process(Process::never_exits()))
+            .build();
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("no sleep implementation");
+        assert!(
+            matches!(err, CredentialsError::InvalidConfiguration { .. }),
+            "{}",
+            err
+        );
+    }
+}
@@ -167,7 +167,7 @@ pub mod retry_config {
         /// # Panics
         ///
         /// - Panics if the `AWS_MAX_ATTEMPTS` env var or `max_attempts` profile var is set to 0
-        /// - Panics if the `AWS_RETRY_MODE` env var or `retry_mode` profile var is set to "adaptive" (it's not yet supported)
+        /// - Panics if the `AWS_RETRY_MODE` env var or `retry_mode` profile var is set to an unknown retry mode
         pub async fn retry_config(self) -> RetryConfig {
             // Both of these can return errors due to invalid config settings and we want to surface those as early as possible
             // hence, we'll panic if any config values are invalid (missing values are OK though)
@@ -284,6 +284,345 @@ pub mod app_name {
     }
 }
 
+/// Default endpoint URL provider chain
+pub mod endpoint_url {
+    use crate::profile;
+    use crate::provider_config::ProviderConfig;
+
+    const ENV_VAR: &str = "AWS_ENDPOINT_URL";
+    const PROFILE_PROPERTY: &str = "endpoint_url";
+
+    /// Default endpoint URL provider chain
+    ///
+    /// This provider will check the following sources in order:
+    /// 1. The `AWS_ENDPOINT_URL` environment variable
+    /// 2. The `endpoint_url` property of the active profile
+    ///
+    /// Service-specific endpoint URLs, e.g. `AWS_ENDPOINT_URL_S3`, are loaded by service clients
+    /// from the [service config](aws_types::config::Config::service_config).
+    pub fn default_provider() -> Builder {
+        Builder::default()
+    }
+
+    /// Default provider builder for the endpoint URL
+    #[derive(Default)]
+    pub struct Builder {
+        config: Option<ProviderConfig>,
+    }
+
+    impl Builder {
+        #[doc(hidden)]
+        /// Configure the default chain
+        ///
+        /// Exposed for overriding the environment when unit-testing providers
+        pub fn configure(mut self, configuration: &ProviderConfig) -> Self {
+            self.config = Some(configuration.clone());
+            self
+        }
+
+        /// Load the endpoint URL from the default chain
+        pub async fn endpoint_url(self) -> Option<String> {
+            let config = self.config.unwrap_or_default();
+            if let Ok(endpoint_url) = config.env().get(ENV_VAR) {
+                return Some(endpoint_url);
+            }
+            match profile::load(&config.fs(), &config.env()).await {
+                Ok(profiles) => profiles.get(PROFILE_PROPERTY).map(|url| url.to_string()),
+                Err(err) => {
+                    tracing::warn!(err = %err, "failed to parse profile");
+                    None
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::provider_config::ProviderConfig;
+        use aws_types::os_shim_internal::{Env, Fs};
+
+        const CONFIG: &str = "[default]\nendpoint_url = http://localhost:9000";
+
+        #[tokio::test]
+        async fn prefer_env_to_profile() {
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(&[
+                    ("AWS_CONFIG_FILE", "test_config"),
+                    ("AWS_ENDPOINT_URL", "http://localhost:4566"),
+                ]))
+                .with_fs(Fs::from_slice(&[("test_config", CONFIG)]));
+            assert_eq!(
+                Some("http://localhost:4566".to_string()),
+                default_provider()
+                    .configure(&provider_config)
+                    .endpoint_url()
+                    .await
+            );
+        }
+
+        #[tokio::test]
+        async fn load_from_profile() {
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(&[("AWS_CONFIG_FILE", "test_config")]))
+                .with_fs(Fs::from_slice(&[("test_config", CONFIG)]));
+            assert_eq!(
+                Some("http://localhost:9000".to_string()),
+                default_provider()
+                    .configure(&provider_config)
+                    .endpoint_url()
+                    .await
+            );
+        }
+
+        #[tokio::test]
+        async fn no_endpoint_url() {
+            let provider_config = ProviderConfig::no_configuration();
+            assert_eq!(
+                None,
+                default_provider()
+                    .configure(&provider_config)
+                    .endpoint_url()
+                    .await
+            );
+        }
+    }
+}
+
+/// Default FIPS endpoint setting provider chain
+pub mod use_fips {
+    use crate::provider_config::ProviderConfig;
+
+    const ENV_VAR: &str = "AWS_USE_FIPS_ENDPOINT";
+    const PROFILE_PROPERTY: &str = "use_fips_endpoint";
+
+    /// Default FIPS endpoint setting provider chain
+    ///
+    /// This provider will check the following sources in order:
+    /// 1. The `AWS_USE_FIPS_ENDPOINT` environment variable
+    /// 2. The `use_fips_endpoint` property of the active profile
+    ///
+    /// Values other than `true` or `false` (case-insensitive) are ignored with a warning.
+    pub fn default_provider() -> Builder {
+        Builder::default()
+    }
+
+    /// Default provider builder for the FIPS endpoint setting
+    #[derive(Default)]
+    pub struct Builder {
+        config: Option<ProviderConfig>,
+    }
+
+    impl Builder {
+        #[doc(hidden)]
+        /// Configure the default chain
+        ///
+        /// Exposed for overriding the environment when unit-testing providers
+        pub fn configure(mut self, configuration: &ProviderConfig) -> Self {
+            self.config = Some(configuration.clone());
+            self
+        }
+
+        /// Load the FIPS endpoint setting from the default chain
+        pub async fn use_fips(self) -> Option<bool> {
+            super::bool_setting::load(&self.config.unwrap_or_default(), ENV_VAR, PROFILE_PROPERTY)
+                .await
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::provider_config::ProviderConfig;
+        use aws_types::os_shim_internal::{Env, Fs};
+
+        async fn use_fips(env: &[(&str, &str)], config: &str) -> Option<bool> {
+            let mut env = env.to_vec();
+            env.push(("AWS_CONFIG_FILE", "test_config"));
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(&env))
+                .with_fs(Fs::from_slice(&[("test_config", config)]));
+            default_provider()
+                .configure(&provider_config)
+                .use_fips()
+                .await
+        }
+
+        #[tokio::test]
+        async fn prefer_env_to_profile() {
+            assert_eq!(
+                Some(false),
+                use_fips(
+                    &[("AWS_USE_FIPS_ENDPOINT", "false")],
+                    "[default]\nuse_fips_endpoint = true"
+                )
+                .await
+            );
+        }
+
+        #[tokio::test]
+        async fn load_from_profile() {
+            assert_eq!(
+                Some(true),
+                use_fips(&[], "[default]\nuse_fips_endpoint = TRUE").await
+            );
+        }
+
+        #[tokio::test]
+        async fn invalid_value_is_ignored() {
+            assert_eq!(
+                Some(true),
+                use_fips(
+                    &[("AWS_USE_FIPS_ENDPOINT", "yes")],
+                    "[default]\nuse_fips_endpoint = true"
+                )
+                .await
+            );
+            assert_eq!(None, use_fips(&[("AWS_USE_FIPS_ENDPOINT", "1")], "").await);
+        }
+
+        #[tokio::test]
+        async fn not_set() {
+            assert_eq!(None, use_fips(&[], "[default]\nregion = us-east-1").await);
+        }
+    }
+}
+
+/// Default dual-stack endpoint setting provider chain
+pub mod use_dual_stack {
+    use crate::provider_config::ProviderConfig;
+
+    const ENV_VAR: &str = "AWS_USE_DUALSTACK_ENDPOINT";
+    const PROFILE_PROPERTY: &str = "use_dualstack_endpoint";
+
+    /// Default dual-stack endpoint setting provider chain
+    ///
+    /// This provider will check the following sources in order:
+    /// 1. The `AWS_USE_DUALSTACK_ENDPOINT` environment variable
+    /// 2. The `use_dualstack_endpoint` property of the active profile
+    ///
+    /// Values other than `true` or `false` (case-insensitive) are ignored with a warning.
+    pub fn default_provider() -> Builder {
+        Builder::default()
+    }
+
+    /// Default provider builder for the dual-stack endpoint setting
+    #[derive(Default)]
+    pub struct Builder {
+        config: Option<ProviderConfig>,
+    }
+
+    impl Builder {
+        #[doc(hidden)]
+        /// Configure the default chain
+        ///
+        /// Exposed for overriding the environment when unit-testing providers
+        pub fn configure(mut self, configuration: &ProviderConfig) -> Self {
+            self.config = Some(configuration.clone());
+            self
+        }
+
+        /// Load the dual-stack endpoint setting from the default chain
+        pub async fn use_dual_stack(self) -> Option<bool> {
+            super::bool_setting::load(&self.config.unwrap_or_default(), ENV_VAR, PROFILE_PROPERTY)
+                .await
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::provider_config::ProviderConfig;
+        use aws_types::os_shim_internal::{Env, Fs};
+
+        #[tokio::test]
+        async fn load_from_env_and_profile() {
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(&[
+                    ("AWS_CONFIG_FILE", "test_config"),
+                    ("AWS_USE_DUALSTACK_ENDPOINT", "true"),
+                ]))
+                .with_fs(Fs::from_slice(&[(
+                    "test_config",
+                    "[default]\nuse_dualstack_endpoint = false",
+                )]));
+            assert_eq!(
+                Some(true),
+                default_provider()
+                    .configure(&provider_config)
+                    .use_dual_stack()
+                    .await
+            );
+
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(&[("AWS_CONFIG_FILE", "test_config")]))
+                .with_fs(Fs::from_slice(&[(
+                    "test_config",
+                    "[default]\nuse_dualstack_endpoint = false",
+                )]));
+            assert_eq!(
+                Some(false),
+                default_provider()
+                    .configure(&provider_config)
+                    .use_dual_stack()
+                    .await
+            );
+        }
+    }
+}
+
+/// Boolean settings loaded from the environment, then the active profile
+pub(crate) mod bool_setting {
+    use crate::profile;
+    use crate::provider_config::ProviderConfig;
+
+    /// Parse a boolean setting read from `source`
+    ///
+    /// Only `true` and `false` (case-insensitive) are valid. Other values are ignored with a warning.
+    pub(crate) fn parse(source: &str, key: &str, value: &str) -> Option<bool> {
+        if value.eq_ignore_ascii_case("true") {
+            Some(true)
+        } else if value.eq_ignore_ascii_case("false") {
+            Some(false)
+        } else {
+            tracing::warn!(
+                source = source,
+                key = key,
+                value = value,
+                "ignoring invalid boolean setting, expected `true` or `false`"
+            );
+            None
+        }
+    }
+
+    /// Load a boolean setting from `env_var`, falling back to `profile_property`
+    ///
+    /// An invalid environment variable is ignored, falling back to the profile.
+    pub(super) async fn load(
+        config: &ProviderConfig,
+        env_var: &str,
+        profile_property: &str,
+    ) -> Option<bool> {
+        if let Some(value) = config
+            .env()
+            .get(env_var)
+            .ok()
+            .and_then(|value| parse("environment", env_var, &value))
+        {
+            return Some(value);
+        }
+        match profile::load(&config.fs(), &config.env()).await {
+            Ok(profiles) => profiles
+                .get(profile_property)
+                .and_then(|value| parse("profile", profile_property, value)),
+            Err(err) => {
+                tracing::warn!(err = %err, "failed to parse profile");
+                None
+            }
+        }
+    }
+}
+
 /// Default timeout configuration provider chain
 pub mod timeout_config {
     use aws_smithy_types::timeout::TimeoutConfig;
@@ -375,27 +714,7 @@ pub mod timeout_config {
                 Err(err) => panic!("{}", err),
             };
 
-            let conf = builder_from_env.take_unset_from(builder_from_profile);
-
-            if conf.tls_negotiation_timeout().is_some() {
-                tracing::warn!(
-                    "A TLS negotiation timeout was set but that feature is currently unimplemented so the setting will be ignored. \
-                    To help us prioritize support for this feature, please upvote aws-sdk-rust#151 (https://github.com/awslabs/aws-sdk-rust/issues/151)")
-            }
-
-            if conf.connect_timeout().is_some() {
-                tracing::warn!(
-                    "A connect timeout was set but that feature is currently unimplemented so the setting will be ignored. \
-                    To help us prioritize support for this feature, please upvote aws-sdk-rust#151 (https://github.com/awslabs/aws-sdk-rust/issues/151)")
-            }
-
-            if conf.read_timeout().is_some() {
-                tracing::warn!(
-                    "A read timeout was set but that feature is currently unimplemented so the setting will be ignored. \
-                    To help us prioritize support for this feature, please upvote aws-sdk-rust#151 (https://github.com/awslabs/aws-sdk-rust/issues/151)")
-            }
-
-            conf
+            builder_from_env.take_unset_from(builder_from_profile)
         }
     }
 }
@@ -403,12 +722,18 @@ pub mod timeout_config {
 /// Default credentials provider chain
 pub mod credentials {
     use std::borrow::Cow;
+    use std::fmt;
+    use std::sync::Arc;
 
     use aws_types::credentials::{future, ProvideCredentials};
 
     use crate::environment::credentials::EnvironmentVariableCredentialsProvider;
-    use crate::meta::credentials::{CredentialsProviderChain, LazyCachingCredentialsProvider};
+    use crate::meta::credentials::{
+        CredentialsProviderChain, LazyCachingCredentialsProvider, ProviderExplanation,
+    };
     use crate::meta::region::ProvideRegion;
+    use crate::profile::credentials::{ProfileFileError, ResolvedProfileChain};
+    use crate::profile::ProfileFileCredentialsProvider;
     use crate::provider_config::ProviderConfig;
 
     #[cfg(any(feature = "rustls", feature = "native-tls"))]
@@ -452,14 +777,94 @@ pub mod credentials {
     ///     .profile_name("otherprofile")
     ///     .build();
     /// ```
+    ///
+    /// Explain why credentials could not be loaded:
+    /// ```no_run
+    /// use aws_config::default_provider::credentials::DefaultCredentialsChain;
+    /// # async fn docs() {
+    /// let credentials_provider = DefaultCredentialsChain::builder().build().await;
+    /// println!("{}", credentials_provider.explain().await);
+    /// # }
+    /// ```
     #[derive(Debug)]
-    pub struct DefaultCredentialsChain(LazyCachingCredentialsProvider);
+    pub struct DefaultCredentialsChain {
+        provider: LazyCachingCredentialsProvider,
+        chain: Arc<CredentialsProviderChain>,
+        profile: Arc<ProfileFileCredentialsProvider>,
+    }
 
     impl DefaultCredentialsChain {
         /// Builder for `DefaultCredentialsChain`
         pub fn builder() -> Builder {
             Builder::default()
         }
+
+        /// Report how credentials were resolved the last time the providers of the chain were evaluated
+        ///
+        /// The report describes the resolution that produced the cached credentials, or the last
+        /// failed attempt to load them. Providers are not evaluated again, so explaining the chain
+        /// doesn't prompt for an MFA code, start an SSO login or run a credential process. If
+        /// credentials haven't been loaded yet, they are loaded and cached first, exactly as
+        /// [`provide_credentials`](ProvideCredentials::provide_credentials) would. The report also
+        /// includes the chain of providers resolved from the selected profile, which only reads the
+        /// profile files.
+        pub async fn explain(&self) -> CredentialsChainExplanation {
+            let providers = match self.chain.last_explanation() {
+                Some(providers) => providers,
+                None => {
+                    // the outcome of the load is part of the report
+                    let _ = self.provider.provide_credentials().await;
+                    self.chain
+                        .last_explanation()
+                        .unwrap_or_else(|| Vec::new().into())
+                }
+            };
+            CredentialsChainExplanation {
+                providers,
+                profile_chain: self.profile.resolved_chain().await,
+            }
+        }
+    }
+
+    /// Report explaining how the [`DefaultCredentialsChain`] resolved credentials
+    ///
+    /// Returned by [`DefaultCredentialsChain::explain`]. The `Display` implementation renders a
+    /// multi-line, human readable report.
+    #[derive(Debug)]
+    pub struct CredentialsChainExplanation {
+        providers: Arc<[ProviderExplanation]>,
+        profile_chain: Result<ResolvedProfileChain, ProfileFileError>,
+    }
+
+    impl CredentialsChainExplanation {
+        /// Outcome of each provider in the chain, in evaluation order
+        ///
+        /// Empty if the providers didn't complete an evaluation, e.g. because loading credentials
+        /// timed out.
+        pub fn providers(&self) -> &[ProviderExplanation] {
+            &self.providers
+        }
+
+        /// Chain of providers resolved from the selected profile, or why it could not be resolved
+        pub fn profile_chain(&self) -> Result<&ResolvedProfileChain, &ProfileFileError> {
+            self.profile_chain.as_ref()
+        }
+    }
+
+    impl fmt::Display for CredentialsChainExplanation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "credential providers:")?;
+            if self.providers.is_empty() {
+                writeln!(f, "  not evaluated")?;
+            }
+            for provider in self.providers.iter() {
+                writeln!(f, "  {}", provider)?;
+            }
+            match &self.profile_chain {
+                Ok(chain) => write!(f, "profile chain: {}", chain),
+                Err(err) => write!(f, "profile chain: not resolved ({})", err),
+            }
+        }
     }
 
     impl ProvideCredentials for DefaultCredentialsChain {
@@ -467,7 +872,7 @@ pub mod credentials {
         where
             Self: 'a,
         {
-            self.0.provide_credentials()
+            self.provider.provide_credentials()
         }
     }
 
@@ -555,19 +960,31 @@ pub mod credentials {
             let conf = self.conf.unwrap_or_default().with_region(region);
 
             let env_provider = EnvironmentVariableCredentialsProvider::new_with_env(conf.env());
-            let profile_provider = self.profile_file_builder.configure(&conf).build();
+            let profile_provider = Arc::new(self.profile_file_builder.configure(&conf).build());
             let web_identity_token_provider = self.web_identity_builder.configure(&conf).build();
             let imds_provider = self.imds_builder.configure(&conf).build();
             let ecs_provider = self.ecs_builder.configure(&conf).build();
 
-            let provider_chain = CredentialsProviderChain::first_try("Environment", env_provider)
-                .or_else("Profile", profile_provider)
-                .or_else("WebIdentityToken", web_identity_token_provider)
-                .or_else("EcsContainer", ecs_provider)
-                .or_else("Ec2InstanceMetadata", imds_provider);
-            let cached_provider = self.credential_cache.configure(&conf).load(provider_chain);
-
-            DefaultCredentialsChain(cached_provider.build())
+            let provider_chain = Arc::new(
+                CredentialsProviderChain::first_try("Environment", env_provider)
+                    .or_else(
+                        "Profile",
+                        profile_provider.clone() as Arc<dyn ProvideCredentials>,
+                    )
+                    .or_else("WebIdentityToken", web_identity_token_provider)
+                    .or_else("EcsContainer", ecs_provider)
+                    .or_else("Ec2InstanceMetadata", imds_provider),
+            );
+            let cached_provider = self
+                .credential_cache
+                .configure(&conf)
+                .load(provider_chain.clone() as Arc<dyn ProvideCredentials>);
+
+            DefaultCredentialsChain {
+                provider: cached_provider.build(),
+                chain: provider_chain,
+                profile: profile_provider,
+            }
         }
     }
 
@@ -581,6 +998,7 @@ pub mod credentials {
 
         use crate::default_provider::credentials::DefaultCredentialsChain;
         use crate::default_provider::retry_config;
+        use crate::meta::credentials::ProviderOutcome;
         use crate::provider_config::ProviderConfig;
         use crate::test_case::TestEnvironment;
 
@@ -678,6 +1096,72 @@ pub mod credentials {
             assert_eq!(creds.access_key_id(), "correct_key_secondary");
         }
 
+        #[tokio::test]
+        async fn explain_environment_credentials() {
+            let (_, conf) =
+                TestEnvironment::from_dir("./test-data/default-provider-chain/prefer_environment")
+                    .unwrap()
+                    .provider_config()
+                    .await;
+            let provider = DefaultCredentialsChain::builder()
+                .configure(conf)
+                .build()
+                .await;
+            let explanation = provider.explain().await;
+            let creds = provider
+                .provide_credentials()
+                .await
+                .expect("creds should load");
+            assert_eq!(creds.access_key_id(), "correct_key");
+            let providers: Vec<String> = explanation
+                .providers()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(
+                vec![
+                    "Environment: resolved credentials",
+                    "Profile: not tried",
+                    "WebIdentityToken: not tried",
+                    "EcsContainer: not tried",
+                    "Ec2InstanceMetadata: not tried",
+                ],
+                providers
+            );
+        }
+
+        #[tokio::test]
+        async fn explain_assume_role_profile() {
+            let (_, conf) =
+                TestEnvironment::from_dir("./test-data/default-provider-chain/imds_assume_role")
+                    .unwrap()
+                    .provider_config()
+                    .await;
+            let provider = DefaultCredentialsChain::builder()
+                .configure(conf)
+                .build()
+                .await;
+            let explanation = provider.explain().await;
+            assert!(matches!(
+                explanation.providers()[0].outcome(),
+                ProviderOutcome::Skipped { .. }
+            ));
+            assert!(matches!(
+                explanation.providers()[1].outcome(),
+                ProviderOutcome::Resolved
+            ));
+            let profile_chain = explanation.profile_chain().expect("profile chain resolved");
+            assert_eq!(
+                "named credential source `Ec2InstanceMetadata`",
+                profile_chain.base()
+            );
+            assert_eq!(
+                &["arn:aws:iam::130633740322:role/imds-chained-role-test".to_string()],
+                profile_chain.assume_roles()
+            );
+            assert!(format!("{}", explanation).contains("profile chain: profile `default`"));
+        }
+
         #[tokio::test]
         #[traced_test]
         async fn no_providers_configured_err() {
@@ -741,14 +1225,12 @@ pub mod credentials {
         #[tokio::test]
         async fn test_creation_of_retry_config_from_profile() {
             let env = Env::from_slice(&[("AWS_CONFIG_FILE", "config")]);
-            // TODO standard is the default mode; this test would be better if it was setting it to adaptive mode
-            // adaptive mode is currently unsupported so that would panic
             let fs = Fs::from_slice(&[(
                 "config",
                 // If the lines with the vars have preceding spaces, they don't get read
                 r#"[default]
 max_attempts = 1
-retry_mode = standard
+retry_mode = adaptive
             "#,
             )]);
 
@@ -761,7 +1243,7 @@ retry_mode = standard
 
             let expected_retry_config = RetryConfig::new()
                 .with_max_attempts(1)
-                .with_retry_mode(RetryMode::Standard);
+                .with_retry_mode(RetryMode::Adaptive);
 
             assert_eq!(actual_retry_config, expected_retry_config)
         }
@@ -773,14 +1255,12 @@ retry_mode = standard
                 ("AWS_MAX_ATTEMPTS", "42"),
                 ("AWS_RETRY_MODE", "standard"),
             ]);
-            // TODO standard is the default mode; this test would be better if it was setting it to adaptive mode
-            // adaptive mode is currently unsupported so that would panic
             let fs = Fs::from_slice(&[(
                 "config",
                 // If the lines with the vars have preceding spaces, they don't get read
                 r#"[default]
 max_attempts = 88
-retry_mode = standard
+retry_mode = adaptive
             "#,
             )]);
 
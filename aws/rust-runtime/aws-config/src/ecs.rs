@@ -18,12 +18,15 @@
 //! **Next**: It wil check the value of `$AWS_CONTAINER_CREDENTIALS_FULL_URI`. This specifies the full
 //! URL to load credentials. The URL MUST satisfy one of the following two properties:
 //! 1. The URL begins with `https`
-//! 2. The URL refers to a loopback device. If a URL contains a domain name instead of an IP address,
-//! a DNS lookup will be performed. ALL resolved IP addresses MUST refer to a loopback interface, or
-//! the credentials provider will return `CredentialsError::InvalidConfiguration`
+//! 2. The URL refers to a loopback device or to the EKS Pod Identity agent (`169.254.170.23` or
+//! `fd00:ec2::23`). If a URL contains a domain name instead of an IP address, a DNS lookup will be
+//! performed. ALL resolved IP addresses MUST refer to one of these addresses, or the credentials
+//! provider will return `CredentialsError::InvalidConfiguration`
 //!
-//! **Finally**: It will check the value of `$AWS_CONTAINER_AUTHORIZATION_TOKEN`. If this is set, the
-//! value will be passed in the `Authorization` header.
+//! **Finally**: It will check the value of `$AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE`. If this is set,
+//! the contents of the file will be passed in the `Authorization` header. The file is read every time
+//! credentials are loaded so that rotated tokens are picked up. Otherwise, if
+//! `$AWS_CONTAINER_AUTHORIZATION_TOKEN` is set, its value will be passed in the `Authorization` header.
 //!
 //! ## Credentials Format
 //! Credentials MUST be returned in a JSON format:
@@ -48,7 +51,7 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::io::ErrorKind;
-use std::net::{IpAddr, ToSocketAddrs};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
 use std::task::{Context, Poll};
 
 use aws_smithy_client::erase::boxclone::{BoxCloneService, BoxFuture};
@@ -61,7 +64,7 @@ use tower::{Service, ServiceExt};
 
 use crate::http_provider::HttpCredentialProvider;
 use crate::provider_config::ProviderConfig;
-use aws_types::os_shim_internal::Env;
+use aws_types::os_shim_internal::{Env, Fs};
 use http::header::InvalidHeaderValue;
 use std::time::Duration;
 use tokio::sync::OnceCell;
@@ -71,6 +74,12 @@ const BASE_HOST: &str = "http://169.254.170.2";
 const ENV_RELATIVE_URI: &str = "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI";
 const ENV_FULL_URI: &str = "AWS_CONTAINER_CREDENTIALS_FULL_URI";
 const ENV_AUTHORIZATION: &str = "AWS_CONTAINER_AUTHORIZATION_TOKEN";
+const ENV_AUTHORIZATION_TOKEN_FILE: &str = "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE";
+
+// Addresses of the EKS Pod Identity agent
+const EKS_POD_IDENTITY_IPV4: IpAddr = IpAddr::V4(Ipv4Addr::new(169, 254, 170, 23));
+const EKS_POD_IDENTITY_IPV6: IpAddr =
+    IpAddr::V6(Ipv6Addr::new(0xfd00, 0x0ec2, 0, 0, 0, 0, 0, 0x23));
 
 /// Credential provider for ECS and generalized HTTP credentials
 ///
@@ -81,6 +90,7 @@ const ENV_AUTHORIZATION: &str = "AWS_CONTAINER_AUTHORIZATION_TOKEN";
 pub struct EcsCredentialsProvider {
     inner: OnceCell<Provider>,
     env: Env,
+    fs: Fs,
     builder: Builder,
 }
 
@@ -92,16 +102,6 @@ impl EcsCredentialsProvider {
 
     /// Load credentials from this credentials provider
     pub async fn credentials(&self) -> credentials::Result {
-        let auth = match self.env.get(ENV_AUTHORIZATION).ok() {
-            Some(auth) => Some(HeaderValue::from_str(&auth).map_err(|err| {
-                tracing::warn!(token = %auth, "invalid auth token");
-                CredentialsError::invalid_configuration(EcsConfigurationErr::InvalidAuthToken {
-                    err,
-                    value: auth,
-                })
-            })?),
-            None => None,
-        };
         match self.provider().await {
             Provider::NotConfigured => {
                 Err(CredentialsError::not_loaded("ECS provider not configured"))
@@ -109,10 +109,39 @@ impl EcsCredentialsProvider {
             Provider::InvalidConfiguration(err) => {
                 Err(CredentialsError::invalid_configuration(format!("{}", err)))
             }
-            Provider::Configured(provider) => provider.credentials(auth).await,
+            Provider::Configured(provider) => {
+                let auth = self
+                    .auth()
+                    .await
+                    .map_err(CredentialsError::invalid_configuration)?;
+                provider.credentials(auth).await
+            }
         }
     }
 
+    /// Load the authorization token, preferring the token file over the token itself
+    ///
+    /// The token file is read on every call so that rotated tokens are picked up.
+    async fn auth(&self) -> Result<Option<HeaderValue>, EcsConfigurationErr> {
+        let auth = match self.env.get(ENV_AUTHORIZATION_TOKEN_FILE).ok() {
+            Some(path) => {
+                let token = self.fs.read_to_end(&path).await.map_err(|err| {
+                    tracing::warn!(path = %path, error = %err, "failed to read auth token file");
+                    EcsConfigurationErr::InvalidAuthTokenFile { err, path }
+                })?;
+                String::from_utf8_lossy(&token).trim_end().to_string()
+            }
+            None => match self.env.get(ENV_AUTHORIZATION).ok() {
+                Some(auth) => auth,
+                None => return Ok(None),
+            },
+        };
+        HeaderValue::from_str(&auth).map(Some).map_err(|err| {
+            tracing::warn!(token = %auth, "invalid auth token");
+            EcsConfigurationErr::InvalidAuthToken { err, value: auth }
+        })
+    }
+
     async fn provider(&self) -> &Provider {
         self.inner
             .get_or_init(|| Provider::make(self.builder.clone()))
@@ -202,6 +231,10 @@ enum EcsConfigurationErr {
         err: InvalidHeaderValue,
         value: String,
     },
+    InvalidAuthTokenFile {
+        err: io::Error,
+        path: String,
+    },
     NotConfigured,
 }
 
@@ -225,6 +258,9 @@ impl Display for EcsConfigurationErr {
                 "`{}` could not be used as a header value for the auth token. {}",
                 value, err
             ),
+            EcsConfigurationErr::InvalidAuthTokenFile { err, path } => {
+                write!(f, "could not read the auth token from `{}`: {}", path, err)
+            }
         }
     }
 }
@@ -234,6 +270,7 @@ impl Error for EcsConfigurationErr {
         match &self {
             EcsConfigurationErr::InvalidRelativeUri { err, .. } => Some(err),
             EcsConfigurationErr::InvalidFullUri { err, .. } => Some(err),
+            EcsConfigurationErr::InvalidAuthTokenFile { err, .. } => Some(err),
             _ => None,
         }
     }
@@ -287,9 +324,15 @@ impl Builder {
             .as_ref()
             .map(|config| config.env())
             .unwrap_or_default();
+        let fs = self
+            .provider_config
+            .as_ref()
+            .map(|config| config.fs())
+            .unwrap_or_default();
         EcsCredentialsProvider {
             inner: OnceCell::new(),
             env,
+            fs,
             builder: self,
         }
     }
@@ -297,7 +340,8 @@ impl Builder {
 
 /// Invalid Full URI
 ///
-/// When the full URI setting is used, the URI must either be HTTPS or point to a loopback interface.
+/// When the full URI setting is used, the URI must either be HTTPS or point to a loopback interface
+/// or the EKS Pod Identity agent.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum InvalidFullUriError {
@@ -309,7 +353,7 @@ pub enum InvalidFullUriError {
     #[non_exhaustive]
     MissingHost,
 
-    /// The URI did not refer to the loopback interface
+    /// The URI did not refer to the loopback interface or the EKS Pod Identity agent
     #[non_exhaustive]
     NotLoopback,
 
@@ -323,7 +367,10 @@ impl Display for InvalidFullUriError {
             InvalidFullUriError::InvalidUri(err) => write!(f, "URI was invalid: {}", err),
             InvalidFullUriError::MissingHost => write!(f, "URI did not specify a host"),
             InvalidFullUriError::NotLoopback => {
-                write!(f, "URI did not refer to the loopback interface")
+                write!(
+                    f,
+                    "URI did not refer to the loopback interface or the EKS Pod Identity agent"
+                )
             }
             InvalidFullUriError::DnsLookupFailed(err) => {
                 write!(
@@ -352,9 +399,9 @@ pub type DnsService = BoxCloneService<String, Vec<IpAddr>, io::Error>;
 /// Validate that `uri` is valid to be used as a full provider URI
 /// Either:
 /// 1. The URL is uses `https`
-/// 2. The URL refers to a loopback device. If a URL contains a domain name instead of an IP address,
-/// a DNS lookup will be performed. ALL resolved IP addresses MUST refer to a loopback interface, or
-/// the credentials provider will return `CredentialsError::InvalidConfiguration`
+/// 2. The URL refers to a loopback device or the EKS Pod Identity agent. If a URL contains a domain
+/// name instead of an IP address, a DNS lookup will be performed. ALL resolved IP addresses MUST refer
+/// to an allowed address, or the credentials provider will return `CredentialsError::InvalidConfiguration`
 async fn validate_full_uri(uri: &str, dns: &mut DnsService) -> Result<Uri, InvalidFullUriError> {
     let uri = uri
         .parse::<Uri>()
@@ -364,8 +411,10 @@ async fn validate_full_uri(uri: &str, dns: &mut DnsService) -> Result<Uri, Inval
     }
     // For HTTP URIs, we need to validate that it points to a loopback address
     let host = uri.host().ok_or(InvalidFullUriError::MissingHost)?;
-    let is_loopback = match host.parse::<IpAddr>() {
-            Ok(addr) => addr.is_loopback(),
+    // IPv6 hosts are enclosed in brackets, e.g. `http://[::1]/`
+    let ip_host = host.trim_start_matches('[').trim_end_matches(']');
+    let is_loopback = match ip_host.parse::<IpAddr>() {
+            Ok(addr) => is_allowed_address(&addr),
             Err(_domain_name) => {
                 dns.ready().await.map_err(InvalidFullUriError::DnsLookupFailed)?
                     .call(host.to_owned())
@@ -373,13 +422,13 @@ async fn validate_full_uri(uri: &str, dns: &mut DnsService) -> Result<Uri, Inval
                     .map_err(InvalidFullUriError::DnsLookupFailed)?
                     .iter()
                     .all(|addr| {
-                        if !addr.is_loopback() {
+                        if !is_allowed_address(addr) {
                             tracing::warn!(
                                 addr = ?addr,
                                 "HTTP credential provider cannot be used: Address does not resolve to the loopback interface."
                             )
                         };
-                        addr.is_loopback()
+                        is_allowed_address(addr)
                     })
             },
         };
@@ -389,6 +438,11 @@ async fn validate_full_uri(uri: &str, dns: &mut DnsService) -> Result<Uri, Inval
     }
 }
 
+/// Returns true if `addr` may be used for credentials over HTTP
+fn is_allowed_address(addr: &IpAddr) -> bool {
+    addr.is_loopback() || *addr == EKS_POD_IDENTITY_IPV4 || *addr == EKS_POD_IDENTITY_IPV6
+}
+
 #[cfg(not(feature = "dns"))]
 fn tokio_dns() -> Option<DnsService> {
     None
@@ -444,7 +498,7 @@ mod test {
 
     use aws_hyper::DynConnector;
     use aws_types::credentials::ProvideCredentials;
-    use aws_types::os_shim_internal::Env;
+    use aws_types::os_shim_internal::{Env, Fs};
     use aws_types::Credentials;
 
     use aws_smithy_client::test_connection::TestConnection;
@@ -460,8 +514,13 @@ mod test {
     use tower::Service;
 
     fn provider(env: Env, connector: DynConnector) -> EcsCredentialsProvider {
+        provider_with_fs(env, Fs::from_slice(&[]), connector)
+    }
+
+    fn provider_with_fs(env: Env, fs: Fs, connector: DynConnector) -> EcsCredentialsProvider {
         let provider_config = ProviderConfig::empty()
             .with_env(env)
+            .with_fs(fs)
             .with_http_connector(connector);
         Builder::default().configure(&provider_config).build()
     }
@@ -536,6 +595,34 @@ mod test {
             .unwrap()
             .expect_err("not a loopback");
         assert!(matches!(err, InvalidFullUriError::NotLoopback));
+
+        assert_eq!(
+            validate_full_uri("http://[::1]:8080/get-credentials", &mut dns)
+                .now_or_never()
+                .unwrap()
+                .expect("valid uri"),
+            Uri::from_static("http://[::1]:8080/get-credentials")
+        );
+    }
+
+    #[test]
+    fn valid_uri_eks_pod_identity() {
+        let never = NeverService::new();
+        let mut dns = BoxCloneService::new(never);
+        for uri in &[
+            "http://169.254.170.23/v1/credentials",
+            "http://[fd00:ec2::23]/v1/credentials",
+        ] {
+            validate_full_uri(uri, &mut dns)
+                .now_or_never()
+                .unwrap()
+                .expect("EKS Pod Identity agent is allowed");
+        }
+        let err = validate_full_uri("http://169.254.170.24/v1/credentials", &mut dns)
+            .now_or_never()
+            .unwrap()
+            .expect_err("not the EKS Pod Identity agent");
+        assert!(matches!(err, InvalidFullUriError::NotLoopback));
     }
 
     #[test]
@@ -620,6 +707,93 @@ mod test {
         connector.assert_requests_match(&[]);
     }
 
+    #[tokio::test]
+    async fn load_valid_creds_auth_token_file() {
+        let env = Env::from_slice(&[
+            (
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+                "http://169.254.170.23/v1/credentials",
+            ),
+            ("AWS_CONTAINER_AUTHORIZATION_TOKEN", "Basic ignored"),
+            (
+                "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE",
+                "/var/run/secrets/token",
+            ),
+        ]);
+        let fs = Fs::from_slice(&[("/var/run/secrets/token", "Basic token-file\n")]);
+        let connector = TestConnection::new(vec![(
+            creds_request(
+                "http://169.254.170.23/v1/credentials",
+                Some("Basic token-file"),
+            ),
+            ok_creds_response(),
+        )]);
+        let provider = provider_with_fs(env, fs, DynConnector::new(connector.clone()));
+        let creds = provider
+            .provide_credentials()
+            .await
+            .expect("valid credentials");
+        assert_correct(creds);
+        connector.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn auth_token_file_is_read_on_every_load() {
+        let token_dir =
+            std::env::temp_dir().join(format!("aws-config-ecs-token-{}", std::process::id()));
+        std::fs::create_dir_all(&token_dir).unwrap();
+        std::fs::write(token_dir.join("ecs-token"), "first-token").unwrap();
+        let fs = Fs::from_test_dir(&token_dir, "/var/run/secrets");
+        let env = Env::from_slice(&[
+            ("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI", "/credentials"),
+            (
+                "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE",
+                "/var/run/secrets/ecs-token",
+            ),
+        ]);
+        let connector = TestConnection::new(vec![
+            (
+                creds_request("http://169.254.170.2/credentials", Some("first-token")),
+                ok_creds_response(),
+            ),
+            (
+                creds_request("http://169.254.170.2/credentials", Some("rotated-token")),
+                ok_creds_response(),
+            ),
+        ]);
+        let provider = provider_with_fs(env, fs, DynConnector::new(connector.clone()));
+        provider
+            .provide_credentials()
+            .await
+            .expect("valid credentials");
+        std::fs::write(token_dir.join("ecs-token"), "rotated-token").unwrap();
+        provider
+            .provide_credentials()
+            .await
+            .expect("valid credentials");
+        std::fs::remove_dir_all(&token_dir).unwrap();
+        connector.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn missing_auth_token_file() {
+        let env = Env::from_slice(&[
+            ("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI", "/credentials"),
+            ("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE", "/missing-token"),
+        ]);
+        let connector = TestConnection::<&'static str>::new(vec![]);
+        let provider = provider(env, DynConnector::new(connector));
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("token file does not exist");
+        assert!(
+            format!("{}", err).contains("could not read the auth token from `/missing-token`"),
+            "{}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn load_valid_creds_no_auth() {
         let env = Env::from_slice(&[("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI", "/credentials")]);
@@ -8,9 +8,11 @@
 //! Client for direct access to IMDSv2.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::future::Future;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -28,18 +30,22 @@ use aws_smithy_http_tower::map_request::{
 };
 use aws_smithy_types::retry::{ErrorKind, RetryKind};
 use aws_smithy_types::timeout::TimeoutConfig;
-use aws_types::os_shim_internal::{Env, Fs};
+use aws_types::os_shim_internal::{Env, TimeSource};
 use bytes::Bytes;
 use http::uri::InvalidUri;
 use http::{Response, Uri};
 
 use crate::connector::expect_connector;
+use crate::imds::client::metadata::{
+    InstanceIdentityDocument, MetadataCaches, MetadataTtls, NetworkInterface, SpotInstanceAction,
+};
 use crate::imds::client::token::TokenMiddleware;
 use crate::profile::ProfileParseError;
 use crate::provider_config::{HttpSettings, ProviderConfig};
-use crate::{profile, PKG_VERSION};
+use crate::{default_provider, profile, PKG_VERSION};
 use tokio::sync::OnceCell;
 
+pub mod metadata;
 mod token;
 
 // 6 hours
@@ -57,7 +63,8 @@ fn user_agent() -> AwsUserAgent {
 /// Client for IMDSv2. This client handles fetching tokens, retrying on failure, and token
 /// caching according to the specified token TTL.
 ///
-/// _Note: This client ONLY supports IMDSv2. It will not fallback to IMDSv1. See
+/// _Note: By default, this client ONLY supports IMDSv2. It will not fallback to IMDSv1 unless
+/// [IMDSv1 fallback](#imdsv1-fallback) is enabled. See
 /// [transitioning to IMDSv2](https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/configuring-instance-metadata-service.html#instance-metadata-transition-to-version-2)
 /// for more information._
 ///
@@ -112,10 +119,30 @@ fn user_agent() -> AwsUserAgent {
 ///
 /// 7. The default value of `http://169.254.169.254` will be used.
 ///
+/// ## IMDSv1 fallback
+/// When the token API returns a 403, 404 or 405 response, the client can fall back to sending
+/// requests without a token (IMDSv1). Fallback is disabled unless it is enabled by:
+/// 1. Explicit configuration via [`Builder::imds_v1_fallback`]
+/// 2. Setting the `AWS_EC2_METADATA_V1_DISABLED` environment variable to `false`
+/// 3. Setting the `ec2_metadata_v1_disabled` field in `~/.aws/config` to `false`
+///
+/// ## Disabling IMDS
+/// IMDS can be disabled entirely by setting the `AWS_EC2_METADATA_DISABLED` environment variable or
+/// the `ec2_metadata_disabled` field in `~/.aws/config` to `true`. A disabled client never contacts
+/// IMDS and fails every request with [`ImdsError::Disabled`]. The IMDS credentials and region
+/// providers, including those in the default chains, will not attempt to use IMDS.
+///
+/// # Typed metadata
+/// Commonly used metadata can be loaded with typed accessors, e.g.
+/// [`instance_identity_document`](Client::instance_identity_document). Their responses are cached
+/// by the client. The time-to-live of each category can be configured on the [`Builder`].
 #[derive(Debug)]
 pub struct Client {
     endpoint: Endpoint,
+    disabled: bool,
     inner: aws_smithy_client::Client<DynConnector, ImdsMiddleware>,
+    time_source: TimeSource,
+    metadata: MetadataCaches,
 }
 
 /// Client where build is sync, but usage is async
@@ -181,6 +208,9 @@ impl Client {
     /// # }
     /// ```
     pub async fn get(&self, path: &str) -> Result<String, ImdsError> {
+        if self.disabled {
+            return Err(ImdsError::Disabled);
+        }
         let operation = self.make_operation(path)?;
         self.inner.call(operation).await.map_err(|err| match err {
             SdkError::ConstructionFailure(err) => match err.downcast::<ImdsError>() {
@@ -202,6 +232,119 @@ impl Client {
         })
     }
 
+    /// Retrieve the [instance identity document](InstanceIdentityDocument)
+    ///
+    /// The document is cached for 6 hours by default, see [`Builder::identity_document_ttl`].
+    pub async fn instance_identity_document(&self) -> Result<InstanceIdentityDocument, ImdsError> {
+        self.metadata
+            .identity_document
+            .get_or_load(&self.time_source, || async {
+                self.get(metadata::IDENTITY_DOCUMENT_PATH).await?.parse()
+            })
+            .await
+    }
+
+    /// Retrieve the tags of the instance
+    ///
+    /// Access to tags must be [enabled in the instance metadata options](https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/Using_Tags.html#allow-access-to-tags-in-IMDS),
+    /// otherwise IMDS returns an error response. Tags are cached for 5 minutes by default, see
+    /// [`Builder::instance_tags_ttl`].
+    pub async fn instance_tags(&self) -> Result<HashMap<String, String>, ImdsError> {
+        self.metadata
+            .instance_tags
+            .get_or_load(&self.time_source, || async {
+                let mut tags = HashMap::new();
+                let keys = self.get(metadata::INSTANCE_TAGS_PATH).await?;
+                for key in metadata::split_lines(&keys) {
+                    let value = self
+                        .get(&format!("{}/{}", metadata::INSTANCE_TAGS_PATH, key))
+                        .await?;
+                    tags.insert(key, value);
+                }
+                Ok(tags)
+            })
+            .await
+    }
+
+    /// Retrieve the pending [spot instance interruption](SpotInstanceAction), if any
+    ///
+    /// Returns `None` when the instance is not scheduled to be interrupted. The result is cached
+    /// for 5 seconds by default, see [`Builder::spot_instance_action_ttl`].
+    pub async fn spot_instance_action(&self) -> Result<Option<SpotInstanceAction>, ImdsError> {
+        self.metadata
+            .spot_instance_action
+            .get_or_load(&self.time_source, || async {
+                match self
+                    .get_optional(metadata::SPOT_INSTANCE_ACTION_PATH)
+                    .await?
+                {
+                    Some(action) => Ok(Some(action.parse()?)),
+                    None => Ok(None),
+                }
+            })
+            .await
+    }
+
+    /// Retrieve the [network interfaces](NetworkInterface) attached to the instance
+    ///
+    /// Interfaces are ordered by device number and cached for 5 minutes by default, see
+    /// [`Builder::network_interfaces_ttl`].
+    pub async fn network_interfaces(&self) -> Result<Vec<NetworkInterface>, ImdsError> {
+        self.metadata
+            .network_interfaces
+            .get_or_load(&self.time_source, || async {
+                let macs = self
+                    .get(&format!("{}/", metadata::NETWORK_INTERFACES_PATH))
+                    .await?;
+                let mut interfaces = Vec::new();
+                for mac in metadata::split_lines(&macs) {
+                    let mac = mac.trim_end_matches('/').to_string();
+                    interfaces.push(self.network_interface(mac).await?);
+                }
+                interfaces.sort_by_key(|interface| interface.device_number);
+                Ok(interfaces)
+            })
+            .await
+    }
+
+    async fn network_interface(&self, mac: String) -> Result<NetworkInterface, ImdsError> {
+        let base = format!("{}/{}", metadata::NETWORK_INTERFACES_PATH, mac);
+        let get = |field: &str| self.get_owned(format!("{}/{}", base, field));
+        let device_number = get("device-number").await?;
+        let device_number = device_number.trim().parse().map_err(|_| {
+            metadata::invalid_response(format!("invalid device number `{}`", device_number))
+        })?;
+        Ok(NetworkInterface {
+            device_number,
+            interface_id: get("interface-id").await?,
+            local_ipv4s: metadata::split_lines(&get("local-ipv4s").await?),
+            ipv6s: self
+                .get_optional(&format!("{}/ipv6s", base))
+                .await?
+                .map(|ipv6s| metadata::split_lines(&ipv6s))
+                .unwrap_or_default(),
+            subnet_id: get("subnet-id").await?,
+            vpc_id: get("vpc-id").await?,
+            security_group_ids: metadata::split_lines(&get("security-group-ids").await?),
+            mac,
+        })
+    }
+
+    async fn get_owned(&self, path: String) -> Result<String, ImdsError> {
+        self.get(&path).await
+    }
+
+    /// Retrieve information from IMDS, returning `None` if it does not exist
+    async fn get_optional(&self, path: &str) -> Result<Option<String>, ImdsError> {
+        match self.get(path).await {
+            Ok(value) => Ok(Some(value)),
+            Err(ImdsError::ErrorResponse { response }) if response.status().as_u16() == 404 => {
+                Ok(None)
+            }
+            Err(other) => Err(other),
+        }
+    }
+
     /// Creates a aws_smithy_http Operation to for `path`
     /// - Convert the path to a URI
     /// - Set the base endpoint on the URI
@@ -253,6 +396,11 @@ pub enum ImdsError {
 
     /// An unexpected error occurred communicating with IMDS
     Unexpected(Box<dyn Error + Send + Sync + 'static>),
+
+    /// IMDS was disabled via `AWS_EC2_METADATA_DISABLED` or the `ec2_metadata_disabled` profile key
+    ///
+    /// No request was sent to IMDS.
+    Disabled,
 }
 
 impl Display for ImdsError {
@@ -279,6 +427,10 @@ impl Display for ImdsError {
                 "An unexpected error occurred communicating with IMDS: {}",
                 err
             ),
+            ImdsError::Disabled => write!(
+                f,
+                "IMDS is disabled by $AWS_EC2_METADATA_DISABLED or `ec2_metadata_disabled` in the profile"
+            ),
         }
     }
 }
@@ -404,9 +556,11 @@ pub struct Builder {
     endpoint: Option<EndpointSource>,
     mode_override: Option<EndpointMode>,
     token_ttl: Option<Duration>,
+    imds_v1_fallback: Option<bool>,
     connect_timeout: Option<Duration>,
     read_timeout: Option<Duration>,
     config: Option<ProviderConfig>,
+    metadata_ttls: MetadataTtls,
 }
 
 /// Error constructing IMDSv2 Client
@@ -498,6 +652,16 @@ impl Builder {
         self
     }
 
+    /// Allow falling back to IMDSv1 when IMDSv2 is unavailable
+    ///
+    /// When enabled, requests are sent without a session token if the token API returns a 403,
+    /// 404 or 405 response. By default, fallback is disabled unless `AWS_EC2_METADATA_V1_DISABLED`
+    /// or the `ec2_metadata_v1_disabled` profile key is set to `false`.
+    pub fn imds_v1_fallback(mut self, enabled: bool) -> Self {
+        self.imds_v1_fallback = Some(enabled);
+        self
+    }
+
     /// Override the connect timeout for IMDS
     ///
     /// This value defaults to 1 second
@@ -514,6 +678,38 @@ impl Builder {
         self
     }
 
+    /// Override how long the [instance identity document](Client::instance_identity_document) is cached
+    ///
+    /// This value defaults to 6 hours
+    pub fn identity_document_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttls.identity_document = ttl;
+        self
+    }
+
+    /// Override how long [instance tags](Client::instance_tags) are cached
+    ///
+    /// This value defaults to 5 minutes
+    pub fn instance_tags_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttls.instance_tags = ttl;
+        self
+    }
+
+    /// Override how long the [spot instance action](Client::spot_instance_action) is cached
+    ///
+    /// This value defaults to 5 seconds
+    pub fn spot_instance_action_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttls.spot_instance_action = ttl;
+        self
+    }
+
+    /// Override how long [network interfaces](Client::network_interfaces) are cached
+    ///
+    /// This value defaults to 5 minutes
+    pub fn network_interfaces_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_ttls.network_interfaces = ttl;
+        self
+    }
+
     /* TODO: Support customizing the port explicitly */
     /*
     pub fn port(mut self, port: u32) -> Self {
@@ -537,10 +733,36 @@ impl Builder {
         let connector = expect_connector(config.connector(&HttpSettings {
             timeout_settings: timeout_config,
         }));
+        let env = config.env();
+        let fs = config.fs();
+        // the profile is only loaded for settings that aren't set explicitly or by the environment
+        let profile = OnceCell::new();
+        let load_profile = || profile.get_or_init(|| profile::load(&fs, &env));
+        let disabled = bool_setting(
+            &env,
+            env::EC2_METADATA_DISABLED,
+            load_profile(),
+            profile_keys::EC2_METADATA_DISABLED,
+        )
+        .await
+        .unwrap_or(false);
+        let imds_v1_fallback = match self.imds_v1_fallback {
+            Some(enabled) => enabled,
+            None => !bool_setting(
+                &env,
+                env::EC2_METADATA_V1_DISABLED,
+                load_profile(),
+                profile_keys::EC2_METADATA_V1_DISABLED,
+            )
+            .await
+            .unwrap_or(true),
+        };
         let endpoint_source = self
             .endpoint
-            .unwrap_or_else(|| EndpointSource::Env(config.env(), config.fs()));
-        let endpoint = endpoint_source.endpoint(self.mode_override).await?;
+            .unwrap_or_else(|| EndpointSource::Env(env.clone()));
+        let endpoint = endpoint_source
+            .endpoint(self.mode_override, load_profile())
+            .await?;
         let endpoint = Endpoint::immutable(endpoint);
         let retry_config = retry::Config::default()
             .with_max_attempts(self.max_attempts.unwrap_or(DEFAULT_ATTEMPTS));
@@ -550,6 +772,7 @@ impl Builder {
             config.time_source(),
             endpoint.clone(),
             self.token_ttl.unwrap_or(DEFAULT_TOKEN_TTL),
+            imds_v1_fallback,
             retry_config.clone(),
             timeout_config.clone(),
         );
@@ -562,7 +785,10 @@ impl Builder {
             .with_timeout_config(timeout_config);
         let client = Client {
             endpoint,
+            disabled,
             inner: inner_client,
+            time_source: config.time_source(),
+            metadata: MetadataCaches::new(&self.metadata_ttls),
         };
         Ok(client)
     }
@@ -571,22 +797,58 @@ impl Builder {
 mod env {
     pub const ENDPOINT: &str = "AWS_EC2_METADATA_SERVICE_ENDPOINT";
     pub const ENDPOINT_MODE: &str = "AWS_EC2_METADATA_SERVICE_ENDPOINT_MODE";
+    pub(super) use crate::imds::env::EC2_METADATA_DISABLED;
+    pub const EC2_METADATA_V1_DISABLED: &str = "AWS_EC2_METADATA_V1_DISABLED";
 }
 
 mod profile_keys {
     pub const ENDPOINT: &str = "ec2_metadata_service_endpoint";
     pub const ENDPOINT_MODE: &str = "ec2_metadata_service_endpoint_mode";
+    pub const EC2_METADATA_DISABLED: &str = "ec2_metadata_disabled";
+    pub const EC2_METADATA_V1_DISABLED: &str = "ec2_metadata_v1_disabled";
+}
+
+/// Load a boolean setting from the environment, falling back to the profile
+///
+/// Values other than `true` or `false` are ignored with a warning. A profile that can't be parsed
+/// is treated as unset.
+async fn bool_setting(
+    env: &Env,
+    env_key: &str,
+    profile: impl Future<Output = &Result<profile::ProfileSet, ProfileParseError>>,
+    profile_key: &str,
+) -> Option<bool> {
+    if let Some(value) = env
+        .get(env_key)
+        .ok()
+        .and_then(|value| default_provider::bool_setting::parse("environment", env_key, &value))
+    {
+        return Some(value);
+    }
+    match profile.await {
+        Ok(profile) => profile
+            .get(profile_key)
+            .and_then(|value| default_provider::bool_setting::parse("profile", profile_key, value)),
+        Err(err) => {
+            tracing::warn!(err = %err, key = profile_key, "failed to parse profile, ignoring the setting");
+            None
+        }
+    }
 }
 
 /// Endpoint Configuration Abstraction
 #[derive(Debug, Clone)]
 enum EndpointSource {
     Explicit(Uri),
-    Env(Env, Fs),
+    Env(Env),
 }
 
 impl EndpointSource {
-    async fn endpoint(&self, mode_override: Option<EndpointMode>) -> Result<Uri, BuildError> {
+    async fn endpoint(
+        &self,
+        mode_override: Option<EndpointMode>,
+        profile: impl Future<Output = &Result<profile::ProfileSet, ProfileParseError>>,
+    ) -> Result<Uri, BuildError> {
         match self {
             EndpointSource::Explicit(uri) => {
                 if mode_override.is_some() {
@@ -596,11 +858,12 @@ impl EndpointSource {
                 }
                 Ok(uri.clone())
             }
-            EndpointSource::Env(env, fs) => {
-                // load an endpoint override from the environment
-                let profile = profile::load(fs, env)
+            EndpointSource::Env(env) => {
+                let profile = profile
                     .await
-                    .map_err(BuildError::InvalidProfile)?;
+                    .as_ref()
+                    .map_err(|err| BuildError::InvalidProfile(err.clone()))?;
+                // load an endpoint override from the environment
                 let uri_override = if let Ok(uri) = env.get(env::ENDPOINT) {
                     Some(Cow::Owned(uri))
                 } else {
@@ -727,6 +990,7 @@ pub(crate) mod test {
     use serde::Deserialize;
     use tracing_test::traced_test;
 
+    use crate::imds::client::metadata::SpotAction;
     use crate::imds::client::{Client, EndpointMode, ImdsError};
     use crate::provider_config::ProviderConfig;
     use http::header::USER_AGENT;
@@ -779,6 +1043,237 @@ pub(crate) mod test {
             .expect("valid client")
     }
 
+    fn make_client_with_time<T>(conn: &TestConnection<T>, time: &ManualTimeSource) -> super::Builder
+    where
+        SdkBody: From<T>,
+        T: Send + 'static,
+    {
+        super::Client::builder().configure(
+            &ProviderConfig::no_configuration()
+                .with_http_connector(DynConnector::new(conn.clone()))
+                .with_time_source(TimeSource::manual(time)),
+        )
+    }
+
+    fn not_found() -> http::Response<&'static str> {
+        http::Response::builder().status(404).body("").unwrap()
+    }
+
+    const IDENTITY_DOCUMENT: &str = r#"{
+        "accountId" : "123456789012",
+        "architecture" : "x86_64",
+        "availabilityZone" : "us-west-2b",
+        "imageId" : "ami-5fb8c835",
+        "instanceId" : "i-1234567890abcdef0",
+        "instanceType" : "t2.micro",
+        "privateIp" : "10.158.112.84",
+        "region" : "us-west-2",
+        "version" : "2017-09-30"
+    }"#;
+
+    #[tokio::test]
+    async fn identity_document_is_cached_until_ttl() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/dynamic/instance-identity/document",
+                    TOKEN_A,
+                ),
+                imds_response(IDENTITY_DOCUMENT),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/dynamic/instance-identity/document",
+                    TOKEN_A,
+                ),
+                imds_response(IDENTITY_DOCUMENT),
+            ),
+        ]);
+        let mut time = ManualTimeSource::new(UNIX_EPOCH);
+        let client = make_client_with_time(&connection, &time)
+            .identity_document_ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .expect("valid client");
+        let document = client
+            .instance_identity_document()
+            .await
+            .expect("valid document");
+        assert_eq!("i-1234567890abcdef0", document.instance_id());
+        time.advance(Duration::from_secs(59));
+        client
+            .instance_identity_document()
+            .await
+            .expect("cached document");
+        assert_eq!(connection.requests().len(), 2);
+
+        time.advance(Duration::from_secs(1));
+        client
+            .instance_identity_document()
+            .await
+            .expect("reloaded document");
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn instance_tags() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/tags/instance",
+                    TOKEN_A,
+                ),
+                imds_response("Name\nteam"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/tags/instance/Name",
+                    TOKEN_A,
+                ),
+                imds_response("web-server"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/tags/instance/team",
+                    TOKEN_A,
+                ),
+                imds_response("platform"),
+            ),
+        ]);
+        let time = ManualTimeSource::new(UNIX_EPOCH);
+        let client = make_client_with_time(&connection, &time)
+            .build()
+            .await
+            .expect("valid client");
+        let tags = client.instance_tags().await.expect("valid tags");
+        assert_eq!(Some("web-server"), tags.get("Name").map(String::as_str));
+        assert_eq!(Some("platform"), tags.get("team").map(String::as_str));
+        // cached
+        client.instance_tags().await.expect("valid tags");
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn spot_instance_action() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/spot/instance-action",
+                    TOKEN_A,
+                ),
+                not_found(),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/spot/instance-action",
+                    TOKEN_A,
+                ),
+                imds_response(r#"{"action": "stop", "time": "2017-09-18T08:22:00Z"}"#),
+            ),
+        ]);
+        let mut time = ManualTimeSource::new(UNIX_EPOCH);
+        let client = make_client_with_time(&connection, &time)
+            .build()
+            .await
+            .expect("valid client");
+        assert_eq!(
+            None,
+            client
+                .spot_instance_action()
+                .await
+                .expect("no interruption")
+        );
+        time.advance(Duration::from_secs(5));
+        let action = client
+            .spot_instance_action()
+            .await
+            .expect("valid action")
+            .expect("interruption scheduled");
+        assert_eq!(&SpotAction::Stop, action.action());
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn network_interfaces() {
+        let interface = |field: &str| {
+            format!(
+                "http://169.254.169.254/latest/meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/{}",
+                field
+            )
+        };
+        let request = |uri: String| {
+            http::Request::builder()
+                .uri(uri)
+                .method("GET")
+                .header("x-aws-ec2-metadata-token", TOKEN_A)
+                .body(SdkBody::empty())
+                .unwrap()
+        };
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/network/interfaces/macs/",
+                    TOKEN_A,
+                ),
+                imds_response("0e:49:61:0f:c3:11/"),
+            ),
+            (request(interface("device-number")), imds_response("0")),
+            (
+                request(interface("interface-id")),
+                imds_response("eni-0f95d3625f5c521cc"),
+            ),
+            (
+                request(interface("local-ipv4s")),
+                imds_response("192.168.1.10\n192.168.1.11"),
+            ),
+            (request(interface("ipv6s")), not_found()),
+            (
+                request(interface("subnet-id")),
+                imds_response("subnet-be9b61d7"),
+            ),
+            (request(interface("vpc-id")), imds_response("vpc-d295a6a7")),
+            (
+                request(interface("security-group-ids")),
+                imds_response("sg-0e1b2f3a\nsg-1a2b3c4d"),
+            ),
+        ]);
+        let time = ManualTimeSource::new(UNIX_EPOCH);
+        let client = make_client_with_time(&connection, &time)
+            .build()
+            .await
+            .expect("valid client");
+        let interfaces = client.network_interfaces().await.expect("valid interfaces");
+        assert_eq!(1, interfaces.len());
+        let interface = &interfaces[0];
+        assert_eq!("0e:49:61:0f:c3:11", interface.mac());
+        assert_eq!(0, interface.device_number());
+        assert_eq!("eni-0f95d3625f5c521cc", interface.interface_id());
+        assert_eq!(&["192.168.1.10", "192.168.1.11"], interface.local_ipv4s());
+        assert!(interface.ipv6s().is_empty());
+        assert_eq!("vpc-d295a6a7", interface.vpc_id());
+        assert_eq!(
+            &["sg-0e1b2f3a", "sg-1a2b3c4d"],
+            interface.security_group_ids()
+        );
+        connection.assert_requests_match(&[]);
+    }
+
     #[tokio::test]
     async fn client_caches_token() {
         let connection = TestConnection::new(vec![
@@ -965,6 +1460,232 @@ pub(crate) mod test {
         connection.assert_requests_match(&[]);
     }
 
+    fn imds_v1_request(path: &'static str) -> http::Request<SdkBody> {
+        http::Request::builder()
+            .uri(Uri::from_static(path))
+            .method("GET")
+            .body(SdkBody::empty())
+            .unwrap()
+    }
+
+    fn assert_no_token<T>(connection: &TestConnection<T>, request: usize) {
+        assert!(
+            connection.requests()[request]
+                .actual
+                .headers()
+                .get("x-aws-ec2-metadata-token")
+                .is_none(),
+            "IMDSv1 requests must not contain a token"
+        );
+    }
+
+    #[tokio::test]
+    async fn imds_v1_fallback_on_forbidden_token() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                http::Response::builder().status(403).body("").unwrap(),
+            ),
+            (
+                imds_v1_request("http://169.254.169.254/latest/metadata"),
+                imds_response("IMDSv1 response"),
+            ),
+        ]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .imds_v1_fallback(true)
+            .build()
+            .await
+            .expect("valid client");
+        let metadata = client.get("/latest/metadata").await.expect("success");
+        assert_eq!("IMDSv1 response", metadata);
+        connection.assert_requests_match(&[]);
+        assert_no_token(&connection, 1);
+    }
+
+    #[tokio::test]
+    async fn imds_v1_fallback_enabled_by_env() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                http::Response::builder().status(405).body("").unwrap(),
+            ),
+            (
+                imds_v1_request("http://169.254.169.254/latest/metadata"),
+                imds_response("IMDSv1 response"),
+            ),
+        ]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[(
+                        "AWS_EC2_METADATA_V1_DISABLED",
+                        "false",
+                    )]))
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .build()
+            .await
+            .expect("valid client");
+        let metadata = client.get("/latest/metadata").await.expect("success");
+        assert_eq!("IMDSv1 response", metadata);
+        connection.assert_requests_match(&[]);
+        assert_no_token(&connection, 1);
+    }
+
+    #[tokio::test]
+    async fn imds_v1_fallback_disabled_by_profile() {
+        let connection = TestConnection::new(vec![(
+            token_request("http://169.254.169.254", 21600),
+            not_found(),
+        )]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[("HOME", "/home")]))
+                    .with_fs(Fs::from_slice(&[(
+                        "/home/.aws/config",
+                        "[default]\nec2_metadata_v1_disabled = true",
+                    )]))
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .build()
+            .await
+            .expect("valid client");
+        let err = client.get("/latest/metadata").await.expect_err("no token");
+        assert!(
+            matches!(err, ImdsError::FailedToLoadToken(_)),
+            "no IMDSv1 fallback: {}",
+            err
+        );
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn no_imds_v1_fallback_on_bad_request() {
+        let connection = TestConnection::new(vec![(
+            token_request("http://169.254.169.254", 21600),
+            http::Response::builder().status(400).body("").unwrap(),
+        )]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .imds_v1_fallback(true)
+            .build()
+            .await
+            .expect("valid client");
+        client.get("/latest/metadata").await.expect_err("no token");
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn imds_disabled_by_profile() {
+        let connection = TestConnection::<&'static str>::new(vec![]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[("HOME", "/home")]))
+                    .with_fs(Fs::from_slice(&[(
+                        "/home/.aws/config",
+                        "[default]\nec2_metadata_disabled = true",
+                    )]))
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .build()
+            .await
+            .expect("valid client");
+        let err = client.get("/latest/metadata").await.expect_err("disabled");
+        assert!(matches!(err, ImdsError::Disabled), "{}", err);
+        assert!(connection.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_bool_settings_are_ignored() {
+        let connection = TestConnection::new(vec![(
+            token_request("http://169.254.169.254", 21600),
+            http::Response::builder().status(403).body("").unwrap(),
+        )]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[
+                        ("AWS_EC2_METADATA_DISABLED", "1"),
+                        ("AWS_EC2_METADATA_V1_DISABLED", "0"),
+                        ("HOME", "/home"),
+                    ]))
+                    .with_fs(Fs::from_slice(&[(
+                        "/home/.aws/config",
+                        "[default]\nec2_metadata_v1_disabled = no",
+                    )]))
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .build()
+            .await
+            .expect("valid client");
+        let err = client.get("/latest/metadata").await.expect_err("no token");
+        assert!(
+            matches!(err, ImdsError::FailedToLoadToken(_)),
+            "IMDS is enabled without IMDSv1 fallback: {}",
+            err
+        );
+        connection.assert_requests_match(&[]);
+    }
+
+    #[tokio::test]
+    async fn bool_settings_are_case_insensitive() {
+        let connection = TestConnection::<&'static str>::new(vec![]);
+        let client = Client::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[("AWS_EC2_METADATA_DISABLED", "TRUE")]))
+                    .with_http_connector(DynConnector::new(connection.clone())),
+            )
+            .build()
+            .await
+            .expect("valid client");
+        let err = client.get("/latest/metadata").await.expect_err("disabled");
+        assert!(matches!(err, ImdsError::Disabled), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn invalid_profile_is_ignored_for_explicit_settings() {
+        let connection = TestConnection::new(vec![(
+            token_request("http://localhost:8080", 21600),
+            token_response(21600, TOKEN_A),
+        )]);
+        let provider_config = ProviderConfig::no_configuration()
+            .with_env(Env::from_slice(&[("HOME", "/home")]))
+            .with_fs(Fs::from_slice(&[(
+                "/home/.aws/config",
+                "[default\nregion = x",
+            )]))
+            .with_http_connector(DynConnector::new(connection.clone()));
+        let client = Client::builder()
+            .configure(&provider_config)
+            .endpoint(Uri::from_static("http://localhost:8080"))
+            .imds_v1_fallback(false)
+            .build()
+            .await
+            .expect("the profile isn't needed");
+        assert!(!client.disabled);
+
+        let err = Client::builder()
+            .configure(&provider_config)
+            .build()
+            .await
+            .expect_err("the endpoint is loaded from the profile");
+        assert!(
+            matches!(err, super::BuildError::InvalidProfile(_)),
+            "{}",
+            err
+        );
+    }
+
     // since tokens are sent as headers, the tokens need to be valid header values
     #[tokio::test]
     async fn invalid_token() {
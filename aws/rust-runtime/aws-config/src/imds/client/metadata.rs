@@ -0,0 +1,424 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Typed instance metadata
+//!
+//! Types returned by the typed accessors of the IMDS [`Client`](super::Client), e.g.
+//! [`Client::instance_identity_document`](super::Client::instance_identity_document).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use aws_smithy_json::deserialize::token::skip_value;
+use aws_smithy_json::deserialize::{json_token_iter, Token};
+use aws_types::os_shim_internal::TimeSource;
+
+use crate::cache::ExpiringCache;
+use crate::imds::client::ImdsError;
+use crate::json_credentials::parse_expiration;
+
+pub(super) const IDENTITY_DOCUMENT_PATH: &str = "/latest/dynamic/instance-identity/document";
+pub(super) const INSTANCE_TAGS_PATH: &str = "/latest/meta-data/tags/instance";
+pub(super) const SPOT_INSTANCE_ACTION_PATH: &str = "/latest/meta-data/spot/instance-action";
+pub(super) const NETWORK_INTERFACES_PATH: &str = "/latest/meta-data/network/interfaces/macs";
+
+/// The identity document never changes during the lifetime of an instance
+pub(super) const DEFAULT_IDENTITY_DOCUMENT_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+pub(super) const DEFAULT_INSTANCE_TAGS_TTL: Duration = Duration::from_secs(5 * 60);
+/// Spot interruption notices are only given two minutes in advance
+pub(super) const DEFAULT_SPOT_INSTANCE_ACTION_TTL: Duration = Duration::from_secs(5);
+pub(super) const DEFAULT_NETWORK_INTERFACES_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Document describing the instance, loaded from `/latest/dynamic/instance-identity/document`
+///
+/// See [instance identity documents](https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/instance-identity-documents.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceIdentityDocument {
+    account_id: String,
+    architecture: String,
+    availability_zone: String,
+    image_id: String,
+    instance_id: String,
+    instance_type: String,
+    private_ip: Option<String>,
+    region: String,
+}
+
+impl InstanceIdentityDocument {
+    /// ID of the AWS account that owns the instance
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// Architecture of the AMI, e.g. `x86_64`
+    pub fn architecture(&self) -> &str {
+        &self.architecture
+    }
+
+    /// Availability zone the instance is running in
+    pub fn availability_zone(&self) -> &str {
+        &self.availability_zone
+    }
+
+    /// ID of the AMI used to launch the instance
+    pub fn image_id(&self) -> &str {
+        &self.image_id
+    }
+
+    /// ID of the instance
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Instance type, e.g. `t2.micro`
+    pub fn instance_type(&self) -> &str {
+        &self.instance_type
+    }
+
+    /// Private IPv4 address of the instance
+    pub fn private_ip(&self) -> Option<&str> {
+        self.private_ip.as_deref()
+    }
+
+    /// Region the instance is running in
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+}
+
+impl FromStr for InstanceIdentityDocument {
+    type Err = ImdsError;
+
+    fn from_str(document: &str) -> Result<Self, Self::Err> {
+        let mut fields = parse_string_fields(document).map_err(invalid_response)?;
+        Self::from_fields(&mut fields).map_err(|field| {
+            invalid_response(format!(
+                "instance identity document did not contain `{}`",
+                field
+            ))
+        })
+    }
+}
+
+impl InstanceIdentityDocument {
+    /// Build the document from its fields, returning the name of the first missing required field
+    fn from_fields(fields: &mut HashMap<String, String>) -> Result<Self, &'static str> {
+        let mut required = |field: &'static str| fields.remove(field).ok_or(field);
+        Ok(InstanceIdentityDocument {
+            account_id: required("accountId")?,
+            architecture: required("architecture")?,
+            availability_zone: required("availabilityZone")?,
+            image_id: required("imageId")?,
+            instance_id: required("instanceId")?,
+            instance_type: required("instanceType")?,
+            region: required("region")?,
+            private_ip: fields.remove("privateIp"),
+        })
+    }
+}
+
+/// Action that will be taken on a spot instance that is being interrupted
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SpotAction {
+    /// The instance will be hibernated
+    Hibernate,
+    /// The instance will be stopped
+    Stop,
+    /// The instance will be terminated
+    Terminate,
+    /// An action that is not known to this version of the SDK
+    Unknown(String),
+}
+
+impl From<&str> for SpotAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "hibernate" => SpotAction::Hibernate,
+            "stop" => SpotAction::Stop,
+            "terminate" => SpotAction::Terminate,
+            other => SpotAction::Unknown(other.to_string()),
+        }
+    }
+}
+
+/// Spot instance interruption notice, loaded from `/latest/meta-data/spot/instance-action`
+///
+/// See [spot instance interruption notices](https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/spot-instance-termination-notices.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpotInstanceAction {
+    action: SpotAction,
+    time: SystemTime,
+}
+
+impl SpotInstanceAction {
+    /// Action that will be taken
+    pub fn action(&self) -> &SpotAction {
+        &self.action
+    }
+
+    /// Time at which the action will be taken
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+}
+
+impl FromStr for SpotInstanceAction {
+    type Err = ImdsError;
+
+    fn from_str(notice: &str) -> Result<Self, Self::Err> {
+        let fields = parse_string_fields(notice).map_err(invalid_response)?;
+        let action = fields
+            .get("action")
+            .ok_or_else(|| invalid_response("spot instance action did not contain `action`"))?;
+        let time = fields
+            .get("time")
+            .ok_or_else(|| invalid_response("spot instance action did not contain `time`"))?;
+        Ok(SpotInstanceAction {
+            action: SpotAction::from(action.as_str()),
+            time: parse_expiration(time).map_err(|err| invalid_response(err.to_string()))?,
+        })
+    }
+}
+
+/// Network interface attached to the instance, loaded from `/latest/meta-data/network/interfaces/macs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkInterface {
+    pub(super) mac: String,
+    pub(super) device_number: u32,
+    pub(super) interface_id: String,
+    pub(super) local_ipv4s: Vec<String>,
+    pub(super) ipv6s: Vec<String>,
+    pub(super) subnet_id: String,
+    pub(super) vpc_id: String,
+    pub(super) security_group_ids: Vec<String>,
+}
+
+impl NetworkInterface {
+    /// MAC address of the interface
+    pub fn mac(&self) -> &str {
+        &self.mac
+    }
+
+    /// Device number of the interface, `0` for the primary interface
+    pub fn device_number(&self) -> u32 {
+        self.device_number
+    }
+
+    /// ID of the interface, e.g. `eni-0123456789abcdef0`
+    pub fn interface_id(&self) -> &str {
+        &self.interface_id
+    }
+
+    /// Private IPv4 addresses of the interface
+    pub fn local_ipv4s(&self) -> &[String] {
+        &self.local_ipv4s
+    }
+
+    /// IPv6 addresses of the interface
+    pub fn ipv6s(&self) -> &[String] {
+        &self.ipv6s
+    }
+
+    /// ID of the subnet the interface is in
+    pub fn subnet_id(&self) -> &str {
+        &self.subnet_id
+    }
+
+    /// ID of the VPC the interface is in
+    pub fn vpc_id(&self) -> &str {
+        &self.vpc_id
+    }
+
+    /// IDs of the security groups applied to the interface
+    pub fn security_group_ids(&self) -> &[String] {
+        &self.security_group_ids
+    }
+}
+
+/// Time-to-live of cached typed metadata
+#[derive(Debug, Clone)]
+pub(super) struct MetadataTtls {
+    pub(super) identity_document: Duration,
+    pub(super) instance_tags: Duration,
+    pub(super) spot_instance_action: Duration,
+    pub(super) network_interfaces: Duration,
+}
+
+impl Default for MetadataTtls {
+    fn default() -> Self {
+        MetadataTtls {
+            identity_document: DEFAULT_IDENTITY_DOCUMENT_TTL,
+            instance_tags: DEFAULT_INSTANCE_TAGS_TTL,
+            spot_instance_action: DEFAULT_SPOT_INSTANCE_ACTION_TTL,
+            network_interfaces: DEFAULT_NETWORK_INTERFACES_TTL,
+        }
+    }
+}
+
+/// Cache for a single category of typed metadata
+#[derive(Debug)]
+pub(super) struct MetadataCache<T> {
+    ttl: Duration,
+    cache: ExpiringCache<T, ImdsError>,
+}
+
+impl<T: Clone> MetadataCache<T> {
+    pub(super) fn new(ttl: Duration) -> Self {
+        MetadataCache {
+            ttl,
+            cache: ExpiringCache::new(Duration::ZERO),
+        }
+    }
+
+    /// Return the cached value if it is still valid, otherwise load a new one with `load`
+    pub(super) async fn get_or_load<F, Fut>(
+        &self,
+        time: &TimeSource,
+        load: F,
+    ) -> Result<T, ImdsError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, ImdsError>>,
+    {
+        let now = time.now();
+        if let Some(value) = self.cache.yield_or_clear_if_expired(now).await {
+            return Ok(value);
+        }
+        let ttl = self.ttl;
+        self.cache
+            .get_or_load(|| async move { Ok((load().await?, now + ttl)) })
+            .await
+    }
+}
+
+/// Caches for every category of typed metadata
+#[derive(Debug)]
+pub(super) struct MetadataCaches {
+    pub(super) identity_document: MetadataCache<InstanceIdentityDocument>,
+    pub(super) instance_tags: MetadataCache<HashMap<String, String>>,
+    pub(super) spot_instance_action: MetadataCache<Option<SpotInstanceAction>>,
+    pub(super) network_interfaces: MetadataCache<Vec<NetworkInterface>>,
+}
+
+impl MetadataCaches {
+    pub(super) fn new(ttls: &MetadataTtls) -> Self {
+        MetadataCaches {
+            identity_document: MetadataCache::new(ttls.identity_document),
+            instance_tags: MetadataCache::new(ttls.instance_tags),
+            spot_instance_action: MetadataCache::new(ttls.spot_instance_action),
+            network_interfaces: MetadataCache::new(ttls.network_interfaces),
+        }
+    }
+}
+
+/// Split a newline delimited IMDS listing into its entries
+pub(super) fn split_lines(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+pub(super) fn invalid_response(message: impl Into<Cow<'static, str>>) -> ImdsError {
+    ImdsError::Unexpected(
+        format!(
+            "IMDS returned an invalid response: {}",
+            message.into().as_ref()
+        )
+        .into(),
+    )
+}
+
+/// Parse the string fields of a flat JSON object, skipping values of any other type
+fn parse_string_fields(document: &str) -> Result<HashMap<String, String>, Cow<'static, str>> {
+    let json_error = |err: aws_smithy_json::deserialize::Error| Cow::Owned(err.to_string());
+    let mut tokens = json_token_iter(document.as_bytes()).peekable();
+    if !matches!(
+        tokens.next().transpose().map_err(json_error)?,
+        Some(Token::StartObject { .. })
+    ) {
+        return Err("expected a JSON document starting with `{`".into());
+    }
+    let mut fields = HashMap::new();
+    loop {
+        match tokens.next().transpose().map_err(json_error)? {
+            Some(Token::EndObject { .. }) => break,
+            Some(Token::ObjectKey { key, .. }) => {
+                if let Some(Ok(Token::ValueString { value, .. })) = tokens.peek() {
+                    let key = key.to_unescaped().map_err(|err| err.to_string())?;
+                    let value = value.to_unescaped().map_err(|err| err.to_string())?;
+                    fields.insert(key.into_owned(), value.into_owned());
+                }
+                skip_value(&mut tokens).map_err(json_error)?;
+            }
+            other => return Err(format!("expected object key, found: {:?}", other).into()),
+        }
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::imds::client::metadata::{InstanceIdentityDocument, SpotAction, SpotInstanceAction};
+
+    #[test]
+    fn parse_identity_document() {
+        let document: InstanceIdentityDocument = r#"{
+            "accountId" : "123456789012",
+            "architecture" : "x86_64",
+            "availabilityZone" : "us-west-2b",
+            "billingProducts" : null,
+            "devpayProductCodes" : null,
+            "marketplaceProductCodes" : [ "1abc2defghijklm3nopqrs4tu" ],
+            "imageId" : "ami-5fb8c835",
+            "instanceId" : "i-1234567890abcdef0",
+            "instanceType" : "t2.micro",
+            "kernelId" : null,
+            "pendingTime" : "2016-11-19T16:32:11Z",
+            "privateIp" : "10.158.112.84",
+            "ramdiskId" : null,
+            "region" : "us-west-2",
+            "version" : "2017-09-30"
+        }"#
+        .parse()
+        .expect("valid document");
+        assert_eq!("123456789012", document.account_id());
+        assert_eq!("us-west-2b", document.availability_zone());
+        assert_eq!("i-1234567890abcdef0", document.instance_id());
+        assert_eq!(Some("10.158.112.84"), document.private_ip());
+        assert_eq!("us-west-2", document.region());
+
+        let err = r#"{ "accountId": "123456789012" }"#
+            .parse::<InstanceIdentityDocument>()
+            .expect_err("missing fields");
+        assert!(
+            format!("{}", err).contains("instance identity document did not contain"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn parse_spot_instance_action() {
+        let action: SpotInstanceAction =
+            r#"{"action": "terminate", "time": "2017-09-18T08:22:00Z"}"#
+                .parse()
+                .expect("valid notice");
+        assert_eq!(&SpotAction::Terminate, action.action());
+        assert_eq!(UNIX_EPOCH + Duration::from_secs(1505722920), action.time());
+
+        let action: SpotInstanceAction = r#"{"action": "reboot", "time": "2017-09-18T08:22:00Z"}"#
+            .parse()
+            .expect("valid notice");
+        assert_eq!(&SpotAction::Unknown("reboot".into()), action.action());
+    }
+}
@@ -13,6 +13,8 @@
 //! - Cache the token according to the TTL
 //! - Retry token loading when it fails
 //! - Attach the token to the request in the `x-aws-ec2-metadata-token` header
+//! - Optionally, fall back to IMDSv1 (sending the request without a token) when the token API
+//!   is unavailable
 
 use std::future::Future;
 use std::pin::Pin;
@@ -34,7 +36,7 @@ use http::{HeaderValue, Uri};
 
 use crate::cache::ExpiringCache;
 use crate::imds::client::{ImdsError, ImdsErrorPolicy, TokenError};
-use aws_smithy_client::retry;
+use aws_smithy_client::{retry, SdkError};
 use aws_smithy_types::timeout::TimeoutConfig;
 use std::fmt::{Debug, Formatter};
 
@@ -68,6 +70,7 @@ pub(super) struct TokenMiddleware {
     time_source: TimeSource,
     endpoint: Endpoint,
     token_ttl: Duration,
+    imds_v1_fallback: bool,
 }
 
 impl Debug for TokenMiddleware {
@@ -82,6 +85,7 @@ impl TokenMiddleware {
         time_source: TimeSource,
         endpoint: Endpoint,
         token_ttl: Duration,
+        imds_v1_fallback: bool,
         retry_config: retry::Config,
         timeout_config: TimeoutConfig,
     ) -> Self {
@@ -98,6 +102,7 @@ impl TokenMiddleware {
             time_source,
             endpoint,
             token_ttl,
+            imds_v1_fallback,
         }
     }
     async fn add_token(&self, request: Request) -> Result<Request, ImdsError> {
@@ -112,7 +117,15 @@ impl TokenMiddleware {
                     .get_or_load(|| async move { self.get_token().await })
                     .await
             }
-        }?;
+        };
+        let token = match token {
+            Ok(token) => token,
+            Err(err) if self.imds_v1_fallback && token_api_unavailable(&err) => {
+                tracing::debug!(err = %err, "failed to load IMDS session token, falling back to IMDSv1");
+                return Ok(request);
+            }
+            Err(err) => return Err(err),
+        };
         request.augment(|mut request, _| {
             request
                 .headers_mut()
@@ -149,6 +162,19 @@ impl TokenMiddleware {
     }
 }
 
+/// Returns true if the token API responded in a way that indicates IMDSv2 is unavailable
+///
+/// IMDS may respond with 403 when the token API is blocked, and IMDS implementations that
+/// predate IMDSv2 may respond with 404 or 405.
+fn token_api_unavailable(err: &ImdsError) -> bool {
+    match err {
+        ImdsError::FailedToLoadToken(SdkError::ServiceError { raw, .. }) => {
+            matches!(raw.http().status().as_u16(), 403..=405)
+        }
+        _ => false,
+    }
+}
+
 impl AsyncMapRequest for TokenMiddleware {
     type Error = ImdsError;
     type Future = Pin<Box<dyn Future<Output = Result<Request, Self::Error>> + Send + 'static>>;
@@ -7,6 +7,13 @@
 //!
 //! # Important
 //! This credential provider will NOT fallback to IMDSv1. Ensure that IMDSv2 is enabled on your instances.
+//!
+//! # Static stability
+//! To keep instances working through short IMDS outages, the provider keeps the last credentials
+//! it successfully retrieved. If a later refresh fails, or IMDS returns credentials that have
+//! already expired, the expiration of those credentials is extended by a randomized window of
+//! 5 to 10 minutes and a warning is logged. Static stability can be disabled with
+//! [`Builder::static_stability`].
 
 use crate::imds;
 use crate::imds::client::{ImdsError, LazyClient};
@@ -14,11 +21,18 @@ use crate::json_credentials::{parse_json_credentials, JsonCredentials};
 use crate::provider_config::ProviderConfig;
 use aws_smithy_client::SdkError;
 use aws_types::credentials::{future, CredentialsError, ProvideCredentials};
-use aws_types::os_shim_internal::Env;
+use aws_types::os_shim_internal::{Env, TimeSource};
 use aws_types::{credentials, Credentials};
 
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
 use tokio::sync::OnceCell;
 
+/// Minimum amount of time the expiration of stale credentials is extended by
+const CREDENTIAL_EXTENSION_BASE: Duration = Duration::from_secs(5 * 60);
+/// Maximum random amount of time added to [`CREDENTIAL_EXTENSION_BASE`]
+const CREDENTIAL_EXTENSION_JITTER: Duration = Duration::from_secs(5 * 60);
+
 /// IMDSv2 Credentials Provider
 ///
 /// _Note: This credentials provider will NOT fallback to the IMDSv1 flow._
@@ -27,6 +41,9 @@ pub struct ImdsCredentialsProvider {
     client: LazyClient,
     env: Env,
     profile: OnceCell<String>,
+    time_source: TimeSource,
+    static_stability: bool,
+    last_retrieved_credentials: RwLock<Option<Credentials>>,
 }
 
 /// Builder for [`ImdsCredentialsProvider`]
@@ -35,6 +52,7 @@ pub struct Builder {
     provider_config: Option<ProviderConfig>,
     profile_override: Option<String>,
     imds_override: Option<imds::Client>,
+    static_stability: Option<bool>,
 }
 
 impl Builder {
@@ -71,6 +89,16 @@ impl Builder {
         self
     }
 
+    /// Enable or disable static stability (enabled by default)
+    ///
+    /// When enabled, the last successfully retrieved credentials are returned with an extended
+    /// expiration if IMDS can't be reached or returns expired credentials. When disabled, these
+    /// failures are returned as errors.
+    pub fn static_stability(mut self, enabled: bool) -> Self {
+        self.static_stability = Some(enabled);
+        self
+    }
+
     /// Create an [`ImdsCredentialsProvider`] from this builder.
     pub fn build(self) -> ImdsCredentialsProvider {
         let provider_config = self.provider_config.unwrap_or_default();
@@ -88,6 +116,9 @@ impl Builder {
             client,
             env,
             profile,
+            time_source: provider_config.time_source(),
+            static_stability: self.static_stability.unwrap_or(true),
+            last_retrieved_credentials: RwLock::new(None),
         }
     }
 }
@@ -143,6 +174,10 @@ impl ImdsCredentialsProvider {
                 );
                 Err(CredentialsError::not_loaded("received 404 from IMDS"))
             }
+            Err(ImdsError::Disabled) => {
+                tracing::debug!("IMDS disabled by the profile");
+                Err(CredentialsError::not_loaded("IMDS disabled by the profile"))
+            }
             Err(ImdsError::FailedToLoadToken(SdkError::DispatchFailure(err))) => Err(
                 CredentialsError::not_loaded(format!("could not communicate with imds: {}", err)),
             ),
@@ -150,13 +185,66 @@ impl ImdsCredentialsProvider {
         }
     }
 
+    /// Returns a new expiration for credentials that are used beyond their original expiration
+    fn extended_expiration(&self, now: SystemTime) -> SystemTime {
+        now + CREDENTIAL_EXTENSION_BASE + CREDENTIAL_EXTENSION_JITTER.mul_f64(fastrand::f64())
+    }
+
     async fn credentials(&self) -> credentials::Result {
         if self.imds_disabled() {
             tracing::debug!("IMDS disabled because $AWS_EC2_METADATA_DISABLED was set to `true`");
             return Err(CredentialsError::not_loaded(
-                "IMDS disabled by $AWS_ECS_METADATA_DISABLED",
+                "IMDS disabled by $AWS_EC2_METADATA_DISABLED",
             ));
         }
+        let result = self.retrieve_credentials().await;
+        if !self.static_stability {
+            return result;
+        }
+        let now = self.time_source.now();
+        match result {
+            Ok(mut credentials) => {
+                *self.last_retrieved_credentials.write().unwrap() = Some(credentials.clone());
+                if matches!(credentials.expiry(), Some(expiry) if expiry <= now) {
+                    let expiry = self.extended_expiration(now);
+                    tracing::warn!(
+                        new_expiry = ?expiry,
+                        "IMDS returned expired credentials. Attempting to extend the expiration of \
+                        these credentials, but they may be rejected by AWS services."
+                    );
+                    *credentials.expiry_mut() = Some(expiry);
+                }
+                Ok(credentials)
+            }
+            Err(err) => {
+                let last_retrieved = self.last_retrieved_credentials.read().unwrap().clone();
+                match last_retrieved {
+                    Some(mut credentials) => {
+                        let expiry = credentials.expiry();
+                        // still valid credentials don't need to be extended
+                        if matches!(expiry, Some(expiry) if expiry > now + CREDENTIAL_EXTENSION_BASE)
+                        {
+                            tracing::warn!(error = %err, "failed to refresh credentials from IMDS, using the previously retrieved credentials");
+                        } else {
+                            let expiry = self.extended_expiration(now);
+                            tracing::warn!(
+                                error = %err,
+                                new_expiry = ?expiry,
+                                "failed to refresh credentials from IMDS. Attempting to extend the \
+                                expiration of the previously retrieved credentials, but they may \
+                                be rejected by AWS services."
+                            );
+                            *credentials.expiry_mut() = Some(expiry);
+                        }
+                        Ok(credentials)
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
+
+    async fn retrieve_credentials(&self) -> credentials::Result {
         tracing::debug!("loading credentials from IMDS");
         let get_profile = self.get_profile_uncached();
         let profile = self.profile.get_or_try_init(|| get_profile).await?;
@@ -204,3 +292,255 @@ impl ImdsCredentialsProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::imds::client::test::{imds_request, imds_response, token_request, token_response};
+    use crate::imds::credentials::ImdsCredentialsProvider;
+    use crate::provider_config::ProviderConfig;
+    use aws_hyper::DynConnector;
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_client::test_connection::TestConnection;
+    use aws_types::credentials::CredentialsError;
+    use aws_types::credentials::ProvideCredentials;
+    use aws_types::os_shim_internal::{Env, Fs, ManualTimeSource, TimeSource};
+    use tracing_test::traced_test;
+
+    const TOKEN_A: &str = "token_a";
+    const CREDENTIALS: &str = r#"{
+  "Code" : "Success",
+  "LastUpdated" : "2021-09-20T21:42:26Z",
+  "Type" : "AWS-HMAC",
+  "AccessKeyId" : "ASIARTEST",
+  "SecretAccessKey" : "testsecret",
+  "Token" : "testtoken",
+  "Expiration" : "2021-09-21T04:16:53Z"
+}"#;
+    /// Expiration of [`CREDENTIALS`] in seconds since the epoch
+    const EXPIRATION_SECS: u64 = 1632197813;
+
+    fn error_response() -> http::Response<&'static str> {
+        http::Response::builder().status(404).body("").unwrap()
+    }
+
+    fn provider(
+        connection: &TestConnection<&'static str>,
+        time: &ManualTimeSource,
+    ) -> ImdsCredentialsProvider {
+        ImdsCredentialsProvider::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_http_connector(DynConnector::new(connection.clone()))
+                    .with_sleep(TokioSleep::new())
+                    .with_time_source(TimeSource::manual(time)),
+            )
+            .build()
+    }
+
+    fn assert_extended(expiry: Option<std::time::SystemTime>, now_secs: u64) {
+        let expiry = expiry
+            .expect("credentials have an expiry")
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        assert!(
+            expiry >= Duration::from_secs(now_secs + 5 * 60)
+                && expiry <= Duration::from_secs(now_secs + 10 * 60),
+            "expiry {:?} should be extended by 5 to 10 minutes",
+            expiry
+        );
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn last_credentials_are_used_when_imds_fails() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials",
+                    TOKEN_A,
+                ),
+                imds_response("profile-name"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                imds_response(CREDENTIALS),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                error_response(),
+            ),
+        ]);
+        let mut time =
+            ManualTimeSource::new(UNIX_EPOCH + Duration::from_secs(EXPIRATION_SECS - 3600));
+        let provider = provider(&connection, &time);
+        let creds = provider.provide_credentials().await.expect("valid creds");
+        assert_eq!(
+            Some(UNIX_EPOCH + Duration::from_secs(EXPIRATION_SECS)),
+            creds.expiry()
+        );
+
+        let now_secs = EXPIRATION_SECS + 60;
+        time.set_time(UNIX_EPOCH + Duration::from_secs(now_secs));
+        let stale = provider
+            .provide_credentials()
+            .await
+            .expect("previous credentials are used");
+        assert_eq!("ASIARTEST", stale.access_key_id());
+        assert_extended(stale.expiry(), now_secs);
+        assert!(logs_contain(
+            "failed to refresh credentials from IMDS. Attempting to extend the expiration"
+        ));
+        connection.assert_requests_match(&[]);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn expired_credentials_are_extended() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials",
+                    TOKEN_A,
+                ),
+                imds_response("profile-name"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                imds_response(CREDENTIALS),
+            ),
+        ]);
+        let now_secs = EXPIRATION_SECS + 3600;
+        let time = ManualTimeSource::new(UNIX_EPOCH + Duration::from_secs(now_secs));
+        let creds = provider(&connection, &time)
+            .provide_credentials()
+            .await
+            .expect("valid creds");
+        assert_extended(creds.expiry(), now_secs);
+        assert!(logs_contain("IMDS returned expired credentials"));
+    }
+
+    #[tokio::test]
+    async fn errors_without_previous_credentials() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials",
+                    TOKEN_A,
+                ),
+                imds_response("profile-name"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                error_response(),
+            ),
+        ]);
+        let time = ManualTimeSource::new(UNIX_EPOCH + Duration::from_secs(EXPIRATION_SECS));
+        provider(&connection, &time)
+            .provide_credentials()
+            .await
+            .expect_err("no credentials were retrieved before");
+    }
+
+    #[tokio::test]
+    async fn static_stability_can_be_disabled() {
+        let connection = TestConnection::new(vec![
+            (
+                token_request("http://169.254.169.254", 21600),
+                token_response(21600, TOKEN_A),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials",
+                    TOKEN_A,
+                ),
+                imds_response("profile-name"),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                imds_response(CREDENTIALS),
+            ),
+            (
+                imds_request(
+                    "http://169.254.169.254/latest/meta-data/iam/security-credentials/profile-name",
+                    TOKEN_A,
+                ),
+                error_response(),
+            ),
+        ]);
+        let time = ManualTimeSource::new(UNIX_EPOCH + Duration::from_secs(EXPIRATION_SECS + 60));
+        let provider = ImdsCredentialsProvider::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_http_connector(DynConnector::new(connection.clone()))
+                    .with_sleep(TokioSleep::new())
+                    .with_time_source(TimeSource::manual(&time)),
+            )
+            .static_stability(false)
+            .build();
+        let expired = provider.provide_credentials().await.expect("valid creds");
+        assert_eq!(
+            Some(UNIX_EPOCH + Duration::from_secs(EXPIRATION_SECS)),
+            expired.expiry()
+        );
+        provider
+            .provide_credentials()
+            .await
+            .expect_err("static stability is disabled");
+    }
+
+    #[tokio::test]
+    async fn imds_disabled_by_profile() {
+        let connection = TestConnection::<&'static str>::new(vec![]);
+        let provider = ImdsCredentialsProvider::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[("HOME", "/home")]))
+                    .with_fs(Fs::from_slice(&[(
+                        "/home/.aws/config",
+                        "[default]\nec2_metadata_disabled = true",
+                    )]))
+                    .with_http_connector(DynConnector::new(connection.clone()))
+                    .with_sleep(TokioSleep::new()),
+            )
+            .build();
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("IMDS is disabled");
+        assert!(
+            matches!(err, CredentialsError::CredentialsNotLoaded { .. }),
+            "{}",
+            err
+        );
+        assert!(connection.requests().is_empty());
+    }
+}
@@ -5,7 +5,7 @@
 
 //! IMDS Region Provider
 //!
-//! Load region from IMDS from `/latest/meta-data/placement/region`
+//! Load region from the [instance identity document](crate::imds::Client::instance_identity_document)
 //! This provider has a 5 second timeout.
 
 use crate::imds;
@@ -28,8 +28,6 @@ pub struct ImdsRegionProvider {
     env: Env,
 }
 
-const REGION_PATH: &str = "/latest/meta-data/placement/region";
-
 impl ImdsRegionProvider {
     /// Builder for [`ImdsRegionProvider`]
     pub fn builder() -> Builder {
@@ -45,18 +43,23 @@ impl ImdsRegionProvider {
 
     /// Load a region from IMDS
     ///
-    /// This provider uses the API `/latest/meta-data/placement/region`
+    /// This provider uses the cached [instance identity document](imds::Client::instance_identity_document)
     pub async fn region(&self) -> Option<Region> {
         if self.imds_disabled() {
             tracing::debug!("not using IMDS to load region, IMDS is disabled");
             return None;
         }
         let client = self.client.client().await.ok()?;
-        match client.get(REGION_PATH).await {
-            Ok(region) => {
+        match client.instance_identity_document().await {
+            Ok(document) => {
+                let region = document.region().to_string();
                 tracing::info!(region = % region, "loaded region from IMDS");
                 Some(Region::new(region))
             }
+            Err(imds::client::ImdsError::Disabled) => {
+                tracing::debug!("not using IMDS to load region, IMDS is disabled by the profile");
+                None
+            }
             Err(err) => {
                 tracing::warn!(err = % err, "failed to load region from IMDS");
                 None
@@ -127,8 +130,21 @@ mod test {
     use aws_smithy_async::rt::sleep::TokioSleep;
     use aws_smithy_client::test_connection::TestConnection;
     use aws_smithy_http::body::SdkBody;
+    use aws_types::os_shim_internal::{Env, Fs};
     use tracing_test::traced_test;
 
+    const IDENTITY_DOCUMENT: &str = r#"{
+        "accountId" : "123456789012",
+        "architecture" : "x86_64",
+        "availabilityZone" : "eu-west-1a",
+        "imageId" : "ami-5fb8c835",
+        "instanceId" : "i-1234567890abcdef0",
+        "instanceType" : "t2.micro",
+        "privateIp" : "10.158.112.84",
+        "region" : "eu-west-1",
+        "version" : "2017-09-30"
+    }"#;
+
     #[tokio::test]
     async fn load_region() {
         let conn = TestConnection::new(vec![
@@ -138,10 +154,10 @@ mod test {
             ),
             (
                 imds_request(
-                    "http://169.254.169.254/latest/meta-data/placement/region",
+                    "http://169.254.169.254/latest/dynamic/instance-identity/document",
                     "token",
                 ),
-                imds_response("eu-west-1"),
+                imds_response(IDENTITY_DOCUMENT),
             ),
         ]);
         let provider = ImdsRegionProvider::builder()
@@ -178,4 +194,23 @@ mod test {
         assert!(logs_contain("failed to load region from IMDS"));
         assert!(logs_contain("IMDS is disabled"));
     }
+
+    #[tokio::test]
+    async fn no_region_imds_disabled_by_profile() {
+        let conn = TestConnection::<&'static str>::new(vec![]);
+        let provider = ImdsRegionProvider::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_env(Env::from_slice(&[("HOME", "/home")]))
+                    .with_fs(Fs::from_slice(&[(
+                        "/home/.aws/config",
+                        "[default]\nec2_metadata_disabled = true",
+                    )]))
+                    .with_http_connector(DynConnector::new(conn.clone()))
+                    .with_sleep(TokioSleep::new()),
+            )
+            .build();
+        assert_eq!(provider.region().await, None);
+        assert!(conn.requests().is_empty());
+    }
 }
@@ -173,16 +173,7 @@ pub(crate) fn parse_json_credentials(
                 session_token.ok_or(InvalidJsonCredentials::MissingField("Token"))?;
             let expiration =
                 expiration.ok_or(InvalidJsonCredentials::MissingField("Expiration"))?;
-            let expiration = SystemTime::try_from(
-                DateTime::from_str(expiration.as_ref(), Format::DateTime).map_err(|err| {
-                    InvalidJsonCredentials::Other(format!("invalid date: {}", err).into())
-                })?,
-            )
-            .map_err(|_| {
-                InvalidJsonCredentials::Other(
-                    "credential expiration time cannot be represented by a SystemTime".into(),
-                )
-            })?;
+            let expiration = parse_expiration(expiration.as_ref())?;
             Ok(JsonCredentials::RefreshableCredentials {
                 access_key_id,
                 secret_access_key,
@@ -197,6 +188,20 @@ pub(crate) fn parse_json_credentials(
     }
 }
 
+/// Parse an RFC-3339 credentials expiration timestamp into a `SystemTime`
+pub(crate) fn parse_expiration(expiration: &str) -> Result<SystemTime, InvalidJsonCredentials> {
+    SystemTime::try_from(
+        DateTime::from_str(expiration, Format::DateTime).map_err(|err| {
+            InvalidJsonCredentials::Other(format!("invalid date: {}", err).into())
+        })?,
+    )
+    .map_err(|_| {
+        InvalidJsonCredentials::Other(
+            "credential expiration time cannot be represented by a SystemTime".into(),
+        )
+    })
+}
+
 #[cfg(test)]
 mod test {
     use crate::json_credentials::{
@@ -75,20 +75,34 @@ pub mod web_identity_token;
 #[cfg(feature = "http-provider")]
 pub mod ecs;
 
+#[cfg(feature = "credential-process")]
+pub mod credential_process;
+
+#[cfg(feature = "sso")]
+pub mod sso;
+
 pub mod provider_config;
 
-#[cfg(any(feature = "meta", feature = "default-provider"))]
+#[cfg(any(feature = "meta", feature = "default-provider", feature = "sts"))]
 mod cache;
 
 #[cfg(feature = "imds")]
 pub mod imds;
 
-#[cfg(any(feature = "http-provider", feature = "imds"))]
+#[cfg(any(
+    feature = "http-provider",
+    feature = "imds",
+    feature = "credential-process",
+    feature = "sso"
+))]
 mod json_credentials;
 
 #[cfg(feature = "http-provider")]
 mod http_provider;
 
+#[cfg(feature = "default-provider")]
+mod service_config;
+
 // Re-export types from smithy-types
 pub use aws_smithy_types::retry::RetryConfig;
 pub use aws_smithy_types::timeout::TimeoutConfig;
@@ -128,14 +142,19 @@ mod loader {
     use std::sync::Arc;
 
     use aws_smithy_async::rt::sleep::{default_async_sleep, AsyncSleep};
+    use aws_smithy_types::pool::PoolConfig;
     use aws_smithy_types::retry::RetryConfig;
     use aws_smithy_types::timeout::TimeoutConfig;
     use aws_types::app_name::AppName;
     use aws_types::config::Config;
     use aws_types::credentials::{ProvideCredentials, SharedCredentialsProvider};
 
-    use crate::default_provider::{app_name, credentials, region, retry_config, timeout_config};
+    use crate::default_provider::{
+        app_name, credentials, region, retry_config, timeout_config, use_dual_stack, use_fips,
+    };
     use crate::meta::region::ProvideRegion;
+    use crate::provider_config::ProviderConfig;
+    use crate::service_config::ServiceConfigSource;
 
     /// Load a cross-service [`Config`](aws_types::config::Config) from the environment
     ///
@@ -147,10 +166,14 @@ mod loader {
     pub struct ConfigLoader {
         app_name: Option<AppName>,
         credentials_provider: Option<SharedCredentialsProvider>,
+        endpoint_url: Option<String>,
+        pool_config: Option<PoolConfig>,
         region: Option<Box<dyn ProvideRegion>>,
         retry_config: Option<RetryConfig>,
         sleep: Option<Arc<dyn AsyncSleep>>,
         timeout_config: Option<TimeoutConfig>,
+        use_dual_stack: Option<bool>,
+        use_fips: Option<bool>,
     }
 
     impl ConfigLoader {
@@ -207,6 +230,26 @@ mod loader {
             self
         }
 
+        /// Set the connection pool config used to build [`Config`](aws_types::config::Config).
+        /// **Note: This only configures the connection pool of clients for AWS services.**
+        ///
+        /// # Examples
+        /// ```rust
+        /// # use std::time::Duration;
+        /// # use aws_smithy_types::pool::PoolConfig;
+        /// # async fn create_config() {
+        ///  let pool_config = PoolConfig::new().with_max_idle_per_host(Some(4));
+        ///  let config = aws_config::from_env()
+        ///     .pool_config(pool_config)
+        ///     .load()
+        ///     .await;
+        /// # }
+        /// ```
+        pub fn pool_config(mut self, pool_config: PoolConfig) -> Self {
+            self.pool_config = Some(pool_config);
+            self
+        }
+
         /// Override the sleep implementation for this [`ConfigLoader`]. The sleep implementation
         /// is used to create timeout futures.
         pub fn sleep_impl(mut self, sleep: impl AsyncSleep + 'static) -> Self {
@@ -240,6 +283,72 @@ mod loader {
             self
         }
 
+        /// Override the endpoint URL used to build [`Config`](aws_types::config::Config).
+        ///
+        /// When set, service clients send requests to this URL instead of resolving an endpoint
+        /// from the region. This takes precedence over endpoint URLs set in the environment or the
+        /// shared config file.
+        ///
+        /// When not set, each service client loads its endpoint URL from the following sources in
+        /// order:
+        /// 1. The `AWS_ENDPOINT_URL_<SERVICE_ID>` environment variable, e.g. `AWS_ENDPOINT_URL_DYNAMODB`
+        /// 2. The `endpoint_url` of the service in a `[services <name>]` section of the config file
+        /// 3. The `AWS_ENDPOINT_URL` environment variable
+        /// 4. The `endpoint_url` property of the active profile
+        ///
+        /// # Examples
+        /// ```rust
+        /// # async fn create_config() {
+        /// let config = aws_config::from_env()
+        ///     .endpoint_url("http://localhost:4566")
+        ///     .load()
+        ///     .await;
+        /// # }
+        /// ```
+        pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+            self.endpoint_url = Some(endpoint_url.into());
+            self
+        }
+
+        /// Override whether service clients only use FIPS endpoints
+        ///
+        /// When enabled, service clients return an error for regions without a FIPS endpoint
+        /// rather than using a non-FIPS endpoint. By default, the setting is loaded from the
+        /// `AWS_USE_FIPS_ENDPOINT` environment variable or the `use_fips_endpoint` profile property.
+        ///
+        /// # Examples
+        /// ```rust
+        /// # async fn create_config() {
+        /// let config = aws_config::from_env()
+        ///     .use_fips(true)
+        ///     .load()
+        ///     .await;
+        /// # }
+        /// ```
+        pub fn use_fips(mut self, use_fips: bool) -> Self {
+            self.use_fips = Some(use_fips);
+            self
+        }
+
+        /// Override whether service clients only use dual-stack (IPv4 and IPv6) endpoints
+        ///
+        /// By default, the setting is loaded from the `AWS_USE_DUALSTACK_ENDPOINT` environment
+        /// variable or the `use_dualstack_endpoint` profile property.
+        ///
+        /// # Examples
+        /// ```rust
+        /// # async fn create_config() {
+        /// let config = aws_config::from_env()
+        ///     .use_dual_stack(true)
+        ///     .load()
+        ///     .await;
+        /// # }
+        /// ```
+        pub fn use_dual_stack(mut self, use_dual_stack: bool) -> Self {
+            self.use_dual_stack = Some(use_dual_stack);
+            self
+        }
+
         /// Load the default configuration chain
         ///
         /// If fields have been overridden during builder construction, the override values will be used.
@@ -297,6 +406,20 @@ mod loader {
                 SharedCredentialsProvider::new(builder.build().await)
             };
 
+            let use_fips = if self.use_fips.is_some() {
+                self.use_fips
+            } else {
+                use_fips::default_provider().use_fips().await
+            };
+
+            let use_dual_stack = if self.use_dual_stack.is_some() {
+                self.use_dual_stack
+            } else {
+                use_dual_stack::default_provider().use_dual_stack().await
+            };
+
+            let service_config = ServiceConfigSource::load(&ProviderConfig::default()).await;
+
             let mut builder = Config::builder()
                 .region(region)
                 .retry_config(retry_config)
@@ -304,6 +427,13 @@ mod loader {
                 .credentials_provider(credentials_provider);
 
             builder.set_app_name(app_name);
+            builder.set_endpoint_url(self.endpoint_url);
+            builder.set_pool_config(self.pool_config);
+            builder.set_use_fips(use_fips);
+            builder.set_use_dual_stack(use_dual_stack);
+            if let Some(service_config) = service_config {
+                builder = builder.service_config(service_config);
+            }
             builder.set_sleep_impl(sleep_impl);
             builder.build()
         }
@@ -344,22 +474,13 @@ mod connector {
         hyper
     }
 
-    #[cfg(feature = "rustls")]
-    pub(crate) fn default_connector(
-        settings: &HttpSettings,
-        sleep: Option<Arc<dyn AsyncSleep>>,
-    ) -> Option<DynConnector> {
-        let hyper = base(settings, sleep).build(aws_smithy_client::conns::https());
-        Some(DynConnector::new(hyper))
-    }
-
-    #[cfg(all(not(feature = "rustls"), feature = "native-tls"))]
+    // `build_https` picks rustls over native-tls when both are enabled
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
     pub(crate) fn default_connector(
         settings: &HttpSettings,
         sleep: Option<Arc<dyn AsyncSleep>>,
     ) -> Option<DynConnector> {
-        let hyper = base(settings, sleep).build(aws_smithy_client::conns::native_tls());
-        Some(DynConnector::new(hyper))
+        Some(base(settings, sleep).build_https())
     }
 
     #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
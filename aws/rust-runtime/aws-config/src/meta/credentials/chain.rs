@@ -4,6 +4,8 @@
  */
 
 use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
 use aws_types::credentials::{self, future, CredentialsError, ProvideCredentials};
 use tracing::Instrument;
@@ -31,6 +33,7 @@ use tracing::Instrument;
 #[derive(Debug)]
 pub struct CredentialsProviderChain {
     providers: Vec<(Cow<'static, str>, Box<dyn ProvideCredentials>)>,
+    last_explanation: Mutex<Option<Arc<[ProviderExplanation]>>>,
 }
 
 impl CredentialsProviderChain {
@@ -41,6 +44,7 @@ impl CredentialsProviderChain {
     ) -> Self {
         CredentialsProviderChain {
             providers: vec![(name.into(), Box::new(provider))],
+            last_explanation: Mutex::new(None),
         }
     }
 
@@ -74,19 +78,52 @@ impl CredentialsProviderChain {
         )
     }
 
+    /// Report the outcome of every provider the last time the chain was evaluated
+    ///
+    /// Providers are not evaluated again, so this has no side effects. Providers after the one that
+    /// resolved credentials (or failed) are reported as [`ProviderOutcome::NotTried`]. Returns
+    /// `None` if the chain hasn't been evaluated yet.
+    pub fn last_explanation(&self) -> Option<Arc<[ProviderExplanation]>> {
+        self.last_explanation.lock().unwrap().clone()
+    }
+
     async fn credentials(&self) -> credentials::Result {
+        let mut outcomes = Vec::with_capacity(self.providers.len());
+        let result = self.evaluate(&mut outcomes).await;
+        let mut outcomes = outcomes.into_iter();
+        let explanation = self
+            .providers
+            .iter()
+            .map(|(name, _)| ProviderExplanation {
+                name: name.clone(),
+                outcome: outcomes.next().unwrap_or(ProviderOutcome::NotTried),
+            })
+            .collect();
+        *self.last_explanation.lock().unwrap() = Some(explanation);
+        result
+    }
+
+    /// Evaluates the providers in order, recording the outcome of each provider that was tried
+    async fn evaluate(&self, outcomes: &mut Vec<ProviderOutcome>) -> credentials::Result {
         for (name, provider) in &self.providers {
             let span = tracing::info_span!("load_credentials", provider = %name);
             match provider.provide_credentials().instrument(span).await {
                 Ok(credentials) => {
                     tracing::info!(provider = %name, "loaded credentials");
+                    outcomes.push(ProviderOutcome::Resolved);
                     return Ok(credentials);
                 }
                 Err(CredentialsError::CredentialsNotLoaded { context, .. }) => {
                     tracing::info!(provider = %name, context = %context, "provider in chain did not provide credentials");
+                    outcomes.push(ProviderOutcome::Skipped {
+                        reason: context.to_string(),
+                    });
                 }
                 Err(e) => {
                     tracing::warn!(provider = %name, error = %e, "provider failed to provide credentials");
+                    outcomes.push(ProviderOutcome::Failed {
+                        reason: e.to_string(),
+                    });
                     return Err(e);
                 }
             }
@@ -105,3 +142,158 @@ impl ProvideCredentials for CredentialsProviderChain {
         future::ProvideCredentials::new(self.credentials())
     }
 }
+
+/// Outcome of evaluating a single provider in a [`CredentialsProviderChain`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProviderOutcome {
+    /// The provider returned credentials
+    Resolved,
+
+    /// The provider was not configured, so the next provider in the chain was tried
+    Skipped {
+        /// Why the provider did not provide credentials
+        reason: String,
+    },
+
+    /// The provider failed, which ended evaluation of the chain
+    Failed {
+        /// The error returned by the provider
+        reason: String,
+    },
+
+    /// The provider was not evaluated because a previous provider resolved credentials or failed
+    NotTried,
+}
+
+/// Outcome of a named provider in a [`CredentialsProviderChain`]
+///
+/// Returned by [`CredentialsProviderChain::last_explanation`].
+#[derive(Debug)]
+pub struct ProviderExplanation {
+    name: Cow<'static, str>,
+    outcome: ProviderOutcome,
+}
+
+impl ProviderExplanation {
+    /// Name of the provider in the chain
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Outcome of evaluating the provider
+    pub fn outcome(&self) -> &ProviderOutcome {
+        &self.outcome
+    }
+}
+
+impl fmt::Display for ProviderExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            ProviderOutcome::Resolved => write!(f, "{}: resolved credentials", self.name),
+            ProviderOutcome::Skipped { reason } => write!(f, "{}: skipped ({})", self.name, reason),
+            ProviderOutcome::Failed { reason } => write!(f, "{}: failed ({})", self.name, reason),
+            ProviderOutcome::NotTried => write!(f, "{}: not tried", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use aws_types::credentials::{CredentialsError, ProvideCredentials};
+    use aws_types::Credentials;
+
+    use crate::meta::credentials::chain::ProviderOutcome;
+    use crate::meta::credentials::{provide_credentials_fn, CredentialsProviderChain};
+
+    #[tokio::test]
+    async fn explain_reports_every_provider() {
+        let chain = CredentialsProviderChain::first_try(
+            "First",
+            provide_credentials_fn(|| async {
+                Err(CredentialsError::not_loaded("not configured"))
+            }),
+        )
+        .or_else(
+            "Second",
+            Credentials::new("akid", "secret", None, None, "test"),
+        )
+        .or_else(
+            "Third",
+            provide_credentials_fn(|| async { unreachable!("not evaluated") }),
+        );
+        assert!(chain.last_explanation().is_none());
+        chain
+            .provide_credentials()
+            .await
+            .expect("second provider resolves credentials");
+        let explanation = chain.last_explanation().expect("the chain was evaluated");
+        let rendered: Vec<String> = explanation.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            vec![
+                "First: skipped (not configured)",
+                "Second: resolved credentials",
+                "Third: not tried"
+            ],
+            rendered
+        );
+    }
+
+    #[tokio::test]
+    async fn explain_stops_on_failure() {
+        let chain = CredentialsProviderChain::first_try(
+            "First",
+            provide_credentials_fn(|| async {
+                Err(CredentialsError::invalid_configuration("bad config"))
+            }),
+        )
+        .or_else(
+            "Second",
+            Credentials::new("akid", "secret", None, None, "test"),
+        );
+        chain
+            .provide_credentials()
+            .await
+            .expect_err("first provider fails");
+        let explanation = chain.last_explanation().expect("the chain was evaluated");
+        match explanation[0].outcome() {
+            ProviderOutcome::Failed { reason } => assert!(reason.contains("bad config")),
+            other => panic!("expected a failure, got {:?}", other),
+        }
+        assert!(matches!(
+            explanation[1].outcome(),
+            ProviderOutcome::NotTried
+        ));
+    }
+
+    #[tokio::test]
+    async fn explain_does_not_evaluate_providers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider_calls = calls.clone();
+        let chain = CredentialsProviderChain::first_try(
+            "Prompting",
+            provide_credentials_fn(move || {
+                let calls = provider_calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(Credentials::new("akid", "secret", None, None, "test"))
+                }
+            }),
+        );
+        chain
+            .provide_credentials()
+            .await
+            .expect("credentials resolve");
+        for _ in 0..3 {
+            let explanation = chain.last_explanation().expect("the chain was evaluated");
+            assert!(matches!(
+                explanation[0].outcome(),
+                ProviderOutcome::Resolved
+            ));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -5,11 +5,14 @@
 
 //! Lazy, caching, credentials provider implementation
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use aws_smithy_async::future::timeout::Timeout;
-use aws_smithy_async::rt::sleep::AsyncSleep;
+use aws_smithy_async::rt::sleep::{AsyncSleep, Sleep};
+use aws_smithy_async::rt::spawn::Spawn;
 use tracing::{trace_span, Instrument};
 
 use aws_types::credentials::{future, CredentialsError, ProvideCredentials};
@@ -20,6 +23,8 @@ use crate::cache::ExpiringCache;
 const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(5);
 const DEFAULT_CREDENTIAL_EXPIRATION: Duration = Duration::from_secs(15 * 60);
 const DEFAULT_BUFFER_TIME: Duration = Duration::from_secs(10);
+/// Delay between refresh attempts after a refresh failed
+const REFRESH_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
 /// `LazyCachingCredentialsProvider` implements [`ProvideCredentials`] by caching
 /// credentials that it loads by calling a user-provided [`ProvideCredentials`] implementation.
@@ -27,6 +32,9 @@ const DEFAULT_BUFFER_TIME: Duration = Duration::from_secs(10);
 /// For example, you can provide an [`ProvideCredentials`] implementation that calls
 /// AWS STS's AssumeRole operation to get temporary credentials, and `LazyCachingCredentialsProvider`
 /// will cache those credentials until they expire.
+///
+/// Optionally, credentials can be refreshed in the background before they expire
+/// (see [`Builder::refresh_ahead`]) so that callers don't have to wait for a reload once they expire.
 #[derive(Debug)]
 pub struct LazyCachingCredentialsProvider {
     time: TimeSource,
@@ -35,6 +43,7 @@ pub struct LazyCachingCredentialsProvider {
     loader: Arc<dyn ProvideCredentials>,
     load_timeout: Duration,
     default_credential_expiration: Duration,
+    refresh: Option<Arc<RefreshAhead>>,
 }
 
 impl LazyCachingCredentialsProvider {
@@ -53,9 +62,30 @@ impl LazyCachingCredentialsProvider {
             loader,
             load_timeout,
             default_credential_expiration,
+            refresh: None,
         }
     }
 
+    /// Enables refreshing the cached credentials `window` (plus a random amount of up to
+    /// `jitter`) before they expire, in tasks run by `spawn`.
+    fn with_refresh_ahead(
+        mut self,
+        spawn: Arc<dyn Spawn>,
+        window: Duration,
+        jitter: Duration,
+        buffer_time: Duration,
+    ) -> Self {
+        self.refresh = Some(Arc::new(RefreshAhead {
+            spawn,
+            window,
+            jitter,
+            buffer_time,
+            next: Mutex::new(None),
+            refreshing: AtomicBool::new(false),
+        }));
+        self
+    }
+
     /// Returns a new `Builder` that can be used to construct the `LazyCachingCredentialsProvider`.
     pub fn builder() -> builder::Builder {
         builder::Builder::new()
@@ -73,10 +103,21 @@ impl ProvideCredentials for LazyCachingCredentialsProvider {
         let load_timeout = self.load_timeout;
         let cache = self.cache.clone();
         let default_credential_expiration = self.default_credential_expiration;
+        let refresh = self.refresh.clone();
 
         future::ProvideCredentials::new(async move {
+            let load = load_credentials(
+                loader,
+                timeout_future,
+                load_timeout,
+                now,
+                default_credential_expiration,
+            );
             // Attempt to get cached credentials, or clear the cache if they're expired
             if let Some(credentials) = cache.yield_or_clear_if_expired(now).await {
+                if let Some(refresh) = refresh {
+                    refresh.refresh_if_due(now, &cache, load);
+                }
                 Ok(credentials)
             } else {
                 // If we didn't get credentials from the cache, then we need to try and load.
@@ -84,17 +125,13 @@ impl ProvideCredentials for LazyCachingCredentialsProvider {
                 // since the futures are not eagerly executed, and the cache will only run one
                 // of them.
                 let span = trace_span!("lazy_load_credentials");
-                let future = Timeout::new(loader.provide_credentials(), timeout_future);
                 cache
                     .get_or_load(|| {
                         async move {
-                            let credentials = future.await.map_err(|_err| {
-                                CredentialsError::provider_timed_out(load_timeout)
-                            })??;
-                            // If the credentials don't have an expiration time, then create a default one
-                            let expiry = credentials
-                                .expiry()
-                                .unwrap_or(now + default_credential_expiration);
+                            let (credentials, expiry) = load.await?;
+                            if let Some(refresh) = refresh {
+                                refresh.schedule(now, expiry);
+                            }
                             Ok((credentials, expiry))
                         }
                         // Only instrument the the actual load future so that no span
@@ -107,6 +144,126 @@ impl ProvideCredentials for LazyCachingCredentialsProvider {
     }
 }
 
+/// Loads credentials, giving them a default expiration if they don't have one
+async fn load_credentials(
+    loader: Arc<dyn ProvideCredentials>,
+    timeout_future: Sleep,
+    load_timeout: Duration,
+    now: SystemTime,
+    default_credential_expiration: Duration,
+) -> Result<(Credentials, SystemTime), CredentialsError> {
+    let credentials = Timeout::new(loader.provide_credentials(), timeout_future)
+        .await
+        .map_err(|_err| CredentialsError::provider_timed_out(load_timeout))??;
+    // If the credentials don't have an expiration time, then create a default one
+    let expiry = credentials
+        .expiry()
+        .unwrap_or(now + default_credential_expiration);
+    Ok((credentials, expiry))
+}
+
+/// State for refreshing credentials before they expire
+///
+/// Once a refresh is due, the next call to `provide_credentials` starts it as a background task
+/// and, like every other call, immediately receives the cached credentials.
+#[derive(Debug)]
+struct RefreshAhead {
+    spawn: Arc<dyn Spawn>,
+    window: Duration,
+    jitter: Duration,
+    buffer_time: Duration,
+    /// Expiry of the cached credentials and the time of their next refresh, if any
+    next: Mutex<Option<(SystemTime, SystemTime)>>,
+    /// Set while a background task is refreshing the credentials
+    refreshing: AtomicBool,
+}
+
+impl RefreshAhead {
+    /// Schedules a refresh of credentials expiring at `expiry` that were loaded at `now`
+    fn schedule(&self, now: SystemTime, expiry: SystemTime) {
+        *self.next.lock().unwrap() = Some((expiry, self.refresh_at(now, expiry)));
+    }
+
+    /// Time of the next refresh of credentials expiring at `expiry`
+    ///
+    /// Refreshes happen `window` plus a random jitter before expiry, but never later than halfway
+    /// to the point where the cached credentials are considered expired so that short-lived
+    /// credentials are not refreshed continuously.
+    fn refresh_at(&self, now: SystemTime, expiry: SystemTime) -> SystemTime {
+        let jitter = self.jitter.mul_f64(fastrand::f64());
+        let usable_until = expiry.checked_sub(self.buffer_time).unwrap_or(now);
+        let halfway = now + usable_until.duration_since(now).unwrap_or_default() / 2;
+        expiry
+            .checked_sub(self.window + jitter)
+            .map(|refresh_at| refresh_at.max(halfway))
+            .unwrap_or(halfway)
+    }
+
+    /// Claims the refresh of the cached credentials if it is due at `now`
+    ///
+    /// Returns the expiry of the cached credentials. Only one caller can hold the claim at a time.
+    fn claim(self: &Arc<Self>, now: SystemTime) -> Option<(SystemTime, RefreshClaim)> {
+        let (expiry, refresh_at) = (*self.next.lock().unwrap())?;
+        if now < refresh_at || self.refreshing.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        Some((expiry, RefreshClaim(self.clone())))
+    }
+
+    /// Starts refreshing the cached credentials with `load` in the background if a refresh is
+    /// due at `now`
+    ///
+    /// If the refresh fails, the cached credentials continue to be used and the refresh is retried
+    /// after [`REFRESH_RETRY_INTERVAL`], unless the credentials expire before then.
+    fn refresh_if_due(
+        self: &Arc<Self>,
+        now: SystemTime,
+        cache: &ExpiringCache<Credentials, CredentialsError>,
+        load: impl Future<Output = Result<(Credentials, SystemTime), CredentialsError>> + Send + 'static,
+    ) {
+        let (expiry, claim) = match self.claim(now) {
+            Some(claim) => claim,
+            None => return,
+        };
+        let refresh = self.clone();
+        let cache = cache.clone();
+        let span = trace_span!("refresh_ahead_credentials");
+        self.spawn.spawn(Box::pin(
+            async move {
+                // Hold the claim until the refresh completes, or the task is dropped
+                let _claim = claim;
+                match load.await {
+                    Ok((credentials, new_expiry)) => {
+                        tracing::debug!(expiry = ?new_expiry, "refreshed credentials ahead of expiration");
+                        cache.set(credentials, new_expiry).await;
+                        refresh.schedule(now, new_expiry);
+                    }
+                    Err(err) => {
+                        let next_attempt = now + REFRESH_RETRY_INTERVAL;
+                        if next_attempt + refresh.buffer_time >= expiry {
+                            tracing::warn!(error = %err, "failed to refresh credentials ahead of expiration, credentials will be reloaded once they expire");
+                            *refresh.next.lock().unwrap() = None;
+                        } else {
+                            tracing::warn!(error = %err, expiry = ?expiry, "failed to refresh credentials ahead of expiration, continuing to use cached credentials");
+                            *refresh.next.lock().unwrap() = Some((expiry, next_attempt));
+                        }
+                    }
+                }
+            }
+            .instrument(span),
+        ));
+    }
+}
+
+/// Claim on refreshing credentials, released when dropped (including when a refresh is cancelled)
+struct RefreshClaim(Arc<RefreshAhead>);
+
+impl Drop for RefreshClaim {
+    fn drop(&mut self) {
+        self.0.refreshing.store(false, Ordering::Release);
+    }
+}
+
 use aws_types::Credentials;
 pub use builder::Builder;
 
@@ -115,6 +272,7 @@ mod builder {
     use std::time::Duration;
 
     use aws_smithy_async::rt::sleep::{default_async_sleep, AsyncSleep};
+    use aws_smithy_async::rt::spawn::{default_spawn, Spawn};
     use aws_types::credentials::ProvideCredentials;
 
     use super::{
@@ -143,11 +301,14 @@ mod builder {
     #[derive(Default)]
     pub struct Builder {
         sleep: Option<Arc<dyn AsyncSleep>>,
+        spawn: Option<Arc<dyn Spawn>>,
         time_source: Option<TimeSource>,
         load: Option<Arc<dyn ProvideCredentials>>,
         load_timeout: Option<Duration>,
         buffer_time: Option<Duration>,
         default_credential_expiration: Option<Duration>,
+        refresh_ahead: Option<Duration>,
+        refresh_jitter: Option<Duration>,
     }
 
     impl Builder {
@@ -179,6 +340,15 @@ mod builder {
             self
         }
 
+        /// Implementation of [`Spawn`] to run `refresh_ahead` refreshes in the background. This
+        /// enables use of `refresh_ahead` with other async runtimes. If using Tokio as the async
+        /// runtime, this should be set to an instance of
+        /// [`TokioSpawn`](aws_smithy_async::rt::spawn::TokioSpawn).
+        pub fn spawn(mut self, spawn: impl Spawn + 'static) -> Self {
+            self.spawn = Some(Arc::new(spawn));
+            self
+        }
+
         /// (Optional) Timeout for the given [`ProvideCredentials`] implementation.
         /// Defaults to 5 seconds.
         pub fn load_timeout(mut self, timeout: Duration) -> Self {
@@ -204,12 +374,35 @@ mod builder {
             self
         }
 
+        /// (Optional) Enables refreshing credentials this long before they expire. Disabled by
+        /// default. This must be longer than the `buffer_time`.
+        ///
+        /// Once the refresh window is reached, the next call to `provide_credentials` starts a
+        /// refresh in the background using the [`spawn`](Builder::spawn) implementation. All calls
+        /// keep receiving the cached credentials without waiting for the refresh. If the refresh
+        /// fails, the cached credentials continue to be used until they expire.
+        pub fn refresh_ahead(mut self, window: Duration) -> Self {
+            self.refresh_ahead = Some(window);
+            self
+        }
+
+        /// (Optional) Maximum random amount of time to add to the `refresh_ahead` window so that
+        /// multiple providers don't refresh at the same time. Defaults to a fifth of the
+        /// `refresh_ahead` window.
+        pub fn refresh_jitter(mut self, jitter: Duration) -> Self {
+            self.refresh_jitter = Some(jitter);
+            self
+        }
+
         /// Creates the [`LazyCachingCredentialsProvider`].
         ///
         /// # Panics
         /// This will panic if no `sleep` implementation is given and if no default crate features
         /// are used. By default, the [`TokioSleep`](aws_smithy_async::rt::sleep::TokioSleep)
-        /// implementation will be set automatically.
+        /// implementation will be set automatically. The same applies to the `spawn`
+        /// implementation if `refresh_ahead` is enabled.
+        ///
+        /// This will also panic if the `refresh_ahead` window is not longer than the `buffer_time`.
         pub fn build(self) -> LazyCachingCredentialsProvider {
             let default_credential_expiration = self
                 .default_credential_expiration
@@ -218,32 +411,51 @@ mod builder {
                 default_credential_expiration >= DEFAULT_CREDENTIAL_EXPIRATION,
                 "default_credential_expiration must be at least 15 minutes"
             );
-            LazyCachingCredentialsProvider::new(
+            let buffer_time = self.buffer_time.unwrap_or(DEFAULT_BUFFER_TIME);
+            let provider = LazyCachingCredentialsProvider::new(
                 self.time_source.unwrap_or_default(),
                 self.sleep.unwrap_or_else(|| {
                     default_async_sleep().expect("no default sleep implementation available")
                 }),
                 self.load.expect("load implementation is required"),
                 self.load_timeout.unwrap_or(DEFAULT_LOAD_TIMEOUT),
-                self.buffer_time.unwrap_or(DEFAULT_BUFFER_TIME),
                 default_credential_expiration,
-            )
+                buffer_time,
+            );
+            match self.refresh_ahead {
+                Some(window) => {
+                    assert!(
+                        window > buffer_time,
+                        "refresh_ahead window must be longer than the buffer_time"
+                    );
+                    let jitter = self.refresh_jitter.unwrap_or(window / 5);
+                    let spawn = self.spawn.unwrap_or_else(|| {
+                        default_spawn().expect("no default spawn implementation available")
+                    });
+                    provider.with_refresh_ahead(spawn, window, jitter, buffer_time)
+                }
+                None => provider,
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, Mutex};
     use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_async::rt::spawn::{BackgroundTask, Spawn, TokioSpawn};
     use aws_types::credentials::{self, CredentialsError, ProvideCredentials};
     use aws_types::Credentials;
+    use tokio::sync::Notify;
     use tracing::info;
     use tracing_test::traced_test;
 
     use crate::meta::credentials::credential_fn::provide_credentials_fn;
+    use crate::provider_config::ProviderConfig;
 
     use super::{
         LazyCachingCredentialsProvider, TimeSource, DEFAULT_BUFFER_TIME,
@@ -424,4 +636,224 @@ mod tests {
             Err(CredentialsError::ProviderTimedOut { .. })
         ));
     }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn builder_applies_buffer_time_and_default_expiration() {
+        let mut time = ManualTimeSource::new(epoch_secs(100));
+        let load_list = Arc::new(Mutex::new(vec![
+            Ok(credentials(1000)),
+            Ok(Credentials::new("test", "test", None, None, "test")),
+        ]));
+        let provider = LazyCachingCredentialsProvider::builder()
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_time_source(TimeSource::manual(&time))
+                    .with_sleep(TokioSleep::new()),
+            )
+            .load(provide_credentials_fn(move || {
+                let list = load_list.clone();
+                async move { list.lock().unwrap().remove(0) }
+            }))
+            .buffer_time(Duration::from_secs(60))
+            .default_credential_expiration(Duration::from_secs(3600))
+            .build();
+
+        expect_creds(1000, &provider).await;
+        time.set_time(epoch_secs(939));
+        expect_creds(1000, &provider).await;
+        // within the buffer time of the expiration, so credentials without an expiry are loaded
+        time.set_time(epoch_secs(941));
+        let creds = provider.provide_credentials().await.unwrap();
+        assert_eq!(creds.expiry(), None);
+        // the default expiration (not the buffer time) applies to them
+        time.set_time(epoch_secs(941 + 3600 - 61));
+        assert_eq!(provider.provide_credentials().await.unwrap().expiry(), None);
+    }
+
+    /// Spawn implementation that holds on to tasks until they are run by the test
+    #[derive(Default)]
+    struct ManualSpawn(Mutex<Vec<BackgroundTask>>);
+
+    impl std::fmt::Debug for ManualSpawn {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_tuple("ManualSpawn").finish()
+        }
+    }
+
+    impl Spawn for ManualSpawn {
+        fn spawn(&self, task: BackgroundTask) {
+            self.0.lock().unwrap().push(task);
+        }
+    }
+
+    impl ManualSpawn {
+        async fn run_pending(&self) {
+            let tasks = std::mem::take(&mut *self.0.lock().unwrap());
+            for task in tasks {
+                task.await;
+            }
+        }
+    }
+
+    fn refresh_ahead_provider(
+        time: TimeSource,
+        spawn: Arc<ManualSpawn>,
+        load_list: Vec<credentials::Result>,
+    ) -> LazyCachingCredentialsProvider {
+        test_provider(time, load_list).with_refresh_ahead(
+            spawn,
+            Duration::from_secs(300),
+            Duration::from_secs(0),
+            DEFAULT_BUFFER_TIME,
+        )
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn refresh_ahead_of_expiration() {
+        let mut time = ManualTimeSource::new(epoch_secs(100));
+        let spawn = Arc::new(ManualSpawn::default());
+        let provider = refresh_ahead_provider(
+            TimeSource::manual(&time),
+            spawn.clone(),
+            vec![Ok(credentials(1000)), Ok(credentials(2000))],
+        );
+
+        expect_creds(1000, &provider).await;
+        // the refresh is due 300 seconds before the expiration
+        time.set_time(epoch_secs(699));
+        expect_creds(1000, &provider).await;
+        assert!(spawn.0.lock().unwrap().is_empty());
+        // the caller that starts the refresh receives the cached credentials
+        time.set_time(epoch_secs(700));
+        expect_creds(1000, &provider).await;
+        expect_creds(1000, &provider).await;
+        assert_eq!(spawn.0.lock().unwrap().len(), 1);
+        spawn.run_pending().await;
+        expect_creds(2000, &provider).await;
+        expect_creds(2000, &provider).await;
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn callers_do_not_wait_for_refresh() {
+        let mut time = ManualTimeSource::new(epoch_secs(100));
+        let loads = Arc::new(AtomicUsize::new(0));
+        let refreshed = Arc::new(Notify::new());
+        let provider = LazyCachingCredentialsProvider::new(
+            TimeSource::manual(&time),
+            Arc::new(TokioSleep::new()),
+            Arc::new(provide_credentials_fn({
+                let (loads, refreshed) = (loads.clone(), refreshed.clone());
+                move || {
+                    let (loads, refreshed) = (loads.clone(), refreshed.clone());
+                    async move {
+                        if loads.fetch_add(1, Ordering::SeqCst) == 0 {
+                            return Ok(credentials(1000));
+                        }
+                        refreshed.notified().await;
+                        Ok(credentials(2000))
+                    }
+                }
+            })),
+            DEFAULT_LOAD_TIMEOUT,
+            DEFAULT_CREDENTIAL_EXPIRATION,
+            DEFAULT_BUFFER_TIME,
+        )
+        .with_refresh_ahead(
+            Arc::new(TokioSpawn::new()),
+            Duration::from_secs(300),
+            Duration::from_secs(0),
+            DEFAULT_BUFFER_TIME,
+        );
+
+        expect_creds(1000, &provider).await;
+        time.set_time(epoch_secs(700));
+        // neither the caller that starts the refresh nor later callers wait for it to complete
+        expect_creds(1000, &provider).await;
+        while loads.load(Ordering::SeqCst) < 2 {
+            tokio::task::yield_now().await;
+        }
+        expect_creds(1000, &provider).await;
+        refreshed.notify_one();
+        while provider.provide_credentials().await.unwrap().expiry() != Some(epoch_secs(2000)) {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn refresh_ahead_failure_keeps_cached_credentials() {
+        let mut time = ManualTimeSource::new(epoch_secs(100));
+        let spawn = Arc::new(ManualSpawn::default());
+        let provider = refresh_ahead_provider(
+            TimeSource::manual(&time),
+            spawn.clone(),
+            vec![
+                Ok(credentials(1000)),
+                Err(CredentialsError::provider_error("refresh failed")),
+                Ok(credentials(2000)),
+            ],
+        );
+
+        expect_creds(1000, &provider).await;
+        time.set_time(epoch_secs(700));
+        expect_creds(1000, &provider).await;
+        spawn.run_pending().await;
+        expect_creds(1000, &provider).await;
+        assert!(logs_contain(
+            "failed to refresh credentials ahead of expiration, continuing to use cached credentials"
+        ));
+
+        // the refresh is retried after the retry interval
+        time.set_time(epoch_secs(709));
+        expect_creds(1000, &provider).await;
+        assert!(spawn.0.lock().unwrap().is_empty());
+        time.set_time(epoch_secs(710));
+        expect_creds(1000, &provider).await;
+        spawn.run_pending().await;
+        expect_creds(2000, &provider).await;
+    }
+
+    #[traced_test]
+    #[tokio::test]
+    async fn refresh_ahead_falls_back_to_lazy_load() {
+        let mut time = ManualTimeSource::new(epoch_secs(100));
+        let spawn = Arc::new(ManualSpawn::default());
+        let provider = refresh_ahead_provider(
+            TimeSource::manual(&time),
+            spawn.clone(),
+            vec![
+                Ok(credentials(1000)),
+                Err(CredentialsError::provider_error("refresh failed")),
+                Ok(credentials(2000)),
+            ],
+        );
+
+        expect_creds(1000, &provider).await;
+        // too close to the expiration to retry the refresh
+        time.set_time(epoch_secs(985));
+        expect_creds(1000, &provider).await;
+        spawn.run_pending().await;
+        assert!(logs_contain(
+            "credentials will be reloaded once they expire"
+        ));
+        time.set_time(epoch_secs(989));
+        expect_creds(1000, &provider).await;
+        assert!(spawn.0.lock().unwrap().is_empty());
+        time.set_time(epoch_secs(995));
+        expect_creds(2000, &provider).await;
+    }
+
+    #[test]
+    #[should_panic(expected = "refresh_ahead window must be longer than the buffer_time")]
+    fn refresh_ahead_must_exceed_buffer_time() {
+        LazyCachingCredentialsProvider::builder()
+            .load(provide_credentials_fn(|| async { Ok(credentials(1000)) }))
+            .sleep(TokioSleep::new())
+            .refresh_ahead(Duration::from_secs(5))
+            .build();
+    }
 }
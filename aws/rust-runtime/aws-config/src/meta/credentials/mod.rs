@@ -6,7 +6,7 @@
 //! Credential providers that augment an existing credentials providers to add functionality
 
 mod chain;
-pub use chain::CredentialsProviderChain;
+pub use chain::{CredentialsProviderChain, ProviderExplanation, ProviderOutcome};
 
 mod credential_fn;
 pub use credential_fn::provide_credentials_fn;
@@ -24,21 +24,18 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error::Error;
-use std::fmt::{Display, Formatter};
+use std::fmt::{self, Display, Formatter};
 use std::sync::Arc;
 
 use aws_types::credentials::{self, future, CredentialsError, ProvideCredentials};
-use aws_types::os_shim_internal::{Env, Fs};
-use aws_types::region::Region;
 use tracing::Instrument;
 
 use crate::connector::expect_connector;
-use crate::meta::region::ProvideRegion;
 use crate::profile::credentials::exec::named::NamedProviderFactory;
 use crate::profile::credentials::exec::{ClientConfiguration, ProviderChain};
 use crate::profile::parser::ProfileParseError;
 use crate::provider_config::ProviderConfig;
-use aws_smithy_client::erase::DynConnector;
+use crate::sts::mfa::{MfaSessions, ProvideMfaToken, SharedMfaTokenProvider};
 
 mod exec;
 mod repr;
@@ -122,15 +119,50 @@ impl ProvideCredentials for ProfileFileCredentialsProvider {
 /// aws_secret_access_key = 456
 /// ```
 ///
+/// `role_session_name` and `duration_seconds` may also be set alongside `role_arn` to control the
+/// name and length of the assumed role session.
+///
+/// ### Assume role credentials that require MFA
+/// ```ini
+/// [default]
+/// role_arn = arn:aws:iam::123456789:role/RoleA
+/// mfa_serial = arn:aws:iam::123456789:mfa/operator
+/// source_profile = base
+/// ```
+///
+/// Token codes are requested from the provider set with
+/// [`mfa_token_provider`](Builder::mfa_token_provider).
+///
+/// ### Credentials loaded from an external process
+/// ```ini
+/// [default]
+/// credential_process = /opt/bin/awscreds-retriever --username susan
+/// ```
+///
+/// See [`CredentialProcessProvider`](crate::credential_process::CredentialProcessProvider) for
+/// the expected output format.
+///
+/// ### Credentials loaded from AWS SSO
+/// ```ini
+/// [default]
+/// sso_session = my-sso
+/// sso_account_id = 123456789011
+/// sso_role_name = readOnly
+///
+/// [sso-session my-sso]
+/// sso_region = us-east-1
+/// sso_start_url = https://my-sso-portal.awsapps.com/start
+/// ```
+///
+/// The SSO access token must already be cached in `~/.aws/sso/cache`, e.g. by running
+/// `aws sso login`. See [`SsoCredentialsProvider`](crate::sso::SsoCredentialsProvider) for details.
+///
 /// Other more complex configurations are possible, consult `test-data/assume-role-tests.json`.
 #[derive(Debug)]
 pub struct ProfileFileCredentialsProvider {
     factory: NamedProviderFactory,
     client_config: ClientConfiguration,
-    fs: Fs,
-    env: Env,
-    region: Option<Region>,
-    connector: DynConnector,
+    provider_config: ProviderConfig,
     profile_override: Option<String>,
 }
 
@@ -140,6 +172,29 @@ impl ProfileFileCredentialsProvider {
         Builder::default()
     }
 
+    /// Resolve the chain of providers configured by the selected profile without loading credentials
+    ///
+    /// This is intended for diagnosing configuration issues, see [`ResolvedProfileChain`].
+    pub async fn resolved_chain(&self) -> Result<ResolvedProfileChain, ProfileFileError> {
+        let profile_set =
+            super::parser::load(&self.provider_config.fs(), &self.provider_config.env())
+                .await
+                .map_err(ProfileFileError::CouldNotParseProfile)?;
+        let repr = repr::resolve_chain(&profile_set, self.profile_override.as_deref())?;
+        Ok(ResolvedProfileChain {
+            profile_name: self
+                .profile_override
+                .clone()
+                .unwrap_or_else(|| profile_set.selected_profile().to_string()),
+            base: repr.base().describe(),
+            assume_roles: repr
+                .chain()
+                .iter()
+                .map(|role| role.role_arn.to_string())
+                .collect(),
+        })
+    }
+
     async fn load_credentials(&self) -> credentials::Result {
         // 1. grab a read lock, use it to see if the base profile has already been loaded
         // 2. If it's loaded, great, lets use it.
@@ -147,10 +202,7 @@ impl ProfileFileCredentialsProvider {
         // 3. Finally, downgrade to ensure no one swapped in the intervening time, then use try_load()
         //    to pull the new state.
         let profile = build_provider_chain(
-            &self.fs,
-            &self.env,
-            &self.region,
-            &self.connector,
+            &self.provider_config,
             &self.factory,
             self.profile_override.as_deref(),
         )
@@ -200,6 +252,44 @@ impl ProfileFileCredentialsProvider {
     }
 }
 
+/// Chain of providers resolved from a profile
+///
+/// Credentials are loaded from the `base` provider and then used to assume each role in
+/// `assume_roles`, in order. Returned by [`ProfileFileCredentialsProvider::resolved_chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedProfileChain {
+    profile_name: String,
+    base: String,
+    assume_roles: Vec<String>,
+}
+
+impl ResolvedProfileChain {
+    /// Name of the profile the chain was resolved from
+    pub fn profile_name(&self) -> &str {
+        &self.profile_name
+    }
+
+    /// Description of the provider that loads the initial credentials
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// ARNs of the roles assumed after loading the base credentials, in order
+    pub fn assume_roles(&self) -> &[String] {
+        &self.assume_roles
+    }
+}
+
+impl Display for ResolvedProfileChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "profile `{}`: {}", self.profile_name, self.base)?;
+        for role in &self.assume_roles {
+            write!(f, " -> assume role `{}`", role)?;
+        }
+        Ok(())
+    }
+}
+
 /// An Error building a Credential source from an AWS Profile
 #[derive(Debug)]
 #[non_exhaustive]
@@ -311,6 +401,7 @@ pub struct Builder {
     provider_config: Option<ProviderConfig>,
     profile_override: Option<String>,
     custom_providers: HashMap<Cow<'static, str>, Arc<dyn ProvideCredentials>>,
+    mfa_token_provider: Option<SharedMfaTokenProvider>,
 }
 
 impl Builder {
@@ -374,6 +465,16 @@ impl Builder {
         self
     }
 
+    /// Set the provider used to request MFA token codes
+    ///
+    /// Profiles that assume a role with `mfa_serial` set will request a token code from this
+    /// provider. The resulting role session is cached until it expires so that a new code is only
+    /// requested once per session. See [`mfa`](crate::sts::mfa) for more details.
+    pub fn mfa_token_provider(mut self, provider: impl ProvideMfaToken + 'static) -> Self {
+        self.mfa_token_provider = Some(SharedMfaTokenProvider::new(provider));
+        self
+    }
+
     /// Builds a [`ProfileFileCredentialsProvider`]
     pub fn build(self) -> ProfileFileCredentialsProvider {
         let build_span = tracing::info_span!("build_profile_provider");
@@ -409,38 +510,37 @@ impl Builder {
             });
         let factory = exec::named::NamedProviderFactory::new(named_providers);
         let connector = expect_connector(conf.default_connector());
-        let core_client = aws_hyper::Client::new(connector.clone());
+        let core_client = aws_hyper::Client::new(connector);
 
         ProfileFileCredentialsProvider {
             factory,
             client_config: ClientConfiguration {
                 core_client,
                 region: conf.region(),
+                mfa_token_provider: self.mfa_token_provider,
+                mfa_sessions: MfaSessions::default(),
+                time_source: conf.time_source(),
             },
-            fs: conf.fs(),
-            env: conf.env(),
-            region: conf.region(),
-            connector,
+            provider_config: conf,
             profile_override: self.profile_override,
         }
     }
 }
 
 async fn build_provider_chain(
-    fs: &Fs,
-    env: &Env,
-    region: &dyn ProvideRegion,
-    connector: &DynConnector,
+    provider_config: &ProviderConfig,
     factory: &NamedProviderFactory,
     profile_override: Option<&str>,
 ) -> Result<ProviderChain, ProfileFileError> {
-    let profile_set = super::parser::load(fs, env).await.map_err(|err| {
-        tracing::warn!(err = %err, "failed to parse profile");
-        ProfileFileError::CouldNotParseProfile(err)
-    })?;
+    let profile_set = super::parser::load(&provider_config.fs(), &provider_config.env())
+        .await
+        .map_err(|err| {
+            tracing::warn!(err = %err, "failed to parse profile");
+            ProfileFileError::CouldNotParseProfile(err)
+        })?;
     let repr = repr::resolve_chain(&profile_set, profile_override)?;
     tracing::info!(chain = ?repr, "constructed abstract provider from config file");
-    exec::ProviderChain::from_repr(fs.clone(), connector, region.region().await, repr, factory)
+    exec::ProviderChain::from_repr(provider_config, repr, factory)
 }
 
 #[cfg(test)]
@@ -471,4 +571,12 @@ mod test {
     make_test!(retry_on_error);
     make_test!(invalid_config);
     make_test!(region_override);
+    #[cfg(not(windows))]
+    make_test!(credential_process);
+    #[cfg(not(windows))]
+    make_test!(credential_process_assume_role);
+    make_test!(sso_credentials);
+    make_test!(sso_session);
+    make_test!(assume_role_invalid_duration);
+    make_test!(assume_role_mfa_no_token_provider);
 }
@@ -11,13 +11,15 @@ use aws_types::region::Region;
 
 use super::repr::{self, BaseProvider};
 
+use crate::credential_process::CredentialProcessProvider;
 use crate::profile::credentials::ProfileFileError;
 use crate::provider_config::ProviderConfig;
+use crate::sso::SsoCredentialsProvider;
 use crate::sts;
+use crate::sts::mfa::{MfaSessions, ProvideMfaToken, SharedMfaTokenProvider};
 use crate::web_identity_token::{StaticConfiguration, WebIdentityTokenCredentialsProvider};
-use aws_smithy_client::erase::DynConnector;
 use aws_types::credentials::{self, CredentialsError, ProvideCredentials};
-use aws_types::os_shim_internal::Fs;
+use aws_types::os_shim_internal::TimeSource;
 use std::fmt::Debug;
 
 #[derive(Debug)]
@@ -25,12 +27,17 @@ pub struct AssumeRoleProvider {
     role_arn: String,
     external_id: Option<String>,
     session_name: Option<String>,
+    duration_seconds: Option<u32>,
+    mfa_serial: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct ClientConfiguration {
     pub(crate) core_client: aws_hyper::StandardClient,
     pub(crate) region: Option<Region>,
+    pub(crate) mfa_token_provider: Option<SharedMfaTokenProvider>,
+    pub(crate) mfa_sessions: MfaSessions,
+    pub(crate) time_source: TimeSource,
 }
 
 impl AssumeRoleProvider {
@@ -38,6 +45,41 @@ impl AssumeRoleProvider {
         &self,
         input_credentials: Credentials,
         client_config: &ClientConfiguration,
+    ) -> credentials::Result {
+        let serial_number = match &self.mfa_serial {
+            Some(serial_number) => serial_number,
+            None => {
+                return self
+                    .assume_role(input_credentials, client_config, None)
+                    .await
+            }
+        };
+        let token_provider = client_config.mfa_token_provider.as_ref().ok_or_else(|| {
+            CredentialsError::invalid_configuration(format!(
+                "role `{}` requires MFA device `{}` but no MFA token provider was configured",
+                self.role_arn, serial_number
+            ))
+        })?;
+        client_config
+            .mfa_sessions
+            .session(&self.role_arn, serial_number)
+            .get_or_load(client_config.time_source.now(), || async move {
+                tracing::info!(serial_number = %serial_number, "requesting MFA token code");
+                let token_code = token_provider
+                    .provide_mfa_token(serial_number)
+                    .await
+                    .map_err(CredentialsError::provider_error)?;
+                self.assume_role(input_credentials, client_config, Some(token_code))
+                    .await
+            })
+            .await
+    }
+
+    async fn assume_role(
+        &self,
+        input_credentials: Credentials,
+        client_config: &ClientConfiguration,
+        token_code: Option<String>,
     ) -> credentials::Result {
         let config = Config::builder()
             .credentials_provider(input_credentials)
@@ -48,10 +90,18 @@ impl AssumeRoleProvider {
             .as_ref()
             .cloned()
             .unwrap_or_else(|| sts::util::default_session_name("assume-role-from-profile"));
+        let duration_seconds = self
+            .duration_seconds
+            .map(sts::util::validate_duration_seconds)
+            .transpose()
+            .map_err(CredentialsError::invalid_configuration)?;
         let operation = AssumeRole::builder()
             .role_arn(&self.role_arn)
             .set_external_id(self.external_id.clone())
             .role_session_name(session_name)
+            .set_duration_seconds(duration_seconds)
+            .set_serial_number(self.mfa_serial.clone())
+            .set_token_code(token_code)
             .build()
             .expect("operation is valid")
             .make_operation(&config)
@@ -85,9 +135,7 @@ impl ProviderChain {
 
 impl ProviderChain {
     pub fn from_repr(
-        fs: Fs,
-        connector: &DynConnector,
-        region: Option<Region>,
+        provider_config: &ProviderConfig,
         repr: repr::ProfileChain,
         factory: &named::NamedProviderFactory,
     ) -> Result<Self, ProfileFileError> {
@@ -105,10 +153,6 @@ impl ProviderChain {
                 web_identity_token_file,
                 session_name,
             } => {
-                let conf = ProviderConfig::empty()
-                    .with_http_connector(connector.clone())
-                    .with_fs(fs)
-                    .with_region(region);
                 let provider = WebIdentityTokenCredentialsProvider::builder()
                     .static_configuration(StaticConfiguration {
                         web_identity_token_file: web_identity_token_file.into(),
@@ -117,10 +161,34 @@ impl ProviderChain {
                             || sts::util::default_session_name("web-identity-token-profile"),
                         ),
                     })
-                    .configure(&conf)
+                    .configure(provider_config)
                     .build();
                 Arc::new(provider)
             }
+            BaseProvider::CredentialProcess(credential_process) => Arc::new(
+                CredentialProcessProvider::builder()
+                    .command(credential_process.unredacted())
+                    .configure(provider_config)
+                    .build(),
+            ),
+            BaseProvider::Sso {
+                sso_session_name,
+                sso_region,
+                sso_start_url,
+                sso_account_id,
+                sso_role_name,
+            } => {
+                let mut builder = SsoCredentialsProvider::builder()
+                    .account_id(*sso_account_id)
+                    .role_name(*sso_role_name)
+                    .start_url(*sso_start_url)
+                    .region(Region::new(sso_region.to_string()))
+                    .configure(provider_config);
+                if let Some(session_name) = sso_session_name {
+                    builder = builder.session_name(*session_name);
+                }
+                Arc::new(builder.build())
+            }
         };
         tracing::info!(base = ?repr.base(), "first credentials will be loaded from {:?}", repr.base());
         let chain = repr
@@ -132,6 +200,8 @@ impl ProviderChain {
                     role_arn: role_arn.role_arn.into(),
                     external_id: role_arn.external_id.map(|id| id.into()),
                     session_name: role_arn.session_name.map(|id| id.into()),
+                    duration_seconds: role_arn.duration_seconds,
+                    mfa_serial: role_arn.mfa_serial.map(|serial| serial.into()),
                 }
             })
             .collect();
@@ -178,6 +248,7 @@ mod test {
     use crate::profile::credentials::exec::named::NamedProviderFactory;
     use crate::profile::credentials::exec::ProviderChain;
     use crate::profile::credentials::repr::{BaseProvider, ProfileChain};
+    use crate::provider_config::ProviderConfig;
     use crate::test_case::no_traffic_connector;
     use aws_sdk_sts::Region;
     use aws_types::Credentials;
@@ -202,9 +273,9 @@ mod test {
     fn error_on_unknown_provider() {
         let factory = NamedProviderFactory::new(HashMap::new());
         let chain = ProviderChain::from_repr(
-            Default::default(),
-            &no_traffic_connector(),
-            Some(Region::new("us-east-1")),
+            &ProviderConfig::empty()
+                .with_http_connector(no_traffic_connector())
+                .with_region(Some(Region::new("us-east-1"))),
             ProfileChain {
                 base: BaseProvider::NamedSource("floozle"),
                 chain: vec![],
@@ -12,8 +12,10 @@
 //! 1-credential-per row (as opposed to a direct profile file representation which can combine
 //! multiple actions into the same profile).
 
+use crate::credential_process::CommandWithSensitiveArgs;
 use crate::profile::credentials::ProfileFileError;
 use crate::profile::{Profile, ProfileSet};
+use aws_types::arn::Arn;
 use aws_types::Credentials;
 
 /// Chain of Profile Providers
@@ -73,16 +75,65 @@ pub enum BaseProvider<'a> {
         role_arn: &'a str,
         web_identity_token_file: &'a str,
         session_name: Option<&'a str>,
-    }, // TODO: add SSO support
-       /*
-       /// An SSO Provider
-       Sso {
-           sso_account_id: &'a str,
-           sso_region: &'a str,
-           sso_role_name: &'a str,
-           sso_start_url: &'a str,
-       },
-        */
+    },
+
+    /// A profile that specifies an external process to load credentials from
+    ///
+    /// Example:
+    /// ```ini
+    /// [profile C]
+    /// credential_process = /opt/bin/awscreds-retriever --username susan
+    /// ```
+    CredentialProcess(CommandWithSensitiveArgs<&'a str>),
+
+    /// A profile that loads credentials from AWS SSO (IAM Identity Center)
+    ///
+    /// `sso_region` and `sso_start_url` may either be configured directly on the profile or be
+    /// loaded from a shared `[sso-session <name>]` section:
+    /// ```ini
+    /// [profile C]
+    /// sso_session = my-sso
+    /// sso_account_id = 123456789011
+    /// sso_role_name = readOnly
+    ///
+    /// [sso-session my-sso]
+    /// sso_region = us-east-1
+    /// sso_start_url = https://my-sso-portal.awsapps.com/start
+    /// ```
+    Sso {
+        sso_session_name: Option<&'a str>,
+        sso_region: &'a str,
+        sso_start_url: &'a str,
+        sso_account_id: &'a str,
+        sso_role_name: &'a str,
+    },
+}
+
+impl BaseProvider<'_> {
+    /// Human readable description of the provider that does not contain secrets
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            BaseProvider::NamedSource(name) => format!("named credential source `{}`", name),
+            BaseProvider::AccessKey(credentials) => format!(
+                "static credentials with access key `{}`",
+                credentials.access_key_id()
+            ),
+            BaseProvider::WebIdentityTokenRole { role_arn, .. } => {
+                format!("web identity token for role `{}`", role_arn)
+            }
+            BaseProvider::CredentialProcess(command) => {
+                format!("credential process `{}`", command)
+            }
+            BaseProvider::Sso {
+                sso_account_id,
+                sso_role_name,
+                ..
+            } => format!(
+                "SSO role `{}` in account `{}`",
+                sso_role_name, sso_account_id
+            ),
+        }
+    }
 }
 
 /// A profile that specifies a role to assume
@@ -98,6 +149,12 @@ pub struct RoleArn<'a> {
 
     /// session name parameter to pass to the assume role provider
     pub session_name: Option<&'a str>,
+
+    /// session duration, in seconds, to request from the assume role provider
+    pub duration_seconds: Option<u32>,
+
+    /// identification number of the MFA device required to assume the role
+    pub mfa_serial: Option<&'a str>,
 }
 
 /// Resolve a ProfileChain from a ProfileSet or return an error
@@ -150,12 +207,12 @@ pub fn resolve_chain<'a>(
             // The existence of a `role_arn` is the only signal that multiple profiles will be chained.
             // We check for one here and then process the profile accordingly as either a "chain provider"
             // or a "base provider"
-            if let Some(role_provider) = role_arn_from_profile(profile) {
+            if let Some(role_provider) = role_arn_from_profile(profile)? {
                 let next = chain_provider(profile)?;
                 chain.push(role_provider);
                 next
             } else {
-                break base_provider(profile).map_err(|err| {
+                break base_provider(profile_set, profile).map_err(|err| {
                     // It's possible for base_provider to return a `ProfileFileError::ProfileDidNotContainCredentials`
                     // if we're still looking at the first provider we want to surface it. However,
                     // if we're looking at any provider after the first we want to instead return a `ProfileFileError::InvalidCredentialSource`
@@ -176,7 +233,7 @@ pub fn resolve_chain<'a>(
                 // self referential profile, don't go through the loop because it will error
                 // on the infinite loop check. Instead, reload this profile as a base profile
                 // and exit.
-                break base_provider(profile)?;
+                break base_provider(profile_set, profile)?;
             }
             NextProfile::Named(name) => source_profile_name = name,
         }
@@ -189,6 +246,8 @@ mod role {
     pub const ROLE_ARN: &str = "role_arn";
     pub const EXTERNAL_ID: &str = "external_id";
     pub const SESSION_NAME: &str = "role_session_name";
+    pub const DURATION_SECONDS: &str = "duration_seconds";
+    pub const MFA_SERIAL: &str = "mfa_serial";
 
     pub const CREDENTIAL_SOURCE: &str = "credential_source";
     pub const SOURCE_PROFILE: &str = "source_profile";
@@ -198,6 +257,18 @@ mod web_identity_token {
     pub const TOKEN_FILE: &str = "web_identity_token_file";
 }
 
+mod credential_process {
+    pub const CREDENTIAL_PROCESS: &str = "credential_process";
+}
+
+mod sso {
+    pub const SSO_SESSION: &str = "sso_session";
+    pub const SSO_ACCOUNT_ID: &str = "sso_account_id";
+    pub const SSO_REGION: &str = "sso_region";
+    pub const SSO_ROLE_NAME: &str = "sso_role_name";
+    pub const SSO_START_URL: &str = "sso_start_url";
+}
+
 mod static_credentials {
     pub const AWS_ACCESS_KEY_ID: &str = "aws_access_key_id";
     pub const AWS_SECRET_ACCESS_KEY: &str = "aws_secret_access_key";
@@ -205,12 +276,24 @@ mod static_credentials {
 }
 const PROVIDER_NAME: &str = "ProfileFile";
 
-fn base_provider(profile: &Profile) -> Result<BaseProvider, ProfileFileError> {
+fn base_provider<'a>(
+    profile_set: &'a ProfileSet,
+    profile: &'a Profile,
+) -> Result<BaseProvider<'a>, ProfileFileError> {
     // the profile must define either a `CredentialsSource` or a concrete set of access keys
     match profile.get(role::CREDENTIAL_SOURCE) {
         Some(source) => Ok(BaseProvider::NamedSource(source)),
-        None => web_identity_token_from_profile(profile)
-            .unwrap_or_else(|| Ok(BaseProvider::AccessKey(static_creds_from_profile(profile)?))),
+        None => web_identity_token_from_profile(profile).unwrap_or_else(|| {
+            // static credentials take priority over SSO and `credential_process`
+            match static_creds_from_profile(profile) {
+                Err(no_credentials @ ProfileFileError::ProfileDidNotContainCredentials { .. }) => {
+                    sso_from_profile(profile_set, profile)
+                        .or_else(|| credential_process_from_profile(profile))
+                        .unwrap_or(Err(no_credentials))
+                }
+                static_credentials => Ok(BaseProvider::AccessKey(static_credentials?)),
+            }
+        }),
     }
 }
 
@@ -246,19 +329,59 @@ fn chain_provider(profile: &Profile) -> Result<NextProfile, ProfileFileError> {
     }
 }
 
-fn role_arn_from_profile(profile: &Profile) -> Option<RoleArn> {
+fn role_arn_from_profile(profile: &Profile) -> Result<Option<RoleArn>, ProfileFileError> {
     // Web Identity Tokens are root providers, not chained roles
     if profile.get(web_identity_token::TOKEN_FILE).is_some() {
-        return None;
+        return Ok(None);
     }
-    let role_arn = profile.get(role::ROLE_ARN)?;
+    let role_arn = match profile.get(role::ROLE_ARN) {
+        Some(role_arn) => role_arn,
+        None => return Ok(None),
+    };
+    validate_role_arn(profile, role_arn)?;
     let session_name = profile.get(role::SESSION_NAME);
     let external_id = profile.get(role::EXTERNAL_ID);
-    Some(RoleArn {
+    let mfa_serial = profile.get(role::MFA_SERIAL);
+    let duration_seconds = profile
+        .get(role::DURATION_SECONDS)
+        .map(|duration| {
+            duration
+                .parse::<u32>()
+                .map_err(|_| ProfileFileError::InvalidCredentialSource {
+                    profile: profile.name().to_string(),
+                    message: format!(
+                        "`{}` must be a positive integer (found `{}`)",
+                        role::DURATION_SECONDS,
+                        duration
+                    )
+                    .into(),
+                })
+        })
+        .transpose()?;
+    Ok(Some(RoleArn {
         role_arn,
         external_id,
         session_name,
-    })
+        duration_seconds,
+        mfa_serial,
+    }))
+}
+
+/// Check that `role_arn` is a valid ARN so that it is reported before any credentials are requested
+fn validate_role_arn(profile: &Profile, role_arn: &str) -> Result<(), ProfileFileError> {
+    role_arn
+        .parse::<Arn>()
+        .map(|_| ())
+        .map_err(|err| ProfileFileError::InvalidCredentialSource {
+            profile: profile.name().to_string(),
+            message: format!(
+                "`{}` is not a valid ARN (found `{}`): {}",
+                role::ROLE_ARN,
+                role_arn,
+                err
+            )
+            .into(),
+        })
 }
 
 fn web_identity_token_from_profile(
@@ -269,11 +392,13 @@ fn web_identity_token_from_profile(
         profile.get(role::ROLE_ARN),
         profile.get(web_identity_token::TOKEN_FILE),
     ) {
-        (Some(role_arn), Some(token_file)) => Some(Ok(BaseProvider::WebIdentityTokenRole {
-            role_arn,
-            web_identity_token_file: token_file,
-            session_name,
-        })),
+        (Some(role_arn), Some(token_file)) => Some(validate_role_arn(profile, role_arn).map(
+            |_| BaseProvider::WebIdentityTokenRole {
+                role_arn,
+                web_identity_token_file: token_file,
+                session_name,
+            },
+        )),
         (None, None) => None,
         (Some(_role_arn), None) => None,
         (None, Some(_token_file)) => Some(Err(ProfileFileError::InvalidCredentialSource {
@@ -283,6 +408,109 @@ fn web_identity_token_from_profile(
     }
 }
 
+/// Load SSO configuration from a profile and, optionally, its `[sso-session <name>]` section
+///
+/// Example:
+/// ```ini
+/// [profile B]
+/// sso_account_id = 123456789011
+/// sso_region = us-east-1
+/// sso_role_name = readOnly
+/// sso_start_url = https://my-sso-portal.awsapps.com/start
+/// ```
+fn sso_from_profile<'a>(
+    profile_set: &'a ProfileSet,
+    profile: &'a Profile,
+) -> Option<Result<BaseProvider<'a>, ProfileFileError>> {
+    use sso::*;
+    let sso_session_name = profile.get(SSO_SESSION);
+    let (account_id, region, role_name, start_url) = (
+        profile.get(SSO_ACCOUNT_ID),
+        profile.get(SSO_REGION),
+        profile.get(SSO_ROLE_NAME),
+        profile.get(SSO_START_URL),
+    );
+    if let (None, None, None, None, None) =
+        (sso_session_name, account_id, region, role_name, start_url)
+    {
+        return None;
+    }
+    let invalid = |message: String| ProfileFileError::InvalidCredentialSource {
+        profile: profile.name().to_string(),
+        message: message.into(),
+    };
+    let (region, start_url) = match sso_session_name {
+        Some(session_name) => {
+            let session = match profile_set.sso_session(session_name) {
+                Some(session) => session,
+                None => {
+                    return Some(Err(invalid(format!(
+                        "sso-session `{}` was referenced by the profile but could not be found",
+                        session_name
+                    ))))
+                }
+            };
+            // values set on the profile must agree with the values in the session
+            for (key, profile_value) in [(SSO_REGION, region), (SSO_START_URL, start_url)] {
+                if let Some(profile_value) = profile_value {
+                    if session.get(key) != Some(profile_value) {
+                        return Some(Err(invalid(format!(
+                            "`{}` in the profile does not match the value in sso-session `{}`",
+                            key, session_name
+                        ))));
+                    }
+                }
+            }
+            (session.get(SSO_REGION), session.get(SSO_START_URL))
+        }
+        None => (region, start_url),
+    };
+    let missing = [
+        (SSO_ACCOUNT_ID, account_id),
+        (SSO_REGION, region),
+        (SSO_ROLE_NAME, role_name),
+        (SSO_START_URL, start_url),
+    ]
+    .iter()
+    .filter(|(_, value)| value.is_none())
+    .map(|(key, _)| *key)
+    .collect::<Vec<_>>();
+    match (account_id, region, role_name, start_url) {
+        (Some(sso_account_id), Some(sso_region), Some(sso_role_name), Some(sso_start_url)) => {
+            Some(Ok(BaseProvider::Sso {
+                sso_session_name,
+                sso_region,
+                sso_start_url,
+                sso_account_id,
+                sso_role_name,
+            }))
+        }
+        _ => Some(Err(invalid(format!(
+            "profile is missing SSO configuration: {}",
+            missing.join(", ")
+        )))),
+    }
+}
+
+/// Load credentials from `credential_process`
+///
+/// Example:
+/// ```ini
+/// [profile B]
+/// credential_process = /opt/bin/awscreds-retriever --username susan
+/// ```
+fn credential_process_from_profile(
+    profile: &Profile,
+) -> Option<Result<BaseProvider, ProfileFileError>> {
+    profile
+        .get(credential_process::CREDENTIAL_PROCESS)
+        .map(|command| {
+            Ok(BaseProvider::CredentialProcess(
+                CommandWithSensitiveArgs::new(command),
+            ))
+        })
+}
+
 /// Load static credentials from a profile
 ///
 /// Example:
@@ -343,7 +571,8 @@ mod tests {
     }
 
     fn check(test_case: TestCase) {
-        let source = ProfileSet::new(test_case.input.profile, test_case.input.selected_profile);
+        let source = ProfileSet::new(test_case.input.profile, test_case.input.selected_profile)
+            .with_sso_sessions(test_case.input.sso_session);
         let actual = resolve_chain(&source, None);
         let expected = test_case.output;
         match (expected, actual) {
@@ -373,6 +602,8 @@ mod tests {
     #[derive(Deserialize)]
     struct TestInput {
         profile: HashMap<String, HashMap<String, String>>,
+        #[serde(default)]
+        sso_session: HashMap<String, HashMap<String, String>>,
         selected_profile: String,
     }
 
@@ -394,12 +625,30 @@ mod tests {
                 web_identity_token_file: web_identity_token_file.into(),
                 role_session_name: session_name.map(|sess| sess.to_string()),
             }),
+            BaseProvider::CredentialProcess(credential_process) => output.push(
+                Provider::CredentialProcess(credential_process.unredacted().into()),
+            ),
+            BaseProvider::Sso {
+                sso_session_name,
+                sso_region,
+                sso_start_url,
+                sso_account_id,
+                sso_role_name,
+            } => output.push(Provider::Sso {
+                sso_session: sso_session_name.map(|sess| sess.to_string()),
+                sso_region: sso_region.into(),
+                sso_start_url: sso_start_url.into(),
+                sso_account_id: sso_account_id.into(),
+                sso_role_name: sso_role_name.into(),
+            }),
         };
         for role in profile_chain.chain {
             output.push(Provider::AssumeRole {
                 role_arn: role.role_arn.into(),
                 external_id: role.external_id.map(ToString::to_string),
                 role_session_name: role.session_name.map(ToString::to_string),
+                duration_seconds: role.duration_seconds,
+                mfa_serial: role.mfa_serial.map(ToString::to_string),
             })
         }
         output
@@ -417,6 +666,8 @@ mod tests {
             role_arn: String,
             external_id: Option<String>,
             role_session_name: Option<String>,
+            duration_seconds: Option<u32>,
+            mfa_serial: Option<String>,
         },
         AccessKey {
             access_key_id: String,
@@ -429,5 +680,13 @@ mod tests {
             web_identity_token_file: String,
             role_session_name: Option<String>,
         },
+        CredentialProcess(String),
+        Sso {
+            sso_session: Option<String>,
+            sso_region: String,
+            sso_start_url: String,
+            sso_account_id: String,
+            sso_role_name: String,
+        },
     }
 }
@@ -8,9 +8,12 @@
 //! AWS profiles are typically stored in `~/.aws/config` and `~/.aws/credentials`. For more details
 //! see the [`load`](parser::load) function.
 
-mod parser;
+pub(crate) mod parser;
 #[doc(inline)]
-pub use parser::{load, Profile, ProfileParseError, ProfileSet, Property};
+pub use parser::{
+    load, Profile, ProfileParseError, ProfileSet, Property, ServiceConfig, ServicesSection,
+    SsoSession,
+};
 
 pub mod app_name;
 pub mod credentials;
@@ -5,7 +5,7 @@
 
 mod normalize;
 mod parse;
-mod source;
+pub(crate) mod source;
 
 use crate::profile::parser::parse::parse_profile_file;
 use crate::profile::parser::source::{FileKind, Source};
@@ -15,6 +15,9 @@ use std::collections::HashMap;
 
 pub use self::parse::ProfileParseError;
 
+/// Profile property selecting a `[services <name>]` section
+const SERVICES_PROPERTY: &str = "services";
+
 /// Read & parse AWS config files
 ///
 /// Loads AWS config file from the filesystem, parses them, and converts them into a [`ProfileSet`](ProfileSet).
@@ -75,6 +78,8 @@ pub async fn load(fs: &Fs, env: &Env) -> Result<ProfileSet, ProfileParseError> {
 #[derive(Debug, Eq, Clone, PartialEq)]
 pub struct ProfileSet {
     profiles: HashMap<String, Profile>,
+    sso_sessions: HashMap<String, SsoSession>,
+    services: HashMap<String, ServicesSection>,
     selected_profile: Cow<'static, str>,
 }
 
@@ -122,6 +127,27 @@ impl ProfileSet {
         self.profiles.get(profile_name)
     }
 
+    /// Retrieves a named `[sso-session <name>]` section from the profile set
+    pub fn sso_session(&self, session_name: &str) -> Option<&SsoSession> {
+        self.sso_sessions.get(session_name)
+    }
+
+    /// Retrieves a named `[services <name>]` section from the profile set
+    pub fn services(&self, services_name: &str) -> Option<&ServicesSection> {
+        self.services.get(services_name)
+    }
+
+    /// Retrieves the configuration for a service from the `[services <name>]` section referenced
+    /// by the `services` property of the currently selected profile
+    ///
+    /// `service_id` is matched case-insensitively, with spaces and dashes treated as underscores,
+    /// so both `Elastic Beanstalk` and `elastic_beanstalk` will find an `elastic_beanstalk` entry.
+    pub fn service_config(&self, service_id: &str) -> Option<&ServiceConfig> {
+        self.get(SERVICES_PROPERTY)
+            .and_then(|name| self.services(name))
+            .and_then(|services| services.service(service_id))
+    }
+
     /// Returns the name of the currently selected profile
     pub fn selected_profile(&self) -> &str {
         self.selected_profile.as_ref()
@@ -152,9 +178,32 @@ impl ProfileSet {
     fn empty() -> Self {
         Self {
             profiles: Default::default(),
+            sso_sessions: Default::default(),
+            services: Default::default(),
             selected_profile: "default".into(),
         }
     }
+
+    /// Add `[sso-session <name>]` sections to a profile set created with [`ProfileSet::new`]
+    #[cfg(test)]
+    pub(crate) fn with_sso_sessions(
+        mut self,
+        sso_sessions: HashMap<String, HashMap<String, String>>,
+    ) -> Self {
+        for (name, properties) in sso_sessions {
+            self.sso_sessions.insert(
+                name.clone(),
+                SsoSession::new(
+                    name,
+                    properties
+                        .into_iter()
+                        .map(|(k, v)| (k.clone(), Property::new(k, v)))
+                        .collect(),
+                ),
+            );
+        }
+        self
+    }
 }
 
 /// An individual configuration profile
@@ -183,6 +232,111 @@ impl Profile {
     }
 }
 
+/// A named `[sso-session <name>]` section of the config file
+///
+/// SSO sessions allow multiple profiles to share a single SSO configuration:
+/// ```ini
+/// [profile dev]
+/// sso_session = my-sso
+/// sso_account_id = 123456789011
+/// sso_role_name = readOnly
+///
+/// [sso-session my-sso]
+/// sso_region = us-east-1
+/// sso_start_url = https://my-sso-portal.awsapps.com/start
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SsoSession {
+    name: String,
+    properties: HashMap<String, Property>,
+}
+
+impl SsoSession {
+    /// Create a new SSO session
+    pub fn new(name: String, properties: HashMap<String, Property>) -> Self {
+        Self { name, properties }
+    }
+
+    /// The name of this SSO session
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a reference to the property named `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(|prop| prop.value())
+    }
+}
+
+/// A named `[services <name>]` section of the config file
+///
+/// Services sections configure individual services. A profile selects a services section with
+/// its `services` property:
+/// ```ini
+/// [profile dev]
+/// services = local
+///
+/// [services local]
+/// s3 =
+///   endpoint_url = http://localhost:9000
+/// dynamodb =
+///   endpoint_url = http://localhost:8000
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ServicesSection {
+    name: String,
+    services: HashMap<String, ServiceConfig>,
+}
+
+impl ServicesSection {
+    /// Create a new services section
+    ///
+    /// `services` must be keyed by normalized service ID, see [`ProfileSet::service_config`].
+    pub(crate) fn new(name: String, services: HashMap<String, ServiceConfig>) -> Self {
+        Self { name, services }
+    }
+
+    /// The name of this services section
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the configuration for `service_id`
+    ///
+    /// See [`ProfileSet::service_config`] for how service IDs are matched.
+    pub fn service(&self, service_id: &str) -> Option<&ServiceConfig> {
+        self.services
+            .get(&normalize::normalize_service_id(service_id))
+    }
+}
+
+/// The configuration of a single service within a [`ServicesSection`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ServiceConfig {
+    service_id: String,
+    properties: HashMap<String, Property>,
+}
+
+impl ServiceConfig {
+    /// Create a new service configuration
+    pub fn new(service_id: String, properties: HashMap<String, Property>) -> Self {
+        Self {
+            service_id,
+            properties,
+        }
+    }
+
+    /// The service ID as it was written in the config file
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    /// Returns a reference to the property named `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(|prop| prop.value())
+    }
+}
+
 /// Key-Value property pair
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Property {
@@ -250,6 +404,76 @@ mod test {
         assert!(profile_set.is_empty());
     }
 
+    #[test]
+    fn sso_session_sections() {
+        let source = Source {
+            config_file: File {
+                path: "~/.aws/config".to_string(),
+                contents: "[profile dev]\nsso_session = my-sso\n\n\
+                    [sso-session my-sso]\nsso_region = us-east-1\n"
+                    .into(),
+            },
+            credentials_file: File {
+                path: "~/.aws/credentials".to_string(),
+                contents: "[sso-session ignored]\nsso_region = us-west-2\n".into(),
+            },
+            profile: "dev".into(),
+        };
+        let profile_set = ProfileSet::parse(source).expect("valid profile");
+        assert_eq!(profile_set.get("sso_session"), Some("my-sso"));
+        assert_eq!(
+            profile_set
+                .sso_session("my-sso")
+                .expect("session exists")
+                .get("sso_region"),
+            Some("us-east-1")
+        );
+        assert!(profile_set.get_profile("sso-session my-sso").is_none());
+        // sso-session sections are only valid in the config file
+        assert!(profile_set.sso_session("ignored").is_none());
+    }
+
+    #[test]
+    #[traced_test]
+    fn services_sections() {
+        let source = Source {
+            config_file: File {
+                path: "~/.aws/config".to_string(),
+                contents: "[profile dev]\nservices = local\n\n\
+                    [services local]\n\
+                    s3 =\n  endpoint_url = http://localhost:9000\n\
+                    Elastic-Beanstalk =\n  endpoint_url = http://localhost:9001\n\
+                    dynamodb = http://localhost:8000\n"
+                    .into(),
+            },
+            credentials_file: File {
+                path: "~/.aws/credentials".to_string(),
+                contents: "[services ignored]\ns3 =\n  endpoint_url = http://localhost:9002\n"
+                    .into(),
+            },
+            profile: "dev".into(),
+        };
+        let profile_set = ProfileSet::parse(source).expect("valid profile");
+        let s3 = profile_set.service_config("S3").expect("s3 is configured");
+        assert_eq!(s3.service_id(), "s3");
+        assert_eq!(s3.get("endpoint_url"), Some("http://localhost:9000"));
+        assert_eq!(
+            profile_set
+                .service_config("Elastic Beanstalk")
+                .expect("service IDs are normalized")
+                .get("endpoint_url"),
+            Some("http://localhost:9001")
+        );
+        // services must be configured with sub-properties
+        assert!(profile_set.service_config("dynamodb").is_none());
+        assert!(logs_contain(
+            "service `dynamodb` ignored because services must be configured with sub-properties"
+        ));
+        assert!(profile_set.get_profile("services local").is_none());
+        // services sections are only valid in the config file
+        assert!(profile_set.services("ignored").is_none());
+    }
+
     /// Run all tests from the fuzzing corpus to validate coverage
     #[test]
     #[ignore]
@@ -8,10 +8,12 @@ use std::collections::HashMap;
 
 use crate::profile::parser::parse::{RawProfileSet, WHITESPACE};
 use crate::profile::parser::source::FileKind;
-use crate::profile::{Profile, ProfileSet, Property};
+use crate::profile::{Profile, ProfileSet, Property, ServiceConfig, ServicesSection, SsoSession};
 
 const DEFAULT: &str = "default";
 const PROFILE_PREFIX: &str = "profile";
+const SSO_SESSION_PREFIX: &str = "sso-session";
+const SERVICES_PREFIX: &str = "services";
 
 #[derive(Eq, PartialEq, Hash, Debug)]
 struct ProfileName<'a> {
@@ -61,6 +63,41 @@ impl ProfileName<'_> {
     }
 }
 
+/// Parse the name of an `[sso-session <name>]` section
+///
+/// Returns `None` if the section is not an SSO session section.
+fn parse_sso_session_name(input: &str) -> Option<&str> {
+    parse_section_name(SSO_SESSION_PREFIX, input)
+}
+
+/// Parse the name of a `[services <name>]` section
+///
+/// Returns `None` if the section is not a services section.
+fn parse_services_name(input: &str) -> Option<&str> {
+    parse_section_name(SERVICES_PREFIX, input)
+}
+
+fn parse_section_name<'a>(prefix: &str, input: &'a str) -> Option<&'a str> {
+    let input = input.trim_matches(WHITESPACE);
+    match input.strip_prefix(prefix) {
+        // e.g. sso-sessionfoo isn't considered an sso-session section
+        Some(stripped) if stripped.starts_with(WHITESPACE) => Some(stripped.trim()),
+        _ => None,
+    }
+}
+
+/// Normalize a service ID so that `S3`, `s3`, and `Elastic Beanstalk` / `elastic_beanstalk` match
+pub(crate) fn normalize_service_id(service_id: &str) -> String {
+    service_id
+        .trim_matches(WHITESPACE)
+        .chars()
+        .map(|ch| match ch {
+            ' ' | '-' => '_',
+            ch => ch.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
 /// Normalize a raw profile into a `MergedProfile`
 ///
 /// This function follows the following rules, codified in the tests & the reference Java implementation
@@ -68,7 +105,65 @@ impl ProfileName<'_> {
 /// - Profile names are validated (see `validate_profile_name`)
 /// - A profile named `profile default` takes priority over a profile named `default`.
 /// - Profiles with identical names are merged
+/// - `[sso-session <name>]` and `[services <name>]` sections in the config file are collected
+///   separately from profiles
 pub fn merge_in(base: &mut ProfileSet, raw_profile_set: RawProfileSet, kind: FileKind) {
+    // split out `[services <name>]` sections, these are not profiles
+    let (services, raw_profile_set): (Vec<_>, Vec<_>) = raw_profile_set
+        .into_iter()
+        .partition(|(name, _)| parse_services_name(name).is_some());
+    for (name, raw_services) in services {
+        let name = parse_services_name(name).expect("partitioned above");
+        if !matches!(kind, FileKind::Config) {
+            tracing::warn!(
+                "services `{}` ignored because services sections are only valid in the config file",
+                name
+            );
+            continue;
+        }
+        if validate_identifier(name).is_err() {
+            tracing::warn!(
+                "services `{}` ignored because `{}` was not a valid identifier",
+                name,
+                name
+            );
+            continue;
+        }
+        let section = base
+            .services
+            .entry(name.to_string())
+            .or_insert_with(|| ServicesSection::new(name.to_string(), Default::default()));
+        merge_services(section, raw_services);
+    }
+
+    // split out `[sso-session <name>]` sections, these are not profiles
+    let (sso_sessions, raw_profile_set): (Vec<_>, Vec<_>) = raw_profile_set
+        .into_iter()
+        .partition(|(name, _)| parse_sso_session_name(name).is_some());
+    for (name, raw_session) in sso_sessions {
+        let name = parse_sso_session_name(name).expect("partitioned above");
+        if !matches!(kind, FileKind::Config) {
+            tracing::warn!(
+                "sso-session `{}` ignored because sso-session sections are only valid in the config file",
+                name
+            );
+            continue;
+        }
+        if validate_identifier(name).is_err() {
+            tracing::warn!(
+                "sso-session `{}` ignored because `{}` was not a valid identifier",
+                name,
+                name
+            );
+            continue;
+        }
+        let session = base
+            .sso_sessions
+            .entry(name.to_string())
+            .or_insert_with(|| SsoSession::new(name.to_string(), Default::default()));
+        merge_into_base(&session.name, &mut session.properties, raw_session);
+    }
+
     // parse / validate profile names
     let validated_profiles = raw_profile_set
         .into_iter()
@@ -106,20 +201,65 @@ pub fn merge_in(base: &mut ProfileSet, raw_profile_set: RawProfileSet, kind: Fil
             .profiles
             .entry(profile_name.name.to_string())
             .or_insert_with(|| Profile::new(profile_name.name.to_string(), Default::default()));
-        merge_into_base(profile, raw_profile)
+        merge_into_base(&profile.name, &mut profile.properties, raw_profile)
+    }
+}
+
+/// Merge the services of a raw `[services <name>]` section into `section`
+///
+/// Each service is configured with sub-properties:
+/// ```ini
+/// [services local]
+/// s3 =
+///   endpoint_url = http://localhost:9000
+/// ```
+fn merge_services(section: &mut ServicesSection, raw_section: HashMap<&str, Cow<'_, str>>) {
+    for (service_id, value) in raw_section {
+        if validate_identifier(service_id).is_err() {
+            tracing::warn!(services = %section.name, key = ?service_id, "service ignored because `{}` was not a valid identifier", service_id);
+            continue;
+        }
+        let mut lines = value.lines();
+        if !lines.next().unwrap_or_default().is_empty() {
+            tracing::warn!(services = %section.name, "service `{}` ignored because services must be configured with sub-properties", service_id);
+            continue;
+        }
+        let service = section
+            .services
+            .entry(normalize_service_id(service_id))
+            .or_insert_with(|| ServiceConfig::new(service_id.to_string(), Default::default()));
+        for line in lines {
+            // sub-properties were validated during parsing
+            if let Some((k, v)) = line.split_once('=') {
+                let k = k.trim_matches(WHITESPACE);
+                match validate_identifier(k) {
+                    Ok(k) => {
+                        service.properties.insert(
+                            k.to_owned(),
+                            Property::new(k.to_owned(), v.trim_matches(WHITESPACE).to_owned()),
+                        );
+                    }
+                    Err(_) => {
+                        tracing::warn!(services = %section.name, service = %service_id, key = ?k, "key ignored because `{}` was not a valid identifier", k);
+                    }
+                }
+            }
+        }
     }
 }
 
-fn merge_into_base<'a>(target: &mut Profile, profile: HashMap<&str, Cow<'a, str>>) {
+fn merge_into_base<'a>(
+    section_name: &str,
+    target: &mut HashMap<String, Property>,
+    profile: HashMap<&str, Cow<'a, str>>,
+) {
     for (k, v) in profile {
         match validate_identifier(k) {
             Ok(k) => {
-                target
-                    .properties
-                    .insert(k.to_owned(), Property::new(k.to_owned(), v.into()));
+                target.insert(k.to_owned(), Property::new(k.to_owned(), v.into()));
             }
             Err(_) => {
-                tracing::warn!(profile = %&target.name, key = ?k, "key ignored because `{}` was not a valid identifier", k);
+                tracing::warn!(profile = %section_name, key = ?k, "key ignored because `{}` was not a valid identifier", k);
             }
         }
     }
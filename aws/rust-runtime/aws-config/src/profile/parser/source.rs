@@ -168,7 +168,7 @@ fn expand_home(path: impl AsRef<Path>, home_dir: &Option<String>) -> PathBuf {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Os {
+pub(crate) enum Os {
     Windows,
     NotWindows,
 }
@@ -183,7 +183,7 @@ impl Os {
 }
 
 /// Resolve a home directory given a set of environment variables
-fn home_dir(env_var: &os_shim_internal::Env, os: Os) -> Option<String> {
+pub(crate) fn home_dir(env_var: &os_shim_internal::Env, os: Os) -> Option<String> {
     if let Ok(home) = env_var.get("HOME") {
         tracing::debug!(src = "HOME", "loaded home directory");
         return Some(home);
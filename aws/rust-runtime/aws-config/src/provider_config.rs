@@ -9,7 +9,7 @@ use crate::connector::default_connector;
 use aws_smithy_async::rt::sleep::{default_async_sleep, AsyncSleep};
 use aws_smithy_client::erase::DynConnector;
 use aws_smithy_client::timeout;
-use aws_types::os_shim_internal::{Env, Fs, TimeSource};
+use aws_types::os_shim_internal::{Env, Fs, Process, TimeSource};
 use aws_types::region::Region;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
@@ -29,6 +29,7 @@ use aws_smithy_client::erase::boxclone::BoxCloneService;
 pub struct ProviderConfig {
     env: Env,
     fs: Fs,
+    process: Process,
     time_source: TimeSource,
     connector: HttpConnector,
     sleep: Option<Arc<dyn AsyncSleep>>,
@@ -44,6 +45,8 @@ pub(crate) enum HttpConnector {
     ConnectorFn(Arc<MakeConnectorFn>),
     #[cfg(feature = "tcp-connector")]
     TcpConnector(BoxCloneService<http::Uri, tokio::net::TcpStream, tower::BoxError>),
+    #[cfg(feature = "tcp-connector")]
+    Proxy(Arc<aws_smithy_client::proxy::ProxyConfig>),
 }
 
 impl Default for HttpConnector {
@@ -66,12 +69,31 @@ impl HttpConnector {
             HttpConnector::Prebuilt(conn) => conn.clone(),
             HttpConnector::ConnectorFn(func) => func(settings, sleep),
             #[cfg(feature = "tcp-connector")]
-            HttpConnector::TcpConnector(connection) => Some(DynConnector::new(
-                aws_smithy_client::hyper_ext::Adapter::builder()
+            HttpConnector::TcpConnector(connection) => {
+                let builder = aws_smithy_client::hyper_ext::Adapter::builder()
+                    .timeout(&settings.timeout_settings)
+                    .sleep_impl(sleep.unwrap());
+                // negotiate TLS over the TCP connection for `https` endpoints
+                #[cfg(any(feature = "rustls", feature = "native-tls"))]
+                let connector = builder.build_https_with_connector(connection.clone());
+                #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+                let connector = DynConnector::new(builder.build(connection.clone()));
+                Some(connector)
+            }
+            #[cfg(feature = "tcp-connector")]
+            HttpConnector::Proxy(proxy_config) => {
+                let builder = aws_smithy_client::hyper_ext::Adapter::builder()
                     .timeout(&settings.timeout_settings)
                     .sleep_impl(sleep.unwrap())
-                    .build(connection.clone()),
-            )),
+                    .proxy_config(proxy_config.as_ref().clone());
+                let connection =
+                    aws_smithy_client::conns::http_with_proxy(proxy_config.as_ref().clone());
+                #[cfg(any(feature = "rustls", feature = "native-tls"))]
+                let connector = builder.build_https_with_connector(connection);
+                #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+                let connector = DynConnector::new(builder.build(connection));
+                Some(connector)
+            }
         }
     }
 }
@@ -81,6 +103,7 @@ impl Debug for ProviderConfig {
         f.debug_struct("ProviderConfig")
             .field("env", &self.env)
             .field("fs", &self.fs)
+            .field("process", &self.process)
             .field("sleep", &self.sleep)
             .field("region", &self.region)
             .finish()
@@ -92,6 +115,7 @@ impl Default for ProviderConfig {
         Self {
             env: Env::default(),
             fs: Fs::default(),
+            process: Process::default(),
             time_source: TimeSource::default(),
             connector: HttpConnector::default(),
             sleep: default_async_sleep(),
@@ -104,8 +128,9 @@ impl Default for ProviderConfig {
 impl ProviderConfig {
     /// ProviderConfig with all configuration removed
     ///
-    /// Unlike [`ProviderConfig::empty`] where `env` and `fs` will use their non-mocked implementations,
-    /// this method will use an empty mock environment and an empty mock file system.
+    /// Unlike [`ProviderConfig::empty`] where `env`, `fs` and `process` will use their non-mocked
+    /// implementations, this method will use an empty mock environment, an empty mock file system
+    /// and a mock process runner without any commands.
     pub fn no_configuration() -> Self {
         use aws_types::os_shim_internal::ManualTimeSource;
         use std::collections::HashMap;
@@ -113,6 +138,7 @@ impl ProviderConfig {
         Self {
             env: Env::from_slice(&[]),
             fs: Fs::from_raw_map(HashMap::new()),
+            process: Process::from_slice(&[]),
             time_source: TimeSource::manual(&ManualTimeSource::new(UNIX_EPOCH)),
             connector: HttpConnector::Prebuilt(None),
             sleep: None,
@@ -163,6 +189,7 @@ impl ProviderConfig {
         ProviderConfig {
             env: Env::default(),
             fs: Fs::default(),
+            process: Process::default(),
             time_source: TimeSource::default(),
             connector: HttpConnector::Prebuilt(None),
             sleep: None,
@@ -199,6 +226,11 @@ impl ProviderConfig {
         self.fs.clone()
     }
 
+    #[allow(dead_code)]
+    pub(crate) fn process(&self) -> Process {
+        self.process.clone()
+    }
+
     #[allow(dead_code)]
     pub(crate) fn time_source(&self) -> TimeSource {
         self.time_source.clone()
@@ -254,6 +286,11 @@ impl ProviderConfig {
         ProviderConfig { env, ..self }
     }
 
+    #[doc(hidden)]
+    pub fn with_process(self, process: Process) -> Self {
+        ProviderConfig { process, ..self }
+    }
+
     #[doc(hidden)]
     pub fn with_time_source(self, time_source: TimeSource) -> Self {
         ProviderConfig {
@@ -278,6 +315,9 @@ impl ProviderConfig {
 
     /// Override the TCP connector for this configuration
     ///
+    /// When the `rustls` or `native-tls` feature is enabled, TLS is negotiated over the
+    /// connections opened by `connector` for `https` endpoints.
+    ///
     /// # Stability
     /// This method is may to change to support HTTP configuration.
     #[cfg(feature = "tcp-connector")]
@@ -291,6 +331,34 @@ impl ProviderConfig {
         }
     }
 
+    /// Connect through the proxies of `proxy_config`
+    ///
+    /// When the `rustls` or `native-tls` feature is enabled, TLS is negotiated end-to-end with
+    /// `https` endpoints through a tunnel.
+    ///
+    /// # Examples
+    /// Connect through the proxy configured by the `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY`
+    /// environment variables:
+    /// ```rust
+    /// use aws_config::provider_config::ProviderConfig;
+    /// use aws_smithy_client::proxy::ProxyConfig;
+    ///
+    /// # fn example() -> Result<(), aws_smithy_client::proxy::InvalidProxy> {
+    /// let provider_config = ProviderConfig::default().with_proxy_config(ProxyConfig::from_env()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Stability
+    /// This method is may to change to support HTTP configuration.
+    #[cfg(feature = "tcp-connector")]
+    pub fn with_proxy_config(self, proxy_config: aws_smithy_client::proxy::ProxyConfig) -> Self {
+        ProviderConfig {
+            connector: HttpConnector::Proxy(Arc::new(proxy_config)),
+            ..self
+        }
+    }
+
     /// Override the sleep implementation for this configuration
     pub fn with_sleep(self, sleep: impl AsyncSleep + 'static) -> Self {
         ProviderConfig {
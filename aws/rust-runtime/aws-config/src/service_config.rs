@@ -0,0 +1,201 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Service-specific configuration loaded from the environment and the shared config file
+
+use aws_types::os_shim_internal::Env;
+use aws_types::service_config::{LoadServiceConfig, ServiceConfigKey};
+
+use crate::profile::{self, ProfileSet};
+use crate::provider_config::ProviderConfig;
+
+/// Service-specific configuration
+///
+/// Values are loaded from:
+/// 1. The service-specific environment variable, e.g. `AWS_ENDPOINT_URL_S3`, when the key has one
+/// 2. The `[services <name>]` section selected by the active profile
+/// 3. The environment variable shared by all services, e.g. `AWS_ENDPOINT_URL`, when the key has one
+/// 4. The property of the active profile, e.g. `endpoint_url`
+#[derive(Debug)]
+pub(crate) struct ServiceConfigSource {
+    env: Env,
+    profiles: ProfileSet,
+}
+
+impl ServiceConfigSource {
+    /// Load service-specific configuration from the environment and profile files
+    ///
+    /// Returns `None` if the profile files could not be parsed.
+    pub(crate) async fn load(provider_config: &ProviderConfig) -> Option<Self> {
+        let env = provider_config.env();
+        match profile::load(&provider_config.fs(), &env).await {
+            Ok(profiles) => Some(Self { env, profiles }),
+            Err(err) => {
+                tracing::warn!(err = %err, "failed to parse profile, service-specific configuration will not be loaded");
+                None
+            }
+        }
+    }
+}
+
+impl LoadServiceConfig for ServiceConfigSource {
+    fn load_config(&self, key: ServiceConfigKey<'_>) -> Option<String> {
+        if let Some(value) = key
+            .service_env_var()
+            .and_then(|env_var| self.env.get(&env_var).ok())
+        {
+            return Some(value);
+        }
+        if let Some(value) = self
+            .profiles
+            .service_config(key.service_id())
+            .and_then(|service| service.get(key.profile()))
+        {
+            return Some(value.to_string());
+        }
+        if let Some(value) = key.env().and_then(|env_var| self.env.get(env_var).ok()) {
+            return Some(value);
+        }
+        self.profiles
+            .get(key.profile())
+            .map(|value| value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use aws_types::os_shim_internal::{Env, Fs};
+    use aws_types::service_config::{LoadServiceConfig, ServiceConfigKey};
+
+    use crate::provider_config::ProviderConfig;
+    use crate::service_config::ServiceConfigSource;
+
+    const CONFIG: &str = r#"[default]
+services = local
+
+[profile other]
+region = us-east-1
+
+[services local]
+s3 =
+  endpoint_url = http://localhost:9000
+elastic_beanstalk =
+  endpoint_url = http://localhost:9001
+"#;
+
+    async fn load(profile: &str) -> ServiceConfigSource {
+        let provider_config = ProviderConfig::no_configuration()
+            .with_env(Env::from_slice(&[
+                ("HOME", "/home"),
+                ("AWS_PROFILE", profile),
+                ("AWS_ENDPOINT_URL_DYNAMODB", "http://localhost:8000"),
+                (
+                    "AWS_ENDPOINT_URL_ELASTIC_BEANSTALK",
+                    "http://localhost:8001",
+                ),
+            ]))
+            .with_fs(Fs::from_slice(&[("/home/.aws/config", CONFIG)]));
+        ServiceConfigSource::load(&provider_config)
+            .await
+            .expect("valid profile")
+    }
+
+    #[tokio::test]
+    async fn per_service_endpoint_url() {
+        let source = load("default").await;
+        assert_eq!(
+            Some("http://localhost:9000".to_string()),
+            source.load_config(ServiceConfigKey::new("S3", "endpoint_url"))
+        );
+        assert_eq!(
+            Some("http://localhost:9001".to_string()),
+            source.load_config(ServiceConfigKey::new("Elastic Beanstalk", "endpoint_url"))
+        );
+        assert_eq!(
+            None,
+            source.load_config(ServiceConfigKey::new("DynamoDB", "endpoint_url"))
+        );
+    }
+
+    #[tokio::test]
+    async fn profile_without_services() {
+        let source = load("other").await;
+        assert_eq!(
+            None,
+            source.load_config(ServiceConfigKey::new("S3", "endpoint_url"))
+        );
+    }
+
+    #[tokio::test]
+    async fn service_env_var_takes_precedence() {
+        let source = load("default").await;
+        let key = |service_id| {
+            ServiceConfigKey::new(service_id, "endpoint_url").with_env("AWS_ENDPOINT_URL")
+        };
+        assert_eq!(
+            Some("http://localhost:8000".to_string()),
+            source.load_config(key("DynamoDB"))
+        );
+        assert_eq!(
+            Some("http://localhost:8001".to_string()),
+            source.load_config(key("Elastic Beanstalk"))
+        );
+        assert_eq!(
+            Some("http://localhost:9000".to_string()),
+            source.load_config(key("S3"))
+        );
+        // only keys with an environment variable are loaded from the environment
+        assert_eq!(
+            Some("http://localhost:9001".to_string()),
+            source.load_config(ServiceConfigKey::new("Elastic Beanstalk", "endpoint_url"))
+        );
+    }
+
+    #[tokio::test]
+    async fn fall_back_to_global_values() {
+        async fn load_global(env: &[(&str, &str)]) -> ServiceConfigSource {
+            let config = format!(
+                "{}\n[profile global]\nservices = local\nendpoint_url = http://localhost:4000\n",
+                CONFIG
+            );
+            let provider_config = ProviderConfig::no_configuration()
+                .with_env(Env::from_slice(env))
+                .with_fs(Fs::from_slice(&[("/home/.aws/config", config.as_str())]));
+            ServiceConfigSource::load(&provider_config)
+                .await
+                .expect("valid profile")
+        }
+        let key = |service_id| {
+            ServiceConfigKey::new(service_id, "endpoint_url").with_env("AWS_ENDPOINT_URL")
+        };
+
+        let source = load_global(&[("HOME", "/home"), ("AWS_PROFILE", "global")]).await;
+        assert_eq!(
+            Some("http://localhost:9000".to_string()),
+            source.load_config(key("S3"))
+        );
+        assert_eq!(
+            Some("http://localhost:4000".to_string()),
+            source.load_config(key("DynamoDB"))
+        );
+
+        // the global environment variable takes precedence over the profile, but not over the
+        // service-specific configuration
+        let source = load_global(&[
+            ("HOME", "/home"),
+            ("AWS_PROFILE", "global"),
+            ("AWS_ENDPOINT_URL", "http://localhost:4566"),
+        ])
+        .await;
+        assert_eq!(
+            Some("http://localhost:9000".to_string()),
+            source.load_config(key("S3"))
+        );
+        assert_eq!(
+            Some("http://localhost:4566".to_string()),
+            source.load_config(key("DynamoDB"))
+        );
+    }
+}
@@ -0,0 +1,499 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! SSO Credentials Provider
+//!
+//! This credentials provider enables loading credentials from `~/.aws/sso/cache`. For more information,
+//! see [Using AWS SSO Credentials](https://docs.aws.amazon.com/toolkit-for-vscode/latest/userguide/sso-credentials.html)
+//!
+//! This provider is included automatically when profiles are loaded. A profile may either configure
+//! SSO directly:
+//! ```ini
+//! [profile sso-legacy]
+//! sso_start_url = https://my-sso-portal.awsapps.com/start
+//! sso_region = us-east-1
+//! sso_account_id = 123456789011
+//! sso_role_name = readOnly
+//! ```
+//!
+//! Or reference a shared `[sso-session <name>]` section:
+//! ```ini
+//! [profile sso-session]
+//! sso_session = my-sso
+//! sso_account_id = 123456789011
+//! sso_role_name = readOnly
+//!
+//! [sso-session my-sso]
+//! sso_start_url = https://my-sso-portal.awsapps.com/start
+//! sso_region = us-east-1
+//! ```
+//!
+//! This provider does not perform the SSO login itself. The cached access token must first be
+//! created, e.g. with `aws sso login`.
+
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use aws_sdk_sso::model::RoleCredentials;
+use aws_smithy_json::deserialize::token::skip_value;
+use aws_smithy_json::deserialize::{json_token_iter, Token};
+use aws_types::credentials::{self, future, CredentialsError, ProvideCredentials};
+use aws_types::os_shim_internal::{Env, Fs, TimeSource};
+use aws_types::region::Region;
+use aws_types::Credentials;
+use tracing::Instrument;
+
+use crate::connector::expect_connector;
+use crate::json_credentials::{parse_expiration, InvalidJsonCredentials};
+use crate::profile::parser::source::{home_dir, Os};
+use crate::provider_config::ProviderConfig;
+
+const PROVIDER_NAME: &str = "SSO";
+
+/// SSO Credentials Provider
+///
+/// _Note: This provider is part of the default credentials chain and is integrated with the profile-file provider._
+///
+/// This credentials provider will use cached SSO tokens stored in `~/.aws/sso/cache/<hash>.json`.
+/// `<hash>` is computed based on the configured [`session_name`](Builder::session_name)
+/// when set, or on the [`start_url`](Builder::start_url) otherwise.
+#[derive(Debug)]
+pub struct SsoCredentialsProvider {
+    fs: Fs,
+    env: Env,
+    sso_config: SsoConfig,
+    client: aws_hyper::StandardClient,
+    time_source: TimeSource,
+}
+
+impl SsoCredentialsProvider {
+    /// Creates a builder for [`SsoCredentialsProvider`]
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    async fn credentials(&self) -> credentials::Result {
+        load_sso_credentials(
+            &self.sso_config,
+            &self.client,
+            &self.env,
+            &self.fs,
+            self.time_source.now(),
+        )
+        .instrument(tracing::debug_span!("load_credentials", provider = %PROVIDER_NAME))
+        .await
+    }
+}
+
+impl ProvideCredentials for SsoCredentialsProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(self.credentials())
+    }
+}
+
+/// Builder for [`SsoCredentialsProvider`]
+#[derive(Default, Debug, Clone)]
+pub struct Builder {
+    provider_config: Option<ProviderConfig>,
+    account_id: Option<String>,
+    role_name: Option<String>,
+    start_url: Option<String>,
+    region: Option<Region>,
+    session_name: Option<String>,
+}
+
+impl Builder {
+    /// Create a new builder for [`SsoCredentialsProvider`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the configuration used for this provider
+    pub fn configure(mut self, provider_config: &ProviderConfig) -> Self {
+        self.provider_config = Some(provider_config.clone());
+        self
+    }
+
+    /// Set the account id used for SSO
+    pub fn account_id(mut self, account_id: impl Into<String>) -> Self {
+        self.account_id = Some(account_id.into());
+        self
+    }
+
+    /// Set the role name used for SSO
+    pub fn role_name(mut self, role_name: impl Into<String>) -> Self {
+        self.role_name = Some(role_name.into());
+        self
+    }
+
+    /// Set the start URL used for SSO
+    pub fn start_url(mut self, start_url: impl Into<String>) -> Self {
+        self.start_url = Some(start_url.into());
+        self
+    }
+
+    /// Set the region used for SSO
+    pub fn region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Set the name of the `[sso-session <name>]` this provider was configured from
+    ///
+    /// When set, the cached token is looked up by session name instead of by start URL.
+    pub fn session_name(mut self, session_name: impl Into<String>) -> Self {
+        self.session_name = Some(session_name.into());
+        self
+    }
+
+    /// Construct an SsoCredentialsProvider from the builder
+    ///
+    /// ## Panics
+    /// This method will panic if the any of the following required fields are unset:
+    /// - [`start_url`](Self::start_url)
+    /// - [`role_name`](Self::role_name)
+    /// - [`account_id`](Self::account_id)
+    /// - [`region`](Self::region)
+    ///
+    /// It will also panic if no connector has been enabled via crate features and no connector
+    /// has been provided via [`configure`](Self::configure).
+    pub fn build(self) -> SsoCredentialsProvider {
+        let provider_config = self.provider_config.unwrap_or_default();
+        let sso_config = SsoConfig {
+            account_id: self.account_id.expect("account_id must be set"),
+            role_name: self.role_name.expect("role_name must be set"),
+            start_url: self.start_url.expect("start_url must be set"),
+            region: self.region.expect("region must be set"),
+            session_name: self.session_name,
+        };
+        SsoCredentialsProvider {
+            fs: provider_config.fs(),
+            env: provider_config.env(),
+            client: aws_hyper::Client::new(expect_connector(provider_config.default_connector())),
+            time_source: provider_config.time_source(),
+            sso_config,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum LoadTokenError {
+    InvalidCredentials(InvalidJsonCredentials),
+    ExpiredToken,
+    NoHomeDirectory,
+    IoError { err: io::Error, path: PathBuf },
+}
+
+impl Display for LoadTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadTokenError::InvalidCredentials(err) => {
+                write!(f, "SSO Token was invalid (expected JSON): {}", err)
+            }
+            LoadTokenError::ExpiredToken => write!(f, "The SSO session has expired"),
+            LoadTokenError::NoHomeDirectory => write!(f, "Could not resolve a home directory"),
+            LoadTokenError::IoError { err, path } => {
+                write!(f, "failed to read `{}`: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl Error for LoadTokenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadTokenError::InvalidCredentials(err) => Some(err as _),
+            LoadTokenError::ExpiredToken => None,
+            LoadTokenError::NoHomeDirectory => None,
+            LoadTokenError::IoError { err, .. } => Some(err as _),
+        }
+    }
+}
+
+impl From<InvalidJsonCredentials> for LoadTokenError {
+    fn from(err: InvalidJsonCredentials) -> Self {
+        LoadTokenError::InvalidCredentials(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SsoConfig {
+    pub(crate) account_id: String,
+    pub(crate) role_name: String,
+    pub(crate) start_url: String,
+    pub(crate) region: Region,
+    pub(crate) session_name: Option<String>,
+}
+
+async fn load_sso_credentials(
+    sso_config: &SsoConfig,
+    client: &aws_hyper::StandardClient,
+    env: &Env,
+    fs: &Fs,
+    now: SystemTime,
+) -> credentials::Result {
+    let token = load_token(sso_config, env, fs, now)
+        .await
+        .map_err(CredentialsError::provider_error)?;
+    let config = aws_sdk_sso::Config::builder()
+        .region(sso_config.region.clone())
+        .build();
+    let operation = aws_sdk_sso::input::GetRoleCredentialsInput::builder()
+        .role_name(&sso_config.role_name)
+        .access_token(&*token.access_token)
+        .account_id(&sso_config.account_id)
+        .build()
+        .map_err(CredentialsError::unhandled)?
+        .make_operation(&config)
+        .await
+        .map_err(CredentialsError::unhandled)?;
+    let resp = client
+        .call(operation)
+        .await
+        .map_err(CredentialsError::provider_error)?;
+    let credentials: RoleCredentials = resp
+        .role_credentials
+        .ok_or_else(|| CredentialsError::unhandled("SSO did not return credentials"))?;
+    let akid = credentials
+        .access_key_id
+        .ok_or_else(|| CredentialsError::unhandled("no access key id in response"))?;
+    let secret_key = credentials
+        .secret_access_key
+        .ok_or_else(|| CredentialsError::unhandled("no secret key in response"))?;
+    let expiration = aws_smithy_types::DateTime::from_millis(credentials.expiration)
+        .try_into()
+        .map_err(|err| {
+            CredentialsError::unhandled(format!(
+                "expiration could not be converted into a system time: {}",
+                err
+            ))
+        })?;
+    Ok(Credentials::new(
+        akid,
+        secret_key,
+        credentials.session_token,
+        Some(expiration),
+        PROVIDER_NAME,
+    ))
+}
+
+/// Load the token for `sso_config` from `~/.aws/sso/cache/<hashofinput>.json`
+async fn load_token(
+    sso_config: &SsoConfig,
+    env: &Env,
+    fs: &Fs,
+    now: SystemTime,
+) -> Result<SsoToken, LoadTokenError> {
+    let home = home_dir(env, Os::real()).ok_or(LoadTokenError::NoHomeDirectory)?;
+    let path = sso_token_path(
+        sso_config
+            .session_name
+            .as_deref()
+            .unwrap_or(&sso_config.start_url),
+        &home,
+    );
+    let data = fs
+        .read_to_end(&path)
+        .await
+        .map_err(|err| LoadTokenError::IoError { err, path })?;
+    let token = parse_token_json(&String::from_utf8_lossy(&data))?;
+    tracing::debug!(expiration = ?token.expires_at, "loaded SSO token");
+    if token.expires_at < now {
+        return Err(LoadTokenError::ExpiredToken);
+    }
+    Ok(token)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SsoToken {
+    access_token: String,
+    expires_at: SystemTime,
+}
+
+/// Parse SSO token JSON from input
+///
+/// Only `accessToken` and `expiresAt` are used, any other fields are ignored.
+pub(crate) fn parse_token_json(input: &str) -> Result<SsoToken, InvalidJsonCredentials> {
+    let mut access_token = None;
+    let mut expires_at = None;
+    let mut tokens = json_token_iter(input.as_bytes()).peekable();
+    if !matches!(tokens.next().transpose()?, Some(Token::StartObject { .. })) {
+        return Err(InvalidJsonCredentials::JsonError(
+            "expected a JSON document starting with `{`".into(),
+        ));
+    }
+    loop {
+        match tokens.next().transpose()? {
+            Some(Token::EndObject { .. }) => break,
+            Some(Token::ObjectKey { key, .. }) => {
+                if let Some(Ok(Token::ValueString { value, .. })) = tokens.peek() {
+                    match key.to_unescaped()?.as_ref() {
+                        "accessToken" => access_token = Some(value.to_unescaped()?.to_string()),
+                        "expiresAt" => expires_at = Some(value.to_unescaped()?),
+                        _ => {}
+                    }
+                }
+                skip_value(&mut tokens)?;
+            }
+            other => {
+                return Err(InvalidJsonCredentials::Other(
+                    format!("expected object key, found: {:?}", other).into(),
+                ));
+            }
+        }
+    }
+    let access_token = access_token.ok_or(InvalidJsonCredentials::MissingField("accessToken"))?;
+    let expires_at = expires_at.ok_or(InvalidJsonCredentials::MissingField("expiresAt"))?;
+    let expires_at = parse_expiration(&expires_at)?;
+    Ok(SsoToken {
+        access_token,
+        expires_at,
+    })
+}
+
+/// Determine the SSO token path for a given start URL or session name
+fn sso_token_path(cache_key: &str, home: &str) -> PathBuf {
+    // hex::encode returns a lowercase string
+    let mut out = PathBuf::with_capacity(home.len() + "/.aws/sso/cache".len() + ".json".len() + 40);
+    out.push(home);
+    out.push(".aws/sso/cache");
+    out.push(hex::encode(ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        cache_key.as_bytes(),
+    )));
+    out.set_extension("json");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::json_credentials::InvalidJsonCredentials;
+    use crate::sso::{
+        load_token, parse_token_json, sso_token_path, LoadTokenError, SsoConfig, SsoToken,
+    };
+    use aws_types::os_shim_internal::{Env, Fs};
+    use aws_types::region::Region;
+    use std::collections::HashMap;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn deserialize_valid_tokens() {
+        let token = r#"
+        {
+            "accessToken": "base64string",
+            "expiresAt": "2009-02-13T23:31:30Z",
+            "region": "us-west-2",
+            "startUrl": "https://d-abc123.awsapps.com/start"
+        }"#;
+        assert_eq!(
+            parse_token_json(token).expect("valid"),
+            SsoToken {
+                access_token: "base64string".into(),
+                expires_at: UNIX_EPOCH + Duration::from_secs(1234567890)
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_timestamp() {
+        let token = r#"
+        {
+            "accessToken": "base64string",
+            "expiresAt": "notatimestamp",
+            "region": "us-west-2",
+            "startUrl": "https://d-abc123.awsapps.com/start"
+        }"#;
+        let err = parse_token_json(token).expect_err("invalid timestamp");
+        assert!(format!("{}", err).contains("invalid date"), "{}", err);
+    }
+
+    #[test]
+    fn missing_fields() {
+        let token = r#"
+        {
+            "expiresAt": "notatimestamp",
+            "region": "us-west-2",
+            "startUrl": "https://d-abc123.awsapps.com/start"
+        }"#;
+        let err = parse_token_json(token).expect_err("no access token");
+        assert!(
+            matches!(err, InvalidJsonCredentials::MissingField("accessToken")),
+            "{:?}",
+            err
+        );
+
+        let token = r#"
+        {
+            "accessToken": "akid",
+            "region": "us-west-2",
+            "startUrl": "https://d-abc123.awsapps.com/start"
+        }"#;
+        let err = parse_token_json(token).expect_err("no expiration");
+        assert!(
+            matches!(err, InvalidJsonCredentials::MissingField("expiresAt")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn determine_correct_cache_filenames() {
+        assert_eq!(
+            sso_token_path("https://d-92671207e4.awsapps.com/start", "/home/me").as_os_str(),
+            "/home/me/.aws/sso/cache/13f9d35043871d073ab260e020f0ffde092cb14b.json"
+        );
+        assert_eq!(
+            sso_token_path("https://d-92671207e4.awsapps.com/start", "/home/me/").as_os_str(),
+            "/home/me/.aws/sso/cache/13f9d35043871d073ab260e020f0ffde092cb14b.json"
+        );
+        assert_eq!(
+            sso_token_path("my-sso-session", "/home/me").as_os_str(),
+            "/home/me/.aws/sso/cache/b755b5ec73400c04400e978208d8559ad1f39053.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_token() {
+        let mut fs = HashMap::new();
+        fs.insert(
+            "/home/me/.aws/sso/cache/13f9d35043871d073ab260e020f0ffde092cb14b.json".to_string(),
+            r#"{"accessToken": "token", "expiresAt": "2009-02-13T23:31:30Z"}"#,
+        );
+        let fs = Fs::from_map(fs);
+        let env = Env::from_slice(&[("HOME", "/home/me")]);
+        let sso_config = SsoConfig {
+            account_id: "012345678901".into(),
+            role_name: "TestRole".into(),
+            start_url: "https://d-92671207e4.awsapps.com/start".into(),
+            region: Region::new("us-east-1"),
+            session_name: None,
+        };
+        let token = load_token(
+            &sso_config,
+            &env,
+            &fs,
+            UNIX_EPOCH + Duration::from_secs(1234567890 - 10),
+        )
+        .await
+        .expect("token is still valid");
+        assert_eq!(token.access_token, "token");
+        let err = load_token(
+            &sso_config,
+            &env,
+            &fs,
+            UNIX_EPOCH + Duration::from_secs(1234567890 + 10),
+        )
+        .await
+        .expect_err("token is expired");
+        assert!(matches!(err, LoadTokenError::ExpiredToken), "{:?}", err);
+    }
+}
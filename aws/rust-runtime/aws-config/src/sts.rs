@@ -8,6 +8,8 @@
 mod assume_role;
 pub use assume_role::{AssumeRoleProvider, AssumeRoleProviderBuilder};
 
+pub mod mfa;
+
 pub(crate) mod util {
     use aws_sdk_sts::model::Credentials as StsCredentials;
     use aws_types::credentials::{self, CredentialsError};
@@ -45,6 +47,23 @@ pub(crate) mod util {
         ))
     }
 
+    /// Minimum session duration accepted by STS AssumeRole
+    pub(crate) const MIN_DURATION_SECONDS: u32 = 900;
+
+    /// Maximum session duration accepted by STS AssumeRole
+    pub(crate) const MAX_DURATION_SECONDS: u32 = 43200;
+
+    /// Validate a requested session duration, returning it in the form expected by STS
+    pub(crate) fn validate_duration_seconds(duration_seconds: u32) -> Result<i32, String> {
+        if !(MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&duration_seconds) {
+            return Err(format!(
+                "duration_seconds must be between {} and {} seconds (found {})",
+                MIN_DURATION_SECONDS, MAX_DURATION_SECONDS, duration_seconds
+            ));
+        }
+        Ok(duration_seconds as i32)
+    }
+
     /// Create a default STS session name
     ///
     /// STS Assume Role providers MUST assign a name to their generated session. When a user does not
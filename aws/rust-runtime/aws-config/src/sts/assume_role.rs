@@ -6,16 +6,27 @@
 //! Assume credentials for a role through the AWS Security Token Service (STS).
 
 use aws_sdk_sts::error::AssumeRoleErrorKind;
+use aws_sdk_sts::model::{PolicyDescriptorType, Tag};
 use aws_sdk_sts::operation::AssumeRole;
 use aws_types::credentials::{
     self, future, CredentialsError, ProvideCredentials, SharedCredentialsProvider,
 };
 use aws_types::region::Region;
 
-use crate::provider_config::HttpSettings;
+use crate::provider_config::{HttpSettings, ProviderConfig};
+use crate::sts::mfa::{MfaSessionCache, ProvideMfaToken, SharedMfaTokenProvider};
 use aws_smithy_async::rt::sleep::default_async_sleep;
+use aws_types::os_shim_internal::TimeSource;
+use std::error::Error;
+use std::fmt;
 use tracing::Instrument;
 
+/// Maximum number of managed policy ARNs that may be passed to AssumeRole
+const MAX_POLICY_ARNS: usize = 10;
+
+/// Maximum number of session tags that may be passed to AssumeRole
+const MAX_TAGS: usize = 50;
+
 /// Credentials provider that uses credentials provided by another provider to assume a role
 /// through the AWS Security Token Service (STS).
 ///
@@ -36,13 +47,64 @@ use tracing::Instrument;
 ///   .session_name("testAR")
 ///   .build(Arc::new(EnvironmentVariableCredentialsProvider::new()) as Arc<_>);
 /// ```
+///
+/// Credentials can be scoped down further with session policies and tags:
+/// ```no_run
+/// use aws_config::sts::AssumeRoleProvider;
+/// use aws_config::environment::credentials::EnvironmentVariableCredentialsProvider;
+/// use aws_types::region::Region;
+/// use std::sync::Arc;
+///
+/// let provider = AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+///   .region(Region::from_static("us-east-2"))
+///   .duration_seconds(3600)
+///   .policy_arn("arn:aws:iam::aws:policy/ReadOnlyAccess")
+///   .tag("tenant", "tenant-a")
+///   .transitive_tag_key("tenant")
+///   .source_identity("worker-17")
+///   .build(Arc::new(EnvironmentVariableCredentialsProvider::new()) as Arc<_>);
+/// ```
+///
+/// Roles that require multi-factor authentication can be assumed by providing the serial number of
+/// the MFA device along with an [MFA token provider](crate::sts::mfa). The resulting session is
+/// cached until it expires so that a new token code is only requested once per session:
+/// ```no_run
+/// use aws_config::sts::AssumeRoleProvider;
+/// use aws_config::sts::mfa::mfa_token_fn;
+/// use aws_config::environment::credentials::EnvironmentVariableCredentialsProvider;
+/// use aws_types::region::Region;
+/// use std::sync::Arc;
+///
+/// let provider = AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/admin")
+///   .region(Region::from_static("us-east-2"))
+///   .mfa_serial("arn:aws:iam::123456789012:mfa/operator")
+///   .mfa_token_provider(mfa_token_fn(|_serial_number: &str| async {
+///       Ok("123456".to_string())
+///   }))
+///   .build(Arc::new(EnvironmentVariableCredentialsProvider::new()) as Arc<_>);
+/// ```
 #[derive(Debug)]
 pub struct AssumeRoleProvider {
     sts: aws_hyper::StandardClient,
     conf: aws_sdk_sts::Config,
-    op: aws_sdk_sts::input::AssumeRoleInput,
+    op: Result<aws_sdk_sts::input::AssumeRoleInput, InvalidAssumeRoleConfig>,
+    mfa_token_provider: Option<SharedMfaTokenProvider>,
+    mfa_session: MfaSessionCache,
+    time_source: TimeSource,
+}
+
+/// The parameters passed to [`AssumeRoleProviderBuilder`] could not form a valid AssumeRole request
+#[derive(Debug, Clone)]
+struct InvalidAssumeRoleConfig(String);
+
+impl fmt::Display for InvalidAssumeRoleConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid AssumeRole configuration: {}", self.0)
+    }
 }
 
+impl Error for InvalidAssumeRoleConfig {}
+
 impl AssumeRoleProvider {
     /// Build a new role-assuming provider for the given role.
     ///
@@ -63,8 +125,17 @@ pub struct AssumeRoleProviderBuilder {
     role_arn: String,
     external_id: Option<String>,
     session_name: Option<String>,
+    duration_seconds: Option<u32>,
+    policy: Option<String>,
+    policy_arns: Vec<String>,
+    tags: Vec<(String, String)>,
+    transitive_tag_keys: Vec<String>,
+    source_identity: Option<String>,
+    mfa_serial: Option<String>,
+    mfa_token_provider: Option<SharedMfaTokenProvider>,
     region: Option<Region>,
     connection: Option<aws_smithy_client::erase::DynConnector>,
+    time_source: TimeSource,
 }
 
 impl AssumeRoleProviderBuilder {
@@ -80,8 +151,17 @@ impl AssumeRoleProviderBuilder {
             role_arn: role.into(),
             external_id: None,
             session_name: None,
+            duration_seconds: None,
+            policy: None,
+            policy_arns: vec![],
+            tags: vec![],
+            transitive_tag_keys: vec![],
+            source_identity: None,
+            mfa_serial: None,
+            mfa_token_provider: None,
             region: None,
             connection: None,
+            time_source: TimeSource::default(),
         }
     }
 
@@ -106,6 +186,111 @@ impl AssumeRoleProviderBuilder {
         self
     }
 
+    /// Set the duration, in seconds, of the role session.
+    ///
+    /// The value must be between 900 seconds (15 minutes) and 43200 seconds (12 hours), and may
+    /// not exceed the maximum session duration configured for the role. When unset, STS defaults
+    /// to one hour.
+    pub fn duration_seconds(mut self, duration_seconds: u32) -> Self {
+        self.duration_seconds = Some(duration_seconds);
+        self
+    }
+
+    /// Set an IAM policy in JSON format that is used as an inline session policy.
+    ///
+    /// The resulting session's permissions are the intersection of the role's identity-based
+    /// policy and the session policies.
+    pub fn policy(mut self, policy: impl Into<String>) -> Self {
+        self.policy = Some(policy.into());
+        self
+    }
+
+    /// Add the ARN of an IAM managed policy to use as a managed session policy.
+    ///
+    /// Up to 10 managed policy ARNs may be provided.
+    pub fn policy_arn(mut self, arn: impl Into<String>) -> Self {
+        self.policy_arns.push(arn.into());
+        self
+    }
+
+    /// Set the ARNs of the IAM managed policies to use as managed session policies.
+    ///
+    /// This replaces any policy ARNs previously added with [`policy_arn`](Self::policy_arn).
+    pub fn policy_arns(mut self, arns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.policy_arns = arns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add a session tag to pass to the assumed role session.
+    ///
+    /// Up to 50 session tags may be provided.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the session tags to pass to the assumed role session.
+    ///
+    /// This replaces any tags previously added with [`tag`](Self::tag).
+    pub fn tags<K, V>(mut self, tags: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.tags = tags
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        self
+    }
+
+    /// Mark a session tag as transitive.
+    ///
+    /// Transitive tags persist through role chaining. Every transitive tag key must also be set
+    /// as a session tag with [`tag`](Self::tag).
+    pub fn transitive_tag_key(mut self, key: impl Into<String>) -> Self {
+        self.transitive_tag_keys.push(key.into());
+        self
+    }
+
+    /// Set the session tag keys that are transitive.
+    ///
+    /// This replaces any keys previously added with [`transitive_tag_key`](Self::transitive_tag_key).
+    pub fn transitive_tag_keys(
+        mut self,
+        keys: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.transitive_tag_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the source identity specified by the principal that is assuming the role.
+    ///
+    /// The value must be between 2 and 64 characters long.
+    pub fn source_identity(mut self, source_identity: impl Into<String>) -> Self {
+        self.source_identity = Some(source_identity.into());
+        self
+    }
+
+    /// Set the identification number of the MFA device used when assuming the role.
+    ///
+    /// This is either the serial number of a hardware device (e.g. `GAHT12345678`) or the ARN of a
+    /// virtual device (e.g. `arn:aws:iam::123456789012:mfa/user`). When set, an
+    /// [`mfa_token_provider`](Self::mfa_token_provider) must also be configured.
+    pub fn mfa_serial(mut self, serial_number: impl Into<String>) -> Self {
+        self.mfa_serial = Some(serial_number.into());
+        self
+    }
+
+    /// Set the provider used to request MFA token codes.
+    ///
+    /// A token code is only requested when a new role session is needed. See [`mfa`](crate::sts::mfa)
+    /// for more details.
+    pub fn mfa_token_provider(mut self, provider: impl ProvideMfaToken + 'static) -> Self {
+        self.mfa_token_provider = Some(SharedMfaTokenProvider::new(provider));
+        self
+    }
+
     /// Set the region to assume the role in.
     ///
     /// This dictates which STS endpoint the AssumeRole action is invoked on.
@@ -123,37 +308,118 @@ impl AssumeRoleProviderBuilder {
         self
     }
 
+    /// Configure generic options of the [`AssumeRoleProvider`]
+    ///
+    /// The connector and region of the provider config are used unless a [`connection`](Self::connection)
+    /// or [`region`](Self::region) have been set explicitly.
+    pub fn configure(mut self, provider_config: &ProviderConfig) -> Self {
+        if self.connection.is_none() {
+            self.connection = provider_config.default_connector();
+        }
+        if self.region.is_none() {
+            self.region = provider_config.region();
+        }
+        self.time_source = provider_config.time_source();
+        self
+    }
+
     /// Build a credentials provider for this role authorized by the given `provider`.
-    pub fn build(self, provider: impl Into<SharedCredentialsProvider>) -> AssumeRoleProvider {
+    ///
+    /// If the configured parameters are invalid (e.g. a `duration_seconds` that is too short), the
+    /// returned provider will fail to load credentials with
+    /// [`CredentialsError::InvalidConfiguration`](aws_types::credentials::CredentialsError::InvalidConfiguration).
+    pub fn build(mut self, provider: impl Into<SharedCredentialsProvider>) -> AssumeRoleProvider {
         let config = aws_sdk_sts::Config::builder()
             .credentials_provider(provider.into())
             .region(self.region.clone())
             .build();
 
-        let conn = self.connection.unwrap_or_else(|| {
+        let conn = self.connection.take().unwrap_or_else(|| {
             crate::connector::expect_connector(crate::connector::default_connector(
                 &HttpSettings::default(),
                 default_async_sleep(),
             ))
         });
         let client = aws_hyper::Client::new(conn);
+        let mfa_token_provider = self.mfa_token_provider.take();
+        let time_source = std::mem::take(&mut self.time_source);
+
+        AssumeRoleProvider {
+            sts: client,
+            conf: config,
+            op: self.operation(),
+            mfa_token_provider,
+            mfa_session: MfaSessionCache::new(),
+            time_source,
+        }
+    }
+
+    fn operation(self) -> Result<aws_sdk_sts::input::AssumeRoleInput, InvalidAssumeRoleConfig> {
+        let duration_seconds = self
+            .duration_seconds
+            .map(super::util::validate_duration_seconds)
+            .transpose()
+            .map_err(InvalidAssumeRoleConfig)?;
+        if self.policy_arns.len() > MAX_POLICY_ARNS {
+            return Err(InvalidAssumeRoleConfig(format!(
+                "at most {} policy ARNs may be provided (found {})",
+                MAX_POLICY_ARNS,
+                self.policy_arns.len()
+            )));
+        }
+        if self.tags.len() > MAX_TAGS {
+            return Err(InvalidAssumeRoleConfig(format!(
+                "at most {} session tags may be provided (found {})",
+                MAX_TAGS,
+                self.tags.len()
+            )));
+        }
+        if let Some(key) = self
+            .transitive_tag_keys
+            .iter()
+            .find(|key| !self.tags.iter().any(|(tag_key, _)| tag_key == *key))
+        {
+            return Err(InvalidAssumeRoleConfig(format!(
+                "transitive tag key `{}` is not set as a session tag",
+                key
+            )));
+        }
+        if let Some(source_identity) = &self.source_identity {
+            if !(2..=64).contains(&source_identity.chars().count()) {
+                return Err(InvalidAssumeRoleConfig(format!(
+                    "source_identity must be between 2 and 64 characters long (found `{}`)",
+                    source_identity
+                )));
+            }
+        }
 
         let session_name = self
             .session_name
             .unwrap_or_else(|| super::util::default_session_name("assume-role-provider"));
+        let policy_arns = self
+            .policy_arns
+            .into_iter()
+            .map(|arn| PolicyDescriptorType::builder().arn(arn).build())
+            .collect::<Vec<_>>();
+        let tags = self
+            .tags
+            .into_iter()
+            .map(|(key, value)| Tag::builder().key(key).value(value).build())
+            .collect::<Vec<_>>();
 
-        let operation = AssumeRole::builder()
+        Ok(AssumeRole::builder()
             .set_role_arn(Some(self.role_arn))
             .set_external_id(self.external_id)
             .set_role_session_name(Some(session_name))
+            .set_duration_seconds(duration_seconds)
+            .set_policy(self.policy)
+            .set_policy_arns(Some(policy_arns).filter(|arns| !arns.is_empty()))
+            .set_tags(Some(tags).filter(|tags| !tags.is_empty()))
+            .set_transitive_tag_keys(Some(self.transitive_tag_keys).filter(|keys| !keys.is_empty()))
+            .set_source_identity(self.source_identity)
+            .set_serial_number(self.mfa_serial)
             .build()
-            .expect("operation is valid");
-
-        AssumeRoleProvider {
-            sts: client,
-            conf: config,
-            op: operation,
-        }
+            .expect("operation is valid"))
     }
 }
 
@@ -166,11 +432,37 @@ impl AssumeRoleProvider {
     )]
     async fn credentials(&self) -> credentials::Result {
         tracing::info!("assuming role");
-
-        tracing::debug!("retrieving assumed credentials");
         let op = self
             .op
             .clone()
+            .map_err(CredentialsError::invalid_configuration)?;
+        let serial_number = match op.serial_number.clone() {
+            Some(serial_number) => serial_number,
+            None => return self.assume_role(op).await,
+        };
+        let token_provider = self.mfa_token_provider.as_ref().ok_or_else(|| {
+            CredentialsError::invalid_configuration(format!(
+                "MFA device `{}` was configured but no MFA token provider was set",
+                serial_number
+            ))
+        })?;
+        self.mfa_session
+            .get_or_load(self.time_source.now(), || async move {
+                tracing::info!(serial_number = %serial_number, "requesting MFA token code");
+                let token_code = token_provider
+                    .provide_mfa_token(&serial_number)
+                    .await
+                    .map_err(CredentialsError::provider_error)?;
+                let mut op = op;
+                op.token_code = Some(token_code);
+                self.assume_role(op).await
+            })
+            .await
+    }
+
+    async fn assume_role(&self, op: aws_sdk_sts::input::AssumeRoleInput) -> credentials::Result {
+        tracing::debug!("retrieving assumed credentials");
+        let op = op
             .make_operation(&self.conf)
             .await
             .expect("valid operation");
@@ -212,3 +504,199 @@ impl ProvideCredentials for AssumeRoleProvider {
         future::ProvideCredentials::new(self.credentials())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::provider_config::ProviderConfig;
+    use crate::sts::mfa::mfa_token_fn;
+    use crate::sts::AssumeRoleProvider;
+    use aws_hyper::DynConnector;
+    use aws_smithy_client::test_connection::{capture_request, TestConnection};
+    use aws_smithy_http::body::SdkBody;
+    use aws_types::credentials::{CredentialsError, ProvideCredentials, SharedCredentialsProvider};
+    use aws_types::os_shim_internal::{ManualTimeSource, TimeSource};
+    use aws_types::region::Region;
+    use aws_types::Credentials;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn base_credentials() -> SharedCredentialsProvider {
+        SharedCredentialsProvider::new(Credentials::new("AKID", "SECRET", None, None, "test"))
+    }
+
+    #[tokio::test]
+    async fn sends_all_assume_role_parameters() {
+        let (conn, request) = capture_request(None);
+        let provider = AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+            .region(Region::new("us-east-1"))
+            .session_name("my-session")
+            .duration_seconds(1800)
+            .policy(r#"{"Version":"2012-10-17"}"#)
+            .policy_arn("arn:aws:iam::aws:policy/ReadOnlyAccess")
+            .tag("tenant", "tenant-a")
+            .transitive_tag_key("tenant")
+            .source_identity("worker-17")
+            .connection(conn)
+            .build(base_credentials());
+        let _ = provider.provide_credentials().await;
+        let req = request.expect_request();
+        let body = std::str::from_utf8(req.body().bytes().expect("body is loaded")).unwrap();
+        for expected in &[
+            "RoleSessionName=my-session",
+            "DurationSeconds=1800",
+            "Policy=%7B%22Version%22%3A%222012-10-17%22%7D",
+            "PolicyArns.member.1.arn=arn%3Aaws%3Aiam%3A%3Aaws%3Apolicy%2FReadOnlyAccess",
+            "Tags.member.1.Key=tenant",
+            "Tags.member.1.Value=tenant-a",
+            "TransitiveTagKeys.member.1=tenant",
+            "SourceIdentity=worker-17",
+        ] {
+            assert!(body.contains(expected), "`{}` not in `{}`", expected, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_parameters_are_configuration_errors() {
+        let invalid = vec![
+            (
+                AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+                    .duration_seconds(60),
+                "duration_seconds must be between 900 and 43200 seconds (found 60)",
+            ),
+            (
+                AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+                    .transitive_tag_key("tenant"),
+                "transitive tag key `tenant` is not set as a session tag",
+            ),
+            (
+                AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+                    .policy_arns((0..11).map(|i| format!("arn:aws:iam::aws:policy/P{}", i))),
+                "at most 10 policy ARNs may be provided (found 11)",
+            ),
+            (
+                AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/demo")
+                    .source_identity("a"),
+                "source_identity must be between 2 and 64 characters long",
+            ),
+        ];
+        for (builder, message) in invalid {
+            let (conn, _request) = capture_request(None);
+            let provider = builder
+                .region(Region::new("us-east-1"))
+                .connection(conn)
+                .build(base_credentials());
+            let err = provider
+                .provide_credentials()
+                .await
+                .expect_err("invalid configuration");
+            assert!(
+                matches!(err, CredentialsError::InvalidConfiguration { .. }),
+                "{:?}",
+                err
+            );
+            assert!(format!("{}", err).contains(message), "{}", err);
+        }
+    }
+
+    fn assume_role_response(expiration: &str) -> http::Response<SdkBody> {
+        http::Response::builder()
+            .status(200)
+            .body(SdkBody::from(format!(
+                "<AssumeRoleResponse xmlns=\"https://sts.amazonaws.com/doc/2011-06-15/\">
+                  <AssumeRoleResult>
+                    <Credentials>
+                      <AccessKeyId>ASIARTESTID</AccessKeyId>
+                      <SecretAccessKey>TESTSECRETKEY</SecretAccessKey>
+                      <SessionToken>TESTSESSIONTOKEN</SessionToken>
+                      <Expiration>{}</Expiration>
+                    </Credentials>
+                  </AssumeRoleResult>
+                </AssumeRoleResponse>",
+                expiration
+            )))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn mfa_session_is_cached_until_expiry() {
+        let conn = TestConnection::new(vec![
+            (
+                http::Request::new(SdkBody::from("unused")),
+                assume_role_response("2021-08-05T19:58:02Z"),
+            ),
+            (
+                http::Request::new(SdkBody::from("unused")),
+                assume_role_response("2021-08-05T21:58:02Z"),
+            ),
+        ]);
+        let mut time_source = ManualTimeSource::new(UNIX_EPOCH + Duration::from_secs(1628186282));
+        let tokens_requested = Arc::new(AtomicUsize::new(0));
+        let counter = tokens_requested.clone();
+        let provider = AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/admin")
+            .mfa_serial("arn:aws:iam::123456789012:mfa/operator")
+            .mfa_token_provider(mfa_token_fn(move |serial_number: &str| {
+                assert_eq!(serial_number, "arn:aws:iam::123456789012:mfa/operator");
+                let token = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                async move { Ok(format!("00000{}", token)) }
+            }))
+            .configure(
+                &ProviderConfig::no_configuration()
+                    .with_region(Some(Region::new("us-east-1")))
+                    .with_http_connector(DynConnector::new(conn.clone()))
+                    .with_time_source(TimeSource::manual(&time_source)),
+            )
+            .build(base_credentials());
+
+        provider.provide_credentials().await.expect("first session");
+        time_source.advance(Duration::from_secs(3600));
+        provider
+            .provide_credentials()
+            .await
+            .expect("cached session");
+        assert_eq!(tokens_requested.load(Ordering::SeqCst), 1);
+
+        // the first session has expired, a new token code is required
+        time_source.advance(Duration::from_secs(3600));
+        provider
+            .provide_credentials()
+            .await
+            .expect("second session");
+        assert_eq!(tokens_requested.load(Ordering::SeqCst), 2);
+
+        let requests = conn.requests();
+        assert_eq!(requests.len(), 2);
+        for (request, token_code) in requests.iter().zip(&["000001", "000002"]) {
+            let body = std::str::from_utf8(request.actual.body().bytes().unwrap()).unwrap();
+            assert!(
+                body.contains("SerialNumber=arn%3Aaws%3Aiam%3A%3A123456789012%3Amfa%2Foperator"),
+                "{}",
+                body
+            );
+            assert!(
+                body.contains(&format!("TokenCode={}", token_code)),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn mfa_serial_requires_token_provider() {
+        let (conn, _request) = capture_request(None);
+        let provider = AssumeRoleProvider::builder("arn:aws:iam::123456789012:role/admin")
+            .region(Region::new("us-east-1"))
+            .mfa_serial("arn:aws:iam::123456789012:mfa/operator")
+            .connection(conn)
+            .build(base_credentials());
+        let err = provider
+            .provide_credentials()
+            .await
+            .expect_err("no token provider");
+        assert!(
+            matches!(err, CredentialsError::InvalidConfiguration { .. }),
+            "{:?}",
+            err
+        );
+    }
+}
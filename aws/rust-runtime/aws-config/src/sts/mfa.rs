@@ -0,0 +1,196 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Multi-factor authentication (MFA) token providers for role assumption
+//!
+//! Roles that require MFA must be assumed with the serial number of the MFA device (configured with
+//! `mfa_serial` in a profile) and a token code from that device. Token codes are requested from a
+//! [`ProvideMfaToken`] implementation. Because token codes are typically entered by a human, the
+//! resulting role session is cached for its full lifetime and a new code is only requested once
+//! the session has expired.
+//!
+//! # Examples
+//! ```no_run
+//! use aws_config::profile::ProfileFileCredentialsProvider;
+//! use aws_config::sts::mfa::mfa_token_fn;
+//!
+//! let provider = ProfileFileCredentialsProvider::builder()
+//!     .mfa_token_provider(mfa_token_fn(|serial_number: &str| {
+//!         let serial_number = serial_number.to_string();
+//!         async move {
+//!             // prompt the user for the current code of `serial_number`
+//!             # let _ = serial_number;
+//!             Ok("123456".to_string())
+//!         }
+//!     }))
+//!     .build();
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use aws_types::credentials::{self, CredentialsError};
+use aws_types::Credentials;
+
+use crate::cache::ExpiringCache;
+
+/// Amount of time before the end of an MFA session that a new token will be requested
+pub(crate) const MFA_SESSION_BUFFER_TIME: Duration = Duration::from_secs(10);
+
+/// Error returned when an MFA token could not be provided
+pub type BoxError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Result type for MFA token providers
+pub type Result = std::result::Result<String, BoxError>;
+
+/// Convenience `ProvideMfaToken` struct that implements the `ProvideMfaToken` trait.
+pub mod future {
+    use aws_smithy_async::future::now_or_later::NowOrLater;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+    /// Future new-type that the `ProvideMfaToken` trait must return.
+    #[derive(Debug)]
+    pub struct ProvideMfaToken<'a>(NowOrLater<super::Result, BoxFuture<'a, super::Result>>);
+
+    impl<'a> ProvideMfaToken<'a> {
+        /// Creates a `ProvideMfaToken` struct from a future.
+        pub fn new(future: impl Future<Output = super::Result> + Send + 'a) -> Self {
+            ProvideMfaToken(NowOrLater::new(Box::pin(future)))
+        }
+
+        /// Creates a `ProvideMfaToken` struct from a resolved token code.
+        pub fn ready(token: super::Result) -> Self {
+            ProvideMfaToken(NowOrLater::ready(token))
+        }
+    }
+
+    impl Future for ProvideMfaToken<'_> {
+        type Output = super::Result;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Pin::new(&mut self.0).poll(cx)
+        }
+    }
+}
+
+/// Asynchronous MFA token code provider
+pub trait ProvideMfaToken: Send + Sync + Debug {
+    /// Returns a future that provides the current token code for the MFA device `serial_number`
+    fn provide_mfa_token<'a>(&'a self, serial_number: &'a str) -> future::ProvideMfaToken<'a>
+    where
+        Self: 'a;
+}
+
+/// MFA token provider backed by a function or closure
+///
+/// Created with [`mfa_token_fn`].
+pub struct MfaTokenFn<F>(F);
+
+impl<F> Debug for MfaTokenFn<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("MfaTokenFn")
+    }
+}
+
+impl<F, Fut> ProvideMfaToken for MfaTokenFn<F>
+where
+    F: Fn(&str) -> Fut + Send + Sync,
+    Fut: Future<Output = Result> + Send + 'static,
+{
+    fn provide_mfa_token<'a>(&'a self, serial_number: &'a str) -> future::ProvideMfaToken<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideMfaToken::new((self.0)(serial_number))
+    }
+}
+
+/// Create an MFA token provider from a function or closure
+///
+/// The function is invoked with the serial number of the MFA device and must return a future
+/// that resolves to the current token code.
+pub fn mfa_token_fn<F, Fut>(f: F) -> MfaTokenFn<F>
+where
+    F: Fn(&str) -> Fut + Send + Sync,
+    Fut: Future<Output = Result> + Send + 'static,
+{
+    MfaTokenFn(f)
+}
+
+/// MFA token provider wrapper that may be shared
+#[derive(Clone, Debug)]
+pub struct SharedMfaTokenProvider(Arc<dyn ProvideMfaToken>);
+
+impl SharedMfaTokenProvider {
+    /// Create a new `SharedMfaTokenProvider` from a `ProvideMfaToken` implementation
+    pub fn new(provider: impl ProvideMfaToken + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl ProvideMfaToken for SharedMfaTokenProvider {
+    fn provide_mfa_token<'a>(&'a self, serial_number: &'a str) -> future::ProvideMfaToken<'a>
+    where
+        Self: 'a,
+    {
+        self.0.provide_mfa_token(serial_number)
+    }
+}
+
+/// Cache for a single MFA-authenticated role session
+///
+/// Sessions are retained until they are about to expire so that the user is only prompted for
+/// a new token code once per session.
+#[derive(Clone, Debug)]
+pub(crate) struct MfaSessionCache(ExpiringCache<Credentials, CredentialsError>);
+
+impl MfaSessionCache {
+    pub(crate) fn new() -> Self {
+        Self(ExpiringCache::new(MFA_SESSION_BUFFER_TIME))
+    }
+
+    /// Return the cached session if it is still valid, otherwise load a new one with `load`
+    pub(crate) async fn get_or_load<F, Fut>(&self, now: SystemTime, load: F) -> credentials::Result
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = credentials::Result>,
+    {
+        if let Some(credentials) = self.0.yield_or_clear_if_expired(now).await {
+            tracing::debug!("reusing cached MFA session");
+            return Ok(credentials);
+        }
+        self.0
+            .get_or_load(|| async move {
+                let credentials = load().await?;
+                // credentials without an expiry are never reused
+                let expiry = credentials.expiry().unwrap_or(now);
+                Ok((credentials, expiry))
+            })
+            .await
+    }
+}
+
+/// MFA session caches keyed by role ARN and MFA device serial number
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MfaSessions(Arc<Mutex<HashMap<(String, String), MfaSessionCache>>>);
+
+impl MfaSessions {
+    pub(crate) fn session(&self, role_arn: &str, serial_number: &str) -> MfaSessionCache {
+        self.0
+            .lock()
+            .unwrap()
+            .entry((role_arn.to_string(), serial_number.to_string()))
+            .or_insert_with(MfaSessionCache::new)
+            .clone()
+    }
+}
@@ -12,7 +12,7 @@ use std::time::{Duration, UNIX_EPOCH};
 use crate::provider_config::{HttpSettings, ProviderConfig};
 use aws_smithy_async::rt::sleep::{AsyncSleep, Sleep, TokioSleep};
 use aws_types::credentials::{self, ProvideCredentials};
-use aws_types::os_shim_internal::{Env, Fs};
+use aws_types::os_shim_internal::{Env, Fs, ManualTimeSource, Process, ProcessOutput, TimeSource};
 use serde::Deserialize;
 
 use aws_smithy_client::dvr::{NetworkTraffic, RecordingConnection, ReplayingConnection};
@@ -61,16 +61,23 @@ impl From<aws_types::Credentials> for Credentials {
 /// A credentials test environment is a directory containing:
 /// - an `fs` directory. This is loaded into the test as if it was mounted at `/`
 /// - an `env.json` file containing environment variables
+/// - an optional `processes.json` file containing the output of the external commands the test runs
 /// - an  `http-traffic.json` file containing an http traffic log from [`dvr`](aws_smithy_client::dvr)
 /// - a `test-case.json` file defining the expected output of the test
 pub struct TestEnvironment {
     env: Env,
     fs: Fs,
+    process: Process,
     network_traffic: NetworkTraffic,
     metadata: Metadata,
     base_dir: PathBuf,
 }
 
+/// Time the test case traffic is replayed at (2021-09-21T00:00:00Z)
+///
+/// Using a fixed time keeps credentials in recorded traffic from being considered expired.
+const TEST_CASE_TIME_SECS: u64 = 1632182400;
+
 /// Connector which expects no traffic
 pub fn no_traffic_connector() -> DynConnector {
     DynConnector::new(ReplayingConnection::new(vec![]))
@@ -84,6 +91,16 @@ impl AsyncSleep for InstantSleep {
     }
 }
 
+/// Output of a fake external command
+#[derive(Deserialize)]
+struct FakeProcessOutput {
+    status: i32,
+    #[serde(default)]
+    stdout: String,
+    #[serde(default)]
+    stderr: String,
+}
+
 #[derive(Deserialize)]
 pub enum GenericTestResult<T> {
     Ok(T),
@@ -138,6 +155,23 @@ impl TestEnvironment {
             serde_json::from_str(&env).map_err(|e| format!("failed to parse env: {}", e))?;
         let env = Env::from(env);
         let fs = Fs::from_test_dir(dir.join("fs"), "/");
+        // test cases never run real commands: commands missing from `processes.json` aren't found
+        let processes: HashMap<String, FakeProcessOutput> =
+            match std::fs::read_to_string(dir.join("processes.json")) {
+                Ok(processes) => serde_json::from_str(&processes)
+                    .map_err(|e| format!("failed to parse processes: {}", e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+                Err(e) => return Err(format!("failed to load processes: {}", e).into()),
+            };
+        let process = Process::from(
+            processes
+                .into_iter()
+                .map(|(command, output)| {
+                    let output = ProcessOutput::new(output.status, output.stdout, output.stderr);
+                    (command, output)
+                })
+                .collect::<HashMap<_, _>>(),
+        );
         let network_traffic = std::fs::read_to_string(dir.join("http-traffic.json"))
             .map_err(|e| format!("failed to load http traffic: {}", e))?;
         let network_traffic: NetworkTraffic = serde_json::from_str(&network_traffic)?;
@@ -150,6 +184,7 @@ impl TestEnvironment {
             base_dir: dir.into(),
             env,
             fs,
+            process,
             network_traffic,
             metadata,
         })
@@ -162,8 +197,12 @@ impl TestEnvironment {
             ProviderConfig::empty()
                 .with_fs(self.fs.clone())
                 .with_env(self.env.clone())
+                .with_process(self.process.clone())
                 .with_http_connector(DynConnector::new(connector.clone()))
                 .with_sleep(TokioSleep::new())
+                .with_time_source(TimeSource::manual(&ManualTimeSource::new(
+                    UNIX_EPOCH + Duration::from_secs(TEST_CASE_TIME_SECS),
+                )))
                 .load_default_region()
                 .await,
         )
@@ -0,0 +1,169 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Navigation of JSON documents that reports the location of errors
+
+use crate::error::RuleSetError;
+use crate::value::Value;
+use aws_smithy_json::deserialize::json_token_iter;
+use aws_smithy_json::deserialize::token::expect_document;
+use aws_smithy_types::{Document, Number};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Parse a complete JSON document
+pub(crate) fn parse(json: &[u8]) -> Result<Document, RuleSetError> {
+    let mut tokens = json_token_iter(json).peekable();
+    let document = expect_document(&mut tokens)
+        .map_err(|err| RuleSetError::new("", format!("invalid JSON: {}", err)))?;
+    if tokens.next().is_some() {
+        return Err(RuleSetError::new("", "unexpected data after the document"));
+    }
+    Ok(document)
+}
+
+/// A node of a JSON document and its location
+#[derive(Debug, Clone)]
+pub(crate) struct Node<'a> {
+    path: String,
+    document: &'a Document,
+}
+
+impl<'a> Node<'a> {
+    pub(crate) fn root(document: &'a Document) -> Self {
+        Self {
+            path: String::new(),
+            document,
+        }
+    }
+
+    pub(crate) fn document(&self) -> &'a Document {
+        self.document
+    }
+
+    pub(crate) fn error(&self, message: impl Into<Cow<'static, str>>) -> RuleSetError {
+        RuleSetError::new(self.path.clone(), message)
+    }
+
+    pub(crate) fn expect_object(&self) -> Result<Object<'a>, RuleSetError> {
+        match self.document {
+            Document::Object(members) => Ok(Object {
+                path: self.path.clone(),
+                members,
+            }),
+            _ => Err(self.error("expected an object")),
+        }
+    }
+
+    pub(crate) fn expect_array(&self) -> Result<Vec<Node<'a>>, RuleSetError> {
+        match self.document {
+            Document::Array(elements) => Ok(elements
+                .iter()
+                .enumerate()
+                .map(|(idx, document)| Node {
+                    path: format!("{}[{}]", self.path, idx),
+                    document,
+                })
+                .collect()),
+            _ => Err(self.error("expected an array")),
+        }
+    }
+
+    pub(crate) fn expect_str(&self) -> Result<&'a str, RuleSetError> {
+        match self.document {
+            Document::String(value) => Ok(value),
+            _ => Err(self.error("expected a string")),
+        }
+    }
+
+    pub(crate) fn expect_bool(&self) -> Result<bool, RuleSetError> {
+        match self.document {
+            Document::Bool(value) => Ok(*value),
+            _ => Err(self.error("expected a boolean")),
+        }
+    }
+
+    pub(crate) fn expect_integer(&self) -> Result<i64, RuleSetError> {
+        match self.document {
+            Document::Number(Number::PosInt(value)) if *value <= i64::MAX as u64 => {
+                Ok(*value as i64)
+            }
+            Document::Number(Number::NegInt(value)) => Ok(*value),
+            _ => Err(self.error("expected an integer")),
+        }
+    }
+
+    /// Convert this node into a [`Value`]
+    pub(crate) fn to_value(&self) -> Result<Value, RuleSetError> {
+        Ok(match self.document {
+            Document::String(value) => Value::String(value.clone()),
+            Document::Bool(value) => Value::Bool(*value),
+            Document::Number(_) => Value::Integer(self.expect_integer()?),
+            Document::Array(_) => Value::Array(
+                self.expect_array()?
+                    .iter()
+                    .map(Node::to_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Document::Object(_) => Value::Object(
+                self.expect_object()?
+                    .members()
+                    .into_iter()
+                    .map(|(key, node)| Ok((key.to_string(), node.to_value()?)))
+                    .collect::<Result<_, RuleSetError>>()?,
+            ),
+            Document::Null => return Err(self.error("null is not a valid value")),
+        })
+    }
+}
+
+/// A JSON object and its location
+#[derive(Debug, Clone)]
+pub(crate) struct Object<'a> {
+    path: String,
+    members: &'a HashMap<String, Document>,
+}
+
+impl<'a> Object<'a> {
+    fn child(&self, key: &str, document: &'a Document) -> Node<'a> {
+        let path = if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        Node { path, document }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Node<'a>> {
+        self.members
+            .get(key)
+            .filter(|document| !matches!(document, Document::Null))
+            .map(|document| self.child(key, document))
+    }
+
+    pub(crate) fn expect(&self, key: &str) -> Result<Node<'a>, RuleSetError> {
+        self.get(key).ok_or_else(|| {
+            RuleSetError::new(
+                self.path.clone(),
+                format!("missing required field `{}`", key),
+            )
+        })
+    }
+
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The members of the object, sorted by key so that loading is deterministic
+    pub(crate) fn members(&self) -> Vec<(&'a str, Node<'a>)> {
+        let mut members: Vec<_> = self
+            .members
+            .iter()
+            .map(|(key, document)| (key.as_str(), self.child(key, document)))
+            .collect();
+        members.sort_by(|a, b| a.0.cmp(b.0));
+        members
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Endpoints resolved by a rule set
+
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// An endpoint resolved by a [`RuleSet`](crate::RuleSet)
+///
+/// Along with the URL, an endpoint may carry properties (e.g. the `authSchemes` used to sign
+/// requests) and headers that must be added to requests sent to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    url: String,
+    properties: HashMap<String, Value>,
+    headers: HashMap<String, Vec<String>>,
+}
+
+impl Endpoint {
+    /// Returns a builder for `Endpoint`
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// The URL of the endpoint
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The properties of the endpoint, e.g. `authSchemes`
+    pub fn properties(&self) -> &HashMap<String, Value> {
+        &self.properties
+    }
+
+    /// The property `name`, if set
+    pub fn property(&self, name: &str) -> Option<&Value> {
+        self.properties.get(name)
+    }
+
+    /// Headers to add to requests sent to the endpoint
+    pub fn headers(&self) -> &HashMap<String, Vec<String>> {
+        &self.headers
+    }
+}
+
+/// Builder for [`Endpoint`]
+#[derive(Debug, Default)]
+pub struct Builder {
+    url: Option<String>,
+    properties: HashMap<String, Value>,
+    headers: HashMap<String, Vec<String>>,
+}
+
+impl Builder {
+    /// Set the URL of the endpoint
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Set the property `name`
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.properties.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add a value for the header `name`
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers
+            .entry(name.into())
+            .or_default()
+            .push(value.into());
+        self
+    }
+
+    /// Build the endpoint
+    ///
+    /// # Panics
+    /// If the URL is not set
+    pub fn build(self) -> Endpoint {
+        Endpoint {
+            url: self.url.expect("url is required"),
+            properties: self.properties,
+            headers: self.headers,
+        }
+    }
+}
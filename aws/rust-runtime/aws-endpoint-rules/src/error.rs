@@ -0,0 +1,109 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Errors loading rule sets and resolving endpoints
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The JSON representation of a rule set, partitions or test cases is invalid
+#[derive(Debug)]
+pub struct RuleSetError {
+    path: String,
+    message: Cow<'static, str>,
+}
+
+impl RuleSetError {
+    pub(crate) fn new(path: impl Into<String>, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The location of the error in the document, e.g. `rules[1].conditions[0]`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for RuleSetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "invalid document: {}", self.message)
+        } else {
+            write!(f, "invalid document at `{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+impl Error for RuleSetError {}
+
+/// An endpoint could not be resolved
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ResolveError {
+    /// A required parameter was not set and has no default
+    MissingParameter {
+        /// The name of the parameter
+        name: String,
+    },
+
+    /// A parameter is not defined by the rule set or has the wrong type
+    InvalidParameter {
+        /// The name of the parameter
+        name: String,
+        /// Why the parameter is invalid
+        message: Cow<'static, str>,
+    },
+
+    /// An error rule matched the parameters
+    ///
+    /// The message describes the invalid configuration, e.g. that FIPS endpoints are not
+    /// supported in a region.
+    Rule {
+        /// The message of the error rule
+        message: String,
+    },
+
+    /// No rule matched the parameters
+    NoMatchingRule,
+
+    /// The rule set could not be evaluated, e.g. a function was called with an argument of the wrong type
+    Evaluation {
+        /// What went wrong
+        message: Cow<'static, str>,
+    },
+}
+
+impl ResolveError {
+    pub(crate) fn evaluation(message: impl Into<Cow<'static, str>>) -> Self {
+        ResolveError::Evaluation {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::MissingParameter { name } => {
+                write!(f, "missing required parameter `{}`", name)
+            }
+            ResolveError::InvalidParameter { name, message } => {
+                write!(f, "invalid parameter `{}`: {}", name, message)
+            }
+            ResolveError::Rule { message } => write!(f, "{}", message),
+            ResolveError::NoMatchingRule => write!(f, "no rules matched the parameters"),
+            ResolveError::Evaluation { message } => {
+                write!(f, "failed to evaluate rule set: {}", message)
+            }
+        }
+    }
+}
+
+impl Error for ResolveError {}
@@ -0,0 +1,192 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Rule set evaluation
+
+use crate::endpoint::Endpoint;
+use crate::error::ResolveError;
+use crate::partition::Partitions;
+use crate::ruleset::{EndpointTemplate, Expr, Literal, Rule, RuleKind};
+use crate::stdlib;
+use crate::template::{Template, TemplatePart};
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// Parameters and variables assigned by conditions
+pub(crate) type Scope = HashMap<String, Value>;
+
+/// State shared by all functions during evaluation
+#[derive(Debug)]
+pub(crate) struct Context<'a> {
+    pub(crate) partitions: &'a Partitions,
+}
+
+/// The result of a matching rule
+#[derive(Debug)]
+pub(crate) enum Outcome {
+    Endpoint(Endpoint),
+    Error(String),
+}
+
+/// Evaluate `rules` in order, returning the outcome of the first rule that matches
+pub(crate) fn evaluate_rules(
+    rules: &[Rule],
+    scope: &Scope,
+    context: &Context<'_>,
+) -> Result<Option<Outcome>, ResolveError> {
+    for rule in rules {
+        if let Some(outcome) = evaluate_rule(rule, scope, context)? {
+            return Ok(Some(outcome));
+        }
+    }
+    Ok(None)
+}
+
+fn evaluate_rule(
+    rule: &Rule,
+    scope: &Scope,
+    context: &Context<'_>,
+) -> Result<Option<Outcome>, ResolveError> {
+    // variables assigned by conditions are only visible to this rule (and its children)
+    let mut scope = scope.clone();
+    for condition in &rule.conditions {
+        match evaluate(&condition.expr, &scope, context)? {
+            None | Some(Value::Bool(false)) => return Ok(None),
+            Some(value) => {
+                if let Some(name) = &condition.assign {
+                    scope.insert(name.clone(), value);
+                }
+            }
+        }
+    }
+    match &rule.kind {
+        RuleKind::Endpoint(endpoint) => Ok(Some(Outcome::Endpoint(render_endpoint(
+            endpoint, &scope, context,
+        )?))),
+        RuleKind::Error(message) => Ok(Some(Outcome::Error(evaluate_string(
+            message, &scope, context,
+        )?))),
+        // tree rules are terminal: once their conditions match, one of their rules must match
+        RuleKind::Tree(rules) => match evaluate_rules(rules, &scope, context)? {
+            Some(outcome) => Ok(Some(outcome)),
+            None => Err(ResolveError::NoMatchingRule),
+        },
+    }
+}
+
+fn render_endpoint(
+    endpoint: &EndpointTemplate,
+    scope: &Scope,
+    context: &Context<'_>,
+) -> Result<Endpoint, ResolveError> {
+    let mut builder = Endpoint::builder().url(evaluate_string(&endpoint.url, scope, context)?);
+    for (name, value) in &endpoint.properties {
+        builder = builder.property(name.clone(), render_literal(value, scope)?);
+    }
+    for (name, values) in &endpoint.headers {
+        for value in values {
+            builder = builder.header(name.clone(), evaluate_string(value, scope, context)?);
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Evaluate an expression that must produce a string
+fn evaluate_string(
+    expr: &Expr,
+    scope: &Scope,
+    context: &Context<'_>,
+) -> Result<String, ResolveError> {
+    match evaluate(expr, scope, context)? {
+        Some(Value::String(value)) => Ok(value),
+        Some(other) => Err(ResolveError::evaluation(format!(
+            "expected a string but got a {}",
+            other.type_name()
+        ))),
+        None => Err(ResolveError::evaluation(
+            "expected a string but the value is not set",
+        )),
+    }
+}
+
+/// Evaluate an expression
+///
+/// Returns `None` if the expression refers to a value that is not set, or if a function
+/// returned no value (e.g. `parseURL` with an invalid URL).
+pub(crate) fn evaluate(
+    expr: &Expr,
+    scope: &Scope,
+    context: &Context<'_>,
+) -> Result<Option<Value>, ResolveError> {
+    match expr {
+        Expr::Literal(literal) => render_literal(literal, scope).map(Some),
+        Expr::Ref(name) => Ok(scope.get(name).cloned()),
+        Expr::GetAttr(expr, path) => {
+            Ok(evaluate(expr, scope, context)?.and_then(|value| path.get(&value).cloned()))
+        }
+        Expr::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, scope, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            stdlib::call(*function, &args, context)
+        }
+    }
+}
+
+fn render_literal(literal: &Literal, scope: &Scope) -> Result<Value, ResolveError> {
+    Ok(match literal {
+        Literal::String(template) => Value::String(render_template(template, scope)?),
+        Literal::Bool(value) => Value::Bool(*value),
+        Literal::Integer(value) => Value::Integer(*value),
+        Literal::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| render_literal(value, scope))
+                .collect::<Result<_, _>>()?,
+        ),
+        Literal::Object(members) => Value::Object(
+            members
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), render_literal(value, scope)?)))
+                .collect::<Result<_, ResolveError>>()?,
+        ),
+    })
+}
+
+fn render_template(template: &Template, scope: &Scope) -> Result<String, ResolveError> {
+    let mut out = String::new();
+    for part in template.parts() {
+        let (name, value) = match part {
+            TemplatePart::Literal(literal) => {
+                out.push_str(literal);
+                continue;
+            }
+            TemplatePart::Ref(name) => (name, scope.get(name)),
+            TemplatePart::Attr(name, path) => {
+                (name, scope.get(name).and_then(|value| path.get(value)))
+            }
+        };
+        match value {
+            Some(Value::String(value)) => out.push_str(value),
+            Some(Value::Bool(value)) => out.push_str(&value.to_string()),
+            Some(Value::Integer(value)) => out.push_str(&value.to_string()),
+            Some(other) => {
+                return Err(ResolveError::evaluation(format!(
+                    "cannot render a {} in a template (`{}`)",
+                    other.type_name(),
+                    name
+                )))
+            }
+            None => {
+                return Err(ResolveError::evaluation(format!(
+                    "template refers to `{}`, which is not set",
+                    name
+                )))
+            }
+        }
+    }
+    Ok(out)
+}
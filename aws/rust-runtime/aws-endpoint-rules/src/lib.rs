@@ -0,0 +1,71 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Evaluation of Smithy endpoint rule sets
+//!
+//! A [`RuleSet`] maps endpoint parameters (e.g. the region and whether FIPS endpoints are used) to
+//! an [`Endpoint`]: a URL along with properties, such as the auth schemes to use, and headers to
+//! add to requests. Rule sets can call the standard library functions (`isSet`, `parseURL`,
+//! `substring`, `isValidHostLabel`, `uriEncode`, ...) and the AWS functions `aws.partition`,
+//! `aws.parseArn` and `aws.isVirtualHostableS3Bucket`.
+//!
+//! Rule sets are verified against [test cases](test_case::TestCases) in the same JSON format as
+//! the Smithy `endpointTests` trait.
+
+#![warn(
+    missing_docs,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+
+mod document;
+pub mod endpoint;
+pub mod error;
+mod eval;
+pub mod partition;
+pub mod ruleset;
+mod stdlib;
+mod template;
+pub mod test_case;
+pub mod value;
+
+pub use endpoint::Endpoint;
+pub use ruleset::{Params, RuleSet};
+pub use value::Value;
+
+#[cfg(test)]
+mod test {
+    use crate::test_case::TestCases;
+    use crate::RuleSet;
+    use std::fs;
+
+    /// Check every rule set in `test-data/valid-rules` against its test cases in `test-data/test-cases`
+    #[test]
+    fn rule_set_test_cases() {
+        let mut checked = 0;
+        for entry in fs::read_dir("test-data/valid-rules").expect("test data exists") {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let rule_set = RuleSet::from_json(&fs::read(&path).unwrap())
+                .unwrap_or_else(|err| panic!("{}: {}", name, err));
+            let test_cases = TestCases::from_json(
+                &fs::read(format!("test-data/test-cases/{}", name))
+                    .unwrap_or_else(|err| panic!("test cases for {}: {}", name, err)),
+            )
+            .unwrap_or_else(|err| panic!("test cases for {}: {}", name, err));
+            assert!(!test_cases.test_cases().is_empty(), "{}", name);
+            if let Err(failures) = test_cases.check(&rule_set) {
+                for failure in &failures {
+                    eprintln!("{}: {}", name, failure);
+                }
+                panic!("{} test case(s) failed for {}", failures.len(), name);
+            }
+            checked += 1;
+        }
+        assert!(checked > 0, "no rule sets were found");
+    }
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Partition metadata used by `aws.partition`
+
+use crate::document::{self, Node};
+use crate::error::RuleSetError;
+use crate::value::Value;
+use aws_smithy_types::Document;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// The id of the partition used for regions that don't match any partition
+const DEFAULT_PARTITION: &str = "aws";
+
+static BUILT_IN: Lazy<Partitions> = Lazy::new(|| {
+    Partitions::from_json(include_str!("partitions.json").as_bytes())
+        .expect("built-in partitions are valid")
+});
+
+/// AWS partitions, loaded from the `partitions.json` format used by Smithy endpoint rules
+///
+/// `aws.partition` returns the outputs of the partition that contains a region: a partition
+/// contains a region if the region is listed in `regions` or matches `regionRegex`. Regions that
+/// don't match any partition use the `aws` partition.
+#[derive(Debug)]
+pub struct Partitions {
+    partitions: Vec<Partition>,
+}
+
+#[derive(Debug)]
+struct Partition {
+    id: String,
+    region_regex: Regex,
+    /// Regions and their overrides of `outputs`
+    regions: HashMap<String, HashMap<String, Value>>,
+    outputs: HashMap<String, Value>,
+}
+
+impl Partitions {
+    /// Load partitions from JSON
+    pub fn from_json(json: &[u8]) -> Result<Self, RuleSetError> {
+        let document = document::parse(json)?;
+        let root = Node::root(&document).expect_object()?;
+        let partitions = root
+            .expect("partitions")?
+            .expect_array()?
+            .iter()
+            .map(Partition::from_node)
+            .collect::<Result<Vec<_>, _>>()?;
+        if partitions.is_empty() {
+            return Err(RuleSetError::new(
+                "partitions",
+                "at least one partition is required",
+            ));
+        }
+        Ok(Self { partitions })
+    }
+
+    /// The partitions bundled with this crate
+    pub(crate) fn built_in() -> &'static Partitions {
+        &BUILT_IN
+    }
+
+    /// The outputs of the partition containing `region`
+    pub(crate) fn resolve(&self, region: &str) -> Option<Value> {
+        let explicit = self.partitions.iter().find_map(|partition| {
+            partition
+                .regions
+                .get(region)
+                .map(|overrides| (partition, Some(overrides)))
+        });
+        let (partition, overrides) = explicit
+            .or_else(|| {
+                self.partitions
+                    .iter()
+                    .find(|partition| partition.region_regex.is_match(region))
+                    .map(|partition| (partition, None))
+            })
+            .or_else(|| {
+                self.partitions
+                    .iter()
+                    .find(|partition| partition.id == DEFAULT_PARTITION)
+                    .map(|partition| (partition, None))
+            })?;
+        let mut outputs = partition.outputs.clone();
+        if let Some(overrides) = overrides {
+            outputs.extend(overrides.clone());
+        }
+        Some(Value::Object(outputs))
+    }
+}
+
+impl Partition {
+    fn from_node(node: &Node<'_>) -> Result<Self, RuleSetError> {
+        let partition = node.expect_object()?;
+        let id = partition.expect("id")?.expect_str()?.to_string();
+        let regex_node = partition.expect("regionRegex")?;
+        // We use a stripped down version of the regex crate without unicode support
+        // To support `\d` and `\w`, we need to explicitly opt into the ascii-only version.
+        let ascii_only = regex_node
+            .expect_str()?
+            .replace("\\d", "(?-u:\\d)")
+            .replace("\\w", "(?-u:\\w)");
+        let region_regex = Regex::new(&ascii_only)
+            .map_err(|err| regex_node.error(format!("invalid regex: {}", err)))?;
+        let regions = match partition.get("regions") {
+            Some(regions) => regions
+                .expect_object()?
+                .members()
+                .into_iter()
+                .map(|(region, node)| Ok((region.to_string(), outputs(&node)?)))
+                .collect::<Result<_, RuleSetError>>()?,
+            None => HashMap::new(),
+        };
+        let outputs = outputs(&partition.expect("outputs")?)?;
+        if !outputs.contains_key("name") {
+            return Err(partition
+                .expect("outputs")?
+                .error("missing required field `name`"));
+        }
+        Ok(Partition {
+            id,
+            region_regex,
+            regions,
+            outputs,
+        })
+    }
+}
+
+/// Partition outputs (or a region's overrides of them)
+fn outputs(node: &Node<'_>) -> Result<HashMap<String, Value>, RuleSetError> {
+    node.expect_object()?
+        .members()
+        .into_iter()
+        .map(|(name, node)| {
+            let value = match node.document() {
+                Document::String(value) => Value::from(value.as_str()),
+                Document::Bool(value) => Value::Bool(*value),
+                _ => return Err(node.error("expected a string or a boolean")),
+            };
+            Ok((name.to_string(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Partitions;
+    use crate::value::Value;
+
+    fn resolve(partitions: &Partitions, region: &str) -> (String, String) {
+        let outputs = partitions.resolve(region).expect("always resolves");
+        let outputs = outputs.as_object().unwrap();
+        let get = |name: &str| outputs[name].as_str().unwrap().to_string();
+        (get("name"), get("dnsSuffix"))
+    }
+
+    #[test]
+    fn built_in_partitions() {
+        let partitions = Partitions::built_in();
+        let cases = [
+            ("us-east-1", "aws", "amazonaws.com"),
+            ("us-west-3", "aws", "amazonaws.com"),
+            ("aws-global", "aws", "amazonaws.com"),
+            ("cn-north-1", "aws-cn", "amazonaws.com.cn"),
+            ("cn-south-7", "aws-cn", "amazonaws.com.cn"),
+            ("us-gov-west-1", "aws-us-gov", "amazonaws.com"),
+            ("us-iso-east-1", "aws-iso", "c2s.ic.gov"),
+            ("us-isob-east-1", "aws-iso-b", "sc2s.sgov.gov"),
+            ("not-a-region", "aws", "amazonaws.com"),
+        ];
+        for (region, name, dns_suffix) in &cases {
+            assert_eq!(
+                (name.to_string(), dns_suffix.to_string()),
+                resolve(partitions, region),
+                "{}",
+                region
+            );
+        }
+        let outputs = partitions.resolve("us-iso-east-1").unwrap();
+        assert_eq!(
+            Some(&Value::Bool(false)),
+            outputs.as_object().unwrap().get("supportsDualStack")
+        );
+    }
+
+    #[test]
+    fn explicit_regions_override_outputs() {
+        let partitions = Partitions::from_json(
+            br#"{
+                "version": "1.1",
+                "partitions": [{
+                    "id": "aws",
+                    "regionRegex": "^us\\-\\w+\\-\\d+$",
+                    "regions": { "special-region": { "dnsSuffix": "example.com" } },
+                    "outputs": { "name": "aws", "dnsSuffix": "amazonaws.com" }
+                }]
+            }"#,
+        )
+        .expect("valid partitions");
+        assert_eq!(
+            ("aws".to_string(), "example.com".to_string()),
+            resolve(&partitions, "special-region")
+        );
+        assert_eq!(
+            ("aws".to_string(), "amazonaws.com".to_string()),
+            resolve(&partitions, "us-east-1")
+        );
+    }
+
+    #[test]
+    fn invalid_partitions() {
+        let err = Partitions::from_json(
+            br#"{ "partitions": [{ "id": "aws", "regionRegex": "(", "outputs": { "name": "aws" } }] }"#,
+        )
+        .expect_err("invalid regex");
+        assert_eq!("partitions[0].regionRegex", err.path());
+
+        let err = Partitions::from_json(
+            br#"{ "partitions": [{ "id": "aws", "regionRegex": ".*", "outputs": {} }] }"#,
+        )
+        .expect_err("missing name");
+        assert_eq!(
+            "invalid document at `partitions[0].outputs`: missing required field `name`",
+            err.to_string()
+        );
+    }
+}
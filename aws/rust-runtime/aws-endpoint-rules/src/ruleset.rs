@@ -0,0 +1,578 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Rule sets and their parameters
+
+use crate::document::{self, Node};
+use crate::endpoint::Endpoint;
+use crate::error::{ResolveError, RuleSetError};
+use crate::eval;
+use crate::partition::Partitions;
+use crate::stdlib::Function;
+use crate::template::{Path, Template};
+use crate::value::Value;
+use aws_smithy_types::Document;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A Smithy endpoint rule set
+///
+/// Rule sets are loaded from the JSON representation of the `smithy.rules#endpointRuleSet` trait.
+/// Rules are evaluated in order: the first rule whose conditions all match produces either an
+/// endpoint or an error.
+///
+/// # Examples
+/// ```rust
+/// use aws_endpoint_rules::{Params, RuleSet};
+///
+/// let rule_set = RuleSet::from_json(br#"{
+///     "version": "1.0",
+///     "parameters": {
+///         "Region": { "type": "string", "builtIn": "AWS::Region", "required": true }
+///     },
+///     "rules": [
+///         {
+///             "conditions": [
+///                 { "fn": "aws.partition", "argv": [{ "ref": "Region" }], "assign": "PartitionResult" }
+///             ],
+///             "type": "endpoint",
+///             "endpoint": { "url": "https://example.{Region}.{PartitionResult#dnsSuffix}" }
+///         }
+///     ]
+/// }"#).expect("valid rule set");
+///
+/// let endpoint = rule_set
+///     .resolve(&Params::new().with("Region", "cn-north-1"))
+///     .expect("valid endpoint");
+/// assert_eq!("https://example.cn-north-1.amazonaws.com.cn", endpoint.url());
+/// ```
+#[derive(Debug)]
+pub struct RuleSet {
+    version: String,
+    parameters: Vec<Parameter>,
+    rules: Vec<Rule>,
+    partitions: Option<Arc<Partitions>>,
+}
+
+impl RuleSet {
+    /// Load a rule set from its JSON representation
+    pub fn from_json(json: &[u8]) -> Result<Self, RuleSetError> {
+        let document = document::parse(json)?;
+        let root = Node::root(&document).expect_object()?;
+        let version = root.expect("version")?.expect_str()?.to_string();
+        let parameters = match root.get("parameters") {
+            Some(parameters) => parameters
+                .expect_object()?
+                .members()
+                .into_iter()
+                .map(|(name, node)| Parameter::parse(name, &node))
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+        let rules = parse_rules(&root.expect("rules")?)?;
+        Ok(RuleSet {
+            version,
+            parameters,
+            rules,
+            partitions: None,
+        })
+    }
+
+    /// Use `partitions` to evaluate `aws.partition` instead of the built-in partitions
+    pub fn with_partitions(mut self, partitions: Partitions) -> Self {
+        self.partitions = Some(Arc::new(partitions));
+        self
+    }
+
+    /// The version of the rule set
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The parameters of the rule set
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    /// The parameter named `name`
+    pub fn parameter(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.iter().find(|param| param.name == name)
+    }
+
+    /// The parameter bound to the built-in `built_in`, e.g. `AWS::Region`
+    pub fn parameter_for_built_in(&self, built_in: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|param| param.built_in.as_deref() == Some(built_in))
+    }
+
+    /// Resolve the endpoint for `params`
+    ///
+    /// Parameters that are not set use their default value. An error is returned if a required
+    /// parameter is missing, if a parameter is not defined by the rule set, or if the rule set
+    /// returns an error for these parameters.
+    pub fn resolve(&self, params: &Params) -> Result<Endpoint, ResolveError> {
+        for (name, value) in &params.values {
+            let parameter = self
+                .parameter(name)
+                .ok_or_else(|| ResolveError::InvalidParameter {
+                    name: name.clone(),
+                    message: "not defined by the rule set".into(),
+                })?;
+            parameter.check_type(value)?;
+        }
+        let mut scope = eval::Scope::default();
+        for parameter in &self.parameters {
+            match params
+                .values
+                .get(&parameter.name)
+                .or(parameter.default.as_ref())
+            {
+                Some(value) => {
+                    scope.insert(parameter.name.clone(), value.clone());
+                }
+                None if parameter.required => {
+                    return Err(ResolveError::MissingParameter {
+                        name: parameter.name.clone(),
+                    })
+                }
+                None => {}
+            }
+        }
+        let partitions = match &self.partitions {
+            Some(partitions) => partitions,
+            None => Partitions::built_in(),
+        };
+        let context = eval::Context { partitions };
+        match eval::evaluate_rules(&self.rules, &scope, &context)? {
+            Some(eval::Outcome::Endpoint(endpoint)) => Ok(endpoint),
+            Some(eval::Outcome::Error(message)) => Err(ResolveError::Rule { message }),
+            None => Err(ResolveError::NoMatchingRule),
+        }
+    }
+}
+
+/// A parameter of a [`RuleSet`]
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    name: String,
+    parameter_type: ParameterType,
+    required: bool,
+    default: Option<Value>,
+    built_in: Option<String>,
+    documentation: Option<String>,
+}
+
+impl Parameter {
+    fn parse(name: &str, node: &Node<'_>) -> Result<Self, RuleSetError> {
+        let object = node.expect_object()?;
+        let type_node = object.expect("type")?;
+        let parameter_type = match type_node.expect_str()? {
+            // parameter types are case-insensitive
+            ty if ty.eq_ignore_ascii_case("string") => ParameterType::String,
+            ty if ty.eq_ignore_ascii_case("boolean") => ParameterType::Boolean,
+            other => return Err(type_node.error(format!("unsupported parameter type `{}`", other))),
+        };
+        let default = match object.get("default") {
+            Some(default) => Some(match parameter_type {
+                ParameterType::String => Value::String(default.expect_str()?.to_string()),
+                ParameterType::Boolean => Value::Bool(default.expect_bool()?),
+            }),
+            None => None,
+        };
+        let required = match object.get("required") {
+            Some(required) => required.expect_bool()?,
+            None => false,
+        };
+        let optional_str = |key: &str| -> Result<Option<String>, RuleSetError> {
+            object
+                .get(key)
+                .map(|node| node.expect_str().map(|value| value.to_string()))
+                .transpose()
+        };
+        Ok(Parameter {
+            name: name.to_string(),
+            parameter_type,
+            required,
+            default,
+            built_in: optional_str("builtIn")?,
+            documentation: optional_str("documentation")?,
+        })
+    }
+
+    fn check_type(&self, value: &Value) -> Result<(), ResolveError> {
+        let matches = match self.parameter_type {
+            ParameterType::String => value.as_str().is_some(),
+            ParameterType::Boolean => value.as_bool().is_some(),
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(ResolveError::InvalidParameter {
+                name: self.name.clone(),
+                message: format!(
+                    "expected a {} but got a {}",
+                    self.parameter_type.name(),
+                    value.type_name()
+                )
+                .into(),
+            })
+        }
+    }
+
+    /// The name of the parameter
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the parameter
+    pub fn parameter_type(&self) -> ParameterType {
+        self.parameter_type
+    }
+
+    /// Whether the parameter must be set when it has no default
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// The default value of the parameter
+    pub fn default_value(&self) -> Option<&Value> {
+        self.default.as_ref()
+    }
+
+    /// The built-in that provides the value of the parameter, e.g. `AWS::Region`
+    pub fn built_in(&self) -> Option<&str> {
+        self.built_in.as_deref()
+    }
+
+    /// Documentation for the parameter
+    pub fn documentation(&self) -> Option<&str> {
+        self.documentation.as_deref()
+    }
+}
+
+/// The type of a [`Parameter`]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterType {
+    /// A string parameter
+    String,
+    /// A boolean parameter
+    Boolean,
+}
+
+impl ParameterType {
+    fn name(&self) -> &'static str {
+        match self {
+            ParameterType::String => "string",
+            ParameterType::Boolean => "boolean",
+        }
+    }
+}
+
+/// Parameters to resolve an endpoint with
+///
+/// # Examples
+/// ```rust
+/// use aws_endpoint_rules::Params;
+/// let params = Params::new()
+///     .with("Region", "us-east-1")
+///     .with("UseFIPS", true);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: HashMap<String, Value>,
+}
+
+impl Params {
+    /// Create an empty set of parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the parameter `name` to `value`
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set the parameter `name` to `value`
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Value>) -> &mut Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    /// The value of the parameter `name`, if set
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Rule {
+    pub(crate) conditions: Vec<Condition>,
+    pub(crate) kind: RuleKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum RuleKind {
+    Endpoint(EndpointTemplate),
+    Error(Expr),
+    Tree(Vec<Rule>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Condition {
+    pub(crate) expr: Expr,
+    pub(crate) assign: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct EndpointTemplate {
+    pub(crate) url: Expr,
+    pub(crate) properties: Vec<(String, Literal)>,
+    pub(crate) headers: Vec<(String, Vec<Expr>)>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Expr {
+    Literal(Literal),
+    Ref(String),
+    GetAttr(Box<Expr>, Path),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug)]
+pub(crate) enum Literal {
+    String(Template),
+    Bool(bool),
+    Integer(i64),
+    Array(Vec<Literal>),
+    Object(Vec<(String, Literal)>),
+}
+
+fn parse_rules(node: &Node<'_>) -> Result<Vec<Rule>, RuleSetError> {
+    node.expect_array()?.iter().map(parse_rule).collect()
+}
+
+fn parse_rule(node: &Node<'_>) -> Result<Rule, RuleSetError> {
+    let object = node.expect_object()?;
+    let conditions = object
+        .expect("conditions")?
+        .expect_array()?
+        .iter()
+        .map(parse_condition)
+        .collect::<Result<_, _>>()?;
+    let type_node = object.expect("type")?;
+    let kind = match type_node.expect_str()? {
+        "endpoint" => RuleKind::Endpoint(parse_endpoint(&object.expect("endpoint")?)?),
+        "error" => RuleKind::Error(parse_expr(&object.expect("error")?)?),
+        "tree" => RuleKind::Tree(parse_rules(&object.expect("rules")?)?),
+        other => return Err(type_node.error(format!("unknown rule type `{}`", other))),
+    };
+    Ok(Rule { conditions, kind })
+}
+
+fn parse_condition(node: &Node<'_>) -> Result<Condition, RuleSetError> {
+    let object = node.expect_object()?;
+    if !object.contains("fn") {
+        return Err(node.error("conditions must be function calls"));
+    }
+    let assign = object
+        .get("assign")
+        .map(|assign| assign.expect_str().map(|name| name.to_string()))
+        .transpose()?;
+    Ok(Condition {
+        expr: parse_expr(node)?,
+        assign,
+    })
+}
+
+fn parse_endpoint(node: &Node<'_>) -> Result<EndpointTemplate, RuleSetError> {
+    let object = node.expect_object()?;
+    let url = parse_expr(&object.expect("url")?)?;
+    let properties = match object.get("properties") {
+        Some(properties) => properties
+            .expect_object()?
+            .members()
+            .into_iter()
+            .map(|(name, node)| Ok((name.to_string(), parse_literal(&node)?)))
+            .collect::<Result<_, RuleSetError>>()?,
+        None => vec![],
+    };
+    let headers = match object.get("headers") {
+        Some(headers) => headers
+            .expect_object()?
+            .members()
+            .into_iter()
+            .map(|(name, node)| {
+                let values = node
+                    .expect_array()?
+                    .iter()
+                    .map(parse_expr)
+                    .collect::<Result<_, _>>()?;
+                Ok((name.to_string(), values))
+            })
+            .collect::<Result<_, RuleSetError>>()?,
+        None => vec![],
+    };
+    Ok(EndpointTemplate {
+        url,
+        properties,
+        headers,
+    })
+}
+
+fn parse_expr(node: &Node<'_>) -> Result<Expr, RuleSetError> {
+    match node.document() {
+        Document::Object(_) => {
+            let object = node.expect_object()?;
+            if let Some(name) = object.get("ref") {
+                return Ok(Expr::Ref(name.expect_str()?.to_string()));
+            }
+            let fn_node = object.get("fn").ok_or_else(|| {
+                node.error("expected a literal, a `ref` or a function call (`fn`)")
+            })?;
+            let name = fn_node.expect_str()?;
+            let function = Function::from_name(name)
+                .ok_or_else(|| fn_node.error(format!("unknown function `{}`", name)))?;
+            let argv = object.expect("argv")?.expect_array()?;
+            if argv.len() != function.arity() {
+                return Err(node.error(format!(
+                    "`{}` expects {} arguments but got {}",
+                    name,
+                    function.arity(),
+                    argv.len()
+                )));
+            }
+            if function == Function::GetAttr {
+                let path = Path::parse(argv[1].expect_str()?).map_err(|err| argv[1].error(err))?;
+                return Ok(Expr::GetAttr(Box::new(parse_expr(&argv[0])?), path));
+            }
+            let args = argv.iter().map(parse_expr).collect::<Result<_, _>>()?;
+            Ok(Expr::Call(function, args))
+        }
+        Document::Array(_) => Err(node.error("arrays are not valid expressions")),
+        _ => Ok(Expr::Literal(parse_literal(node)?)),
+    }
+}
+
+fn parse_literal(node: &Node<'_>) -> Result<Literal, RuleSetError> {
+    Ok(match node.document() {
+        Document::String(value) => {
+            Literal::String(Template::parse(value).map_err(|err| node.error(err))?)
+        }
+        Document::Bool(value) => Literal::Bool(*value),
+        Document::Number(_) => Literal::Integer(node.expect_integer()?),
+        Document::Array(_) => Literal::Array(
+            node.expect_array()?
+                .iter()
+                .map(parse_literal)
+                .collect::<Result<_, _>>()?,
+        ),
+        Document::Object(_) => Literal::Object(
+            node.expect_object()?
+                .members()
+                .into_iter()
+                .map(|(key, node)| Ok((key.to_string(), parse_literal(&node)?)))
+                .collect::<Result<_, RuleSetError>>()?,
+        ),
+        Document::Null => return Err(node.error("null is not a valid literal")),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ResolveError;
+    use crate::{Params, RuleSet};
+
+    const RULE_SET: &str = r#"{
+        "version": "1.0",
+        "parameters": {
+            "Region": { "type": "String", "required": true },
+            "UseFIPS": { "type": "Boolean", "required": true, "default": false }
+        },
+        "rules": [
+            {
+                "conditions": [
+                    { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] }
+                ],
+                "type": "error",
+                "error": "FIPS is not supported in {Region}"
+            },
+            {
+                "conditions": [],
+                "type": "endpoint",
+                "endpoint": { "url": "https://service.{Region}.amazonaws.com" }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parameters() {
+        let rule_set = RuleSet::from_json(RULE_SET.as_bytes()).expect("valid rule set");
+        assert_eq!("1.0", rule_set.version());
+        assert_eq!(2, rule_set.parameters().len());
+        let use_fips = rule_set.parameter("UseFIPS").expect("defined");
+        assert!(use_fips.is_required());
+        assert_eq!(Some(&false.into()), use_fips.default_value());
+    }
+
+    #[test]
+    fn parameter_errors() {
+        let rule_set = RuleSet::from_json(RULE_SET.as_bytes()).expect("valid rule set");
+        assert!(matches!(
+            rule_set.resolve(&Params::new()),
+            Err(ResolveError::MissingParameter { name }) if name == "Region"
+        ));
+        assert!(matches!(
+            rule_set.resolve(&Params::new().with("Region", true)),
+            Err(ResolveError::InvalidParameter { name, .. }) if name == "Region"
+        ));
+        assert!(matches!(
+            rule_set.resolve(&Params::new().with("Region", "us-east-1").with("Bucket", "b")),
+            Err(ResolveError::InvalidParameter { name, .. }) if name == "Bucket"
+        ));
+        assert_eq!(
+            "FIPS is not supported in us-east-1",
+            rule_set
+                .resolve(
+                    &Params::new()
+                        .with("Region", "us-east-1")
+                        .with("UseFIPS", true)
+                )
+                .expect_err("error rule")
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_rule_sets() {
+        let check = |json: &str, expected: &str| {
+            let err = RuleSet::from_json(json.as_bytes()).expect_err("invalid rule set");
+            assert_eq!(expected, err.to_string());
+        };
+        check(
+            r#"{"version": "1.0", "rules": [{"conditions": [], "type": "unknown"}]}"#,
+            "invalid document at `rules[0].type`: unknown rule type `unknown`",
+        );
+        check(
+            r#"{"version": "1.0", "rules": [{"conditions": [{"fn": "nope", "argv": []}], "type": "error", "error": "e"}]}"#,
+            "invalid document at `rules[0].conditions[0].fn`: unknown function `nope`",
+        );
+        check(
+            r#"{"version": "1.0", "rules": [{"conditions": [{"fn": "isSet", "argv": []}], "type": "error", "error": "e"}]}"#,
+            "invalid document at `rules[0].conditions[0]`: `isSet` expects 1 arguments but got 0",
+        );
+        check(
+            r#"{"version": "1.0", "rules": [{"conditions": [], "type": "error", "error": "{Region"}]}"#,
+            "invalid document at `rules[0].error`: unterminated template in `{Region`",
+        );
+        check(
+            r#"{"rules": []}"#,
+            "invalid document: missing required field `version`",
+        );
+    }
+}
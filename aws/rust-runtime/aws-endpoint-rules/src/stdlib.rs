@@ -0,0 +1,416 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Functions available to rule sets
+//!
+//! Functions return `None` when they have no result, e.g. `parseURL` for an invalid URL. A
+//! condition that evaluates to `None` or `false` does not match.
+
+mod aws;
+
+use crate::error::ResolveError;
+use crate::eval::Context;
+use crate::value::Value;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+
+/// A function that can be called by a rule set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Function {
+    IsSet,
+    Not,
+    BooleanEquals,
+    StringEquals,
+    GetAttr,
+    ParseUrl,
+    Substring,
+    IsValidHostLabel,
+    UriEncode,
+    AwsPartition,
+    AwsParseArn,
+    AwsIsVirtualHostableS3Bucket,
+}
+
+impl Function {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "isSet" => Function::IsSet,
+            "not" => Function::Not,
+            "booleanEquals" => Function::BooleanEquals,
+            "stringEquals" => Function::StringEquals,
+            "getAttr" => Function::GetAttr,
+            "parseURL" => Function::ParseUrl,
+            "substring" => Function::Substring,
+            "isValidHostLabel" => Function::IsValidHostLabel,
+            "uriEncode" => Function::UriEncode,
+            "aws.partition" => Function::AwsPartition,
+            "aws.parseArn" => Function::AwsParseArn,
+            "aws.isVirtualHostableS3Bucket" => Function::AwsIsVirtualHostableS3Bucket,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::IsSet => "isSet",
+            Function::Not => "not",
+            Function::BooleanEquals => "booleanEquals",
+            Function::StringEquals => "stringEquals",
+            Function::GetAttr => "getAttr",
+            Function::ParseUrl => "parseURL",
+            Function::Substring => "substring",
+            Function::IsValidHostLabel => "isValidHostLabel",
+            Function::UriEncode => "uriEncode",
+            Function::AwsPartition => "aws.partition",
+            Function::AwsParseArn => "aws.parseArn",
+            Function::AwsIsVirtualHostableS3Bucket => "aws.isVirtualHostableS3Bucket",
+        }
+    }
+
+    /// The number of arguments the function takes
+    pub(crate) fn arity(&self) -> usize {
+        match self {
+            Function::IsSet
+            | Function::Not
+            | Function::ParseUrl
+            | Function::UriEncode
+            | Function::AwsPartition
+            | Function::AwsParseArn => 1,
+            Function::BooleanEquals
+            | Function::StringEquals
+            | Function::GetAttr
+            | Function::IsValidHostLabel
+            | Function::AwsIsVirtualHostableS3Bucket => 2,
+            Function::Substring => 4,
+        }
+    }
+}
+
+/// Call `function` with evaluated `args`
+///
+/// Except for `isSet`, functions return `None` if any of their arguments is not set.
+pub(crate) fn call(
+    function: Function,
+    args: &[Option<Value>],
+    context: &Context<'_>,
+) -> Result<Option<Value>, ResolveError> {
+    debug_assert_eq!(function.arity(), args.len());
+    if function == Function::IsSet {
+        return Ok(Some(Value::Bool(args[0].is_some())));
+    }
+    let args = match args.iter().map(Option::as_ref).collect::<Option<Vec<_>>>() {
+        Some(args) => Args { function, args },
+        None => return Ok(None),
+    };
+    Ok(match function {
+        Function::IsSet => unreachable!("handled above"),
+        Function::Not => Some(Value::Bool(!args.bool(0)?)),
+        Function::BooleanEquals => Some(Value::Bool(args.bool(0)? == args.bool(1)?)),
+        Function::StringEquals => Some(Value::Bool(args.str(0)? == args.str(1)?)),
+        Function::GetAttr => unreachable!("getAttr is evaluated as an expression"),
+        Function::ParseUrl => parse_url(args.str(0)?),
+        Function::Substring => substring(
+            args.str(0)?,
+            args.integer(1)?,
+            args.integer(2)?,
+            args.bool(3)?,
+        )
+        .map(Value::from),
+        Function::IsValidHostLabel => Some(Value::Bool(is_valid_host_label(
+            args.str(0)?,
+            args.bool(1)?,
+        ))),
+        Function::UriEncode => Some(Value::String(uri_encode(args.str(0)?))),
+        Function::AwsPartition => context.partitions.resolve(args.str(0)?),
+        Function::AwsParseArn => aws::parse_arn(args.str(0)?),
+        Function::AwsIsVirtualHostableS3Bucket => Some(Value::Bool(
+            aws::is_virtual_hostable_s3_bucket(args.str(0)?, args.bool(1)?),
+        )),
+    })
+}
+
+/// Arguments of a function call, with type checks
+struct Args<'a> {
+    function: Function,
+    args: Vec<&'a Value>,
+}
+
+impl<'a> Args<'a> {
+    fn type_error(&self, idx: usize, expected: &str) -> ResolveError {
+        ResolveError::evaluation(format!(
+            "argument {} of `{}` must be a {} but was a {}",
+            idx,
+            self.function.name(),
+            expected,
+            self.args[idx].type_name()
+        ))
+    }
+
+    fn str(&self, idx: usize) -> Result<&'a str, ResolveError> {
+        self.args[idx]
+            .as_str()
+            .ok_or_else(|| self.type_error(idx, "string"))
+    }
+
+    fn bool(&self, idx: usize) -> Result<bool, ResolveError> {
+        self.args[idx]
+            .as_bool()
+            .ok_or_else(|| self.type_error(idx, "boolean"))
+    }
+
+    fn integer(&self, idx: usize) -> Result<i64, ResolveError> {
+        self.args[idx]
+            .as_integer()
+            .ok_or_else(|| self.type_error(idx, "integer"))
+    }
+}
+
+/// Parse an `http` or `https` URL into its `scheme`, `authority`, `path`, `normalizedPath` and `isIp`
+///
+/// URLs with a query string or fragment are not supported.
+fn parse_url(url: &str) -> Option<Value> {
+    let (scheme, rest) = url.split_once("://")?;
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    if rest.contains('?') || rest.contains('#') {
+        return None;
+    }
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    if authority.is_empty() {
+        return None;
+    }
+    let normalized_path = match (path.starts_with('/'), path.ends_with('/')) {
+        _ if path.is_empty() => "/".to_string(),
+        (true, true) => path.to_string(),
+        (true, false) => format!("{}/", path),
+        _ => unreachable!("the path starts at `/`"),
+    };
+    let mut url = HashMap::new();
+    url.insert("scheme".to_string(), Value::from(scheme));
+    url.insert("authority".to_string(), Value::from(authority));
+    url.insert("path".to_string(), Value::from(path));
+    url.insert("normalizedPath".to_string(), Value::from(normalized_path));
+    url.insert("isIp".to_string(), Value::Bool(is_ip(authority)));
+    Some(Value::Object(url))
+}
+
+/// Whether the host of `authority` is an IPv4 or IPv6 address
+fn is_ip(authority: &str) -> bool {
+    if let Some(ipv6) = authority.strip_prefix('[') {
+        return match ipv6.split_once(']') {
+            Some((host, _port)) => host.parse::<Ipv6Addr>().is_ok(),
+            None => false,
+        };
+    }
+    let host = match authority.rsplit_once(':') {
+        Some((host, _port)) => host,
+        None => authority,
+    };
+    host.parse::<IpAddr>().is_ok()
+}
+
+/// The substring of `input` from `start` (inclusive) to `stop` (exclusive)
+///
+/// When `reverse` is set, indices are counted from the end of `input`. Returns `None` if `input`
+/// is not ASCII or the indices are out of range.
+fn substring(input: &str, start: i64, stop: i64, reverse: bool) -> Option<String> {
+    if !input.is_ascii() || start < 0 || start >= stop || stop as usize > input.len() {
+        return None;
+    }
+    let (start, stop) = (start as usize, stop as usize);
+    let (start, stop) = if reverse {
+        (input.len() - stop, input.len() - start)
+    } else {
+        (start, stop)
+    };
+    Some(input[start..stop].to_string())
+}
+
+/// Whether `value` is a valid host label as defined by RFC 1123
+///
+/// When `allow_subdomains` is set, each `.`-separated label must be valid.
+pub(crate) fn is_valid_host_label(value: &str, allow_subdomains: bool) -> bool {
+    let is_valid_label = |label: &str| {
+        let mut chars = label.chars();
+        label.len() <= 63
+            && matches!(chars.next(), Some(ch) if ch.is_ascii_alphanumeric())
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    };
+    if allow_subdomains {
+        value.split('.').all(is_valid_label)
+    } else {
+        is_valid_label(value)
+    }
+}
+
+/// Characters that are not encoded by `uriEncode`: the RFC 3986 unreserved characters
+const URI_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Percent-encode `value` as a URI path segment or query parameter
+fn uri_encode(value: &str) -> String {
+    percent_encoding::utf8_percent_encode(value, URI_ENCODE_SET).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_url_cases() {
+        struct Case {
+            url: &'static str,
+            // scheme, authority, path, normalizedPath, isIp
+            expected: Option<(&'static str, &'static str, &'static str, &'static str, bool)>,
+        }
+        let cases = [
+            Case {
+                url: "https://example.com",
+                expected: Some(("https", "example.com", "", "/", false)),
+            },
+            Case {
+                url: "http://example.com:8443/foo/bar",
+                expected: Some(("http", "example.com:8443", "/foo/bar", "/foo/bar/", false)),
+            },
+            Case {
+                url: "https://127.0.0.1:8000/",
+                expected: Some(("https", "127.0.0.1:8000", "/", "/", true)),
+            },
+            Case {
+                url: "https://[::1]:8000/path/",
+                expected: Some(("https", "[::1]:8000", "/path/", "/path/", true)),
+            },
+            Case {
+                url: "https://example.com?query=1",
+                expected: None,
+            },
+            Case {
+                url: "https://example.com/#fragment",
+                expected: None,
+            },
+            Case {
+                url: "ftp://example.com",
+                expected: None,
+            },
+            Case {
+                url: "example.com",
+                expected: None,
+            },
+            Case {
+                url: "https:///path",
+                expected: None,
+            },
+        ];
+        for case in &cases {
+            let expected =
+                case.expected
+                    .map(|(scheme, authority, path, normalized_path, is_ip)| {
+                        let mut url = HashMap::new();
+                        url.insert("scheme".to_string(), Value::from(scheme));
+                        url.insert("authority".to_string(), Value::from(authority));
+                        url.insert("path".to_string(), Value::from(path));
+                        url.insert("normalizedPath".to_string(), Value::from(normalized_path));
+                        url.insert("isIp".to_string(), Value::Bool(is_ip));
+                        Value::Object(url)
+                    });
+            assert_eq!(expected, parse_url(case.url), "{}", case.url);
+        }
+    }
+
+    #[test]
+    fn substring_cases() {
+        let cases: &[(&str, i64, i64, bool, Option<&str>)] = &[
+            ("abcdefg", 0, 4, false, Some("abcd")),
+            ("abcdefg", 0, 4, true, Some("defg")),
+            ("abcdefg", 1, 7, false, Some("bcdefg")),
+            ("abcdefg", 5, 7, true, Some("ab")),
+            ("abcdefg", 0, 8, false, None),
+            ("abcdefg", 4, 4, false, None),
+            ("abcdefg", 5, 4, false, None),
+            ("abcdefg", -1, 4, false, None),
+            ("\u{1F600}abc", 0, 2, false, None),
+        ];
+        for (input, start, stop, reverse, expected) in cases {
+            assert_eq!(
+                expected.map(|s| s.to_string()),
+                substring(input, *start, *stop, *reverse),
+                "substring({:?}, {}, {}, {})",
+                input,
+                start,
+                stop,
+                reverse
+            );
+        }
+    }
+
+    #[test]
+    fn host_label_cases() {
+        let cases: &[(&str, bool, bool)] = &[
+            ("us-east-1", false, true),
+            ("a", false, true),
+            ("0abc", false, true),
+            ("-abc", false, false),
+            ("", false, false),
+            ("a.b", false, false),
+            ("a.b", true, true),
+            ("a..b", true, false),
+            ("a.-b", true, false),
+            ("under_score", false, false),
+            (&"a".repeat(63), false, true),
+            (&"a".repeat(64), false, false),
+        ];
+        for (value, allow_subdomains, expected) in cases {
+            assert_eq!(
+                *expected,
+                is_valid_host_label(value, *allow_subdomains),
+                "isValidHostLabel({:?}, {})",
+                value,
+                allow_subdomains
+            );
+        }
+    }
+
+    #[test]
+    fn uri_encode_cases() {
+        let cases = [
+            ("abc-_.~XYZ019", "abc-_.~XYZ019"),
+            ("a b/c", "a%20b%2Fc"),
+            ("key=value&x", "key%3Dvalue%26x"),
+            ("\u{e9}", "%C3%A9"),
+        ];
+        for (input, expected) in &cases {
+            assert_eq!(*expected, uri_encode(input));
+        }
+    }
+
+    #[test]
+    fn unset_arguments_produce_no_value() {
+        let context = Context {
+            partitions: crate::partition::Partitions::built_in(),
+        };
+        assert_eq!(
+            Some(Value::Bool(false)),
+            call(Function::IsSet, &[None], &context).unwrap()
+        );
+        assert_eq!(
+            None,
+            call(Function::StringEquals, &[None, Some("a".into())], &context).unwrap()
+        );
+        assert!(call(
+            Function::StringEquals,
+            &[Some(true.into()), Some("a".into())],
+            &context
+        )
+        .is_err());
+    }
+}
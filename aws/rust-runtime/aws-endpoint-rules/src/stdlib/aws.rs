@@ -0,0 +1,147 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! AWS-specific functions (`aws.partition` is implemented by [`Partitions`](crate::partition::Partitions))
+
+use super::is_valid_host_label;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+/// Parse an ARN into its `partition`, `service`, `region`, `accountId` and `resourceId`
+///
+/// `resourceId` is the resource split on `:` and `/`, e.g. `accesspoint/myendpoint` becomes
+/// `["accesspoint", "myendpoint"]`. Returns `None` if `arn` is not a valid ARN.
+pub(super) fn parse_arn(arn: &str) -> Option<Value> {
+    let mut parts = arn.splitn(6, ':');
+    if parts.next()? != "arn" {
+        return None;
+    }
+    let partition = parts.next()?;
+    let service = parts.next()?;
+    let region = parts.next()?;
+    let account_id = parts.next()?;
+    let resource = parts.next()?;
+    if partition.is_empty() || service.is_empty() || resource.is_empty() {
+        return None;
+    }
+    let resource_id = resource
+        .split([':', '/'])
+        .map(Value::from)
+        .collect::<Vec<_>>();
+
+    let mut out = HashMap::new();
+    out.insert("partition".to_string(), Value::from(partition));
+    out.insert("service".to_string(), Value::from(service));
+    out.insert("region".to_string(), Value::from(region));
+    out.insert("accountId".to_string(), Value::from(account_id));
+    out.insert("resourceId".to_string(), Value::Array(resource_id));
+    Some(Value::Object(out))
+}
+
+/// Whether `bucket` can be used as a host label (virtual-hosted-style addressing)
+///
+/// When `allow_subdomains` is set, buckets containing `.` are allowed, e.g. for use with `http`
+/// endpoints where the TLS certificate does not need to match.
+pub(super) fn is_virtual_hostable_s3_bucket(bucket: &str, allow_subdomains: bool) -> bool {
+    let valid_chars = bucket
+        .chars()
+        .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '.' || ch == '-');
+    (3..=63).contains(&bucket.len())
+        && valid_chars
+        && is_valid_host_label(bucket, allow_subdomains)
+        && !bucket.ends_with('-')
+        && !bucket.contains("..")
+        && !bucket.contains(".-")
+        && !bucket.contains("-.")
+        && bucket.parse::<Ipv4Addr>().is_err()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_virtual_hostable_s3_bucket, parse_arn};
+    use crate::value::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parse_arns() {
+        let arn =
+            |partition: &str, service: &str, region: &str, account_id: &str, resource: &[&str]| {
+                let mut out = HashMap::new();
+                out.insert("partition".to_string(), Value::from(partition));
+                out.insert("service".to_string(), Value::from(service));
+                out.insert("region".to_string(), Value::from(region));
+                out.insert("accountId".to_string(), Value::from(account_id));
+                out.insert(
+                    "resourceId".to_string(),
+                    Value::Array(resource.iter().map(|r| Value::from(*r)).collect()),
+                );
+                Some(Value::Object(out))
+            };
+        assert_eq!(
+            arn(
+                "aws",
+                "s3",
+                "us-west-2",
+                "123456789012",
+                &["accesspoint", "myendpoint"]
+            ),
+            parse_arn("arn:aws:s3:us-west-2:123456789012:accesspoint/myendpoint")
+        );
+        assert_eq!(
+            arn("aws-cn", "s3-outposts", "cn-north-1", "123456789012", &["outpost", "op-01234567890123456", "accesspoint", "reports"]),
+            parse_arn("arn:aws-cn:s3-outposts:cn-north-1:123456789012:outpost/op-01234567890123456/accesspoint/reports")
+        );
+        assert_eq!(
+            arn("aws", "iam", "", "123456789012", &["role", "my-role"]),
+            parse_arn("arn:aws:iam::123456789012:role/my-role")
+        );
+        assert_eq!(
+            arn("aws", "sns", "us-east-1", "123456789012", &["topic"]),
+            parse_arn("arn:aws:sns:us-east-1:123456789012:topic")
+        );
+        for invalid in &[
+            "",
+            "arn",
+            "arn:aws:s3:us-west-2:123456789012",
+            "arn:aws:s3:us-west-2:123456789012:",
+            "arn::s3:us-west-2:123456789012:bucket",
+            "arn:aws::us-west-2:123456789012:bucket",
+            "nra:aws:s3:us-west-2:123456789012:bucket",
+        ] {
+            assert_eq!(None, parse_arn(invalid), "`{}` should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn virtual_hostable_buckets() {
+        let cases: &[(&str, bool, bool)] = &[
+            ("bucket-name", false, true),
+            ("abc", false, true),
+            ("ab", false, false),
+            (&"a".repeat(63), false, true),
+            (&"a".repeat(64), false, false),
+            ("Bucket", false, false),
+            ("bucket_name", false, false),
+            ("-bucket", false, false),
+            ("bucket-", false, false),
+            ("bucket.name", false, false),
+            ("bucket.name", true, true),
+            ("bucket..name", true, false),
+            ("bucket.-name", true, false),
+            ("bucket-.name", true, false),
+            ("192.168.0.1", true, false),
+        ];
+        for (bucket, allow_subdomains, expected) in cases {
+            assert_eq!(
+                *expected,
+                is_virtual_hostable_s3_bucket(bucket, *allow_subdomains),
+                "isVirtualHostableS3Bucket({:?}, {})",
+                bucket,
+                allow_subdomains
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! String templates and attribute paths
+//!
+//! String literals in a rule set are templates: `{Region}` is replaced with the value of `Region`
+//! and `{PartitionResult#dnsSuffix}` with the `dnsSuffix` field of `PartitionResult`. Literal
+//! braces are escaped by doubling them (`{{` and `}}`).
+
+use crate::value::Value;
+use std::borrow::Cow;
+
+/// A parsed string template
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TemplatePart {
+    Literal(String),
+    Ref(String),
+    Attr(String, Path),
+}
+
+impl Template {
+    pub(crate) fn parse(input: &str) -> Result<Self, Cow<'static, str>> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut chars = input.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => name.push(ch),
+                            None => {
+                                return Err(format!("unterminated template in `{}`", input).into())
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(match name.split_once('#') {
+                        Some((name, path)) => {
+                            TemplatePart::Attr(name.to_string(), Path::parse(path)?)
+                        }
+                        None => TemplatePart::Ref(name),
+                    });
+                }
+                '}' => return Err(format!("unmatched `}}` in `{}`", input).into()),
+                ch => literal.push(ch),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    pub(crate) fn parts(&self) -> &[TemplatePart] {
+        &self.parts
+    }
+}
+
+/// A path to a field of an object or an element of an array, e.g. `resourceId[1]`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Path {
+    segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl Path {
+    pub(crate) fn parse(input: &str) -> Result<Self, Cow<'static, str>> {
+        let invalid =
+            || -> Cow<'static, str> { format!("invalid attribute path `{}`", input).into() };
+        let mut segments = vec![];
+        for part in input.split('.') {
+            let (key, index) = match part.split_once('[') {
+                Some((key, index)) => {
+                    let index = index
+                        .strip_suffix(']')
+                        .and_then(|index| index.parse::<usize>().ok())
+                        .ok_or_else(invalid)?;
+                    (key, Some(index))
+                }
+                None => (part, None),
+            };
+            if !key.is_empty() {
+                segments.push(PathSegment::Key(key.to_string()));
+            } else if index.is_none() {
+                return Err(invalid());
+            }
+            if let Some(index) = index {
+                segments.push(PathSegment::Index(index));
+            }
+        }
+        Ok(Path { segments })
+    }
+
+    /// The value at this path in `value`, if it exists
+    pub(crate) fn get<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |value, segment| match segment {
+                PathSegment::Key(key) => value.as_object()?.get(key),
+                PathSegment::Index(idx) => value.as_array()?.get(*idx),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Path, Template, TemplatePart};
+    use crate::value::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parse_template() {
+        let template =
+            Template::parse("https://{Bucket}.s3.{PartitionResult#dnsSuffix}/{{literal}}")
+                .expect("valid template");
+        assert_eq!(
+            &[
+                TemplatePart::Literal("https://".into()),
+                TemplatePart::Ref("Bucket".into()),
+                TemplatePart::Literal(".s3.".into()),
+                TemplatePart::Attr("PartitionResult".into(), Path::parse("dnsSuffix").unwrap()),
+                TemplatePart::Literal("/{literal}".into()),
+            ],
+            template.parts()
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for input in &["{Region", "Region}", "{Arn#}", "{Arn#resourceId[x]}"] {
+            assert!(
+                Template::parse(input).is_err(),
+                "`{}` should be invalid",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn get_path() {
+        let mut arn = HashMap::new();
+        arn.insert(
+            "resourceId".to_string(),
+            Value::Array(vec!["accesspoint".into(), "myendpoint".into()]),
+        );
+        let mut outer = HashMap::new();
+        outer.insert("arn".to_string(), Value::Object(arn));
+        let value = Value::Object(outer);
+
+        let get = |path: &str| Path::parse(path).expect("valid path").get(&value).cloned();
+        assert_eq!(Some("myendpoint".into()), get("arn.resourceId[1]"));
+        assert_eq!(None, get("arn.resourceId[2]"));
+        assert_eq!(None, get("arn.region"));
+        assert_eq!(None, get("arn[0]"));
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Endpoint test cases
+//!
+//! Test cases are loaded from the JSON representation of the `smithy.rules#endpointTests` trait
+//! so that rule sets can be verified offline:
+//!
+//! ```json
+//! {
+//!     "testCases": [
+//!         {
+//!             "documentation": "FIPS in us-east-1",
+//!             "params": { "Region": "us-east-1", "UseFIPS": true },
+//!             "expect": { "endpoint": { "url": "https://service-fips.us-east-1.amazonaws.com" } }
+//!         },
+//!         {
+//!             "params": { "Region": "us-iso-east-1", "UseDualStack": true },
+//!             "expect": { "error": "DualStack is not supported in this partition" }
+//!         }
+//!     ]
+//! }
+//! ```
+
+use crate::document::{self, Node};
+use crate::endpoint::Endpoint;
+use crate::error::RuleSetError;
+use crate::ruleset::{Params, RuleSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// A set of test cases for a rule set
+#[derive(Debug)]
+pub struct TestCases {
+    test_cases: Vec<TestCase>,
+}
+
+impl TestCases {
+    /// Load test cases from JSON
+    pub fn from_json(json: &[u8]) -> Result<Self, RuleSetError> {
+        let document = document::parse(json)?;
+        let root = Node::root(&document).expect_object()?;
+        let test_cases = root
+            .expect("testCases")?
+            .expect_array()?
+            .iter()
+            .map(TestCase::from_node)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { test_cases })
+    }
+
+    /// The test cases
+    pub fn test_cases(&self) -> &[TestCase] {
+        &self.test_cases
+    }
+
+    /// Check every test case against `rule_set`, returning all failures
+    pub fn check(&self, rule_set: &RuleSet) -> Result<(), Vec<TestFailure>> {
+        let failures: Vec<_> = self
+            .test_cases
+            .iter()
+            .filter_map(|test_case| test_case.check(rule_set).err())
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+/// A single test case: parameters and the expected endpoint or error
+#[derive(Debug)]
+pub struct TestCase {
+    documentation: Option<String>,
+    params: Params,
+    expectation: Expectation,
+}
+
+/// The expected result of resolving an endpoint
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expectation {
+    /// The rule set returns this endpoint
+    Endpoint(Endpoint),
+    /// The rule set returns an error with this message
+    Error(String),
+}
+
+impl TestCase {
+    fn from_node(node: &Node<'_>) -> Result<Self, RuleSetError> {
+        let test_case = node.expect_object()?;
+        let documentation = match test_case.get("documentation") {
+            Some(documentation) => Some(documentation.expect_str()?.to_string()),
+            None => None,
+        };
+        let mut params = Params::new();
+        if let Some(node) = test_case.get("params") {
+            for (name, value) in node.expect_object()?.members() {
+                params.set(name, value.to_value()?);
+            }
+        }
+        let expect = test_case.expect("expect")?.expect_object()?;
+        let expectation = match (expect.get("endpoint"), expect.get("error")) {
+            (Some(endpoint), None) => Expectation::Endpoint(parse_endpoint(&endpoint)?),
+            (None, Some(error)) => Expectation::Error(error.expect_str()?.to_string()),
+            _ => {
+                return Err(test_case
+                    .expect("expect")?
+                    .error("expected exactly one of `endpoint` or `error`"))
+            }
+        };
+        Ok(TestCase {
+            documentation,
+            params,
+            expectation,
+        })
+    }
+
+    /// A description of the test case
+    pub fn documentation(&self) -> Option<&str> {
+        self.documentation.as_deref()
+    }
+
+    /// The parameters to resolve an endpoint with
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// The expected endpoint or error
+    pub fn expectation(&self) -> &Expectation {
+        &self.expectation
+    }
+
+    /// Resolve an endpoint with `rule_set` and compare it to the expectation
+    ///
+    /// Errors are compared by their message.
+    pub fn check(&self, rule_set: &RuleSet) -> Result<(), TestFailure> {
+        let actual = match rule_set.resolve(&self.params) {
+            Ok(endpoint) => Expectation::Endpoint(endpoint),
+            Err(err) => Expectation::Error(err.to_string()),
+        };
+        if actual == self.expectation {
+            Ok(())
+        } else {
+            Err(TestFailure {
+                documentation: self.documentation.clone(),
+                expected: Box::new(self.expectation.clone()),
+                actual: Box::new(actual),
+            })
+        }
+    }
+}
+
+fn parse_endpoint(node: &Node<'_>) -> Result<Endpoint, RuleSetError> {
+    let endpoint = node.expect_object()?;
+    let mut builder = Endpoint::builder().url(endpoint.expect("url")?.expect_str()?);
+    if let Some(properties) = endpoint.get("properties") {
+        for (name, value) in properties.expect_object()?.members() {
+            builder = builder.property(name, value.to_value()?);
+        }
+    }
+    if let Some(headers) = endpoint.get("headers") {
+        for (name, values) in headers.expect_object()?.members() {
+            for value in values.expect_array()? {
+                builder = builder.header(name, value.expect_str()?);
+            }
+        }
+    }
+    Ok(builder.build())
+}
+
+/// A test case did not produce the expected result
+#[derive(Debug)]
+pub struct TestFailure {
+    documentation: Option<String>,
+    expected: Box<Expectation>,
+    actual: Box<Expectation>,
+}
+
+impl TestFailure {
+    /// The expected result
+    pub fn expected(&self) -> &Expectation {
+        &self.expected
+    }
+
+    /// The actual result
+    pub fn actual(&self) -> &Expectation {
+        &self.actual
+    }
+}
+
+impl Display for TestFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "test case `{}` failed: expected {:?} but got {:?}",
+            self.documentation.as_deref().unwrap_or("<undocumented>"),
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+impl Error for TestFailure {}
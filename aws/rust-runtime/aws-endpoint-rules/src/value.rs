@@ -0,0 +1,128 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Values of parameters, variables and endpoint properties
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// A value produced while evaluating a rule set
+///
+/// Parameters are strings or booleans. Functions such as `parseURL` or `aws.partition` return
+/// objects whose fields are accessed with `getAttr` or a `{Name#field}` template.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A string
+    String(String),
+    /// A boolean
+    Bool(bool),
+    /// An integer, e.g. an index passed to `substring`
+    Integer(i64),
+    /// An array of values
+    Array(Vec<Value>),
+    /// An object with named fields
+    Object(HashMap<String, Value>),
+}
+
+impl Value {
+    /// The value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a boolean, if it is one
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value as an integer, if it is one
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value as an array, if it is one
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an object, if it is one
+    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+        match self {
+            Value::Object(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Bool(_) => "boolean",
+            Value::Integer(_) => "integer",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::Array(value)
+    }
+}
+
+impl From<HashMap<String, Value>> for Value {
+    fn from(value: HashMap<String, Value>) -> Self {
+        Value::Object(value)
+    }
+}
@@ -35,6 +35,11 @@ pub struct AwsEndpoint {
 }
 
 impl AwsEndpoint {
+    /// The endpoint to send requests to
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
     pub fn set_endpoint(&self, mut uri: &mut http::Uri, endpoint_prefix: Option<&EndpointPrefix>) {
         self.endpoint.set_endpoint(&mut uri, endpoint_prefix);
     }
@@ -75,8 +80,109 @@ pub type BoxError = Box<dyn Error + Send + Sync + 'static>;
 pub trait ResolveAwsEndpoint: Send + Sync {
     // TODO: consider if we want modeled error variants here
     fn resolve_endpoint(&self, region: &Region) -> Result<AwsEndpoint, BoxError>;
+
+    /// Resolve the endpoint for `region` that supports `variant`, e.g. a FIPS endpoint
+    ///
+    /// Implementations MUST NOT fall back to an endpoint that doesn't support the variant. By
+    /// default, no variants are supported and an [`UnsupportedVariant`] error is returned.
+    fn resolve_variant(
+        &self,
+        region: &Region,
+        variant: EndpointVariant,
+    ) -> Result<AwsEndpoint, BoxError> {
+        Err(UnsupportedVariant::new(region.clone(), variant).into())
+    }
+}
+
+/// Endpoint variant required by the client configuration
+///
+/// Variants are selected with the `use_fips` and `use_dual_stack` settings. When a variant is
+/// selected, the endpoint resolver returns an error rather than falling back to the
+/// default endpoint if the region has no endpoint for the variant.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointVariant {
+    /// FIPS 140-2 compliant endpoint
+    Fips,
+    /// Dual-stack (IPv4 and IPv6) endpoint
+    DualStack,
+    /// FIPS 140-2 compliant, dual-stack endpoint
+    FipsDualStack,
+}
+
+impl EndpointVariant {
+    /// The variant selected by the `use_fips` and `use_dual_stack` settings
+    ///
+    /// Returns `None` when neither setting is enabled.
+    pub fn from_settings(use_fips: bool, use_dual_stack: bool) -> Option<Self> {
+        match (use_fips, use_dual_stack) {
+            (false, false) => None,
+            (true, false) => Some(EndpointVariant::Fips),
+            (false, true) => Some(EndpointVariant::DualStack),
+            (true, true) => Some(EndpointVariant::FipsDualStack),
+        }
+    }
+
+    /// Whether this variant requires a FIPS endpoint
+    pub fn is_fips(&self) -> bool {
+        matches!(self, EndpointVariant::Fips | EndpointVariant::FipsDualStack)
+    }
+
+    /// Whether this variant requires a dual-stack endpoint
+    pub fn is_dual_stack(&self) -> bool {
+        matches!(
+            self,
+            EndpointVariant::DualStack | EndpointVariant::FipsDualStack
+        )
+    }
+}
+
+impl Display for EndpointVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointVariant::Fips => write!(f, "FIPS"),
+            EndpointVariant::DualStack => write!(f, "dual-stack"),
+            EndpointVariant::FipsDualStack => write!(f, "FIPS and dual-stack"),
+        }
+    }
+}
+
+/// No endpoint supporting the requested [`EndpointVariant`] exists for the region
+#[derive(Debug)]
+pub struct UnsupportedVariant {
+    region: Region,
+    variant: EndpointVariant,
+}
+
+impl UnsupportedVariant {
+    /// Create a new `UnsupportedVariant` error
+    pub fn new(region: Region, variant: EndpointVariant) -> Self {
+        Self { region, variant }
+    }
+
+    /// The region an endpoint was requested for
+    pub fn region(&self) -> &Region {
+        &self.region
+    }
+
+    /// The variant that is not supported
+    pub fn variant(&self) -> EndpointVariant {
+        self.variant
+    }
 }
 
+impl Display for UnsupportedVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no {} endpoint is available in region `{}`",
+            self.variant, self.region
+        )
+    }
+}
+
+impl Error for UnsupportedVariant {}
+
 #[derive(Clone, Default, Debug)]
 pub struct CredentialScope {
     region: Option<SigningRegion>,
@@ -130,6 +236,9 @@ impl CredentialScope {
 }
 
 /// An `Endpoint` can be its own resolver to support static endpoints
+///
+/// A static endpoint is used as-is for every [`EndpointVariant`]: the caller is responsible for
+/// choosing an endpoint that satisfies their requirements.
 impl ResolveAwsEndpoint for Endpoint {
     fn resolve_endpoint(&self, _region: &Region) -> Result<AwsEndpoint, BoxError> {
         Ok(AwsEndpoint {
@@ -137,6 +246,14 @@ impl ResolveAwsEndpoint for Endpoint {
             credential_scope: Default::default(),
         })
     }
+
+    fn resolve_variant(
+        &self,
+        region: &Region,
+        _variant: EndpointVariant,
+    ) -> Result<AwsEndpoint, BoxError> {
+        self.resolve_endpoint(region)
+    }
 }
 
 type AwsEndpointResolver = Arc<dyn ResolveAwsEndpoint>;
@@ -148,14 +265,24 @@ pub fn set_endpoint_resolver(properties: &mut PropertyBag, provider: AwsEndpoint
     properties.insert(provider);
 }
 
+pub fn get_endpoint_variant(properties: &PropertyBag) -> Option<EndpointVariant> {
+    properties.get().copied()
+}
+
+pub fn set_endpoint_variant(properties: &mut PropertyBag, variant: EndpointVariant) {
+    properties.insert(variant);
+}
+
 /// Middleware Stage to Add an Endpoint to a Request
 ///
 /// AwsEndpointStage implements [`MapRequest`](aws_smithy_http::middleware::MapRequest). It will:
 /// 1. Load an endpoint provider from the property bag.
-/// 2. Load an endpoint given the [`Region`](aws_types::region::Region) in the property bag.
+/// 2. Load an endpoint given the [`Region`](aws_types::region::Region) in the property bag. If
+///    an [`EndpointVariant`] is set in the property bag, only an endpoint supporting that variant
+///    is used.
 /// 3. Apply the endpoint to the URI in the request
 /// 4. Set the `SigningRegion` and `SigningService` in the property bag to drive downstream
-/// signing middleware.
+///    signing middleware.
 #[derive(Clone, Debug)]
 pub struct AwsEndpointStage;
 
@@ -183,10 +310,13 @@ impl MapRequest for AwsEndpointStage {
             let region = props
                 .get::<Region>()
                 .ok_or(AwsEndpointStageError::NoRegion)?;
-            let endpoint = provider
-                .resolve_endpoint(region)
-                .map_err(AwsEndpointStageError::EndpointResolutionError)?;
-            tracing::debug!(endpoint = ?endpoint, base_region = ?region, "resolved endpoint");
+            let variant = get_endpoint_variant(props);
+            let endpoint = match variant {
+                Some(variant) => provider.resolve_variant(region, variant),
+                None => provider.resolve_endpoint(region),
+            }
+            .map_err(AwsEndpointStageError::EndpointResolutionError)?;
+            tracing::debug!(endpoint = ?endpoint, base_region = ?region, variant = ?variant, "resolved endpoint");
             let signing_region = endpoint
                 .credential_scope
                 .region
@@ -217,7 +347,10 @@ mod test {
     use aws_types::SigningService;
 
     use crate::partition::endpoint::{Metadata, Protocol, SignatureVersion};
-    use crate::{set_endpoint_resolver, AwsEndpointStage, CredentialScope};
+    use crate::{
+        set_endpoint_resolver, set_endpoint_variant, AwsEndpointStage, AwsEndpointStageError,
+        CredentialScope, EndpointVariant,
+    };
 
     #[test]
     fn default_endpoint_updates_request() {
@@ -281,4 +414,29 @@ mod test {
             Some(&SigningService::from_static("qldb-override"))
         );
     }
+
+    #[test]
+    fn variant_is_required_when_set() {
+        let provider = Arc::new(Metadata {
+            uri_template: "kinesis.{region}.amazonaws.com",
+            protocol: Protocol::Https,
+            credential_scope: Default::default(),
+            signature_versions: SignatureVersion::V4,
+        });
+        let req = http::Request::new(SdkBody::from(""));
+        let mut req = operation::Request::new(req);
+        {
+            let mut props = req.properties_mut();
+            props.insert(Region::new("us-east-1"));
+            set_endpoint_resolver(&mut props, provider);
+            set_endpoint_variant(&mut props, EndpointVariant::Fips);
+        };
+        match AwsEndpointStage.apply(req) {
+            Err(AwsEndpointStageError::EndpointResolutionError(err)) => assert_eq!(
+                "no FIPS endpoint is available in region `us-east-1`",
+                err.to_string()
+            ),
+            other => panic!("expected an endpoint resolution error, got {:?}", other),
+        }
+    }
 }
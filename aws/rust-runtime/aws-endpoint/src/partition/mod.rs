@@ -5,7 +5,7 @@
 
 pub mod endpoint;
 
-use crate::{AwsEndpoint, BoxError, ResolveAwsEndpoint};
+use crate::{AwsEndpoint, BoxError, EndpointVariant, ResolveAwsEndpoint, UnsupportedVariant};
 use aws_types::region::Region;
 use regex::Regex;
 use std::collections::HashMap;
@@ -47,6 +47,18 @@ impl ResolveAwsEndpoint for PartitionResolver {
             .unwrap_or(&self.base);
         matching_partition.resolve_endpoint(region)
     }
+
+    fn resolve_variant(
+        &self,
+        region: &Region,
+        variant: EndpointVariant,
+    ) -> Result<AwsEndpoint, BoxError> {
+        let matching_partition = self
+            .partitions()
+            .find(|partition| partition.can_resolve(region))
+            .unwrap_or(&self.base);
+        matching_partition.resolve_variant(region, variant)
+    }
 }
 
 #[derive(Debug)]
@@ -57,6 +69,8 @@ pub struct Partition {
     regionalized: Regionalized,
     default_endpoint: endpoint::Metadata,
     endpoints: HashMap<Region, endpoint::Metadata>,
+    default_variants: HashMap<EndpointVariant, endpoint::Metadata>,
+    variant_endpoints: HashMap<(Region, EndpointVariant), endpoint::Metadata>,
 }
 
 #[derive(Default)]
@@ -67,6 +81,8 @@ pub struct Builder {
     regionalized: Option<Regionalized>,
     default_endpoint: Option<endpoint::Metadata>,
     endpoints: HashMap<Region, endpoint::Metadata>,
+    default_variants: HashMap<EndpointVariant, endpoint::Metadata>,
+    variant_endpoints: HashMap<(Region, EndpointVariant), endpoint::Metadata>,
 }
 
 impl Builder {
@@ -105,6 +121,28 @@ impl Builder {
         self
     }
 
+    /// Set the endpoint used for `variant` in regions without a more specific variant endpoint
+    pub fn default_variant(
+        mut self,
+        variant: EndpointVariant,
+        default: endpoint::Metadata,
+    ) -> Self {
+        self.default_variants.insert(variant, default);
+        self
+    }
+
+    /// Set the endpoint used for `variant` in `region`
+    pub fn variant_endpoint(
+        mut self,
+        region: &'static str,
+        variant: EndpointVariant,
+        endpoint: endpoint::Metadata,
+    ) -> Self {
+        self.variant_endpoints
+            .insert((Region::new(region), variant), endpoint);
+        self
+    }
+
     /// Construct a Partition from the builder
     ///
     /// Returns `None` if:
@@ -120,6 +158,8 @@ impl Builder {
             regionalized: self.regionalized.unwrap_or_default(),
             default_endpoint,
             endpoints,
+            default_variants: self.default_variants,
+            variant_endpoints: self.variant_endpoints,
         })
     }
 }
@@ -144,6 +184,14 @@ impl Partition {
     pub fn builder() -> Builder {
         Builder::default()
     }
+
+    /// The region whose endpoint is used for `region` when it has no endpoint of its own
+    fn resolved_region<'a>(&'a self, region: &'a Region) -> Option<&'a Region> {
+        match self.regionalized {
+            Regionalized::NotRegionalized => self.partition_endpoint.as_ref(),
+            Regionalized::Regionalized => Some(region),
+        }
+    }
 }
 
 impl ResolveAwsEndpoint for Partition {
@@ -151,15 +199,31 @@ impl ResolveAwsEndpoint for Partition {
         if let Some(endpoint) = self.endpoints.get(region) {
             return endpoint.resolve_endpoint(region);
         }
-        let resolved_region = match self.regionalized {
-            Regionalized::NotRegionalized => self.partition_endpoint.as_ref(),
-            Regionalized::Regionalized => Some(region),
-        };
-        let endpoint_for_region = resolved_region
+        let endpoint_for_region = self
+            .resolved_region(region)
             .and_then(|region| self.endpoints.get(region))
             .unwrap_or(&self.default_endpoint);
         endpoint_for_region.resolve_endpoint(region)
     }
+
+    /// Resolve the endpoint for `variant`
+    ///
+    /// Resolution mirrors [`resolve_endpoint`](Self::resolve_endpoint) but only considers
+    /// endpoints registered for `variant`. If none match, an [`UnsupportedVariant`] error is
+    /// returned: the non-variant endpoints are never used.
+    fn resolve_variant(
+        &self,
+        region: &Region,
+        variant: EndpointVariant,
+    ) -> Result<AwsEndpoint, BoxError> {
+        let variant_endpoint =
+            |region: &Region| self.variant_endpoints.get(&(region.clone(), variant));
+        let endpoint_for_region = variant_endpoint(region)
+            .or_else(|| self.resolved_region(region).and_then(variant_endpoint))
+            .or_else(|| self.default_variants.get(&variant))
+            .ok_or_else(|| UnsupportedVariant::new(region.clone(), variant))?;
+        endpoint_for_region.resolve_endpoint(region)
+    }
 }
 
 #[cfg(test)]
@@ -169,7 +233,7 @@ mod test {
     use crate::partition::endpoint::SignatureVersion::{self, V4};
     use crate::partition::{endpoint, Partition};
     use crate::partition::{PartitionResolver, Regionalized};
-    use crate::{CredentialScope, ResolveAwsEndpoint};
+    use crate::{AwsEndpoint, CredentialScope, EndpointVariant, ResolveAwsEndpoint};
     use aws_types::region::{Region, SigningRegion};
     use aws_types::SigningService;
     use http::Uri;
@@ -207,6 +271,34 @@ mod test {
                     signature_versions: V4,
                 },
             )
+            .default_variant(
+                EndpointVariant::Fips,
+                Metadata {
+                    uri_template: "service-fips.{region}.amazonaws.com",
+                    protocol: Https,
+                    credential_scope: CredentialScope::default(),
+                    signature_versions: V4,
+                },
+            )
+            .default_variant(
+                EndpointVariant::DualStack,
+                Metadata {
+                    uri_template: "service.{region}.api.aws",
+                    protocol: Https,
+                    credential_scope: CredentialScope::default(),
+                    signature_versions: V4,
+                },
+            )
+            .variant_endpoint(
+                "us-west-1",
+                EndpointVariant::Fips,
+                Metadata {
+                    uri_template: "fips.service.us-west-1.amazonaws.com",
+                    protocol: Https,
+                    credential_scope: CredentialScope::default(),
+                    signature_versions: V4,
+                },
+            )
             .build()
             .expect("valid partition")
     }
@@ -250,6 +342,19 @@ mod test {
                     signature_versions: SignatureVersion::V4,
                 },
             )
+            .variant_endpoint(
+                "partition",
+                EndpointVariant::Fips,
+                Metadata {
+                    uri_template: "some-global-thing-fips.amazonaws.cn",
+                    protocol: Https,
+                    credential_scope: CredentialScope {
+                        region: Some(SigningRegion::from_static("cn-east-1")),
+                        service: Some(SigningService::from_static("foo")),
+                    },
+                    signature_versions: SignatureVersion::V4,
+                },
+            )
             .build()
             .expect("valid partition")
     }
@@ -341,6 +446,44 @@ mod test {
         NON_REGIONALIZED_EXACT_MATCH,
     ];
 
+    /// Modeled region with a variant endpoint override
+    const MODELED_REGION_FIPS: TestCase = TestCase {
+        region: "us-west-1",
+        uri: "https://fips.service.us-west-1.amazonaws.com",
+        signing_region: "us-west-1",
+        signing_service: None,
+    };
+
+    /// Validates falling back onto the default variant endpoint
+    const FALLBACK_REGION_FIPS: TestCase = TestCase {
+        region: "us-east-1",
+        uri: "https://service-fips.us-east-1.amazonaws.com",
+        signing_region: "us-east-1",
+        signing_service: None,
+    };
+
+    const FALLBACK_REGION_DUAL_STACK: TestCase = TestCase {
+        region: "us-west-1",
+        uri: "https://service.us-west-1.api.aws",
+        signing_region: "us-west-1",
+        signing_service: None,
+    };
+
+    /// Validates "PartitionName" for variants
+    const PARTITION_NAME_FIPS: TestCase = TestCase {
+        region: "cn-central-1",
+        uri: "https://some-global-thing-fips.amazonaws.cn",
+        signing_region: "cn-east-1",
+        signing_service: Some("foo"),
+    };
+
+    const VARIANT_TEST_CASES: &[(EndpointVariant, TestCase)] = &[
+        (EndpointVariant::Fips, MODELED_REGION_FIPS),
+        (EndpointVariant::Fips, FALLBACK_REGION_FIPS),
+        (EndpointVariant::DualStack, FALLBACK_REGION_DUAL_STACK),
+        (EndpointVariant::Fips, PARTITION_NAME_FIPS),
+    ];
+
     #[test]
     fn validate_basic_partition() {
         let p10n = basic_partition();
@@ -369,11 +512,60 @@ mod test {
         }
     }
 
+    #[test]
+    fn validate_partition_resolver_variants() {
+        let resolver = partition_resolver();
+        for (variant, test_case) in VARIANT_TEST_CASES {
+            check_variant(&resolver, *variant, test_case);
+        }
+    }
+
+    #[test]
+    fn unsupported_variant_does_not_fall_back() {
+        let resolver = partition_resolver();
+        for (region, variant) in [
+            // no FIPS variant modeled for the partition
+            ("eu-west-1", EndpointVariant::Fips),
+            // the partition endpoint has no dual-stack variant
+            ("cn-central-1", EndpointVariant::DualStack),
+            // FIPS and dual-stack must be supported by the same endpoint
+            ("us-west-1", EndpointVariant::FipsDualStack),
+        ] {
+            let err = resolver
+                .resolve_variant(&Region::new(region), variant)
+                .expect_err("no endpoint for variant");
+            assert_eq!(
+                format!(
+                    "no {} endpoint is available in region `{}`",
+                    variant, region
+                ),
+                err.to_string()
+            );
+        }
+    }
+
+    #[track_caller]
+    fn check_variant(
+        resolver: &impl ResolveAwsEndpoint,
+        variant: EndpointVariant,
+        test_case: &TestCase,
+    ) {
+        let endpoint = resolver
+            .resolve_variant(&Region::new(test_case.region), variant)
+            .expect("valid region");
+        check_resolved(endpoint, test_case);
+    }
+
     #[track_caller]
     fn check_endpoint(resolver: &impl ResolveAwsEndpoint, test_case: &TestCase) {
         let endpoint = resolver
             .resolve_endpoint(&Region::new(test_case.region))
             .expect("valid region");
+        check_resolved(endpoint, test_case);
+    }
+
+    #[track_caller]
+    fn check_resolved(endpoint: AwsEndpoint, test_case: &TestCase) {
         let mut test_uri = Uri::from_static("/");
         endpoint.set_endpoint(&mut test_uri, None);
         assert_eq!(test_uri, Uri::from_static(test_case.uri));
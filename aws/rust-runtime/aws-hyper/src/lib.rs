@@ -10,6 +10,7 @@ use aws_http::auth::CredentialsStage;
 use aws_http::user_agent::UserAgentStage;
 use aws_sig_auth::middleware::SigV4SigningStage;
 use aws_sig_auth::signer::SigV4Signer;
+use aws_smithy_http::interceptor::BeforeSigningStage;
 pub use aws_smithy_http::result::{SdkError, SdkSuccess};
 use aws_smithy_http_tower::map_request::{AsyncMapRequestLayer, MapRequestLayer};
 use std::fmt::Debug;
@@ -19,8 +20,11 @@ use tower::ServiceBuilder;
 type AwsMiddlewareStack = Stack<
     MapRequestLayer<SigV4SigningStage>,
     Stack<
-        AsyncMapRequestLayer<CredentialsStage>,
-        Stack<MapRequestLayer<UserAgentStage>, MapRequestLayer<AwsEndpointStage>>,
+        MapRequestLayer<BeforeSigningStage>,
+        Stack<
+            AsyncMapRequestLayer<CredentialsStage>,
+            Stack<MapRequestLayer<UserAgentStage>, MapRequestLayer<AwsEndpointStage>>,
+        >,
     >,
 >;
 
@@ -32,6 +36,7 @@ impl<S> tower::Layer<S> for AwsMiddleware {
 
     fn layer(&self, inner: S) -> Self::Service {
         let credential_provider = AsyncMapRequestLayer::for_mapper(CredentialsStage::new());
+        let interceptors = MapRequestLayer::for_mapper(BeforeSigningStage::new());
         let signer = MapRequestLayer::for_mapper(SigV4SigningStage::new(SigV4Signer::new()));
         let endpoint_resolver = MapRequestLayer::for_mapper(AwsEndpointStage);
         let user_agent = MapRequestLayer::for_mapper(UserAgentStage::new());
@@ -39,12 +44,14 @@ impl<S> tower::Layer<S> for AwsMiddleware {
         // 1. Resolve an endpoint
         // 2. Add a user agent
         // 3. Acquire credentials
-        // 4. Sign with credentials
-        // (5. Dispatch over the wire)
+        // 4. Run the interceptors that modify requests before signing
+        // 5. Sign with credentials
+        // (6. Dispatch over the wire)
         ServiceBuilder::new()
             .layer(endpoint_resolver)
             .layer(user_agent)
             .layer(credential_provider)
+            .layer(interceptors)
             .layer(signer)
             .service(inner)
     }
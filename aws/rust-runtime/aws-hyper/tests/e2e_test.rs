@@ -3,15 +3,16 @@
  * SPDX-License-Identifier: Apache-2.0.
  */
 
+use std::any::Any;
 use std::convert::Infallible;
 use std::error::Error;
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, UNIX_EPOCH};
 
 use bytes::Bytes;
-use http::header::{AUTHORIZATION, USER_AGENT};
+use http::header::{HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT};
 use http::{self, Uri};
 use tokio::time::Instant;
 
@@ -19,15 +20,23 @@ use aws_endpoint::partition::endpoint::{Protocol, SignatureVersion};
 use aws_endpoint::set_endpoint_resolver;
 use aws_http::user_agent::AwsUserAgent;
 use aws_http::AwsErrorRetryPolicy;
-use aws_hyper::{Client, RetryConfig};
+use aws_hyper::{AwsMiddleware, Client, RetryConfig};
 use aws_sig_auth::signer::OperationSigningConfig;
+use aws_smithy_client::config_override::ConfigOverride;
+use aws_smithy_client::never::NeverService;
+use aws_smithy_client::retry::{AttemptOutcome, AttemptRecorder};
 use aws_smithy_client::test_connection::TestConnection;
 use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::interceptor::{Interceptor, Interceptors};
 use aws_smithy_http::operation;
-use aws_smithy_http::operation::Operation;
+use aws_smithy_http::operation::{Metadata, Operation};
+use aws_smithy_http::property_bag::PropertyBag;
 use aws_smithy_http::response::ParseHttpResponse;
-use aws_smithy_http::result::SdkError;
-use aws_smithy_types::retry::{ErrorKind, ProvideErrorKind};
+use aws_smithy_http::result::{ConnectorError, SdkError};
+use aws_smithy_types::retry::{
+    ErrorKind, ProvideErrorKind, RetryConfigBuilder, RetryKind, RetryMode,
+};
+use aws_smithy_types::timeout::TimeoutConfig;
 use aws_types::credentials::SharedCredentialsProvider;
 use aws_types::region::Region;
 use aws_types::Credentials;
@@ -213,6 +222,314 @@ async fn retry_test() {
     assert_time_passed(initial, Duration::from_secs(7));
 }
 
+#[tokio::test]
+async fn adaptive_retry_test() {
+    fn req() -> http::Request<SdkBody> {
+        http::Request::builder()
+            .body(SdkBody::from("request body"))
+            .unwrap()
+    }
+
+    fn ok() -> http::Response<&'static str> {
+        http::Response::builder()
+            .status(200)
+            .body("response body")
+            .unwrap()
+    }
+
+    fn throttled() -> http::Response<&'static str> {
+        http::Response::builder()
+            .status(429)
+            .body("response body")
+            .unwrap()
+    }
+    // 1 throttled response followed by 1 successful response
+    let events = vec![(req(), throttled()), (req(), ok())];
+    let conn = TestConnection::new(events);
+    let retry_config = RetryConfig::default()
+        .with_retry_mode(RetryMode::Adaptive)
+        .with_base(|| 1_f64);
+    let client = Client::new(conn.clone()).with_retry_config(retry_config);
+    tokio::time::pause();
+    let initial = tokio::time::Instant::now();
+    let resp = client
+        .call(test_operation())
+        .await
+        .expect("successful operation");
+    assert_eq!(resp, "Hello!");
+    assert_eq!(conn.requests().len(), 2);
+    // 1 second of backoff, then the retry waits for the rate limiter's token bucket, which starts
+    // empty and is refilled at the minimum rate of 0.5 requests per second after throttling
+    assert_time_passed(initial, Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn retry_observer_test() {
+    fn req() -> http::Request<SdkBody> {
+        http::Request::builder()
+            .body(SdkBody::from("request body"))
+            .unwrap()
+    }
+
+    fn response(status: u16) -> http::Response<&'static str> {
+        http::Response::builder()
+            .status(status)
+            .body("response body")
+            .unwrap()
+    }
+    let events = vec![
+        // First operation: throttled once, then successful
+        (req(), response(429)),
+        (req(), response(200)),
+        // Second operation: fails every attempt
+        (req(), response(500)),
+        (req(), response(500)),
+    ];
+    let conn = TestConnection::new(events);
+    let recorder = AttemptRecorder::new();
+    let client = aws_smithy_client::Builder::new()
+        .connector(conn.clone())
+        .middleware(AwsMiddleware::default())
+        .retry_observer(recorder.clone())
+        .build()
+        .with_retry_config(
+            RetryConfig::default()
+                .with_max_attempts(2)
+                .with_base(|| 1_f64),
+        );
+    tokio::time::pause();
+    client
+        .call(test_operation().with_metadata(Metadata::new("TestOperation", "test-service")))
+        .await
+        .expect("successful operation");
+    client
+        .call(test_operation())
+        .await
+        .expect_err("all responses failed");
+
+    let attempts = recorder.attempts();
+    let summary = attempts
+        .iter()
+        .map(|attempt| {
+            (
+                attempt
+                    .metadata()
+                    .map(|metadata| metadata.name().to_string()),
+                attempt.number(),
+                attempt.outcome().clone(),
+                attempt.quota_remaining(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let test_operation = || Some("TestOperation".to_string());
+    assert_eq!(
+        summary,
+        vec![
+            (
+                test_operation(),
+                1,
+                AttemptOutcome::Retry(Duration::from_secs(1)),
+                495
+            ),
+            (test_operation(), 2, AttemptOutcome::Success, 495),
+            (None, 1, AttemptOutcome::Retry(Duration::from_secs(1)), 490),
+            (None, 2, AttemptOutcome::MaxAttemptsReached, 490),
+        ]
+    );
+    assert_eq!(
+        attempts[0].retry_kind(),
+        &RetryKind::Error(ErrorKind::ThrottlingError)
+    );
+    assert_eq!(attempts[1].retry_kind(), &RetryKind::NotRetryable);
+    assert_eq!(attempts[0].backoff(), Some(Duration::from_secs(1)));
+    assert!(attempts[3].is_final());
+}
+
+/// Records the hooks it is called for, and tags requests and responses with its name
+#[derive(Debug)]
+struct HookRecorder {
+    name: &'static str,
+    hooks: Arc<Mutex<Vec<String>>>,
+}
+
+impl HookRecorder {
+    fn record(&self, hook: &str) {
+        self.hooks
+            .lock()
+            .unwrap()
+            .push(format!("{}:{}", self.name, hook));
+    }
+}
+
+impl Interceptor for HookRecorder {
+    fn modify_before_signing(&self, request: &mut http::Request<SdkBody>, _: &mut PropertyBag) {
+        self.record("signing");
+        request.headers_mut().insert(
+            HeaderName::from_static(self.name),
+            HeaderValue::from_static("signed"),
+        );
+    }
+
+    fn modify_before_transmit(&self, request: &mut http::Request<SdkBody>, _: &mut PropertyBag) {
+        self.record("transmit");
+        assert!(request.headers().contains_key(AUTHORIZATION));
+    }
+
+    fn modify_after_response_headers(
+        &self,
+        response: &mut http::Response<SdkBody>,
+        _: &mut PropertyBag,
+    ) {
+        self.record(&format!("response {}", response.status().as_u16()));
+    }
+
+    fn modify_after_deserialization(&self, output: &mut dyn Any, _: &mut PropertyBag) {
+        self.record("deserialization");
+        output
+            .downcast_mut::<String>()
+            .expect("output is a string")
+            .push_str(self.name);
+    }
+
+    fn on_error(&self, error: &(dyn Error + 'static), _: &mut PropertyBag) {
+        self.record(&format!("error {}", error));
+    }
+}
+
+#[tokio::test]
+async fn interceptor_test() {
+    fn req() -> http::Request<SdkBody> {
+        http::Request::builder()
+            .body(SdkBody::from("request body"))
+            .unwrap()
+    }
+
+    fn response(status: u16) -> http::Response<&'static str> {
+        http::Response::builder()
+            .status(status)
+            .body("response body")
+            .unwrap()
+    }
+    let conn = TestConnection::new(vec![(req(), response(200)), (req(), response(400))]);
+    let hooks = Arc::new(Mutex::new(vec![]));
+    let client = aws_smithy_client::Builder::new()
+        .connector(conn.clone())
+        .middleware(AwsMiddleware::default())
+        .interceptor(HookRecorder {
+            name: "client",
+            hooks: hooks.clone(),
+        })
+        .build()
+        .with_retry_config(RetryConfig::default().with_max_attempts(1));
+
+    let mut operation = test_operation();
+    operation
+        .properties_mut()
+        .insert(Interceptors::new().with(HookRecorder {
+            name: "call",
+            hooks: hooks.clone(),
+        }));
+    let output = client.call(operation).await.expect("successful operation");
+    assert_eq!(output, "Hello!clientcall");
+    client
+        .call(test_operation())
+        .await
+        .expect_err("the response is an error");
+
+    assert_eq!(
+        hooks.lock().unwrap().as_slice(),
+        &[
+            "client:signing",
+            "call:signing",
+            "client:transmit",
+            "call:transmit",
+            "client:response 200",
+            "call:response 200",
+            "client:deserialization",
+            "call:deserialization",
+            // the interceptor of the first call isn't used by the second call
+            "client:signing",
+            "client:transmit",
+            "client:response 400",
+            "client:error OperationError",
+        ]
+    );
+    // headers added before signing are signed
+    let requests = conn.requests();
+    let authorization = requests[0].actual.headers()[AUTHORIZATION]
+        .to_str()
+        .unwrap();
+    assert!(
+        authorization.contains("SignedHeaders=call;client;host;"),
+        "{}",
+        authorization
+    );
+}
+
+#[tokio::test]
+async fn config_override_test() {
+    fn response(status: u16) -> http::Response<&'static str> {
+        http::Response::builder()
+            .status(status)
+            .body("response body")
+            .unwrap()
+    }
+    let conn = TestConnection::new(vec![(
+        http::Request::new(SdkBody::from("request body")),
+        response(500),
+    )]);
+    // the client retries, but the override disables retries for this call
+    let client = Client::new(conn.clone()).with_retry_config(RetryConfig::default());
+    let mut operation = test_operation();
+    operation.properties_mut().insert(
+        ConfigOverride::new()
+            .with_retry_config(RetryConfigBuilder::new().max_attempts(1))
+            .with_header(
+                HeaderName::from_static("x-custom"),
+                HeaderValue::from_static("override"),
+            ),
+    );
+    client
+        .call(operation)
+        .await
+        .expect_err("the response is an error");
+    let requests = conn.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].actual.headers()["x-custom"], "override");
+    // headers of the override are signed
+    let authorization = requests[0].actual.headers()[AUTHORIZATION]
+        .to_str()
+        .unwrap();
+    assert!(
+        authorization.contains("SignedHeaders=host;x-amz-date;x-amz-user-agent;x-custom,"),
+        "{}",
+        authorization
+    );
+}
+
+#[tokio::test]
+async fn config_override_timeout_test() {
+    tokio::time::pause();
+    let client = Client::new(NeverService::<
+        http::Request<SdkBody>,
+        http::Response<SdkBody>,
+        ConnectorError,
+    >::new());
+    let mut operation = test_operation();
+    operation
+        .properties_mut()
+        .insert(ConfigOverride::new().with_timeout_config(
+            TimeoutConfig::new().with_api_call_timeout(Some(Duration::from_secs(2))),
+        ));
+    let initial = Instant::now();
+    let err = client
+        .call(operation)
+        .await
+        .expect_err("the call times out");
+    assert_eq!(format!("{:?}", err), "ConstructionFailure(TimedOutError)");
+    assert_time_passed(initial, Duration::from_secs(2));
+}
+
 /// Validate that time has passed with a 5ms tolerance
 ///
 /// This is to account for some non-determinism in the Tokio timer
@@ -0,0 +1,355 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Amazon Resource Names (ARNs)
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// An Amazon Resource Name (ARN)
+///
+/// ARNs have the format `arn:partition:service:region:account-id:resource`. The region and
+/// account ID may be empty for resources that are not regional or not owned by an account, e.g.
+/// S3 buckets. The resource is service-specific and may be prefixed by a resource type, e.g.
+/// `role/MyRole` or `accesspoint:my-access-point`.
+///
+/// # Examples
+/// ```rust
+/// use aws_types::arn::Arn;
+///
+/// let arn: Arn = "arn:aws:iam::123456789012:role/MyRole".parse().expect("valid ARN");
+/// assert_eq!("iam", arn.service());
+/// assert_eq!(None, arn.region());
+/// assert_eq!(Some("123456789012"), arn.account_id());
+/// assert_eq!(Some("role"), arn.resource_type());
+/// assert_eq!("MyRole", arn.resource_id());
+///
+/// // ARNs that are known statically can be used in `const` and `static` items
+/// const BUCKET: Arn = Arn::from_static("aws", "s3", "", "", "my-bucket");
+/// assert_eq!("arn:aws:s3:::my-bucket", BUCKET.to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Arn {
+    partition: Cow<'static, str>,
+    service: Cow<'static, str>,
+    region: Cow<'static, str>,
+    account_id: Cow<'static, str>,
+    resource: Cow<'static, str>,
+}
+
+impl Arn {
+    /// Creates an ARN from its components, validating them
+    ///
+    /// `region` and `account_id` may be empty.
+    pub fn new(
+        partition: impl Into<Cow<'static, str>>,
+        service: impl Into<Cow<'static, str>>,
+        region: impl Into<Cow<'static, str>>,
+        account_id: impl Into<Cow<'static, str>>,
+        resource: impl Into<Cow<'static, str>>,
+    ) -> Result<Self, InvalidArn> {
+        let arn = Self {
+            partition: partition.into(),
+            service: service.into(),
+            region: region.into(),
+            account_id: account_id.into(),
+            resource: resource.into(),
+        };
+        arn.validate()?;
+        Ok(arn)
+    }
+
+    /// Const function that creates an ARN from static components
+    ///
+    /// The components are not validated. Use [`Arn::new`] or [`str::parse`] for ARNs that are
+    /// not known statically.
+    pub const fn from_static(
+        partition: &'static str,
+        service: &'static str,
+        region: &'static str,
+        account_id: &'static str,
+        resource: &'static str,
+    ) -> Self {
+        Self {
+            partition: Cow::Borrowed(partition),
+            service: Cow::Borrowed(service),
+            region: Cow::Borrowed(region),
+            account_id: Cow::Borrowed(account_id),
+            resource: Cow::Borrowed(resource),
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvalidArn> {
+        fn is_identifier(value: &str) -> bool {
+            value
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '.')
+        }
+        if self.partition.is_empty() {
+            return Err(InvalidArnKind::Missing("partition").into());
+        }
+        if self.service.is_empty() {
+            return Err(InvalidArnKind::Missing("service").into());
+        }
+        if self.resource.is_empty() {
+            return Err(InvalidArnKind::Missing("resource").into());
+        }
+        for (component, value) in [
+            ("partition", &self.partition),
+            ("service", &self.service),
+            ("region", &self.region),
+            ("account ID", &self.account_id),
+        ] {
+            if !is_identifier(value) {
+                return Err(InvalidArnKind::InvalidComponent(component).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// The partition, e.g. `aws` or `aws-cn`
+    pub fn partition(&self) -> &str {
+        &self.partition
+    }
+
+    /// The service namespace, e.g. `iam` or `s3`
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// The region, if the resource is regional
+    pub fn region(&self) -> Option<&str> {
+        Some(self.region.as_ref()).filter(|region| !region.is_empty())
+    }
+
+    /// The ID of the account that owns the resource, if any
+    pub fn account_id(&self) -> Option<&str> {
+        Some(self.account_id.as_ref()).filter(|account_id| !account_id.is_empty())
+    }
+
+    /// The complete resource, e.g. `role/MyRole`
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    /// The resource type: the part of the resource before the first `/` or `:`, if any
+    ///
+    /// For `role/MyRole`, this is `role`.
+    pub fn resource_type(&self) -> Option<&str> {
+        self.split_resource()
+            .map(|(resource_type, _)| resource_type)
+    }
+
+    /// The resource ID: the part of the resource after the resource type
+    ///
+    /// For `role/MyRole`, this is `MyRole`. If the resource has no type, this is the complete
+    /// resource.
+    pub fn resource_id(&self) -> &str {
+        match self.split_resource() {
+            Some((_, resource_id)) => resource_id,
+            None => &self.resource,
+        }
+    }
+
+    fn split_resource(&self) -> Option<(&str, &str)> {
+        let idx = self.resource.find(['/', ':'])?;
+        Some((&self.resource[..idx], &self.resource[idx + 1..]))
+    }
+}
+
+impl fmt::Display for Arn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arn:{}:{}:{}:{}:{}",
+            self.partition, self.service, self.region, self.account_id, self.resource
+        )
+    }
+}
+
+impl FromStr for Arn {
+    type Err = InvalidArn;
+
+    fn from_str(arn: &str) -> Result<Self, Self::Err> {
+        let mut parts = arn.splitn(6, ':');
+        if parts.next() != Some("arn") {
+            return Err(InvalidArnKind::MissingPrefix.into());
+        }
+        let mut next = |component| {
+            parts
+                .next()
+                .map(|part| part.to_string())
+                .ok_or(InvalidArnKind::Missing(component))
+        };
+        Arn::new(
+            next("partition")?,
+            next("service")?,
+            next("region")?,
+            next("account ID")?,
+            next("resource")?,
+        )
+    }
+}
+
+/// Error for when a string is not a valid ARN
+#[derive(Debug)]
+pub struct InvalidArn {
+    kind: InvalidArnKind,
+}
+
+#[derive(Debug)]
+enum InvalidArnKind {
+    MissingPrefix,
+    Missing(&'static str),
+    InvalidComponent(&'static str),
+}
+
+impl From<InvalidArnKind> for InvalidArn {
+    fn from(kind: InvalidArnKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl fmt::Display for InvalidArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            InvalidArnKind::MissingPrefix => write!(f, "ARNs must start with `arn:`"),
+            InvalidArnKind::Missing(component) => write!(f, "ARN is missing the {}", component),
+            InvalidArnKind::InvalidComponent(component) => {
+                write!(f, "ARN contains an invalid {}", component)
+            }
+        }
+    }
+}
+
+impl Error for InvalidArn {}
+
+#[cfg(test)]
+mod tests {
+    use super::Arn;
+
+    // ARN, partition, service, region, account ID, resource type, resource ID
+    type Case = (
+        &'static str,
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+        Option<&'static str>,
+        Option<&'static str>,
+        &'static str,
+    );
+
+    #[test]
+    fn parse_and_format() {
+        let cases: &[Case] = &[
+            (
+                "arn:aws:iam::123456789012:role/MyRole",
+                "aws",
+                "iam",
+                None,
+                Some("123456789012"),
+                Some("role"),
+                "MyRole",
+            ),
+            (
+                "arn:aws:s3:::my-bucket",
+                "aws",
+                "s3",
+                None,
+                None,
+                None,
+                "my-bucket",
+            ),
+            (
+                "arn:aws-cn:s3:cn-north-1:123456789012:accesspoint:my-access-point",
+                "aws-cn",
+                "s3",
+                Some("cn-north-1"),
+                Some("123456789012"),
+                Some("accesspoint"),
+                "my-access-point",
+            ),
+            (
+                "arn:aws:s3-outposts:us-west-2:123456789012:outpost/op-01234567890123456/accesspoint/reports",
+                "aws",
+                "s3-outposts",
+                Some("us-west-2"),
+                Some("123456789012"),
+                Some("outpost"),
+                "op-01234567890123456/accesspoint/reports",
+            ),
+            (
+                "arn:aws:iam::aws:policy/ReadOnlyAccess",
+                "aws",
+                "iam",
+                None,
+                Some("aws"),
+                Some("policy"),
+                "ReadOnlyAccess",
+            ),
+        ];
+        for (input, partition, service, region, account_id, resource_type, resource_id) in cases {
+            let arn: Arn = input.parse().expect(input);
+            assert_eq!(*partition, arn.partition());
+            assert_eq!(*service, arn.service());
+            assert_eq!(*region, arn.region());
+            assert_eq!(*account_id, arn.account_id());
+            assert_eq!(*resource_type, arn.resource_type());
+            assert_eq!(*resource_id, arn.resource_id());
+            assert_eq!(*input, arn.to_string());
+        }
+    }
+
+    #[test]
+    fn invalid_arns() {
+        let cases = [
+            ("", "ARNs must start with `arn:`"),
+            ("arn:foo", "ARN is missing the service"),
+            (
+                "nra:aws:iam::123456789012:role/MyRole",
+                "ARNs must start with `arn:`",
+            ),
+            ("arn:aws:iam::123456789012", "ARN is missing the resource"),
+            ("arn:aws:iam::123456789012:", "ARN is missing the resource"),
+            (
+                "arn::iam::123456789012:role/MyRole",
+                "ARN is missing the partition",
+            ),
+            (
+                "arn:aws:::123456789012:role/MyRole",
+                "ARN is missing the service",
+            ),
+            (
+                "arn:aws:iam:us east 1:123456789012:role/MyRole",
+                "ARN contains an invalid region",
+            ),
+            (
+                "arn:aws:iam::1234/5678:role/MyRole",
+                "ARN contains an invalid account ID",
+            ),
+        ];
+        for (input, message) in &cases {
+            let err = input.parse::<Arn>().expect_err(input);
+            assert_eq!(*message, err.to_string(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn static_arns() {
+        static ROLE: Arn = Arn::from_static("aws", "iam", "", "123456789012", "role/MyRole");
+        assert_eq!(
+            Ok(ROLE.clone()),
+            "arn:aws:iam::123456789012:role/MyRole"
+                .parse::<Arn>()
+                .map_err(|err| err.to_string())
+        );
+        assert_eq!(
+            ROLE,
+            Arn::new("aws", "iam", "", "123456789012", "role/MyRole").unwrap()
+        );
+    }
+}
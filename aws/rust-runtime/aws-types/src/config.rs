@@ -12,22 +12,29 @@
 use std::sync::Arc;
 
 use aws_smithy_async::rt::sleep::AsyncSleep;
+use aws_smithy_types::pool::PoolConfig;
 use aws_smithy_types::retry::RetryConfig;
 use aws_smithy_types::timeout::TimeoutConfig;
 
 use crate::app_name::AppName;
 use crate::credentials::SharedCredentialsProvider;
 use crate::region::Region;
+use crate::service_config::LoadServiceConfig;
 
 /// AWS Shared Configuration
 #[derive(Debug)]
 pub struct Config {
     app_name: Option<AppName>,
     credentials_provider: Option<SharedCredentialsProvider>,
+    endpoint_url: Option<String>,
+    pool_config: Option<PoolConfig>,
     region: Option<Region>,
     retry_config: Option<RetryConfig>,
+    service_config: Option<Arc<dyn LoadServiceConfig>>,
     sleep_impl: Option<Arc<dyn AsyncSleep>>,
     timeout_config: Option<TimeoutConfig>,
+    use_dual_stack: Option<bool>,
+    use_fips: Option<bool>,
 }
 
 /// Builder for AWS Shared Configuration
@@ -35,10 +42,15 @@ pub struct Config {
 pub struct Builder {
     app_name: Option<AppName>,
     credentials_provider: Option<SharedCredentialsProvider>,
+    endpoint_url: Option<String>,
+    pool_config: Option<PoolConfig>,
     region: Option<Region>,
     retry_config: Option<RetryConfig>,
+    service_config: Option<Arc<dyn LoadServiceConfig>>,
     sleep_impl: Option<Arc<dyn AsyncSleep>>,
     timeout_config: Option<TimeoutConfig>,
+    use_dual_stack: Option<bool>,
+    use_fips: Option<bool>,
 }
 
 impl Builder {
@@ -154,6 +166,48 @@ impl Builder {
         self
     }
 
+    /// Set the [`PoolConfig`] for the builder
+    ///
+    /// The pool config configures the connection pool of the default HTTP connector.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// use aws_types::config::Config;
+    /// use aws_smithy_types::pool::PoolConfig;
+    ///
+    /// let pool_config = PoolConfig::new()
+    ///     .with_idle_timeout(Some(Duration::from_secs(20)));
+    /// let config = Config::builder().pool_config(pool_config).build();
+    /// ```
+    pub fn pool_config(mut self, pool_config: PoolConfig) -> Self {
+        self.set_pool_config(Some(pool_config));
+        self
+    }
+
+    /// Set the [`PoolConfig`] for the builder
+    ///
+    /// # Examples
+    /// ```rust
+    /// use aws_types::config::{Config, Builder};
+    /// use aws_smithy_types::pool::{HttpVersion, PoolConfig};
+    ///
+    /// fn use_http1(builder: &mut Builder) {
+    ///     let pool_config = PoolConfig::new()
+    ///         .with_http_version(Some(HttpVersion::Http1Only));
+    ///     builder.set_pool_config(Some(pool_config));
+    /// }
+    ///
+    /// let mut builder = Config::builder();
+    /// use_http1(&mut builder);
+    /// let config = builder.build();
+    /// ```
+    pub fn set_pool_config(&mut self, pool_config: Option<PoolConfig>) -> &mut Self {
+        self.pool_config = pool_config;
+        self
+    }
+
     #[doc(hidden)]
     /// Set the sleep implementation for the builder. The sleep implementation is used to create
     /// timeout futures.
@@ -283,15 +337,117 @@ impl Builder {
         self
     }
 
+    /// Set the endpoint URL for the builder
+    ///
+    /// When set, service clients send requests to this URL instead of resolving an endpoint from
+    /// the region. This takes precedence over any `endpoint_url` from the
+    /// [service config](Self::service_config).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use aws_types::config::Config;
+    /// let config = Config::builder().endpoint_url("http://localhost:4566").build();
+    /// ```
+    pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.set_endpoint_url(Some(endpoint_url.into()));
+        self
+    }
+
+    /// Set the endpoint URL for the builder
+    ///
+    /// When set, service clients send requests to this URL instead of resolving an endpoint from
+    /// the region. This takes precedence over any `endpoint_url` from the
+    /// [service config](Self::service_config).
+    pub fn set_endpoint_url(&mut self, endpoint_url: Option<String>) -> &mut Self {
+        self.endpoint_url = endpoint_url;
+        self
+    }
+
+    /// Set the source of service-specific configuration for the builder
+    ///
+    /// Service clients query the source for their own configuration, e.g. an `endpoint_url` set
+    /// in a `[services <name>]` section of the shared config file.
+    pub fn service_config(mut self, service_config: impl LoadServiceConfig + 'static) -> Self {
+        self.set_service_config(Some(Arc::new(service_config)));
+        self
+    }
+
+    /// Set the source of service-specific configuration for the builder
+    ///
+    /// Service clients query the source for their own configuration, e.g. an `endpoint_url` set
+    /// in a `[services <name>]` section of the shared config file.
+    pub fn set_service_config(
+        &mut self,
+        service_config: Option<Arc<dyn LoadServiceConfig>>,
+    ) -> &mut Self {
+        self.service_config = service_config;
+        self
+    }
+
+    /// Set whether service clients should only use FIPS endpoints
+    ///
+    /// When enabled, service clients fail to resolve an endpoint for a region that has no FIPS
+    /// endpoint rather than falling back to a non-FIPS endpoint. An explicitly configured
+    /// `endpoint_url` is used as-is.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use aws_types::config::Config;
+    /// let config = Config::builder().use_fips(true).build();
+    /// ```
+    pub fn use_fips(mut self, use_fips: bool) -> Self {
+        self.set_use_fips(Some(use_fips));
+        self
+    }
+
+    /// Set whether service clients should only use FIPS endpoints
+    ///
+    /// When enabled, service clients fail to resolve an endpoint for a region that has no FIPS
+    /// endpoint rather than falling back to a non-FIPS endpoint. An explicitly configured
+    /// `endpoint_url` is used as-is.
+    pub fn set_use_fips(&mut self, use_fips: Option<bool>) -> &mut Self {
+        self.use_fips = use_fips;
+        self
+    }
+
+    /// Set whether service clients should only use dual-stack (IPv4 and IPv6) endpoints
+    ///
+    /// When enabled, service clients fail to resolve an endpoint for a region that has no
+    /// dual-stack endpoint. An explicitly configured `endpoint_url` is used as-is.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use aws_types::config::Config;
+    /// let config = Config::builder().use_dual_stack(true).build();
+    /// ```
+    pub fn use_dual_stack(mut self, use_dual_stack: bool) -> Self {
+        self.set_use_dual_stack(Some(use_dual_stack));
+        self
+    }
+
+    /// Set whether service clients should only use dual-stack (IPv4 and IPv6) endpoints
+    ///
+    /// When enabled, service clients fail to resolve an endpoint for a region that has no
+    /// dual-stack endpoint. An explicitly configured `endpoint_url` is used as-is.
+    pub fn set_use_dual_stack(&mut self, use_dual_stack: Option<bool>) -> &mut Self {
+        self.use_dual_stack = use_dual_stack;
+        self
+    }
+
     /// Build a [`Config`](Config) from this builder
     pub fn build(self) -> Config {
         Config {
             app_name: self.app_name,
             credentials_provider: self.credentials_provider,
+            endpoint_url: self.endpoint_url,
+            pool_config: self.pool_config,
             region: self.region,
             retry_config: self.retry_config,
+            service_config: self.service_config,
             sleep_impl: self.sleep_impl,
             timeout_config: self.timeout_config,
+            use_dual_stack: self.use_dual_stack,
+            use_fips: self.use_fips,
         }
     }
 }
@@ -312,6 +468,11 @@ impl Config {
         self.timeout_config.as_ref()
     }
 
+    /// Configured pool config
+    pub fn pool_config(&self) -> Option<&PoolConfig> {
+        self.pool_config.as_ref()
+    }
+
     #[doc(hidden)]
     /// Configured sleep implementation
     pub fn sleep_impl(&self) -> Option<Arc<dyn AsyncSleep>> {
@@ -328,6 +489,26 @@ impl Config {
         self.app_name.as_ref()
     }
 
+    /// Configured endpoint URL, which takes precedence over the service config
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.endpoint_url.as_deref()
+    }
+
+    /// Configured source of service-specific configuration
+    pub fn service_config(&self) -> Option<&dyn LoadServiceConfig> {
+        self.service_config.as_deref()
+    }
+
+    /// Configured FIPS endpoint setting
+    pub fn use_fips(&self) -> Option<bool> {
+        self.use_fips
+    }
+
+    /// Configured dual-stack endpoint setting
+    pub fn use_dual_stack(&self) -> Option<bool> {
+        self.use_dual_stack
+    }
+
     /// Config builder
     pub fn builder() -> Builder {
         Builder::default()
@@ -14,12 +14,14 @@
 )]
 
 pub mod app_name;
+pub mod arn;
 pub mod build_metadata;
 pub mod config;
 pub mod credentials;
 #[doc(hidden)]
 pub mod os_shim_internal;
 pub mod region;
+pub mod service_config;
 
 pub use credentials::Credentials;
 
@@ -6,6 +6,7 @@
 //! Abstractions for testing code that interacts with the operating system:
 //! - Reading environment variables
 //! - Reading from the file system
+//! - Running external processes
 
 use std::collections::HashMap;
 use std::env::VarError;
@@ -241,6 +242,173 @@ mod env {
     }
 }
 
+/// Process abstraction
+///
+/// Enables running external commands, or returning canned output for them in tests.
+///
+/// # Examples
+/// Construct a process runner that runs commands with the platform shell:
+/// ```rust
+/// let process = aws_types::os_shim_internal::Process::real();
+/// ```
+///
+/// Construct a fake process runner for testing:
+/// ```rust
+/// # async fn example() {
+/// use aws_types::os_shim_internal::{Process, ProcessOutput};
+/// let process = Process::from_slice(&[(
+///     "/usr/bin/my-helper --role admin",
+///     ProcessOutput::new(0, "hello", ""),
+/// )]);
+/// let output = process.run("/usr/bin/my-helper --role admin").await.unwrap();
+/// assert_eq!(output.stdout(), b"hello");
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Process(process::Inner);
+
+impl Default for Process {
+    fn default() -> Self {
+        Self::real()
+    }
+}
+
+impl Process {
+    /// Create a process runner that runs commands with the platform shell
+    ///
+    /// Commands are run with `sh -c` on Unix and `cmd.exe /C` on Windows. Running commands requires
+    /// the `process` feature of this crate and a Tokio runtime.
+    pub fn real() -> Self {
+        Self(process::Inner::Real)
+    }
+
+    /// Create a fake process runner from a slice of commands and their output
+    ///
+    /// Running a command that isn't in `commands` fails with
+    /// [`NotFound`](std::io::ErrorKind::NotFound).
+    pub fn from_slice(commands: &[(&str, ProcessOutput)]) -> Self {
+        let map: HashMap<_, _> = commands
+            .iter()
+            .map(|(command, output)| (command.to_string(), output.clone()))
+            .collect();
+        Self::from(map)
+    }
+
+    /// Create a fake process runner whose commands never exit
+    pub fn never_exits() -> Self {
+        Self(process::Inner::NeverExits)
+    }
+
+    /// Run `command` and wait for it to exit
+    ///
+    /// The standard input of the command is closed, and its standard output and error are
+    /// captured. If the returned future is dropped before the command exits, the command is killed.
+    pub async fn run(&self, command: &str) -> std::io::Result<ProcessOutput> {
+        use process::Inner;
+        match &self.0 {
+            Inner::Real => process::run(command).await,
+            Inner::Fake(commands) => commands
+                .get(command)
+                .cloned()
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into()),
+            Inner::NeverExits => std::future::pending().await,
+        }
+    }
+}
+
+impl From<HashMap<String, ProcessOutput>> for Process {
+    fn from(hash_map: HashMap<String, ProcessOutput>) -> Self {
+        Self(process::Inner::Fake(Arc::new(hash_map)))
+    }
+}
+
+/// Output of a process that has exited
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessOutput {
+    status: Option<i32>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl ProcessOutput {
+    /// Create the output of a process that exited with `status`
+    pub fn new(status: i32, stdout: impl Into<Vec<u8>>, stderr: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: Some(status),
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+        }
+    }
+
+    /// The exit code of the process, or `None` if it was terminated by a signal
+    pub fn status(&self) -> Option<i32> {
+        self.status
+    }
+
+    /// Whether the process exited with a zero exit code
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
+
+    /// What the process wrote to its standard output
+    pub fn stdout(&self) -> &[u8] {
+        &self.stdout
+    }
+
+    /// What the process wrote to its standard error
+    pub fn stderr(&self) -> &[u8] {
+        &self.stderr
+    }
+}
+
+mod process {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::os_shim_internal::ProcessOutput;
+
+    #[derive(Clone, Debug)]
+    pub(super) enum Inner {
+        Real,
+        Fake(Arc<HashMap<String, ProcessOutput>>),
+        NeverExits,
+    }
+
+    #[cfg(feature = "process")]
+    pub(super) async fn run(command: &str) -> std::io::Result<ProcessOutput> {
+        let mut process = if cfg!(windows) {
+            let mut process = tokio::process::Command::new("cmd.exe");
+            process.args(["/C", command]);
+            process
+        } else {
+            let mut process = tokio::process::Command::new("sh");
+            process.args(["-c", command]);
+            process
+        };
+        let output = process
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            // the process is killed if the future waiting for it is dropped, e.g. by a timeout
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        Ok(ProcessOutput {
+            status: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    #[cfg(not(feature = "process"))]
+    pub(super) async fn run(_command: &str) -> std::io::Result<ProcessOutput> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "running processes requires the `process` feature of `aws-types`",
+        ))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimeSource(time_source::Inner);
 
@@ -341,7 +509,7 @@ mod test {
 
     use futures_util::FutureExt;
 
-    use crate::os_shim_internal::{Env, Fs, ManualTimeSource, TimeSource};
+    use crate::os_shim_internal::{Env, Fs, ManualTimeSource, Process, ProcessOutput, TimeSource};
 
     #[test]
     fn env_works() {
@@ -369,6 +537,31 @@ mod test {
             .expect_err("file doesnt exists");
     }
 
+    #[test]
+    fn process_works() {
+        let process = Process::from_slice(&[("helper --arg", ProcessOutput::new(1, "", "failed"))]);
+        let output = process
+            .run("helper --arg")
+            .now_or_never()
+            .expect("future should not poll")
+            .expect("command exists");
+        assert!(!output.success());
+        assert_eq!(output.status(), Some(1));
+        assert_eq!(output.stderr(), b"failed");
+
+        let err = process
+            .run("helper")
+            .now_or_never()
+            .expect("future should not poll")
+            .expect_err("command doesn't exist");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        assert!(Process::never_exits()
+            .run("helper")
+            .now_or_never()
+            .is_none());
+    }
+
     #[test]
     fn ts_works() {
         let real = TimeSource::real();
@@ -0,0 +1,84 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Service-specific configuration
+//!
+//! Some configuration, e.g. `endpoint_url`, may be set differently for each service. Because
+//! [`Config`](crate::config::Config) is shared across services, it carries a [`LoadServiceConfig`]
+//! implementation that service clients query for their own values when they are constructed.
+
+use std::fmt::Debug;
+
+/// A key identifying a service-specific configuration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceConfigKey<'a> {
+    service_id: &'a str,
+    profile: &'a str,
+    env: Option<&'a str>,
+}
+
+impl<'a> ServiceConfigKey<'a> {
+    /// Create a new key
+    ///
+    /// `service_id` is the SDK ID of the service, e.g. `S3` or `Elastic Beanstalk`. `profile` is
+    /// the name of the property in the service's section of the shared config file, e.g. `endpoint_url`.
+    pub fn new(service_id: &'a str, profile: &'a str) -> Self {
+        Self {
+            service_id,
+            profile,
+            env: None,
+        }
+    }
+
+    /// Also load the value from a service-specific environment variable
+    ///
+    /// The name of the environment variable is `env` followed by the service ID in upper case,
+    /// with spaces replaced by underscores, e.g. `AWS_ENDPOINT_URL_ELASTIC_BEANSTALK` for an
+    /// `env` of `AWS_ENDPOINT_URL`. The environment variable takes precedence over the shared config file.
+    ///
+    /// Sources may fall back to the `env` environment variable itself, e.g. `AWS_ENDPOINT_URL`,
+    /// which applies to all services.
+    pub fn with_env(mut self, env: &'a str) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// The SDK ID of the service
+    pub fn service_id(&self) -> &'a str {
+        self.service_id
+    }
+
+    /// The name of the property in the shared config file
+    pub fn profile(&self) -> &'a str {
+        self.profile
+    }
+
+    /// The prefix of the service-specific environment variable, if the value may be set in the environment
+    pub fn env(&self) -> Option<&'a str> {
+        self.env
+    }
+
+    /// The name of the service-specific environment variable, e.g. `AWS_ENDPOINT_URL_S3`
+    pub fn service_env_var(&self) -> Option<String> {
+        self.env.map(|env| {
+            let service_id: String = self
+                .service_id
+                .trim()
+                .chars()
+                .map(|ch| match ch {
+                    ' ' | '-' => '_',
+                    ch => ch.to_ascii_uppercase(),
+                })
+                .collect();
+            format!("{}_{}", env, service_id)
+        })
+    }
+}
+
+/// Source of service-specific configuration
+pub trait LoadServiceConfig: Debug + Send + Sync {
+    /// Load the configuration value identified by `key`, if it was set
+    fn load_config(&self, key: ServiceConfigKey<'_>) -> Option<String>;
+}
@@ -7,6 +7,7 @@ package software.amazon.smithy.rustsdk
 
 import software.amazon.smithy.rust.codegen.smithy.customizations.DocsRsMetadataDecorator
 import software.amazon.smithy.rust.codegen.smithy.customizations.DocsRsMetadataSettings
+import software.amazon.smithy.rust.codegen.smithy.customizations.PoolConfigDecorator
 import software.amazon.smithy.rust.codegen.smithy.customizations.RetryConfigDecorator
 import software.amazon.smithy.rust.codegen.smithy.customizations.SleepImplDecorator
 import software.amazon.smithy.rust.codegen.smithy.customizations.TimeoutConfigDecorator
@@ -37,6 +38,7 @@ val DECORATORS = listOf(
     RetryConfigDecorator(),
     SleepImplDecorator(),
     TimeoutConfigDecorator(),
+    PoolConfigDecorator(),
 
     // Service specific decorators
     DisabledAuthDecorator(),
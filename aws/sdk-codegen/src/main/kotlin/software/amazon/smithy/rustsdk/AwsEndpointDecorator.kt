@@ -72,15 +72,26 @@ class EndpointConfigCustomization(private val codegenContext: CodegenContext, pr
     ConfigCustomization() {
     private val runtimeConfig = codegenContext.runtimeConfig
     private val resolveAwsEndpoint = runtimeConfig.awsEndpointDependency().asType().copy(name = "ResolveAwsEndpoint")
+    private val endpointVariant = runtimeConfig.awsEndpointDependency().asType().member("EndpointVariant")
     override fun section(section: ServiceConfig): Writable = writable {
         when (section) {
             is ServiceConfig.ConfigStruct -> rust(
-                "pub (crate) endpoint_resolver: ::std::sync::Arc<dyn #T>,",
-                resolveAwsEndpoint
+                """
+                pub (crate) endpoint_resolver: ::std::sync::Arc<dyn #T>,
+                pub (crate) endpoint_variant: Option<#T>,
+                """,
+                resolveAwsEndpoint, endpointVariant
             )
             is ServiceConfig.ConfigImpl -> emptySection
             is ServiceConfig.BuilderStruct ->
-                rust("endpoint_resolver: Option<::std::sync::Arc<dyn #T>>,", resolveAwsEndpoint)
+                rust(
+                    """
+                    endpoint_resolver: Option<::std::sync::Arc<dyn #T>>,
+                    use_fips: Option<bool>,
+                    use_dual_stack: Option<bool>,
+                    """,
+                    resolveAwsEndpoint
+                )
             ServiceConfig.BuilderImpl ->
                 rust(
                     """
@@ -90,6 +101,42 @@ class EndpointConfigCustomization(private val codegenContext: CodegenContext, pr
                         self.endpoint_resolver = Some(::std::sync::Arc::new(endpoint_resolver));
                         self
                     }
+
+                    /// Sets whether requests must only be sent to FIPS endpoints.
+                    ///
+                    /// When enabled, requests fail for regions without a FIPS endpoint rather than
+                    /// using a non-FIPS endpoint. A static endpoint set with `endpoint_resolver` is used as-is.
+                    pub fn use_fips(mut self, use_fips: bool) -> Self {
+                        self.set_use_fips(Some(use_fips));
+                        self
+                    }
+
+                    /// Sets whether requests must only be sent to FIPS endpoints.
+                    ///
+                    /// When enabled, requests fail for regions without a FIPS endpoint rather than
+                    /// using a non-FIPS endpoint. A static endpoint set with `endpoint_resolver` is used as-is.
+                    pub fn set_use_fips(&mut self, use_fips: Option<bool>) -> &mut Self {
+                        self.use_fips = use_fips;
+                        self
+                    }
+
+                    /// Sets whether requests must only be sent to dual-stack (IPv4 and IPv6) endpoints.
+                    ///
+                    /// When enabled, requests fail for regions without a dual-stack endpoint. A static
+                    /// endpoint set with `endpoint_resolver` is used as-is.
+                    pub fn use_dual_stack(mut self, use_dual_stack: bool) -> Self {
+                        self.set_use_dual_stack(Some(use_dual_stack));
+                        self
+                    }
+
+                    /// Sets whether requests must only be sent to dual-stack (IPv4 and IPv6) endpoints.
+                    ///
+                    /// When enabled, requests fail for regions without a dual-stack endpoint. A static
+                    /// endpoint set with `endpoint_resolver` is used as-is.
+                    pub fn set_use_dual_stack(&mut self, use_dual_stack: Option<bool>) -> &mut Self {
+                        self.use_dual_stack = use_dual_stack;
+                        self
+                    }
                     """,
                     resolveAwsEndpoint
                 )
@@ -100,8 +147,13 @@ class EndpointConfigCustomization(private val codegenContext: CodegenContext, pr
                     endpoint_resolver: self.endpoint_resolver.unwrap_or_else(||
                         ::std::sync::Arc::new(#T())
                     ),
+                    endpoint_variant: #T::from_settings(
+                        self.use_fips.unwrap_or_default(),
+                        self.use_dual_stack.unwrap_or_default(),
+                    ),
                     """,
                     resolverGenerator.resolver(),
+                    endpointVariant
                 )
             }
         }
@@ -119,7 +171,11 @@ class EndpointResolverFeature(private val runtimeConfig: RuntimeConfig, private
                 rust(
                     """
                     #T::set_endpoint_resolver(&mut ${section.request}.properties_mut(), ${section.config}.endpoint_resolver.clone());
+                    if let Some(variant) = ${section.config}.endpoint_variant {
+                        #T::set_endpoint_variant(&mut ${section.request}.properties_mut(), variant);
+                    }
                     """,
+                    runtimeConfig.awsEndpointDependency().asType(),
                     runtimeConfig.awsEndpointDependency().asType()
                 )
             }
@@ -155,6 +211,7 @@ class EndpointResolverGenerator(codegenContext: CodegenContext, private val endp
             "Protocol" to awsEndpoint.member("partition::endpoint::Protocol"),
             "SignatureVersion" to awsEndpoint.member("partition::endpoint::SignatureVersion"),
             "PartitionResolver" to awsEndpoint.member("PartitionResolver"),
+            "EndpointVariant" to awsEndpoint.member("EndpointVariant"),
             "ResolveAwsEndpoint" to awsEndpoint.member("ResolveAwsEndpoint")
         )
 
@@ -233,6 +290,25 @@ class EndpointResolverGenerator(codegenContext: CodegenContext, private val endp
                 }
             }
         }
+        partition.defaultVariants.forEach { (variant, endpoint) ->
+            withBlockTemplate(".default_variant(#{EndpointVariant}::$variant, ", ")", *codegenScope) {
+                with(endpoint) {
+                    render()
+                }
+            }
+        }
+        partition.variantEndpoints.forEach { (key, endpoint) ->
+            val (region, variant) = key
+            withBlockTemplate(
+                ".variant_endpoint(${region.dq()}, #{EndpointVariant}::$variant, ",
+                ")",
+                *codegenScope
+            ) {
+                with(endpoint) {
+                    render()
+                }
+            }
+        }
         rust(""".build().expect("invalid partition")""")
     }
 
@@ -279,6 +355,8 @@ class EndpointResolverGenerator(codegenContext: CodegenContext, private val endp
         }
     }
 
+    private val legacyFipsRegion = Regex("^fips-(.+)$|^(.+)-fips$")
+
     /**
      * Represents a partition from endpoints.json
      */
@@ -300,22 +378,61 @@ class EndpointResolverGenerator(codegenContext: CodegenContext, private val endp
 
         val endpoints: List<Pair<String, EndpointMeta>>
 
+        // default endpoint variants (e.g. FIPS), keyed by the `EndpointVariant` name
+        val defaultVariants: List<Pair<String, EndpointMeta>>
+
+        private val mergedDefaults: ObjectNode
+
+        private val serviceEndpoints: Map<String, ObjectNode> = service
+            .getObjectMember("endpoints").orElse(Node.objectNode())
+            .members.map { (k, v) -> k.value to v.expectObjectNode() }.toMap()
+
         init {
 
             val partitionDefaults = config.expectObjectMember("defaults")
             val serviceDefaults = service.getObjectMember("defaults").orElse(Node.objectNode())
-            val mergedDefaults = partitionDefaults.merge(serviceDefaults)
-            endpoints = service.getObjectMember("endpoints").orElse(Node.objectNode()).members.mapNotNull { (k, v) ->
-                val endpointObject = mergedDefaults.merge(v.expectObjectNode())
+            mergedDefaults = partitionDefaults.merge(serviceDefaults)
+            endpoints = serviceEndpoints.mapNotNull { (k, v) ->
+                val endpointObject = mergedDefaults.merge(v)
                 // There is no point in generating lots of endpoints that are just empty
                 if (endpointObject != mergedDefaults) {
-                    k.value to EndpointMeta(endpointObject, endpointPrefix, dnsSuffix)
+                    k to EndpointMeta(endpointObject, endpointPrefix, dnsSuffix)
                 } else {
                     null
                 }
             }
 
             defaults = EndpointMeta(mergedDefaults, endpointPrefix, dnsSuffix)
+            defaultVariants = variants(mergedDefaults)
+        }
+
+        /**
+         * Variants of [endpoint], e.g. FIPS or dual-stack.
+         *
+         * Each variant overrides the hostname (and optionally the DNS suffix) of [endpoint]. Variants with tags that
+         * don't map to an `EndpointVariant` are ignored.
+         */
+        private fun variants(endpoint: ObjectNode): List<Pair<String, EndpointMeta>> {
+            val variants = endpoint.getArrayMember("variants").map { it.getElementsAs(Node::expectObjectNode) }
+                .orElse(listOf())
+            return variants.mapNotNull { variant ->
+                variantName(variant)?.let { name ->
+                    val variantDnsSuffix = variant.getStringMember("dnsSuffix").map(StringNode::getValue).orElse(dnsSuffix)
+                    val variantEndpoint = endpoint.withoutMember("variants")
+                        .merge(variant.withoutMember("tags").withoutMember("dnsSuffix"))
+                    name to EndpointMeta(variantEndpoint, endpointPrefix, variantDnsSuffix)
+                }
+            }
+        }
+
+        private fun variantName(variant: ObjectNode): String? {
+            val tags = variant.expectArrayMember("tags").map { it.expectStringNode().value }.toSet()
+            return when (tags) {
+                setOf("fips") -> "Fips"
+                setOf("dualstack") -> "DualStack"
+                setOf("fips", "dualstack") -> "FipsDualStack"
+                else -> null
+            }
         }
 
         val regionalized: Boolean = service.getBooleanMemberOrDefault("isRegionalized", true)
@@ -328,6 +445,26 @@ class EndpointResolverGenerator(codegenContext: CodegenContext, private val endp
         }
 
         val regionRegex: String = config.expectStringMember("regionRegex").value
+
+        // endpoint variants for specific regions, keyed by region and `EndpointVariant` name
+        val variantEndpoints: List<Pair<Pair<String, String>, EndpointMeta>> by lazy {
+            // Legacy FIPS endpoints are modeled as pseudo-regions, e.g. `fips-us-east-1` or `us-east-1-fips`.
+            // For non-regionalized services (e.g. `iam-fips`), they are variants of the partition endpoint.
+            val legacyFipsEndpoints = serviceEndpoints.mapNotNull { (k, v) ->
+                legacyFipsRegion.matchEntire(k)?.let { match ->
+                    val region = match.groupValues.drop(1).first { it.isNotEmpty() }
+                    val variantRegion = if (serviceEndpoints.containsKey(region)) region else partitionEndpoint ?: region
+                    (variantRegion to "Fips") to EndpointMeta(mergedDefaults.merge(v), endpointPrefix, dnsSuffix)
+                }
+            }
+            // endpoints without their own variants use the default variants
+            val modeledVariantEndpoints = serviceEndpoints.filterValues { it.getMember("variants").isPresent }
+                .flatMap { (k, v) ->
+                    variants(mergedDefaults.merge(v)).map { (variant, meta) -> (k to variant) to meta }
+                }
+            // modeled variants take precedence over legacy pseudo-regions
+            (legacyFipsEndpoints + modeledVariantEndpoints).toMap().toList()
+        }
     }
 
     inner class CredentialScope(private val objectNode: ObjectNode) {
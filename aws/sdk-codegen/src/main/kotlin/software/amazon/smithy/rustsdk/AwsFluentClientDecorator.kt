@@ -5,6 +5,7 @@
 
 package software.amazon.smithy.rustsdk
 
+import software.amazon.smithy.aws.traits.ServiceTrait
 import software.amazon.smithy.rust.codegen.rustlang.Attribute
 import software.amazon.smithy.rust.codegen.rustlang.CargoDependency
 import software.amazon.smithy.rust.codegen.rustlang.Feature
@@ -25,6 +26,8 @@ import software.amazon.smithy.rust.codegen.smithy.generators.ClientGenerics
 import software.amazon.smithy.rust.codegen.smithy.generators.FluentClientGenerator
 import software.amazon.smithy.rust.codegen.smithy.generators.LibRsCustomization
 import software.amazon.smithy.rust.codegen.smithy.generators.LibRsSection
+import software.amazon.smithy.rust.codegen.util.dq
+import software.amazon.smithy.rust.codegen.util.expectTrait
 
 private class Types(runtimeConfig: RuntimeConfig) {
     private val smithyClientDep = CargoDependency.SmithyClient(runtimeConfig).copy(optional = true)
@@ -33,6 +36,7 @@ private class Types(runtimeConfig: RuntimeConfig) {
     val awsTypes = awsTypes(runtimeConfig).asType()
     val awsHyper = awsHyperDep.asType()
     val smithyClientRetry = RuntimeType("retry", smithyClientDep, "aws_smithy_client")
+    val smithyClientHyperExt = RuntimeType("hyper_ext", smithyClientDep, "aws_smithy_client")
 
     val AwsMiddleware = RuntimeType("AwsMiddleware", awsHyperDep, "aws_hyper")
     val DynConnector = RuntimeType("DynConnector", smithyClientDep, "aws_smithy_client::erase")
@@ -69,7 +73,8 @@ class AwsFluentClientDecorator : RustCodegenDecorator {
                 ),
                 customizations = listOf(AwsPresignedFluentBuilderMethod(codegenContext.runtimeConfig))
             ).render(writer)
-            AwsFluentClientExtensions(types).render(writer)
+            val serviceId = codegenContext.serviceShape.expectTrait<ServiceTrait>().sdkId.toLowerCase().replace(" ", "")
+            AwsFluentClientExtensions(types, serviceId).render(writer)
         }
         val awsHyper = "aws-hyper"
         rustCrate.mergeFeature(Feature("client", default = true, listOf(awsHyper, "aws-smithy-client")))
@@ -93,18 +98,45 @@ class AwsFluentClientDecorator : RustCodegenDecorator {
     }
 }
 
-private class AwsFluentClientExtensions(private val types: Types) {
+private class AwsFluentClientExtensions(private val types: Types, private val serviceId: String) {
     fun render(writer: RustWriter) {
+        writer.rustTemplate(
+            """
+            /// Retry config for a client, using a retry partition shared by all clients for this service, region and endpoint
+            fn retry_config(conf: &crate::Config) -> #{retry}::Config {
+                let retry_config: #{retry}::Config = conf.retry_config.as_ref().cloned().unwrap_or_default().into();
+                let region = match &conf.region {
+                    Some(region) => region,
+                    None => return retry_config,
+                };
+                let endpoint = match conf.endpoint_variant {
+                    Some(variant) => conf.endpoint_resolver.resolve_variant(region, variant),
+                    None => conf.endpoint_resolver.resolve_endpoint(region),
+                };
+                let partition = match endpoint {
+                    Ok(endpoint) => #{retry}::RetryPartition::for_endpoint(
+                        ${serviceId.dq()},
+                        region.as_ref(),
+                        &endpoint.endpoint().uri().to_string(),
+                    ),
+                    // requests will fail to resolve the endpoint too, they are never retried
+                    Err(_) => #{retry}::RetryPartition::for_service_and_region(${serviceId.dq()}, region.as_ref()),
+                };
+                retry_config.with_retry_partition(partition)
+            }
+            """,
+            "retry" to types.smithyClientRetry,
+        )
         writer.rustBlock("impl<C> Client<C, aws_hyper::AwsMiddleware, aws_smithy_client::retry::Standard>") {
             rustTemplate(
                 """
                 /// Creates a client with the given service config and connector override.
                 pub fn from_conf_conn(conf: crate::Config, conn: C) -> Self {
-                    let retry_config = conf.retry_config.as_ref().cloned().unwrap_or_default();
+                    let retry_config = retry_config(&conf);
                     let timeout_config = conf.timeout_config.as_ref().cloned().unwrap_or_default();
                     let sleep_impl = conf.sleep_impl.clone();
                     let mut client = #{aws_hyper}::Client::new(conn)
-                        .with_retry_config(retry_config.into())
+                        .with_retry_config(retry_config)
                         .with_timeout_config(timeout_config);
 
                     client.set_sleep_impl(sleep_impl);
@@ -126,11 +158,18 @@ private class AwsFluentClientExtensions(private val types: Types) {
                 /// Creates a new client from the service [`Config`](crate::Config).
                 ##[cfg(any(feature = "rustls", feature = "native-tls"))]
                 pub fn from_conf(conf: crate::Config) -> Self {
-                    let retry_config = conf.retry_config.as_ref().cloned().unwrap_or_default();
+                    let retry_config = retry_config(&conf);
                     let timeout_config = conf.timeout_config.as_ref().cloned().unwrap_or_default();
                     let sleep_impl = conf.sleep_impl.clone();
-                    let mut client = #{aws_hyper}::Client::https()
-                        .with_retry_config(retry_config.into())
+                    let pool_config = conf.pool_config.as_ref().cloned().unwrap_or_default();
+                    let mut connector = #{hyper_ext}::Adapter::builder()
+                        .timeout(&(&timeout_config).into())
+                        .pool_config(&pool_config);
+                    if let Some(sleep_impl) = &sleep_impl {
+                        connector = connector.sleep_impl(sleep_impl.clone());
+                    }
+                    let mut client = #{aws_hyper}::Client::new(connector.build_https())
+                        .with_retry_config(retry_config)
                         .with_timeout_config(timeout_config);
 
                     client.set_sleep_impl(sleep_impl);
@@ -138,7 +177,8 @@ private class AwsFluentClientExtensions(private val types: Types) {
                 }
                 """,
                 "aws_hyper" to types.awsHyper,
-                "aws_types" to types.awsTypes
+                "aws_types" to types.awsTypes,
+                "hyper_ext" to types.smithyClientHyperExt,
             )
         }
     }
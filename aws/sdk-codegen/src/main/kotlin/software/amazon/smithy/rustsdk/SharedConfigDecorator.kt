@@ -5,6 +5,8 @@
 
 package software.amazon.smithy.rustsdk
 
+import software.amazon.smithy.aws.traits.ServiceTrait
+import software.amazon.smithy.rust.codegen.rustlang.CargoDependency
 import software.amazon.smithy.rust.codegen.rustlang.RustModule
 import software.amazon.smithy.rust.codegen.rustlang.Writable
 import software.amazon.smithy.rust.codegen.rustlang.asType
@@ -12,16 +14,27 @@ import software.amazon.smithy.rust.codegen.rustlang.rustTemplate
 import software.amazon.smithy.rust.codegen.rustlang.writable
 import software.amazon.smithy.rust.codegen.smithy.CodegenContext
 import software.amazon.smithy.rust.codegen.smithy.RuntimeConfig
+import software.amazon.smithy.rust.codegen.smithy.RuntimeType
 import software.amazon.smithy.rust.codegen.smithy.RustCrate
 import software.amazon.smithy.rust.codegen.smithy.customize.RustCodegenDecorator
 import software.amazon.smithy.rust.codegen.smithy.generators.config.ConfigCustomization
 import software.amazon.smithy.rust.codegen.smithy.generators.config.ServiceConfig
+import software.amazon.smithy.rust.codegen.util.dq
+import software.amazon.smithy.rust.codegen.util.expectTrait
 
 /**
  * Adds functionality for constructing <service>::Config objects from aws_types::config::Config (SharedConfig)
  *
  * - `From<&aws_types::config::Config> for <service>::config::Builder`: Enabling customization
  * - `pub fn new(&aws_types::config::Config) -> <service>::Config`: Direct construction without customization
+ *
+ * Service-specific configuration (e.g. `endpoint_url` from `AWS_ENDPOINT_URL_<SERVICE_ID>` or a `[services <name>]`
+ * section of the shared config file) is loaded from the shared config's `service_config` using the service's SDK ID.
+ * An `endpoint_url` set explicitly on the shared config takes precedence over the service config, which falls back from
+ * service-specific values to `AWS_ENDPOINT_URL` and the profile's `endpoint_url`. Either one replaces the
+ * partition-based endpoint resolver with an immutable endpoint.
+ *
+ * `use_fips` and `use_dual_stack` select the endpoint variant used by the partition-based endpoint resolver.
  */
 class SharedConfigDecorator : RustCodegenDecorator {
     override val name: String = "SharedConfig"
@@ -35,8 +48,14 @@ class SharedConfigDecorator : RustCodegenDecorator {
     }
 
     override fun extras(codegenContext: CodegenContext, rustCrate: RustCrate) {
+        val runtimeConfig = codegenContext.runtimeConfig
+        val sdkId = codegenContext.serviceShape.expectTrait<ServiceTrait>().sdkId
         val codegenScope = arrayOf(
-            "Config" to awsTypes(runtimeConfig = codegenContext.runtimeConfig).asType().member("config::Config")
+            "Config" to awsTypes(runtimeConfig = runtimeConfig).asType().member("config::Config"),
+            "ServiceConfigKey" to awsTypes(runtimeConfig = runtimeConfig).asType().member("service_config::ServiceConfigKey"),
+            "Endpoint" to CargoDependency.SmithyHttp(runtimeConfig).asType().member("endpoint::Endpoint"),
+            "Uri" to RuntimeType.Http("Uri"),
+            "tracing" to CargoDependency.Tracing.asType()
         )
         rustCrate.withModule(RustModule.Config) {
             // TODO(sharedconfig): As more items are added to aws_types::config::Config, use them here to configure the config builder
@@ -48,9 +67,32 @@ class SharedConfigDecorator : RustCodegenDecorator {
                         builder = builder.region(input.region().cloned());
                         builder.set_retry_config(input.retry_config().cloned());
                         builder.set_timeout_config(input.timeout_config().cloned());
+                        builder.set_pool_config(input.pool_config().cloned());
                         builder.set_sleep_impl(input.sleep_impl().clone());
                         builder.set_credentials_provider(input.credentials_provider().cloned());
                         builder.set_app_name(input.app_name().cloned());
+                        builder.set_use_fips(input.use_fips());
+                        builder.set_use_dual_stack(input.use_dual_stack());
+                        let endpoint_url = input
+                            .endpoint_url()
+                            .map(|endpoint_url| endpoint_url.to_string())
+                            .or_else(|| {
+                                input.service_config().and_then(|service_config| {
+                                    service_config.load_config(
+                                        #{ServiceConfigKey}::new(${sdkId.dq()}, "endpoint_url").with_env("AWS_ENDPOINT_URL")
+                                    )
+                                })
+                            });
+                        if let Some(endpoint_url) = endpoint_url {
+                            match endpoint_url.parse::<#{Uri}>() {
+                                Ok(uri) => builder = builder.endpoint_resolver(#{Endpoint}::immutable(uri)),
+                                Err(err) => #{tracing}::warn!(
+                                    endpoint_url = %endpoint_url,
+                                    err = %err,
+                                    "ignoring invalid `endpoint_url`"
+                                ),
+                            }
+                        }
                         builder
                     }
                 }
@@ -170,4 +170,62 @@ internal class EndpointConfigCustomizationTest {
         println("file:///" + project.baseDir + "/src/aws_endpoint.rs")
         project.compileAndTest()
     }
+
+    @Test
+    fun `support fips endpoints`() {
+        val project =
+            stubConfigProject(endpointCustomization("test#TestService"), TestWorkspace.testProject())
+        project.lib {
+            it.addDependency(awsTypes(AwsTestRuntimeConfig))
+            it.addDependency(AwsTestRuntimeConfig.awsEndpointDependency())
+            it.addDependency(CargoDependency.Http)
+            it.unitTest(
+                "fips_endpoints",
+                """
+                use aws_endpoint::EndpointVariant;
+                use aws_types::region::Region;
+                use http::Uri;
+                let conf = crate::config::Config::builder().use_fips(true).build();
+                assert_eq!(conf.endpoint_variant, Some(EndpointVariant::Fips));
+                let endpoint = conf.endpoint_resolver
+                    .resolve_variant(&Region::new("us-west-1"), EndpointVariant::Fips).expect("FIPS endpoint is modeled");
+                let mut uri = Uri::from_static("/?k=v");
+                endpoint.set_endpoint(&mut uri, None);
+                assert_eq!(uri, Uri::from_static("https://access-analyzer-fips.us-west-1.amazonaws.com/?k=v"));
+
+                // never fall back to a non-FIPS endpoint
+                conf.endpoint_resolver
+                    .resolve_variant(&Region::new("us-west-2"), EndpointVariant::Fips).expect_err("no FIPS endpoint is modeled");
+                """
+            )
+        }
+        project.compileAndTest()
+    }
+
+    @Test
+    fun `support fips endpoints for region-agnostic services`() {
+        val project =
+            stubConfigProject(endpointCustomization("test#NoRegions"), TestWorkspace.testProject())
+        project.lib {
+            it.addDependency(awsTypes(AwsTestRuntimeConfig))
+            it.addDependency(AwsTestRuntimeConfig.awsEndpointDependency())
+            it.addDependency(CargoDependency.Http)
+            it.unitTest(
+                "global_fips_endpoints",
+                """
+                use aws_endpoint::EndpointVariant;
+                use aws_types::region::Region;
+                use http::Uri;
+                let conf = crate::config::Config::builder().build();
+                assert_eq!(conf.endpoint_variant, None);
+                let endpoint = conf.endpoint_resolver
+                    .resolve_variant(&Region::new("us-east-1"), EndpointVariant::Fips).expect("FIPS endpoint is modeled");
+                let mut uri = Uri::from_static("/?k=v");
+                endpoint.set_endpoint(&mut uri, None);
+                assert_eq!(uri, Uri::from_static("https://iam-fips.amazonaws.com/?k=v"));
+                """
+            )
+        }
+        project.compileAndTest()
+    }
 }
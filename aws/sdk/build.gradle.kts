@@ -36,6 +36,7 @@ val runtimeModules = listOf(
 val awsModules = listOf(
     "aws-config",
     "aws-endpoint",
+    "aws-endpoint-rules",
     "aws-http",
     "aws-hyper",
     "aws-sig-auth",
@@ -0,0 +1,125 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+package software.amazon.smithy.rust.codegen.smithy.customizations
+
+import software.amazon.smithy.rust.codegen.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.rustlang.writable
+import software.amazon.smithy.rust.codegen.smithy.CodegenContext
+import software.amazon.smithy.rust.codegen.smithy.RuntimeConfig
+import software.amazon.smithy.rust.codegen.smithy.RuntimeType
+import software.amazon.smithy.rust.codegen.smithy.customize.RustCodegenDecorator
+import software.amazon.smithy.rust.codegen.smithy.generators.config.ConfigCustomization
+import software.amazon.smithy.rust.codegen.smithy.generators.config.ServiceConfig
+
+/* Example Generated Code */
+/*
+pub struct Config {
+    pub(crate) pool_config: Option<aws_smithy_types::pool::PoolConfig>,
+}
+#[derive(Default)]
+pub struct Builder {
+    pool_config: Option<aws_smithy_types::pool::PoolConfig>,
+}
+impl Builder {
+    pub fn pool_config(mut self, pool_config: aws_smithy_types::pool::PoolConfig) -> Self {
+        self.set_pool_config(Some(pool_config));
+        self
+    }
+    pub fn set_pool_config(
+        &mut self,
+        pool_config: Option<aws_smithy_types::pool::PoolConfig>,
+    ) -> &mut Self {
+        self.pool_config = pool_config;
+        self
+    }
+    pub fn build(self) -> Config {
+        Config {
+            pool_config: self.pool_config,
+        }
+    }
+}
+ */
+
+class PoolConfigDecorator : RustCodegenDecorator {
+    override val name: String = "PoolConfig"
+    override val order: Byte = 0
+
+    override fun configCustomizations(
+        codegenContext: CodegenContext,
+        baseCustomizations: List<ConfigCustomization>
+    ): List<ConfigCustomization> {
+        return baseCustomizations + PoolConfigProviderConfig(codegenContext)
+    }
+}
+
+class PoolConfigProviderConfig(codegenContext: CodegenContext) : ConfigCustomization() {
+    private val poolConfig = smithyTypesPool(codegenContext.runtimeConfig)
+    private val moduleUseName = codegenContext.moduleUseName()
+    private val codegenScope = arrayOf("PoolConfig" to poolConfig.member("PoolConfig"))
+    override fun section(section: ServiceConfig) = writable {
+        when (section) {
+            is ServiceConfig.ConfigStruct -> rustTemplate(
+                "pub(crate) pool_config: Option<#{PoolConfig}>,",
+                *codegenScope
+            )
+            is ServiceConfig.ConfigImpl -> emptySection
+            is ServiceConfig.BuilderStruct ->
+                rustTemplate("pool_config: Option<#{PoolConfig}>,", *codegenScope)
+            ServiceConfig.BuilderImpl ->
+                rustTemplate(
+                    """
+                    /// Set the pool_config for the builder
+                    ///
+                    /// ## Examples
+                    /// ```rust
+                    /// ## use std::time::Duration;
+                    /// use $moduleUseName::config::Config;
+                    /// use #{PoolConfig};
+                    ///
+                    /// let pool_config = PoolConfig::new()
+                    ///     .with_idle_timeout(Some(Duration::from_secs(20)));
+                    /// let config = Config::builder().pool_config(pool_config).build();
+                    /// ```
+                    pub fn pool_config(mut self, pool_config: #{PoolConfig}) -> Self {
+                        self.set_pool_config(Some(pool_config));
+                        self
+                    }
+
+                    /// Set the pool_config for the builder
+                    ///
+                    /// ## Examples
+                    /// ```rust
+                    /// use $moduleUseName::config::{Builder, Config};
+                    /// use #{PoolConfig};
+                    ///
+                    /// fn limit_idle_connections(builder: &mut Builder) {
+                    ///     let pool_config = PoolConfig::new()
+                    ///         .with_max_idle_per_host(Some(4));
+                    ///     builder.set_pool_config(Some(pool_config));
+                    /// }
+                    ///
+                    /// let mut builder = Config::builder();
+                    /// limit_idle_connections(&mut builder);
+                    /// let config = builder.build();
+                    /// ```
+                    pub fn set_pool_config(&mut self, pool_config: Option<#{PoolConfig}>) -> &mut Self {
+                        self.pool_config = pool_config;
+                        self
+                    }
+                    """,
+                    *codegenScope
+                )
+            ServiceConfig.BuilderBuild -> rustTemplate(
+                """pool_config: self.pool_config,""",
+                *codegenScope
+            )
+        }
+    }
+}
+
+// Generate path to the pool module in aws_smithy_types
+fun smithyTypesPool(runtimeConfig: RuntimeConfig) =
+    RuntimeType("pool", runtimeConfig.runtimeCrate("types"), "aws_smithy_types")
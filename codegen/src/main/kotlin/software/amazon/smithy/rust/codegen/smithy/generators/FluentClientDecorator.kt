@@ -249,10 +249,14 @@ class FluentClientGenerator(
                     ##[derive(std::fmt::Debug)]
                     pub struct ${operationSymbol.name}${generics.decl} {
                         handle: std::sync::Arc<super::Handle${generics.inst}>,
-                        inner: #{Inner}
+                        inner: #{Inner},
+                        interceptors: #{Interceptors},
+                        config_override: Option<#{ConfigOverride}>
                     }
                     """,
                     "Inner" to input.builderSymbol(symbolProvider),
+                    "Interceptors" to CargoDependency.SmithyHttp(runtimeConfig).asType().copy(name = "interceptor::Interceptors"),
+                    "ConfigOverride" to clientDep.asType().copy(name = "config_override::ConfigOverride"),
                     *generics.codegenScope.toTypedArray(),
                     "client" to clientDep.asType(),
                     "operation" to operationSymbol
@@ -266,7 +270,21 @@ class FluentClientGenerator(
                         """
                         /// Creates a new `${operationSymbol.name}`.
                         pub(crate) fn new(handle: std::sync::Arc<super::Handle${generics.inst}>) -> Self {
-                            Self { handle, inner: Default::default() }
+                            Self { handle, inner: Default::default(), interceptors: Default::default(), config_override: None }
+                        }
+
+                        /// Adds an interceptor that is only called for this request.
+                        ///
+                        /// Interceptors added here are called after the interceptors of the client.
+                        pub fn interceptor(mut self, interceptor: impl #{Interceptor} + 'static) -> Self {
+                            self.interceptors.push(std::sync::Arc::new(interceptor));
+                            self
+                        }
+
+                        /// Overrides the timeouts, retry settings and headers of the client for this request.
+                        pub fn config_override(mut self, config_override: #{client}::config_override::ConfigOverride) -> Self {
+                            self.config_override = Some(config_override);
+                            self
                         }
 
                         /// Sends the request and returns the response.
@@ -284,10 +302,22 @@ class FluentClientGenerator(
                             #{operation_err},
                             #{input}OperationRetryAlias>,
                         {
-                            let input = self.inner.build().map_err(|err|#{sdk_err}::ConstructionFailure(err.into()))?;
-                            let op = input.make_operation(&self.handle.conf)
+                            let mut input = self.inner.build().map_err(|err|#{sdk_err}::ConstructionFailure(err.into()))?;
+                            let mut interceptors = self.handle.client.interceptors().clone();
+                            interceptors.append(&self.interceptors);
+                            let mut properties = #{PropertyBag}::new();
+                            interceptors.modify_before_serialization(&mut input, &mut properties);
+                            let mut op = input.make_operation(&self.handle.conf)
                                 .await
                                 .map_err(|err|#{sdk_err}::ConstructionFailure(err.into()))?;
+                            op.properties_mut().extend(properties);
+                            // the client adds its own interceptors when the operation is sent
+                            if !self.interceptors.is_empty() {
+                                op.properties_mut().insert(self.interceptors);
+                            }
+                            if let Some(config_override) = self.config_override {
+                                op.properties_mut().insert(config_override);
+                            }
                             self.handle.client.call(op).await
                         }
                         """,
@@ -296,6 +326,8 @@ class FluentClientGenerator(
                         "operation_err" to operation.errorSymbol(symbolProvider),
                         "sdk_err" to CargoDependency.SmithyHttp(runtimeConfig).asType().copy(name = "result::SdkError"),
                         "client" to clientDep.asType(),
+                        "Interceptor" to CargoDependency.SmithyHttp(runtimeConfig).asType().copy(name = "interceptor::Interceptor"),
+                        "PropertyBag" to CargoDependency.SmithyHttp(runtimeConfig).asType().copy(name = "property_bag::PropertyBag"),
                     )
                     writeCustomizations(
                         customizations,
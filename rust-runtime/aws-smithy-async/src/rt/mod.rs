@@ -6,3 +6,5 @@
 //! Async runtime agnostic traits and implementations.
 
 pub mod sleep;
+pub mod spawn;
+pub mod time;
@@ -0,0 +1,79 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Provides a [`Spawn`] trait that runs a future in the background, and implementations of
+//! `Spawn` for different async runtimes.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Future that can be run in the background by [`Spawn`].
+pub type BackgroundTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Trait with a `spawn` function that runs a future as a detached background task.
+pub trait Spawn: std::fmt::Debug + Send + Sync {
+    /// Runs the given `task` in the background without waiting for it to complete.
+    fn spawn(&self, task: BackgroundTask);
+}
+
+impl<T> Spawn for Box<T>
+where
+    T: Spawn,
+    T: ?Sized,
+{
+    fn spawn(&self, task: BackgroundTask) {
+        T::spawn(self, task)
+    }
+}
+
+impl<T> Spawn for Arc<T>
+where
+    T: Spawn,
+    T: ?Sized,
+{
+    fn spawn(&self, task: BackgroundTask) {
+        T::spawn(self, task)
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+/// Returns a default spawn implementation based on the features enabled
+pub fn default_spawn() -> Option<Arc<dyn Spawn>> {
+    Some(spawn_tokio())
+}
+
+#[cfg(not(feature = "rt-tokio"))]
+pub fn default_spawn() -> Option<Arc<dyn Spawn>> {
+    None
+}
+
+/// Implementation of [`Spawn`] for Tokio.
+///
+/// # Panics
+/// Spawning a task panics if it is not called from within a Tokio runtime.
+#[non_exhaustive]
+#[cfg(feature = "rt-tokio")]
+#[derive(Debug, Default)]
+pub struct TokioSpawn;
+
+#[cfg(feature = "rt-tokio")]
+impl TokioSpawn {
+    pub fn new() -> TokioSpawn {
+        Default::default()
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+impl Spawn for TokioSpawn {
+    fn spawn(&self, task: BackgroundTask) {
+        tokio::spawn(task);
+    }
+}
+
+#[cfg(feature = "rt-tokio")]
+fn spawn_tokio() -> Arc<dyn Spawn> {
+    Arc::new(TokioSpawn::new())
+}
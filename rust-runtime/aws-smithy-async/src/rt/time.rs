@@ -0,0 +1,60 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Provides a [`TimeSource`] trait that reads a monotonic clock, and an implementation of
+//! `TimeSource` for the system clock.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Trait with a `now` function that reads a monotonic clock.
+///
+/// Code that measures elapsed time takes a `TimeSource` so that tests can control the clock.
+pub trait TimeSource: std::fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+impl<T> TimeSource for Box<T>
+where
+    T: TimeSource,
+    T: ?Sized,
+{
+    fn now(&self) -> Instant {
+        T::now(self)
+    }
+}
+
+impl<T> TimeSource for Arc<T>
+where
+    T: TimeSource,
+    T: ?Sized,
+{
+    fn now(&self) -> Instant {
+        T::now(self)
+    }
+}
+
+/// Returns the default time source, which reads the system clock
+pub fn default_time_source() -> Arc<dyn TimeSource> {
+    Arc::new(SystemTimeSource::new())
+}
+
+/// Implementation of [`TimeSource`] that reads the system clock.
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct SystemTimeSource;
+
+impl SystemTimeSource {
+    pub fn new() -> SystemTimeSource {
+        Default::default()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
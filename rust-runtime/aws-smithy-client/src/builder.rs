@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::{bounds, erase, retry, Client};
 use aws_smithy_async::rt::sleep::AsyncSleep;
 use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::interceptor::{Interceptor, Interceptors};
 use aws_smithy_http::result::ConnectorError;
 use aws_smithy_types::timeout::TimeoutConfig;
 
@@ -23,6 +24,8 @@ pub struct Builder<C = (), M = (), R = retry::Standard> {
     retry_policy: R,
     timeout_config: TimeoutConfig,
     sleep_impl: Option<Arc<dyn AsyncSleep>>,
+    retry_observer: Option<Arc<dyn retry::RetryObserver>>,
+    interceptors: Interceptors,
 }
 
 // It'd be nice to include R where R: Default here, but then the caller ends up always having to
@@ -62,6 +65,8 @@ impl<M, R> Builder<(), M, R> {
             middleware: self.middleware,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 
@@ -118,6 +123,8 @@ impl<C, R> Builder<C, (), R> {
             timeout_config: self.timeout_config,
             middleware,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 
@@ -163,6 +170,8 @@ impl<C, M> Builder<C, M, retry::Standard> {
             timeout_config: self.timeout_config,
             middleware: self.middleware,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 }
@@ -185,6 +194,38 @@ impl<C, M> Builder<C, M> {
 }
 
 impl<C, M, R> Builder<C, M, R> {
+    /// Set the [`RetryObserver`](retry::RetryObserver) that is notified of every attempt made by
+    /// the [`Client`].
+    pub fn set_retry_observer(&mut self, retry_observer: Option<Arc<dyn retry::RetryObserver>>) {
+        self.retry_observer = retry_observer;
+    }
+
+    /// Set the [`RetryObserver`](retry::RetryObserver) that is notified of every attempt made by
+    /// the [`Client`].
+    ///
+    /// The observer is passed to the retry policy through each operation's property bag, so it
+    /// works with any operation, including those of generated clients. The [standard retry
+    /// policy](retry::Standard) notifies the observer after each attempt, with the retry decision
+    /// made for it.
+    pub fn retry_observer(mut self, retry_observer: impl retry::RetryObserver + 'static) -> Self {
+        self.set_retry_observer(Some(Arc::new(retry_observer)));
+        self
+    }
+
+    /// Set the [`Interceptor`]s that are called for every request made by the [`Client`].
+    pub fn set_interceptors(&mut self, interceptors: Interceptors) {
+        self.interceptors = interceptors;
+    }
+
+    /// Add an [`Interceptor`] that is called for every request made by the [`Client`].
+    ///
+    /// Interceptors are called in the order they were added, before the interceptors registered
+    /// for a single call.
+    pub fn interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
     /// Use a connector that wraps the current connector.
     pub fn map_connector<F, C2>(self, map: F) -> Builder<C2, M, R>
     where
@@ -196,6 +237,8 @@ impl<C, M, R> Builder<C, M, R> {
             retry_policy: self.retry_policy,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 
@@ -210,6 +253,8 @@ impl<C, M, R> Builder<C, M, R> {
             retry_policy: self.retry_policy,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 
@@ -221,6 +266,8 @@ impl<C, M, R> Builder<C, M, R> {
             middleware: self.middleware,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 }
@@ -0,0 +1,102 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Configuration overrides for a single call
+//!
+//! A [`ConfigOverride`] inserted into the property bag of an operation changes the configuration
+//! of the [`Client`](crate::Client) for that operation only. Settings that aren't set in the
+//! override keep the value configured for the client.
+
+use aws_smithy_types::retry::RetryConfigBuilder;
+use aws_smithy_types::timeout::TimeoutConfig;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Configuration that overrides the configuration of a [`Client`](crate::Client) for a single call
+///
+/// - Timeouts set in the [timeout config](ConfigOverride::with_timeout_config) replace the
+///   timeouts of the client. Only the API call and API call attempt timeouts can be overridden; the
+///   other timeouts are properties of the connector.
+/// - Settings set in the [retry config](ConfigOverride::with_retry_config) replace the settings of
+///   the retry policy of the client, if it supports overrides. The retry mode and the initial retry
+///   tokens are shared by all requests, so they can't be overridden.
+/// - [Headers](ConfigOverride::with_header) are added to the request before it is passed to the
+///   middleware, replacing headers with the same name. They are signed like any other header.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_client::config_override::ConfigOverride;
+/// use aws_smithy_types::retry::RetryConfigBuilder;
+/// use aws_smithy_types::timeout::TimeoutConfig;
+/// use std::time::Duration;
+/// # use aws_smithy_http::body::SdkBody;
+/// # use aws_smithy_http::operation::{Operation, Request};
+/// # let mut operation = Operation::new(Request::new(http::Request::new(SdkBody::empty())), ());
+///
+/// // allow a large download to take longer, and don't retry it
+/// let config_override = ConfigOverride::new()
+///     .with_timeout_config(
+///         TimeoutConfig::new().with_api_call_timeout(Some(Duration::from_secs(300))),
+///     )
+///     .with_retry_config(RetryConfigBuilder::new().max_attempts(1));
+/// operation.properties_mut().insert(config_override);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConfigOverride {
+    timeout_config: TimeoutConfig,
+    retry_config: RetryConfigBuilder,
+    headers: HeaderMap,
+}
+
+impl ConfigOverride {
+    /// Create an override that doesn't change any setting
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the timeouts that are set in `timeout_config`
+    pub fn with_timeout_config(mut self, timeout_config: TimeoutConfig) -> Self {
+        self.set_timeout_config(timeout_config);
+        self
+    }
+
+    /// Override the timeouts that are set in `timeout_config`
+    pub fn set_timeout_config(&mut self, timeout_config: TimeoutConfig) {
+        self.timeout_config = timeout_config;
+    }
+
+    /// Override the retry settings that are set in `retry_config`
+    pub fn with_retry_config(mut self, retry_config: RetryConfigBuilder) -> Self {
+        self.set_retry_config(retry_config);
+        self
+    }
+
+    /// Override the retry settings that are set in `retry_config`
+    pub fn set_retry_config(&mut self, retry_config: RetryConfigBuilder) {
+        self.retry_config = retry_config;
+    }
+
+    /// Add a header to the request
+    ///
+    /// Adding the same header more than once sends all of its values.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// The timeouts to override
+    pub fn timeout_config(&self) -> &TimeoutConfig {
+        &self.timeout_config
+    }
+
+    /// The retry settings to override
+    pub fn retry_config(&self) -> &RetryConfigBuilder {
+        &self.retry_config
+    }
+
+    /// The headers to add to the request
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
@@ -58,6 +58,8 @@ where
             retry_policy: self.retry_policy,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 }
@@ -98,6 +100,8 @@ where
             retry_policy: self.retry_policy,
             timeout_config: self.timeout_config,
             sleep_impl: self.sleep_impl,
+            retry_observer: self.retry_observer,
+            interceptors: self.interceptors,
         }
     }
 
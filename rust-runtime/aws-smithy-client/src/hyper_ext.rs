@@ -21,17 +21,25 @@
 //! let client = Client::<DynConnector, MyMiddleware>::https();
 //! ```
 //!
-//! ### Create a Hyper client with a custom timeout
-//! One common use case for constructing a connector directly is setting `CONNECT` timeouts. Since the
+//! ### Create a Hyper client with a custom timeout and connection pool
+//! One common use case for constructing a connector directly is setting `CONNECT` timeouts or tuning the
+//! connection pool. Since the
 //! internal connector is cheap to clone, you can also use this to share a connector between multiple services.
 //! ```rust
 //! use std::time::Duration;
 //! use aws_smithy_client::{Client, conns, hyper_ext};
 //! use aws_smithy_client::erase::DynConnector;
 //! use aws_smithy_client::timeout::Settings;
+//! use aws_smithy_types::pool::PoolConfig;
 //!
-//! let timeout = Settings::new().with_connect_timeout(Duration::from_secs(1));
-//! let connector = hyper_ext::Adapter::builder().timeout(&timeout).build(conns::https());
+//! let timeout = Settings::new()
+//!     .with_connect_timeout(Duration::from_secs(1))
+//!     .with_tls_negotiation_timeout(Duration::from_secs(1));
+//! let pool_config = PoolConfig::new().with_idle_timeout(Some(Duration::from_secs(20)));
+//! let connector = hyper_ext::Adapter::builder()
+//!     .timeout(&timeout)
+//!     .pool_config(&pool_config)
+//!     .build(conns::https());
 //! // TODO: replace this with your middleware
 //! type MyMiddleware = tower::layer::util::Identity;
 //! // once you have a connector, use it to construct a Smithy client:
@@ -51,19 +59,30 @@ use aws_smithy_async::rt::sleep::{default_async_sleep, AsyncSleep};
 use aws_smithy_http::body::SdkBody;
 use aws_smithy_http::result::ConnectorError;
 pub use aws_smithy_http::result::{SdkError, SdkSuccess};
+use aws_smithy_types::pool::{HttpVersion, PoolConfig};
 use aws_smithy_types::retry::ErrorKind;
 
+use crate::proxy::{ProxyConfig, TunnelError};
+use crate::stalled_stream::{self, StalledStreamProtection};
 use crate::{timeout, Builder as ClientBuilder};
 
 use self::timeout_middleware::{ConnectTimeout, HttpReadTimeout, TimeoutError};
 
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+mod tls;
+
 /// Adapter from a [`hyper::Client`](hyper::Client) to a connector usable by a Smithy [`Client`](crate::Client).
 ///
-/// This adapter also enables TCP `CONNECT` and HTTP `READ` timeouts via [`Adapter::builder`]. For examples
+/// This adapter also enables TCP `CONNECT` and HTTP `READ` timeouts, and protection against stalled
+/// response bodies, via [`Adapter::builder`]. For examples
 /// see [the module documentation](crate::hyper_ext).
 #[derive(Clone, Debug)]
 #[non_exhaustive]
-pub struct Adapter<C>(HttpReadTimeout<hyper::Client<ConnectTimeout<C>, SdkBody>>);
+pub struct Adapter<C>(
+    HttpReadTimeout<hyper::Client<ConnectTimeout<C>, SdkBody>>,
+    Option<(StalledStreamProtection, Arc<dyn AsyncSleep>)>,
+    Option<Arc<ProxyConfig>>,
+);
 
 impl<C> Service<http::Request<SdkBody>> for Adapter<C>
 where
@@ -88,9 +107,27 @@ where
         self.0.poll_ready(cx).map_err(downcast_error)
     }
 
-    fn call(&mut self, req: http::Request<SdkBody>) -> Self::Future {
+    fn call(&mut self, mut req: http::Request<SdkBody>) -> Self::Future {
+        if let Some(authorization) = self
+            .2
+            .as_ref()
+            .and_then(|proxy_config| proxy_config.proxy_authorization(req.uri()))
+        {
+            req.headers_mut()
+                .entry(http::header::PROXY_AUTHORIZATION)
+                .or_insert(authorization);
+        }
         let fut = self.0.call(req);
-        Box::pin(async move { Ok(fut.await.map_err(downcast_error)?.map(SdkBody::from)) })
+        let stalled_stream_protection = self.1.clone();
+        Box::pin(async move {
+            let response = fut.await.map_err(downcast_error)?.map(SdkBody::from);
+            Ok(match stalled_stream_protection {
+                Some((protection, sleep)) => {
+                    response.map(|body| stalled_stream::protect(body, protection, sleep))
+                }
+                None => response,
+            })
+        })
     }
 }
 
@@ -130,9 +167,17 @@ fn downcast_error(err: BoxError) -> ConnectorError {
 fn to_connector_error(err: hyper::Error) -> ConnectorError {
     if err.is_timeout() || find_source::<TimeoutError>(&err).is_some() {
         ConnectorError::timeout(err.into())
-    } else if err.is_user() {
+    } else if err.is_user()
+        || matches!(
+            find_source::<TunnelError>(&err),
+            Some(TunnelError::Rejected { status: 407 })
+        )
+    {
         ConnectorError::user(err.into())
-    } else if err.is_closed() || err.is_canceled() || find_source::<std::io::Error>(&err).is_some()
+    } else if err.is_closed()
+        || err.is_canceled()
+        || find_source::<std::io::Error>(&err).is_some()
+        || find_source::<TunnelError>(&err).is_some()
     {
         ConnectorError::io(err.into())
     }
@@ -179,6 +224,9 @@ fn find_source<'a, E: Error + 'static>(err: &'a (dyn Error + 'static)) -> Option
 /// ```
 pub struct Builder {
     timeout: timeout::Settings,
+    pool_config: PoolConfig,
+    stalled_stream_protection: Option<StalledStreamProtection>,
+    proxy_config: Option<ProxyConfig>,
     sleep: Option<Arc<dyn AsyncSleep>>,
     client_builder: hyper::client::Builder,
 }
@@ -193,19 +241,43 @@ impl Builder {
         C::Future: Unpin + Send + 'static,
         C::Error: Into<BoxError>,
     {
-        // if we are using Hyper, Tokio must already be enabled so we can fallback to Tokio.
-        let sleep = self.sleep.or_else(default_async_sleep);
-        let connector = match self.timeout.connect() {
+        let connector = self.connect_timeout(connector);
+        self.build_adapter(connector)
+    }
+
+    /// Wrap `connector` with the connect timeout, if there is one
+    fn connect_timeout<C>(&self, connector: C) -> ConnectTimeout<C> {
+        match self.timeout.connect() {
             Some(duration) => ConnectTimeout::new(
                 connector,
-                sleep
-                    .clone()
+                self.sleep()
                     .expect("a sleep impl must be provided to use timeouts"),
                 duration,
             ),
             None => ConnectTimeout::no_timeout(connector),
-        };
-        let base = self.client_builder.build(connector);
+        }
+    }
+
+    fn build_adapter<C>(self, connector: ConnectTimeout<C>) -> Adapter<C>
+    where
+        C: Clone + Send + Sync + 'static,
+        C: tower::Service<Uri>,
+        C::Response: Connection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        C::Future: Unpin + Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        let sleep = self.sleep();
+        let mut client_builder = self.client_builder;
+        if let Some(idle_timeout) = self.pool_config.idle_timeout() {
+            client_builder.pool_idle_timeout(idle_timeout);
+        }
+        if let Some(max_idle_per_host) = self.pool_config.max_idle_per_host() {
+            client_builder.pool_max_idle_per_host(max_idle_per_host);
+        }
+        if self.pool_config.http_version() == Some(HttpVersion::Http2Only) {
+            client_builder.http2_only(true);
+        }
+        let base = client_builder.build(connector);
         let http_timeout = match self.timeout.read() {
             Some(duration) => HttpReadTimeout::new(
                 base,
@@ -216,7 +288,120 @@ impl Builder {
             ),
             None => HttpReadTimeout::no_timeout(base),
         };
-        Adapter(http_timeout)
+        let stalled_stream_protection = self.stalled_stream_protection.map(|protection| {
+            (
+                protection,
+                sleep.expect("a sleep impl must be provided to use stalled stream protection"),
+            )
+        });
+        Adapter(
+            http_timeout,
+            stalled_stream_protection,
+            self.proxy_config.map(Arc::new),
+        )
+    }
+
+    /// Create a HyperAdapter from this builder that connects over HTTPS
+    ///
+    /// The connector uses Rustls when the `rustls` feature is enabled, and the native TLS library of
+    /// the platform otherwise. It is type-erased with [`DynConnector`](crate::erase::DynConnector)
+    /// so that its type doesn't depend on the enabled features.
+    ///
+    /// The connect timeout applies to the TCP connection, and the TLS negotiation timeout to the
+    /// TLS handshake that follows it. With Rustls, only the HTTP versions allowed by the
+    /// [pool config](Builder::pool_config) are offered with ALPN. The native TLS connector doesn't
+    /// use ALPN, so it never negotiates HTTP/2.
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn build_https(self) -> crate::erase::DynConnector {
+        let mut connector = hyper::client::HttpConnector::new();
+        connector.enforce_http(false);
+        connector.set_connect_timeout(self.timeout.connect());
+        self.build_tls(connector)
+    }
+
+    /// Create a HyperAdapter from this builder that negotiates TLS over the connections opened by
+    /// `connector`
+    ///
+    /// `connector` must accept `https` URIs and return unencrypted connections, like the
+    /// [proxy connector](crate::conns::http_with_proxy). The connect timeout applies to `connector`.
+    /// Otherwise, this is the same as [`build_https`](Builder::build_https).
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    pub fn build_https_with_connector<C>(self, connector: C) -> crate::erase::DynConnector
+    where
+        C: Clone + Send + Sync + 'static,
+        C: tower::Service<Uri>,
+        C::Response: Connection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        let connector = self.connect_timeout(connector);
+        self.build_tls(connector)
+    }
+
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    fn build_tls<C>(self, connector: C) -> crate::erase::DynConnector
+    where
+        C: Clone + Send + Sync + 'static,
+        C: tower::Service<Uri>,
+        C::Response: Connection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        #[cfg(feature = "rustls")]
+        let adapter = self.build_rustls(connector, &crate::conns::TLS_CONFIG_NATIVE_ROOTS);
+        // If we are compiling this function & rustls is not enabled, then native-tls MUST be enabled
+        #[cfg(not(feature = "rustls"))]
+        let adapter = {
+            let tls = tokio_native_tls::native_tls::TlsConnector::new()
+                .expect("failed to create a native TLS connector");
+            let tls_timeout = self.tls_negotiation_timeout();
+            let tls = tokio_native_tls::TlsConnector::from(tls);
+            let connector = tls::HttpsConnector::new(connector, tls, tls_timeout);
+            self.build_adapter(ConnectTimeout::no_timeout(connector))
+        };
+        crate::erase::DynConnector::new(adapter)
+    }
+
+    /// Create a HyperAdapter that negotiates TLS with Rustls, using a copy of `tls_config` that
+    /// offers the HTTP versions allowed by the pool config
+    #[cfg(feature = "rustls")]
+    fn build_rustls<C>(
+        self,
+        connector: C,
+        tls_config: &tokio_rustls::rustls::ClientConfig,
+    ) -> Adapter<tls::HttpsConnector<C, Arc<tokio_rustls::rustls::ClientConfig>>>
+    where
+        C: Clone + Send + Sync + 'static,
+        C: tower::Service<Uri>,
+        C::Response: Connection + AsyncRead + AsyncWrite + Send + Unpin + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<BoxError>,
+    {
+        let mut tls_config = tls_config.clone();
+        tls_config.alpn_protocols = match self.pool_config.http_version() {
+            Some(HttpVersion::Http1Only) => vec![b"http/1.1".to_vec()],
+            Some(HttpVersion::Http2Only) => vec![b"h2".to_vec()],
+            _ => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        };
+        let tls_timeout = self.tls_negotiation_timeout();
+        let connector = tls::HttpsConnector::new(connector, Arc::new(tls_config), tls_timeout);
+        self.build_adapter(ConnectTimeout::no_timeout(connector))
+    }
+
+    #[cfg(any(feature = "rustls", feature = "native-tls"))]
+    fn tls_negotiation_timeout(&self) -> Option<(Arc<dyn AsyncSleep>, std::time::Duration)> {
+        self.timeout.tls_negotiation().map(|duration| {
+            (
+                self.sleep()
+                    .expect("a sleep impl must be provided to use timeouts"),
+                duration,
+            )
+        })
+    }
+
+    fn sleep(&self) -> Option<Arc<dyn AsyncSleep>> {
+        // if we are using Hyper, Tokio must already be enabled so we can fallback to Tokio.
+        self.sleep.clone().or_else(default_async_sleep)
     }
 
     /// Set the async sleep implementation used for timeouts
@@ -232,7 +417,10 @@ impl Builder {
 
     /// Configure the timeout for the HyperAdapter
     ///
-    /// When unset, the underlying adaptor will not use any timeouts.
+    /// When unset, the underlying adaptor will not use any timeouts. The TLS negotiation timeout is
+    /// only used by the connectors that [`build_https`](Builder::build_https) creates. A connector
+    /// passed to [`build`](Builder::build) negotiates TLS as part of the connection, so the connect
+    /// timeout covers both steps.
     pub fn timeout(self, timeout_config: &timeout::Settings) -> Self {
         Self {
             timeout: timeout_config.clone(),
@@ -240,6 +428,43 @@ impl Builder {
         }
     }
 
+    /// Configure the connection pool for the HyperAdapter
+    ///
+    /// Settings that aren't set in `pool_config` keep the defaults of the Hyper client. Connectors
+    /// created by [`build_https`](Builder::build_https) only offer the allowed HTTP versions with
+    /// ALPN. A connector passed to [`build`](Builder::build) chooses the HTTP version itself: Hyper
+    /// uses HTTP/2 on connections that negotiated it, even if the pool is restricted to HTTP/1.1.
+    pub fn pool_config(self, pool_config: &PoolConfig) -> Self {
+        Self {
+            pool_config: pool_config.clone(),
+            ..self
+        }
+    }
+
+    /// Fail response bodies that deliver less than a minimum throughput
+    ///
+    /// When unset, response bodies may stall forever once the response headers have been received.
+    /// See [`stalled_stream`](crate::stalled_stream) for details.
+    pub fn stalled_stream_protection(self, protection: StalledStreamProtection) -> Self {
+        Self {
+            stalled_stream_protection: Some(protection),
+            ..self
+        }
+    }
+
+    /// Add the `Proxy-Authorization` header of the proxies of `proxy_config` to `http` requests
+    ///
+    /// A [`ProxyConnector`](crate::proxy::ProxyConnector) sends `http` requests to the proxy in
+    /// absolute form instead of tunneling them, so the proxy credentials must be sent with every
+    /// request. Set this to the configuration of the proxy connector when its `http` proxy requires
+    /// authentication.
+    pub fn proxy_config(self, proxy_config: ProxyConfig) -> Self {
+        Self {
+            proxy_config: Some(proxy_config),
+            ..self
+        }
+    }
+
     /// Override the Hyper client [`Builder`](hyper::client::Builder) used to construct this client.
     ///
     /// This enables changing settings like forcing HTTP2 and modifying other default client behavior.
@@ -328,6 +553,16 @@ mod timeout_middleware {
         cause: TimedOutError,
     }
 
+    impl TimeoutError {
+        pub(crate) fn new(operation: &'static str, duration: Duration) -> Self {
+            Self {
+                operation,
+                duration,
+                cause: TimedOutError,
+            }
+        }
+    }
+
     impl std::fmt::Display for TimeoutError {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             write!(f, "timed out after {:?}", self.duration)
@@ -578,23 +813,75 @@ mod timeout_middleware {
             assert!(resp.is_timeout(), "{:?}", resp);
             assert_elapsed!(now, Duration::from_secs(2));
         }
+
+        #[cfg(feature = "rustls")]
+        #[tokio::test]
+        async fn tls_negotiation_timeout_works() {
+            // accept TCP connections right away, but never respond to the TLS handshake
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tokio::spawn(async move {
+                let mut connections = Vec::new();
+                loop {
+                    let (connection, _) = listener.accept().await.unwrap();
+                    connections.push(connection);
+                }
+            });
+            let timeout = timeout::Settings::new()
+                .with_connect_timeout(Duration::from_secs(1))
+                .with_tls_negotiation_timeout(Duration::from_millis(200));
+            let mut hyper = Adapter::builder()
+                .timeout(&timeout)
+                .sleep_impl(TokioSleep::new())
+                .build_https();
+            let now = std::time::Instant::now();
+            let resp = hyper
+                .call(
+                    http::Request::builder()
+                        .uri(format!("https://localhost:{}/", port))
+                        .body(SdkBody::empty())
+                        .unwrap(),
+                )
+                .await
+                .expect_err("timeout");
+            assert!(resp.is_timeout(), "{:?}", resp);
+            assert_eq!(
+                format!("{}", resp),
+                "timeout: error trying to connect: timed out after 200ms"
+            );
+            let elapsed = now.elapsed();
+            // the TLS negotiation timeout doesn't include the connect timeout
+            assert!(
+                elapsed >= Duration::from_millis(200) && elapsed < Duration::from_secs(1),
+                "{:?}",
+                elapsed
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::future::Future;
     use std::io::{Error, ErrorKind};
     use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::task::{Context, Poll};
+    use std::time::Duration;
 
     use http::Uri;
     use hyper::client::connect::{Connected, Connection};
-    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf};
     use tower::BoxError;
 
+    use aws_smithy_async::assert_elapsed;
+    use aws_smithy_async::rt::sleep::TokioSleep;
     use aws_smithy_http::body::SdkBody;
+    use aws_smithy_types::pool::{HttpVersion, PoolConfig};
 
     use crate::hyper_ext::Adapter;
+    use crate::stalled_stream::{StalledStreamError, StalledStreamProtection};
 
     #[tokio::test]
     async fn hyper_io_error() {
@@ -615,6 +902,277 @@ mod test {
         assert!(err.is_io(), "{:?}", err);
     }
 
+    #[tokio::test]
+    async fn stalled_response_body() {
+        let mut adapter = Adapter::builder()
+            .stalled_stream_protection(StalledStreamProtection::new(100, Duration::from_secs(1)))
+            .sleep_impl(TokioSleep::new())
+            .build(StallingStream::connector());
+        use tower::Service;
+        tokio::time::pause();
+        let response = adapter
+            .call(
+                http::Request::builder()
+                    .uri("http://amazon.com")
+                    .body(SdkBody::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("the response headers are received");
+        let now = tokio::time::Instant::now();
+        let err = hyper::body::to_bytes(response.into_body())
+            .await
+            .expect_err("the body stalls");
+        let err = err
+            .downcast_ref::<StalledStreamError>()
+            .expect("a stalled stream error");
+        assert_eq!(err.bytes_received(), 10);
+        assert_elapsed!(now, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn pool_max_idle_per_host() {
+        async fn connections(pool_config: &PoolConfig) -> usize {
+            let connections = Arc::new(AtomicUsize::new(0));
+            let mut adapter = Adapter::builder()
+                .pool_config(pool_config)
+                .build(RespondingStream::connector(connections.clone()));
+            use tower::Service;
+            for _ in 0..2 {
+                let response = adapter
+                    .call(
+                        http::Request::builder()
+                            .uri("http://amazon.com")
+                            .body(SdkBody::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .expect("success");
+                hyper::body::to_bytes(response.into_body()).await.unwrap();
+            }
+            connections.load(Ordering::SeqCst)
+        }
+
+        assert_eq!(connections(&PoolConfig::new()).await, 1);
+        assert_eq!(
+            connections(&PoolConfig::new().with_max_idle_per_host(Some(0))).await,
+            2
+        );
+    }
+
+    #[cfg(feature = "rustls")]
+    #[tokio::test]
+    async fn http1_only_is_offered_with_alpn() {
+        use tokio_rustls::rustls::internal::pemfile::{certs, pkcs8_private_keys};
+        use tokio_rustls::rustls::{ClientConfig, NoClientAuth, ServerConfig, Session};
+
+        /// Send a request to a TLS server that offers HTTP/2 and HTTP/1.1, and return the protocol
+        /// that was negotiated
+        async fn negotiated_protocol(pool_config: &PoolConfig) -> Option<Vec<u8>> {
+            let mut server_config = ServerConfig::new(NoClientAuth::new());
+            let cert = certs(&mut &include_bytes!("../test-data/tls/localhost.pem")[..]).unwrap();
+            let key =
+                pkcs8_private_keys(&mut &include_bytes!("../test-data/tls/localhost.key")[..])
+                    .unwrap()
+                    .remove(0);
+            server_config.set_single_cert(cert, key).unwrap();
+            server_config.set_protocols(&[b"h2".to_vec(), b"http/1.1".to_vec()]);
+            let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = acceptor.accept(stream).await.unwrap();
+                let protocol = stream.get_ref().1.get_alpn_protocol().map(|p| p.to_vec());
+                // only HTTP/1.1 is answered; an HTTP/2 client sees the connection close
+                if protocol.as_deref() == Some(b"http/1.1") {
+                    let mut request = [0; 1024];
+                    let _ = stream.read(&mut request).await.unwrap();
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                        .await
+                        .unwrap();
+                    stream.flush().await.unwrap();
+                }
+                protocol
+            });
+
+            let mut client_config = ClientConfig::new();
+            client_config
+                .root_store
+                .add_pem_file(&mut &include_bytes!("../test-data/tls/ca.pem")[..])
+                .unwrap();
+            let mut connector = hyper::client::HttpConnector::new();
+            connector.enforce_http(false);
+            let mut adapter = Adapter::builder()
+                .pool_config(pool_config)
+                .build_rustls(connector, &client_config);
+            use tower::Service;
+            let result = adapter
+                .call(
+                    http::Request::builder()
+                        .uri(format!("https://localhost:{}/", port))
+                        .body(SdkBody::empty())
+                        .unwrap(),
+                )
+                .await;
+            let protocol = server.await.unwrap();
+            if protocol.as_deref() == Some(b"http/1.1") {
+                assert_eq!(result.expect("success").status(), 200);
+            }
+            protocol
+        }
+
+        assert_eq!(
+            negotiated_protocol(&PoolConfig::new()).await.as_deref(),
+            Some(&b"h2"[..])
+        );
+        assert_eq!(
+            negotiated_protocol(&PoolConfig::new().with_http_version(Some(HttpVersion::Http1Only)))
+                .await
+                .as_deref(),
+            Some(&b"http/1.1"[..])
+        );
+    }
+
+    // ---- machinery to make a Hyper connector that responds to every request with an empty response
+    struct RespondingStream(DuplexStream);
+
+    impl RespondingStream {
+        fn connector(
+            connections: Arc<AtomicUsize>,
+        ) -> impl tower::Service<
+            Uri,
+            Response = RespondingStream,
+            Error = BoxError,
+            Future = impl Future<Output = Result<RespondingStream, BoxError>> + Send + Unpin,
+        > + Clone
+               + Send
+               + Sync {
+            tower::service_fn(move |_uri: Uri| {
+                connections.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    let (client, mut server) = tokio::io::duplex(1024);
+                    tokio::spawn(async move {
+                        let mut request = [0; 1024];
+                        while server.read(&mut request).await.unwrap_or(0) > 0 {
+                            server
+                                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                                .await
+                                .unwrap();
+                        }
+                    });
+                    Ok::<_, BoxError>(RespondingStream(client))
+                })
+            })
+        }
+    }
+
+    impl Connection for RespondingStream {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    impl AsyncRead for RespondingStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for RespondingStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Error>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Error>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+
+    // ---- machinery to make a Hyper connector that sends part of a response, then stalls
+    struct StallingStream(DuplexStream);
+
+    impl StallingStream {
+        fn connector() -> impl tower::Service<
+            Uri,
+            Response = StallingStream,
+            Error = BoxError,
+            Future = impl Future<Output = Result<StallingStream, BoxError>> + Send + Unpin,
+        > + Clone
+               + Send
+               + Sync {
+            tower::service_fn(|_uri: Uri| {
+                Box::pin(async {
+                    let (client, mut server) = tokio::io::duplex(1024);
+                    tokio::spawn(async move {
+                        let mut request = [0; 1024];
+                        let _ = server.read(&mut request).await.unwrap();
+                        server
+                            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 1000\r\n\r\n0123456789")
+                            .await
+                            .unwrap();
+                        // keep the connection open without sending the rest of the body
+                        std::future::pending::<()>().await;
+                    });
+                    Ok::<_, BoxError>(StallingStream(client))
+                })
+            })
+        }
+    }
+
+    impl Connection for StallingStream {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    impl AsyncRead for StallingStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for StallingStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Error>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Error>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+
     // ---- machinery to make a Hyper connector that responds with an IO Error
     #[derive(Clone)]
     struct HangupStream;
@@ -0,0 +1,153 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! TLS connector that times out the TLS handshake separately from the TCP connection
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::Uri;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower::{BoxError, Service};
+
+use aws_smithy_async::future::timeout::Timeout;
+use aws_smithy_async::rt::sleep::AsyncSleep;
+
+use super::timeout_middleware::TimeoutError;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, BoxError>> + Send>>;
+
+/// A TLS implementation that can negotiate TLS over a stream
+pub(super) trait Negotiate<S> {
+    /// The stream returned for both `http` and `https` URIs
+    type Stream;
+
+    /// Return `stream` unencrypted
+    fn plain(stream: S) -> Self::Stream;
+
+    /// Negotiate TLS for `host` over `stream`
+    fn negotiate(&self, host: &str, stream: S) -> BoxFuture<Self::Stream>;
+}
+
+#[cfg(feature = "rustls")]
+impl<S> Negotiate<S> for Arc<tokio_rustls::rustls::ClientConfig>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    type Stream = hyper_rustls::MaybeHttpsStream<S>;
+
+    fn plain(stream: S) -> Self::Stream {
+        hyper_rustls::MaybeHttpsStream::Http(stream)
+    }
+
+    fn negotiate(&self, host: &str, stream: S) -> BoxFuture<Self::Stream> {
+        let connecting = match tokio_rustls::webpki::DNSNameRef::try_from_ascii_str(host) {
+            Ok(dns_name) => {
+                tokio_rustls::TlsConnector::from(self.clone()).connect(dns_name, stream)
+            }
+            Err(_) => {
+                let err = std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid DNS name");
+                return Box::pin(std::future::ready(Err(err.into())));
+            }
+        };
+        Box::pin(async move { Ok(hyper_rustls::MaybeHttpsStream::Https(connecting.await?)) })
+    }
+}
+
+#[cfg(feature = "native-tls")]
+impl<S> Negotiate<S> for tokio_native_tls::TlsConnector
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    type Stream = hyper_tls::MaybeHttpsStream<S>;
+
+    fn plain(stream: S) -> Self::Stream {
+        hyper_tls::MaybeHttpsStream::Http(stream)
+    }
+
+    fn negotiate(&self, host: &str, stream: S) -> BoxFuture<Self::Stream> {
+        let connector = self.clone();
+        let host = host.to_string();
+        Box::pin(async move {
+            Ok(hyper_tls::MaybeHttpsStream::Https(
+                connector.connect(&host, stream).await?,
+            ))
+        })
+    }
+}
+
+/// Connector that negotiates TLS over the connections of an inner connector for `https` URIs
+///
+/// Unlike the connectors of `hyper-rustls` and `hyper-tls`, the TLS handshake has a timeout of its
+/// own, so the time spent opening the connection doesn't count towards it.
+#[derive(Clone)]
+pub(super) struct HttpsConnector<C, T> {
+    inner: C,
+    tls: T,
+    timeout: Option<(Arc<dyn AsyncSleep>, Duration)>,
+}
+
+impl<C, T> HttpsConnector<C, T> {
+    pub(super) fn new(inner: C, tls: T, timeout: Option<(Arc<dyn AsyncSleep>, Duration)>) -> Self {
+        Self {
+            inner,
+            tls,
+            timeout,
+        }
+    }
+}
+
+impl<C, T> std::fmt::Debug for HttpsConnector<C, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpsConnector")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<C, T> Service<Uri> for HttpsConnector<C, T>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+    T: Negotiate<C::Response> + Clone + Send + 'static,
+{
+    type Response = T::Stream;
+    type Error = BoxError;
+    type Future = BoxFuture<T::Stream>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(|err| err.into())
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let is_https = uri.scheme_str() == Some("https");
+        let host = uri.host().unwrap_or_default().to_string();
+        let connecting = self.inner.call(uri);
+        let tls = self.tls.clone();
+        let timeout = self.timeout.clone();
+        Box::pin(async move {
+            let stream = connecting.await.map_err(|err| err.into())?;
+            if !is_https {
+                return Ok(T::plain(stream));
+            }
+            let negotiating = tls.negotiate(&host, stream);
+            match timeout {
+                Some((sleep, duration)) => {
+                    match Timeout::new(negotiating, sleep.sleep(duration)).await {
+                        Ok(result) => result,
+                        Err(_timed_out) => {
+                            Err(TimeoutError::new("TLS negotiation", duration).into())
+                        }
+                    }
+                }
+                None => negotiating.await,
+            }
+        })
+    }
+}
@@ -11,8 +11,10 @@
 )]
 
 pub mod bounds;
+pub mod config_override;
 pub mod erase;
 pub mod retry;
+pub mod stalled_stream;
 
 // https://github.com/rust-lang/rust/issues/72081
 #[allow(rustdoc::private_doc_tests)]
@@ -35,6 +37,8 @@ pub mod static_tests;
 
 #[cfg(feature = "hyper")]
 pub mod never;
+#[cfg(feature = "hyper")]
+pub mod proxy;
 pub mod timeout;
 pub use timeout::TimeoutLayer;
 
@@ -46,15 +50,42 @@ pub mod conns {
     #[cfg(feature = "rustls")]
     pub type Https = hyper_rustls::HttpsConnector<hyper::client::HttpConnector>;
 
-    // Creating a `with_native_roots` HTTP client takes 300ms on OS X. Cache this so that we
-    // don't need to repeatedly incur that cost.
+    // Loading the native root certificates takes 300ms on OS X. Cache the TLS configuration so
+    // that we don't need to repeatedly incur that cost.
     #[cfg(feature = "rustls")]
     lazy_static::lazy_static! {
         static ref HTTPS_NATIVE_ROOTS: Https = {
-            hyper_rustls::HttpsConnector::with_native_roots()
+            let mut http = hyper::client::HttpConnector::new();
+            http.enforce_http(false);
+            (http, TLS_CONFIG_NATIVE_ROOTS.clone()).into()
+        };
+
+        /// TLS configuration of every Rustls connector, trusting the native root certificates
+        pub(crate) static ref TLS_CONFIG_NATIVE_ROOTS: std::sync::Arc<tokio_rustls::rustls::ClientConfig> = {
+            let mut config = tokio_rustls::rustls::ClientConfig::new();
+            match rustls_native_certs::load_native_certs() {
+                Ok(store) => config.root_store = store,
+                Err((Some(store), err)) => {
+                    tracing::warn!(err = %err, "could not load all native certificates");
+                    config.root_store = store;
+                }
+                Err((None, err)) => {
+                    tracing::error!(err = %err, "cannot access the native certificate store")
+                }
+            }
+            if config.root_store.is_empty() {
+                tracing::error!("no CA certificates found, TLS connections will fail");
+            }
+            config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+            config.ct_logs = Some(&ct_logs::LOGS);
+            std::sync::Arc::new(config)
         };
     }
 
+    /// Create an HTTPS connector (rustls) trusting the native root certificates
+    ///
+    /// If the native certificate store can't be loaded, an error is logged and TLS connections
+    /// will fail to verify the server.
     #[cfg(feature = "rustls")]
     pub fn https() -> Https {
         HTTPS_NATIVE_ROOTS.clone()
@@ -68,6 +99,49 @@ pub mod conns {
     #[cfg(feature = "native-tls")]
     pub type NativeTls = hyper_tls::HttpsConnector<hyper::client::HttpConnector>;
 
+    /// A TCP connector that opens connections through the proxies of a [`ProxyConfig`](crate::proxy::ProxyConfig)
+    pub type HttpProxy = crate::proxy::ProxyConnector<hyper::client::HttpConnector>;
+
+    /// Create a TCP connector that opens connections through the proxies of `config`
+    ///
+    /// The connector doesn't negotiate TLS, but it accepts `https` URIs so that it can be wrapped
+    /// with a TLS connector.
+    pub fn http_with_proxy(config: crate::proxy::ProxyConfig) -> HttpProxy {
+        let mut http = hyper::client::HttpConnector::new();
+        http.enforce_http(false);
+        crate::proxy::ProxyConnector::new(http, config)
+    }
+
+    #[cfg(feature = "rustls")]
+    pub type HttpsProxy = hyper_rustls::HttpsConnector<HttpProxy>;
+
+    /// Negotiate TLS (rustls) on top of the connections opened by `connector` for `https` URIs
+    #[cfg(feature = "rustls")]
+    pub fn https_with_connector<C>(connector: C) -> hyper_rustls::HttpsConnector<C> {
+        (connector, TLS_CONFIG_NATIVE_ROOTS.clone()).into()
+    }
+
+    /// Create an HTTPS connector (rustls) that opens connections through the proxies of `config`
+    #[cfg(feature = "rustls")]
+    pub fn https_with_proxy(config: crate::proxy::ProxyConfig) -> HttpsProxy {
+        https_with_connector(http_with_proxy(config))
+    }
+
+    #[cfg(feature = "native-tls")]
+    pub type NativeTlsProxy = hyper_tls::HttpsConnector<HttpProxy>;
+
+    /// Negotiate TLS (native-tls) on top of the connections opened by `connector` for `https` URIs
+    #[cfg(feature = "native-tls")]
+    pub fn native_tls_with_connector<C>(connector: C) -> hyper_tls::HttpsConnector<C> {
+        hyper_tls::HttpsConnector::new_with_connector(connector)
+    }
+
+    /// Create an HTTPS connector (native-tls) that opens connections through the proxies of `config`
+    #[cfg(feature = "native-tls")]
+    pub fn native_tls_with_proxy(config: crate::proxy::ProxyConfig) -> NativeTlsProxy {
+        native_tls_with_connector(http_with_proxy(config))
+    }
+
     #[cfg(feature = "rustls")]
     pub type Rustls =
         crate::hyper_ext::Adapter<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>;
@@ -77,9 +151,12 @@ use std::error::Error;
 use std::sync::Arc;
 use tower::{Layer, Service, ServiceBuilder, ServiceExt};
 
+use crate::config_override::ConfigOverride;
 use crate::timeout::generate_timeout_service_params_from_timeout_config;
 use aws_smithy_async::rt::sleep::{default_async_sleep, AsyncSleep};
 use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::interceptor::Interceptors;
+use aws_smithy_http::operation;
 use aws_smithy_http::operation::Operation;
 use aws_smithy_http::response::ParseHttpResponse;
 pub use aws_smithy_http::result::{SdkError, SdkSuccess};
@@ -119,6 +196,8 @@ pub struct Client<
     retry_policy: RetryPolicy,
     timeout_config: TimeoutConfig,
     sleep_impl: Option<Arc<dyn AsyncSleep>>,
+    retry_observer: Option<Arc<dyn retry::RetryObserver>>,
+    interceptors: Interceptors,
 }
 
 // Quick-create for people who just want "the default".
@@ -175,6 +254,14 @@ impl<C, M> Client<C, M> {
     }
 }
 
+impl<C, M, R> Client<C, M, R> {
+    /// The [`Interceptor`](aws_smithy_http::interceptor::Interceptor)s that are called for every
+    /// request made by the client.
+    pub fn interceptors(&self) -> &Interceptors {
+        &self.interceptors
+    }
+}
+
 fn check_send_sync<T: Send + Sync>(t: T) -> T {
     t
 }
@@ -189,10 +276,18 @@ where
     ///
     /// For ergonomics, this does not include the raw response for successful responses. To
     /// access the raw response use `call_raw`.
+    ///
+    /// `input` has already been serialized, so
+    /// [`Interceptor::modify_before_serialization`](aws_smithy_http::interceptor::Interceptor::modify_before_serialization)
+    /// is not called. Generated fluent builders call it before serializing the input. Every other
+    /// hook of the client's interceptors and of the [`Interceptors`] in the operation's property
+    /// bag is called.
     pub async fn call<O, T, E, Retry>(&self, input: Operation<O, Retry>) -> Result<T, SdkError<E>>
     where
-        O: Send + Sync,
-        Retry: Send + Sync,
+        O: Send + Sync + Clone,
+        T: 'static,
+        E: Error + 'static,
+        Retry: Send + Sync + Clone,
         R::Policy: bounds::SmithyRetryPolicy<O, T, E, Retry>,
         bounds::Parsed<<M as bounds::SmithyMiddleware<C>>::Service, O, Retry>:
             Service<Operation<O, Retry>, Response = SdkSuccess<T>, Error = SdkError<E>> + Clone,
@@ -204,13 +299,17 @@ where
     ///
     /// The returned result contains the raw HTTP response which can be useful for debugging or
     /// implementing unsupported features.
+    ///
+    /// Like [`call`](Client::call), this doesn't call the before-serialization hook of interceptors.
     pub async fn call_raw<O, T, E, Retry>(
         &self,
-        input: Operation<O, Retry>,
+        mut input: Operation<O, Retry>,
     ) -> Result<SdkSuccess<T>, SdkError<E>>
     where
-        O: Send + Sync,
-        Retry: Send + Sync,
+        O: Send + Sync + Clone,
+        T: 'static,
+        E: Error + 'static,
+        Retry: Send + Sync + Clone,
         R::Policy: bounds::SmithyRetryPolicy<O, T, E, Retry>,
         // This bound is not _technically_ inferred by all the previous bounds, but in practice it
         // is because _we_ know that there is only implementation of Service for Parsed
@@ -223,14 +322,66 @@ where
     {
         let connector = self.connector.clone();
 
+        if let Some(retry_observer) = &self.retry_observer {
+            input.properties_mut().insert(retry_observer.clone());
+        }
+
+        // the interceptors of the call run after the interceptors of the client
+        let interceptors = {
+            let mut properties = input.properties_mut();
+            let call_interceptors = properties.remove::<Interceptors>();
+            let mut interceptors = self.interceptors.clone();
+            if let Some(call_interceptors) = &call_interceptors {
+                interceptors.append(call_interceptors);
+            }
+            if !interceptors.is_empty() {
+                properties.insert(interceptors.clone());
+            }
+            interceptors
+        };
+        let config_override = input.properties_mut().remove::<ConfigOverride>();
+        let (request, parts) = input.into_request_response();
+        let (mut request, properties) = request.into_parts();
+        if let Some(config_override) = &config_override {
+            request
+                .headers_mut()
+                .extend(config_override.headers().clone());
+        }
+        let input = Operation::from_parts(
+            operation::Request::from_parts(request, properties.clone()),
+            parts,
+        );
+
+        let (timeout_config, retry_handler) = match &config_override {
+            Some(config_override) => (
+                config_override
+                    .timeout_config()
+                    .clone()
+                    .take_unset_from(self.timeout_config.clone()),
+                self.retry_policy
+                    .new_request_policy_with_override(config_override.retry_config()),
+            ),
+            None => (
+                self.timeout_config.clone(),
+                self.retry_policy.new_request_policy(),
+            ),
+        };
         let timeout_servic_params = generate_timeout_service_params_from_timeout_config(
-            &self.timeout_config,
+            &timeout_config,
             self.sleep_impl.clone(),
         );
 
         let svc = ServiceBuilder::new()
             .layer(TimeoutLayer::new(timeout_servic_params.api_call))
-            .retry(self.retry_policy.new_request_policy())
+            .retry(retry_handler)
+            .layer(retry::HedgeLayer::new(
+                self.retry_policy.quota(),
+                self.sleep_impl.clone(),
+            ))
+            .layer(retry::RateLimitLayer::new(
+                self.retry_policy.rate_limiter(),
+                self.sleep_impl.clone(),
+            ))
             .layer(TimeoutLayer::new(timeout_servic_params.api_call_attempt))
             .layer(ParseResponseLayer::<O, Retry>::new())
             // These layers can be considered as occurring in order. That is, first invoke the
@@ -239,7 +390,24 @@ where
             .layer(DispatchLayer::new())
             .service(connector);
 
-        check_send_sync(svc).ready().await?.call(input).await
+        let result = match check_send_sync(svc).ready().await {
+            Ok(svc) => svc.call(input).await,
+            Err(err) => Err(err),
+        };
+        if interceptors.is_empty() {
+            return result;
+        }
+        let mut properties = properties.acquire_mut();
+        match result {
+            Ok(mut success) => {
+                interceptors.modify_after_deserialization(&mut success.parsed, &mut properties);
+                Ok(success)
+            }
+            Err(err) => {
+                interceptors.on_error(&err, &mut properties);
+                Err(err)
+            }
+        }
     }
 
     /// Statically check the validity of a `Client` without a request to send.
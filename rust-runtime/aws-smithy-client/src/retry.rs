@@ -15,6 +15,16 @@
 //! - [`RetryHandler`]: A request-scoped retry policy, backed by request-local state and shared
 //!   state contained within [`Standard`].
 //! - [`Config`]: Static configuration (max attempts, max backoff etc.)
+//! - [`ClientRateLimiter`]: Client-side rate limiting used by the adaptive retry mode.
+//! - [`RetryPartition`]: A scope for the retry quota and rate limiter, e.g. a service and region.
+//!   [`RetryPartitions`] holds the state of each partition.
+//! - [`RetryObserver`]: Notified of every attempt and the retry decision made for it.
+//! - [`HedgingPolicy`]: Sends a second attempt when the first attempt of an operation is slow.
+
+mod hedging;
+mod observer;
+mod partition;
+mod rate_limiter;
 
 use std::future::Future;
 use std::pin::Pin;
@@ -22,12 +32,20 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use crate::{SdkError, SdkSuccess};
+use aws_smithy_async::rt::time::{default_time_source, TimeSource};
 use aws_smithy_http::operation;
 use aws_smithy_http::operation::Operation;
 use aws_smithy_http::retry::ClassifyResponse;
-use aws_smithy_types::retry::{ErrorKind, RetryKind};
+use aws_smithy_types::retry::{ErrorKind, JitterMode, RetryConfigBuilder, RetryKind, RetryMode};
 use tracing::Instrument;
 
+pub(crate) use hedging::HedgeLayer;
+pub use hedging::HedgingPolicy;
+pub use observer::{Attempt, AttemptOutcome, AttemptRecorder, RetryObserver};
+pub use partition::{RetryPartition, RetryPartitions};
+pub use rate_limiter::ClientRateLimiter;
+pub(crate) use rate_limiter::RateLimitLayer;
+
 /// A policy instantiator.
 ///
 /// Implementors are essentially "policy factories" that can produce a new instance of a retry
@@ -42,29 +60,59 @@ where
 
     /// Create a new policy mechanism instance.
     fn new_request_policy(&self) -> Self::Policy;
+
+    /// The rate limiter that every attempt must acquire capacity from before it is sent, if any
+    ///
+    /// By default, requests are not rate limited.
+    fn rate_limiter(&self) -> Option<ClientRateLimiter> {
+        None
+    }
+
+    /// The retry quota that hedged attempts are charged against, if any
+    ///
+    /// By default, hedged attempts are not limited by a retry quota.
+    fn quota(&self) -> Option<RetryQuota> {
+        None
+    }
+
+    /// Create a new policy mechanism instance for a request whose retry configuration is overridden
+    /// by `retry_config` (see [`ConfigOverride`](crate::config_override::ConfigOverride))
+    ///
+    /// By default, the override is ignored.
+    fn new_request_policy_with_override(&self, retry_config: &RetryConfigBuilder) -> Self::Policy {
+        let _ = retry_config;
+        self.new_request_policy()
+    }
 }
 
 /// Retry Policy Configuration
 ///
 /// Without specific use cases, users should generally rely on the default values set by `[Config::default]`(Config::default).`
 ///
-/// Currently these fields are private and no setters provided. As needed, this configuration will become user-modifiable in the future..
+/// A `Config` is usually created from an [`aws_smithy_types::retry::RetryConfig`], which carries
+/// all of these settings except for the retry partition.
 #[derive(Clone, Debug)]
 pub struct Config {
+    mode: RetryMode,
+    partition: Option<RetryPartition>,
+    partitions: RetryPartitions,
     initial_retry_tokens: usize,
     retry_cost: usize,
     no_retry_increment: usize,
     timeout_retry_cost: usize,
     max_attempts: u32,
+    initial_backoff: Duration,
     max_backoff: Duration,
+    jitter_mode: JitterMode,
     base: fn() -> f64,
+    time_source: Arc<dyn TimeSource>,
 }
 
 impl Config {
     /// Override `b` in the exponential backoff computation
     ///
-    /// By default, `base` is a randomly generated value between 0 and 1. In tests, it can
-    /// be helpful to override this:
+    /// By default, `base` is a randomly generated value between 0 and 1 that is used to apply
+    /// [jitter](Config::with_jitter_mode). In tests, it can be helpful to override this:
     /// ```rust
     /// use aws_smithy_client::retry::Config;
     /// let conf = Config::default().with_base(||1_f64);
@@ -81,40 +129,155 @@ impl Config {
         self.max_attempts = max_attempts;
         self
     }
+
+    /// Override the backoff before the first retry, before jitter is applied
+    ///
+    /// The backoff doubles with every attempt, up to the [max backoff](Config::with_max_backoff).
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Override the maximum backoff between retries
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Override how randomness is added to the backoff
+    pub fn with_jitter_mode(mut self, jitter_mode: JitterMode) -> Self {
+        self.jitter_mode = jitter_mode;
+        self
+    }
+
+    /// Override the size of the retry quota
+    pub fn with_initial_retry_tokens(mut self, initial_retry_tokens: usize) -> Self {
+        self.initial_retry_tokens = initial_retry_tokens;
+        self
+    }
+
+    /// Override how much of the retry quota a retry costs
+    pub fn with_retry_cost(mut self, retry_cost: usize) -> Self {
+        self.retry_cost = retry_cost;
+        self
+    }
+
+    /// Override how much of the retry quota a retry of a transient error, such as a timeout, costs
+    pub fn with_timeout_retry_cost(mut self, timeout_retry_cost: usize) -> Self {
+        self.timeout_retry_cost = timeout_retry_cost;
+        self
+    }
+
+    /// Override the retry mode
+    ///
+    /// In [`RetryMode::Adaptive`], requests are rate limited by a [`ClientRateLimiter`] that is
+    /// shared by all requests using the same [`Standard`] policy.
+    pub fn with_retry_mode(mut self, mode: RetryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Override the time source that the rate limiter of the adaptive retry mode measures time with
+    ///
+    /// By default, the system clock is used. A retry partition keeps the time source of the first
+    /// policy that used it.
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Override the retry partition
+    ///
+    /// Retry policies that use the same partition share a retry quota and, in the adaptive retry
+    /// mode, a rate limiter. Without a partition, each [`Standard`] policy has its own state.
+    pub fn with_retry_partition(mut self, partition: RetryPartition) -> Self {
+        self.partition = Some(partition);
+        self
+    }
+
+    /// Override the set of retry partitions that the [retry partition](Config::with_retry_partition)
+    /// is looked up in
+    ///
+    /// By default, the [global](RetryPartitions::global) set is used.
+    pub fn with_retry_partitions(mut self, partitions: RetryPartitions) -> Self {
+        self.partitions = partitions;
+        self
+    }
+
+    /// Override the settings that are set in `retry_config`
+    ///
+    /// The retry mode and the initial retry tokens configure the state shared by all requests
+    /// using the same policy, so they are never overridden.
+    fn with_override(self, retry_config: &RetryConfigBuilder) -> Self {
+        let mut current = RetryConfigBuilder::new();
+        current
+            .set_max_attempts(Some(self.max_attempts))
+            .set_initial_backoff(Some(self.initial_backoff))
+            .set_max_backoff(Some(self.max_backoff))
+            .set_jitter_mode(Some(self.jitter_mode))
+            .set_retry_cost(Some(self.retry_cost))
+            .set_timeout_retry_cost(Some(self.timeout_retry_cost));
+        let merged = retry_config.clone().take_unset_from(current).build();
+        Self {
+            max_attempts: merged.max_attempts(),
+            initial_backoff: merged.initial_backoff(),
+            max_backoff: merged.max_backoff(),
+            jitter_mode: merged.jitter_mode(),
+            retry_cost: merged.retry_cost(),
+            timeout_retry_cost: merged.timeout_retry_cost(),
+            ..self
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            mode: RetryMode::Standard,
+            partition: None,
+            partitions: RetryPartitions::global(),
             initial_retry_tokens: INITIAL_RETRY_TOKENS,
             retry_cost: RETRY_COST,
             no_retry_increment: 1,
             timeout_retry_cost: 10,
             max_attempts: MAX_ATTEMPTS,
-            max_backoff: Duration::from_secs(20),
+            initial_backoff: INITIAL_BACKOFF,
+            max_backoff: MAX_BACKOFF,
+            jitter_mode: JitterMode::Full,
             // by default, use a random base for exponential backoff
             base: fastrand::f64,
+            time_source: default_time_source(),
         }
     }
 }
 
 impl From<aws_smithy_types::retry::RetryConfig> for Config {
     fn from(conf: aws_smithy_types::retry::RetryConfig) -> Self {
-        Self::default().with_max_attempts(conf.max_attempts())
+        Self::default()
+            .with_max_attempts(conf.max_attempts())
+            .with_retry_mode(conf.mode())
+            .with_initial_backoff(conf.initial_backoff())
+            .with_max_backoff(conf.max_backoff())
+            .with_jitter_mode(conf.jitter_mode())
+            .with_initial_retry_tokens(conf.initial_retry_tokens())
+            .with_retry_cost(conf.retry_cost())
+            .with_timeout_retry_cost(conf.timeout_retry_cost())
     }
 }
 
 const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(20);
 const INITIAL_RETRY_TOKENS: usize = 500;
 const RETRY_COST: usize = 5;
 
 /// Manage retries for a service
 ///
-/// An implementation of the `standard` AWS retry strategy as specified in the SEP. A `Strategy` is scoped to a client.
+/// An implementation of the `standard` and `adaptive` AWS retry strategies as specified in the SEP. A `Strategy` is scoped to a client.
 /// For an individual request, call [`Standard::new_request_policy()`](Standard::new_request_policy)
 ///
-/// In the future, adding support for the adaptive retry strategy will be added by adding a `TokenBucket` to
-/// `CrossRequestRetryState`
+/// When the [retry mode](Config::with_retry_mode) is [`RetryMode::Adaptive`], `CrossRequestRetryState`
+/// also contains a [`ClientRateLimiter`] that delays requests after the service starts throttling them.
 /// Its main functionality is via `new_request_policy` which creates a `RetryHandler` to manage the retry for
 /// an individual request.
 #[derive(Debug)]
@@ -126,17 +289,47 @@ pub struct Standard {
 impl Standard {
     /// Construct a new standard retry policy from the given policy configuration.
     pub fn new(config: Config) -> Self {
+        let shared_state = match &config.partition {
+            Some(partition) => config.partitions.get_or_create(
+                partition,
+                config.initial_retry_tokens,
+                config.time_source.clone(),
+            ),
+            None => {
+                CrossRequestRetryState::new(config.initial_retry_tokens, config.time_source.clone())
+            }
+        }
+        .with_mode(config.mode);
         Self {
-            shared_state: CrossRequestRetryState::new(config.initial_retry_tokens),
+            shared_state,
             config,
         }
     }
 
     /// Set the configuration for this retry policy.
+    ///
+    /// If the retry partition is unchanged, the retry quota is kept.
     pub fn with_config(&mut self, config: Config) -> &mut Self {
-        self.config = config;
+        let same_partition = config.partition == self.config.partition
+            && (config.partition.is_none() || config.partitions.ptr_eq(&self.config.partitions));
+        if same_partition {
+            self.shared_state.set_mode(config.mode);
+            self.config = config;
+        } else {
+            *self = Self::new(config);
+        }
         self
     }
+
+    /// The retry partition used by this policy, if any
+    pub fn retry_partition(&self) -> Option<&RetryPartition> {
+        self.config.partition.as_ref()
+    }
+
+    /// The retry quota currently available to this policy
+    pub fn retry_quota(&self) -> usize {
+        self.shared_state.quota()
+    }
 }
 
 impl NewRequestPolicy for Standard {
@@ -149,6 +342,25 @@ impl NewRequestPolicy for Standard {
             config: self.config.clone(),
         }
     }
+
+    fn new_request_policy_with_override(&self, retry_config: &RetryConfigBuilder) -> Self::Policy {
+        RetryHandler {
+            local: RequestLocalRetryState::new(),
+            shared: self.shared_state.clone(),
+            config: self.config.clone().with_override(retry_config),
+        }
+    }
+
+    fn rate_limiter(&self) -> Option<ClientRateLimiter> {
+        self.shared_state.active_rate_limiter().cloned()
+    }
+
+    fn quota(&self) -> Option<RetryQuota> {
+        Some(RetryQuota {
+            shared: self.shared_state.clone(),
+            cost: self.config.retry_cost,
+        })
+    }
 }
 
 impl Default for Standard {
@@ -161,6 +373,8 @@ impl Default for Standard {
 struct RequestLocalRetryState {
     attempts: u32,
     last_quota_usage: Option<usize>,
+    /// Only used by [`JitterMode::Decorrelated`]
+    last_backoff: Option<Duration>,
 }
 
 impl Default for RequestLocalRetryState {
@@ -169,6 +383,7 @@ impl Default for RequestLocalRetryState {
             // Starts at one to account for the initial request that failed and warranted a retry
             attempts: 1,
             last_quota_usage: None,
+            last_backoff: None,
         }
     }
 }
@@ -179,25 +394,49 @@ impl RequestLocalRetryState {
     }
 }
 
-/* TODO in followup PR:
-/// RetryPartition represents a scope for cross request retry state
-///
-/// For example, a retry partition could be the id of a service. This would give each service a separate retry budget.
-struct RetryPartition(Cow<'static, str>); */
-
-/// Shared state between multiple requests to the same client.
+/// Shared state between multiple requests to the same client, or to the same [`RetryPartition`].
 #[derive(Clone, Debug)]
 struct CrossRequestRetryState {
     quota_available: Arc<Mutex<usize>>,
+    rate_limiter: ClientRateLimiter,
+    /// The rate limiter is only used in the adaptive retry mode
+    adaptive: bool,
 }
 
 // clippy is upset that we didn't use AtomicUsize here, but doing so makes the code
 // significantly more complicated for negligible benefit.
 #[allow(clippy::mutex_atomic)]
 impl CrossRequestRetryState {
-    pub fn new(initial_quota: usize) -> Self {
+    pub fn new(initial_quota: usize, time_source: Arc<dyn TimeSource>) -> Self {
         Self {
             quota_available: Arc::new(Mutex::new(initial_quota)),
+            rate_limiter: ClientRateLimiter::with_time_source(time_source),
+            adaptive: false,
+        }
+    }
+
+    fn with_mode(mut self, mode: RetryMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    fn set_mode(&mut self, mode: RetryMode) {
+        self.adaptive = mode == RetryMode::Adaptive;
+    }
+
+    fn quota(&self) -> usize {
+        *self.quota_available.lock().unwrap()
+    }
+
+    /// The rate limiter, if requests are rate limited
+    fn active_rate_limiter(&self) -> Option<&ClientRateLimiter> {
+        Some(&self.rate_limiter).filter(|_| self.adaptive)
+    }
+
+    /// Update the sending rate of the rate limiter, if requests are rate limited
+    fn rate_limiter_update(&self, retry_kind: &RetryKind) {
+        if let Some(rate_limiter) = self.active_rate_limiter() {
+            rate_limiter.update(retry_kind == &RetryKind::Error(ErrorKind::ThrottlingError));
         }
     }
 
@@ -226,6 +465,39 @@ impl CrossRequestRetryState {
     }
 }
 
+/// A handle to the retry quota of a retry policy
+///
+/// Used to charge attempts that aren't retries, such as hedged attempts, against the same quota as
+/// retries. Each attempt costs as much as retrying a non-timeout error.
+#[derive(Clone, Debug)]
+pub struct RetryQuota {
+    shared: CrossRequestRetryState,
+    cost: usize,
+}
+
+impl RetryQuota {
+    /// The retry quota currently available
+    pub fn available(&self) -> usize {
+        self.shared.quota()
+    }
+
+    /// Acquire the quota for an attempt. Returns `false` if there isn't enough quota left.
+    pub(crate) fn acquire(&self) -> bool {
+        let mut quota = self.shared.quota_available.lock().unwrap();
+        if self.cost > *quota {
+            false
+        } else {
+            *quota -= self.cost;
+            true
+        }
+    }
+
+    /// Return the quota acquired for a successful attempt
+    pub(crate) fn release(&self) {
+        *self.shared.quota_available.lock().unwrap() += self.cost;
+    }
+}
+
 /// RetryHandler
 ///
 /// Implement retries for an individual request.
@@ -241,7 +513,7 @@ pub struct RetryHandler {
 #[cfg(test)]
 impl RetryHandler {
     fn retry_quota(&self) -> usize {
-        *self.shared.quota_available.lock().unwrap()
+        self.shared.quota()
     }
 }
 
@@ -264,6 +536,22 @@ impl RetryHandler {
                 self.shared.quota_acquire(&e, &self.config)?
             }
         };
+        let backoff = self.backoff();
+        let next = RetryHandler {
+            local: RequestLocalRetryState {
+                attempts: self.local.attempts + 1,
+                last_quota_usage: Some(quota_used),
+                last_backoff: Some(backoff),
+            },
+            shared: self.shared.clone(),
+            config: self.config.clone(),
+        };
+
+        Some((next, backoff))
+    }
+
+    /// Compute the backoff before the next attempt
+    fn backoff(&self) -> Duration {
         /*
         From the retry spec:
             b = random number within the range of: 0 <= b <= 1
@@ -272,20 +560,24 @@ impl RetryHandler {
          */
         let r: i32 = 2;
         let b = (self.config.base)();
+        let initial = self.config.initial_backoff.as_secs_f64();
+        let max = self.config.max_backoff.as_secs_f64();
         // `self.local.attempts` tracks number of requests made including the initial request
         // The initial attempt shouldn't count towards backoff calculations so we subtract it
-        let backoff = b * (r.pow(self.local.attempts - 1) as f64);
-        let backoff = Duration::from_secs_f64(backoff).min(self.config.max_backoff);
-        let next = RetryHandler {
-            local: RequestLocalRetryState {
-                attempts: self.local.attempts + 1,
-                last_quota_usage: Some(quota_used),
-            },
-            shared: self.shared.clone(),
-            config: self.config.clone(),
+        let exponential = || (initial * r.pow(self.local.attempts - 1) as f64).min(max);
+        let backoff = match self.config.jitter_mode {
+            JitterMode::Equal => exponential() / 2.0 * (1.0 + b),
+            JitterMode::Decorrelated => {
+                let last = self
+                    .local
+                    .last_backoff
+                    .map(|backoff| backoff.as_secs_f64())
+                    .unwrap_or(initial);
+                initial + b * (last * 3.0 - initial).max(0.0)
+            }
+            _ => b * exponential(),
         };
-
-        Some((next, backoff))
+        Duration::from_secs_f64(backoff.min(max))
     }
 }
 
@@ -305,12 +597,32 @@ where
     ) -> Option<Self::Future> {
         let policy = req.retry_policy();
         let retry = policy.classify(result);
-        let (next, dur) = match retry {
-            RetryKind::Explicit(dur) => (self.clone(), dur),
-            RetryKind::NotRetryable => return None,
-            RetryKind::Error(err) => self.attempt_retry(Err(err))?,
-            _ => return None,
+        self.shared.rate_limiter_update(&retry);
+        let decision = match &retry {
+            RetryKind::Explicit(dur) => Some((self.clone(), *dur)),
+            RetryKind::Error(err) => self.attempt_retry(Err(*err)),
+            _ => None,
         };
+        let observer = req.properties().get::<Arc<dyn RetryObserver>>().cloned();
+        if let Some(observer) = observer {
+            let outcome = match (&decision, &retry) {
+                (Some((_, dur)), _) => AttemptOutcome::Retry(*dur),
+                (None, _) if result.is_ok() => AttemptOutcome::Success,
+                (None, RetryKind::Error(_)) if self.local.attempts == self.config.max_attempts => {
+                    AttemptOutcome::MaxAttemptsReached
+                }
+                (None, RetryKind::Error(_)) => AttemptOutcome::QuotaExhausted,
+                (None, _) => AttemptOutcome::NotRetryable,
+            };
+            observer.on_attempt(&Attempt::new(
+                req.metadata().cloned(),
+                self.local.attempts,
+                retry.clone(),
+                outcome,
+                self.shared.quota(),
+            ));
+        }
+        let (next, dur) = decision?;
 
         let fut = async move {
             tokio::time::sleep(dur).await;
@@ -331,8 +643,12 @@ fn check_send_sync<T: Send + Sync>(t: T) -> T {
 
 #[cfg(test)]
 mod test {
-    use crate::retry::{Config, NewRequestPolicy, RetryHandler, Standard};
-    use aws_smithy_types::retry::ErrorKind;
+    use crate::retry::{
+        Config, NewRequestPolicy, RetryHandler, RetryPartition, RetryPartitions, Standard,
+    };
+    use aws_smithy_types::retry::{
+        ErrorKind, JitterMode, RetryConfigBuilder, RetryKind, RetryMode,
+    };
     use std::time::Duration;
 
     fn test_config() -> Config {
@@ -469,4 +785,215 @@ mod test {
         assert!(no_retry.is_none());
         assert_eq!(policy.retry_quota(), 480);
     }
+
+    #[test]
+    fn backoff_settings() {
+        let conf = test_config()
+            .with_max_attempts(5)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(250))
+            .with_retry_cost(1);
+        let mut policy = Standard::new(conf).new_request_policy();
+        let mut backoffs = vec![];
+        while let Some((next, dur)) = policy.attempt_retry(Err(ErrorKind::ServerError)) {
+            backoffs.push(dur);
+            policy = next;
+        }
+        assert_eq!(
+            backoffs,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(250),
+                Duration::from_millis(250)
+            ]
+        );
+        assert_eq!(policy.retry_quota(), 496);
+    }
+
+    #[test]
+    fn jitter_modes() {
+        fn backoffs(conf: Config) -> Vec<Duration> {
+            let mut policy = Standard::new(conf.with_max_attempts(5)).new_request_policy();
+            let mut backoffs = vec![];
+            while let Some((next, dur)) = policy.attempt_retry(Err(ErrorKind::ServerError)) {
+                backoffs.push(dur);
+                policy = next;
+            }
+            backoffs
+        }
+        let secs = |secs: &[f64]| {
+            secs.iter()
+                .map(|secs| Duration::from_secs_f64(*secs))
+                .collect::<Vec<_>>()
+        };
+        let half = || Config::default().with_base(|| 0.5);
+
+        assert_eq!(
+            backoffs(half().with_jitter_mode(JitterMode::Full)),
+            secs(&[0.5, 1.0, 2.0, 4.0])
+        );
+        assert_eq!(
+            backoffs(half().with_jitter_mode(JitterMode::Equal)),
+            secs(&[0.75, 1.5, 3.0, 6.0])
+        );
+        // random(1, previous * 3): 1 + 0.5 * (1 * 3 - 1) = 2, then 1 + 0.5 * (2 * 3 - 1) = 3.5...
+        assert_eq!(
+            backoffs(
+                half()
+                    .with_jitter_mode(JitterMode::Decorrelated)
+                    .with_max_backoff(Duration::from_secs(8))
+            ),
+            secs(&[2.0, 3.5, 5.75, 8.0])
+        );
+        // equal jitter never waits less than half of the exponential backoff
+        assert_eq!(
+            backoffs(
+                Config::default()
+                    .with_base(|| 0.0)
+                    .with_jitter_mode(JitterMode::Equal)
+            ),
+            secs(&[0.5, 1.0, 2.0, 4.0])
+        );
+    }
+
+    #[test]
+    fn config_from_retry_config() {
+        let retry_config = aws_smithy_types::retry::RetryConfig::new()
+            .with_initial_backoff(Duration::from_millis(50))
+            .with_max_backoff(Duration::from_secs(2))
+            .with_jitter_mode(JitterMode::Equal)
+            .with_initial_retry_tokens(20)
+            .with_retry_cost(2)
+            .with_timeout_retry_cost(4);
+        let conf = Config::from(retry_config);
+        assert_eq!(conf.initial_backoff, Duration::from_millis(50));
+        assert_eq!(conf.max_backoff, Duration::from_secs(2));
+        assert_eq!(conf.jitter_mode, JitterMode::Equal);
+        assert_eq!(conf.initial_retry_tokens, 20);
+        assert_eq!(conf.retry_cost, 2);
+        assert_eq!(conf.timeout_retry_cost, 4);
+
+        let policy = Standard::new(conf).new_request_policy();
+        let (policy, _) = policy
+            .attempt_retry(Err(ErrorKind::TransientError))
+            .expect("should retry");
+        assert_eq!(policy.retry_quota(), 16);
+    }
+
+    #[test]
+    fn retry_config_override() {
+        let standard = Standard::new(
+            test_config()
+                .with_max_attempts(5)
+                .with_initial_backoff(Duration::from_millis(100)),
+        );
+        let policy = standard.new_request_policy_with_override(
+            &RetryConfigBuilder::new()
+                .max_attempts(2)
+                .retry_cost(1)
+                .initial_retry_tokens(1),
+        );
+        assert_eq!(policy.config.max_attempts, 2);
+        assert_eq!(policy.config.retry_cost, 1);
+        // unset settings keep the value of the policy
+        assert_eq!(policy.config.initial_backoff, Duration::from_millis(100));
+        // the retry quota is shared, so its size can't be overridden
+        assert_eq!(policy.config.initial_retry_tokens, 500);
+
+        let (policy, dur) = policy
+            .attempt_retry(Err(ErrorKind::ServerError))
+            .expect("should retry");
+        assert_eq!(dur, Duration::from_millis(100));
+        assert_eq!(policy.retry_quota(), 499);
+        assert!(policy.attempt_retry(Err(ErrorKind::ServerError)).is_none());
+
+        // the override only applies to a single request
+        assert_eq!(standard.new_request_policy().config.max_attempts, 5);
+    }
+
+    #[test]
+    fn adaptive_mode_rate_limits_after_throttling() {
+        let mut standard = Standard::new(test_config());
+        assert!(standard.rate_limiter().is_none());
+
+        standard.with_config(test_config().with_retry_mode(RetryMode::Adaptive));
+        let rate_limiter = standard
+            .rate_limiter()
+            .expect("adaptive mode has a rate limiter");
+        assert_eq!(rate_limiter.fill_rate(), None);
+
+        let policy = standard.new_request_policy();
+        policy
+            .shared
+            .rate_limiter_update(&RetryKind::Error(ErrorKind::ServerError));
+        assert_eq!(rate_limiter.fill_rate(), None);
+        policy
+            .shared
+            .rate_limiter_update(&RetryKind::Error(ErrorKind::ThrottlingError));
+        assert!(rate_limiter.fill_rate().is_some());
+        assert!(rate_limiter.acquire() > Duration::ZERO);
+
+        standard.with_config(test_config());
+        assert!(standard.rate_limiter().is_none());
+    }
+
+    #[test]
+    fn retry_partitions_have_separate_quotas() {
+        let partitions = RetryPartitions::new();
+        let dynamodb = RetryPartition::for_service_and_region("dynamodb", "us-east-1");
+        let s3 = RetryPartition::for_service_and_region("s3", "us-east-1");
+        let config = |partition: &RetryPartition| {
+            test_config()
+                .with_retry_partition(partition.clone())
+                .with_retry_partitions(partitions.clone())
+        };
+        let client_a = Standard::new(config(&dynamodb));
+        let client_b = Standard::new(config(&dynamodb));
+        let client_c = Standard::new(config(&s3));
+        assert_eq!(client_a.retry_partition(), Some(&dynamodb));
+
+        let policy = client_a.new_request_policy();
+        let (policy, _) = policy
+            .attempt_retry(Err(ErrorKind::ServerError))
+            .expect("should retry");
+        assert_eq!(policy.retry_quota(), 495);
+
+        // clients in the same partition share a quota, other partitions are unaffected
+        assert_eq!(client_b.retry_quota(), 495);
+        assert_eq!(client_c.retry_quota(), 500);
+        assert_eq!(partitions.retry_quota(&dynamodb), Some(495));
+        assert_eq!(partitions.retry_quota(&s3), Some(500));
+
+        // switching partitions switches quotas
+        let mut client_b = client_b;
+        client_b.with_config(config(&s3));
+        assert_eq!(client_b.retry_quota(), 500);
+        client_b.with_config(test_config());
+        assert_eq!(client_b.retry_partition(), None);
+        assert_eq!(client_b.retry_quota(), 500);
+        assert_eq!(partitions.retry_quota(&dynamodb), Some(495));
+    }
+
+    #[test]
+    fn retry_partitions_share_rate_limiters() {
+        let partitions = RetryPartitions::new();
+        let partition = RetryPartition::new("custom");
+        let config = test_config()
+            .with_retry_mode(RetryMode::Adaptive)
+            .with_retry_partition(partition.clone())
+            .with_retry_partitions(partitions.clone());
+        let client_a = Standard::new(config.clone());
+        let client_b = Standard::new(config);
+        client_a
+            .new_request_policy()
+            .shared
+            .rate_limiter_update(&RetryKind::Error(ErrorKind::ThrottlingError));
+        assert!(client_b.rate_limiter().unwrap().fill_rate().is_some());
+        assert!(partitions
+            .rate_limiter(&partition)
+            .unwrap()
+            .fill_rate()
+            .is_some());
+    }
 }
@@ -0,0 +1,529 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Request hedging
+//!
+//! A hedged request sends a second, speculative, attempt if the first attempt hasn't completed
+//! after a delay. Whichever attempt completes first wins, and the other one is cancelled. This
+//! reduces tail latency for idempotent operations at the cost of sending more requests.
+//!
+//! Hedging is enabled per operation by inserting a [`HedgingPolicy`] into the operation's
+//! property bag. Operations whose body can't be cloned (see [`Operation::try_clone`]) are never
+//! hedged.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use aws_smithy_async::rt::sleep::{AsyncSleep, Sleep};
+use aws_smithy_http::operation::Operation;
+use pin_project_lite::pin_project;
+use tower::util::Oneshot;
+use tower::{Layer, Service, ServiceExt};
+
+use super::RetryQuota;
+
+/// How many latencies are kept to compute percentiles
+const MAX_LATENCY_SAMPLES: usize = 128;
+/// How many latencies must be recorded before percentiles are used instead of the initial delay
+const MIN_LATENCY_SAMPLES: usize = 10;
+
+/// When to send a hedged attempt
+#[derive(Clone, Debug)]
+enum HedgeDelay {
+    Fixed(Duration),
+    Percentile { percentile: f64, initial: Duration },
+}
+
+/// Policy for hedging requests
+///
+/// Insert a `HedgingPolicy` into the property bag of an operation to hedge it. Only hedge
+/// operations that are idempotent, such as reads.
+///
+/// Each hedged attempt costs as much retry quota as a retry. Requests are not hedged when there
+/// isn't enough retry quota left. The quota is returned when the hedged request succeeds.
+///
+/// Cloning a `HedgingPolicy` shares the latencies it has recorded, so a policy that uses a
+/// [percentile](HedgingPolicy::percentile) should be created once and cloned into each operation.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_client::retry::HedgingPolicy;
+/// # use aws_smithy_http::body::SdkBody;
+/// # use aws_smithy_http::operation::{Operation, Request};
+/// # let operation = Operation::new(Request::new(http::Request::new(SdkBody::empty())), ());
+///
+/// // send a second attempt if the first attempt is slower than 95% of requests
+/// let hedging = HedgingPolicy::percentile(95.0, std::time::Duration::from_millis(50));
+/// let mut operation = operation;
+/// operation.properties_mut().insert(hedging.clone());
+/// ```
+#[derive(Clone, Debug)]
+pub struct HedgingPolicy {
+    delay: HedgeDelay,
+    latencies: Arc<Mutex<VecDeque<Duration>>>,
+}
+
+impl HedgingPolicy {
+    /// Send a hedged attempt if the first attempt hasn't completed after `delay`
+    pub fn fixed(delay: Duration) -> Self {
+        Self::new(HedgeDelay::Fixed(delay))
+    }
+
+    /// Send a hedged attempt if the first attempt is slower than `percentile` percent of recent
+    /// requests that used this policy
+    ///
+    /// Until enough requests have completed, `initial_delay` is used instead. Requests that were
+    /// answered by a hedged attempt aren't counted, since their latency includes the delay.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `percentile` is not between 0 and 100.
+    pub fn percentile(percentile: f64, initial_delay: Duration) -> Self {
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "percentile must be between 0 and 100 (was {})",
+            percentile
+        );
+        Self::new(HedgeDelay::Percentile {
+            percentile,
+            initial: initial_delay,
+        })
+    }
+
+    fn new(delay: HedgeDelay) -> Self {
+        Self {
+            delay,
+            latencies: Default::default(),
+        }
+    }
+
+    /// The delay after which the next hedged attempt will be sent
+    pub fn delay(&self) -> Duration {
+        match self.delay {
+            HedgeDelay::Fixed(delay) => delay,
+            HedgeDelay::Percentile {
+                percentile,
+                initial,
+            } => {
+                let latencies = self.latencies.lock().unwrap();
+                if latencies.len() < MIN_LATENCY_SAMPLES {
+                    return initial;
+                }
+                let mut sorted = latencies.iter().copied().collect::<Vec<_>>();
+                sorted.sort();
+                let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+                sorted[rank.max(1) - 1]
+            }
+        }
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        if let HedgeDelay::Percentile { .. } = self.delay {
+            let mut latencies = self.latencies.lock().unwrap();
+            if latencies.len() == MAX_LATENCY_SAMPLES {
+                latencies.pop_front();
+            }
+            latencies.push_back(latency);
+        }
+    }
+}
+
+/// A layer that hedges requests that have a [`HedgingPolicy`] in their property bag
+#[derive(Debug)]
+pub(crate) struct HedgeLayer {
+    quota: Option<RetryQuota>,
+    sleep_impl: Option<Arc<dyn AsyncSleep>>,
+}
+
+impl HedgeLayer {
+    pub(crate) fn new(quota: Option<RetryQuota>, sleep_impl: Option<Arc<dyn AsyncSleep>>) -> Self {
+        Self { quota, sleep_impl }
+    }
+}
+
+impl<S> Layer<S> for HedgeLayer {
+    type Service = HedgeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HedgeService {
+            inner,
+            quota: self.quota.clone(),
+            sleep_impl: self.sleep_impl.clone(),
+        }
+    }
+}
+
+/// A service that sends a hedged attempt if the first attempt is slow
+#[derive(Clone, Debug)]
+pub(crate) struct HedgeService<S> {
+    inner: S,
+    quota: Option<RetryQuota>,
+    sleep_impl: Option<Arc<dyn AsyncSleep>>,
+}
+
+impl<S, H, R> Service<Operation<H, R>> for HedgeService<S>
+where
+    S: Service<Operation<H, R>> + Clone,
+    H: Clone,
+    R: Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = HedgeFuture<S, Operation<H, R>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Operation<H, R>) -> Self::Future {
+        let policy = req.properties().get::<HedgingPolicy>().cloned();
+        let (hedge, sleep) = match (policy, &self.sleep_impl) {
+            (Some(policy), Some(sleep_impl)) => match req.try_clone() {
+                Some(hedged_req) => {
+                    let sleep = sleep_impl.sleep(policy.delay());
+                    let hedge = Hedge {
+                        pending: Some((self.inner.clone(), hedged_req)),
+                        policy,
+                    };
+                    (Some(hedge), Some(sleep))
+                }
+                None => {
+                    tracing::debug!("request body can't be cloned; the request won't be hedged");
+                    (None, None)
+                }
+            },
+            (Some(_), None) => {
+                tracing::warn!(
+                    "hedging is enabled but no async_sleep fn was passed. The request won't be hedged."
+                );
+                (None, None)
+            }
+            _ => (None, None),
+        };
+        HedgeFuture {
+            primary: self.inner.call(req),
+            secondary: None,
+            sleep,
+            hedge,
+            quota: self.quota.clone(),
+            quota_used: false,
+            start: Instant::now(),
+        }
+    }
+}
+
+/// A request that may be hedged
+#[derive(Debug)]
+struct Hedge<S, Req> {
+    /// The service and request for the hedged attempt, until it is sent
+    ///
+    /// The service is a clone that hasn't been polled yet, so the attempt is sent with
+    /// [`ServiceExt::oneshot`], which waits for it to be ready.
+    pending: Option<(S, Req)>,
+    policy: HedgingPolicy,
+}
+
+pin_project! {
+    /// Future returned by [`HedgeService`]
+    pub(crate) struct HedgeFuture<S, Req, F>
+    where
+        S: Service<Req>,
+    {
+        #[pin]
+        primary: F,
+        #[pin]
+        secondary: Option<Oneshot<S, Req>>,
+        #[pin]
+        sleep: Option<Sleep>,
+        hedge: Option<Hedge<S, Req>>,
+        quota: Option<RetryQuota>,
+        quota_used: bool,
+        start: Instant,
+    }
+}
+
+impl<S, Req, T, E> Future for HedgeFuture<S, Req, S::Future>
+where
+    S: Service<Req, Response = T, Error = E>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            let mut result = match this.primary.as_mut().poll(cx) {
+                Poll::Ready(result) => Some(result),
+                Poll::Pending => None,
+            };
+            let primary_won = result.is_some();
+            if let (None, Some(secondary)) = (&result, this.secondary.as_mut().as_pin_mut()) {
+                if let Poll::Ready(secondary_result) = secondary.poll(cx) {
+                    result = Some(secondary_result);
+                }
+            }
+            if let Some(result) = result {
+                // the latency of a hedged attempt includes the hedging delay, so only the latency
+                // of first attempts is recorded
+                if let (true, Some(hedge)) = (primary_won, this.hedge.as_ref()) {
+                    hedge.policy.record_latency(this.start.elapsed());
+                }
+                if let (true, Some(quota), Ok(_)) = (*this.quota_used, this.quota.as_ref(), &result)
+                {
+                    quota.release();
+                }
+                // dropping this future cancels the attempt that lost
+                return Poll::Ready(result);
+            }
+
+            match this.sleep.as_mut().as_pin_mut() {
+                Some(sleep) => {
+                    if sleep.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                }
+                None => return Poll::Pending,
+            }
+            this.sleep.set(None);
+            let pending = this.hedge.as_mut().and_then(|hedge| hedge.pending.take());
+            if let Some((inner, req)) = pending {
+                let acquired = this.quota.as_ref().map(RetryQuota::acquire);
+                if acquired == Some(false) {
+                    tracing::debug!("not enough retry quota to send a hedged attempt");
+                } else {
+                    tracing::debug!("first attempt is slow; sending a hedged attempt");
+                    *this.quota_used = acquired.is_some();
+                    this.secondary.set(Some(inner.oneshot(req)));
+                }
+            }
+            // poll the attempts again, now that the hedged attempt may have been sent
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HedgeLayer, HedgingPolicy};
+    use crate::retry::{Config, NewRequestPolicy, RetryQuota, Standard};
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_http::body::SdkBody;
+    use aws_smithy_http::operation::{Operation, Request};
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+    use tokio::time::Instant;
+    use tower::{Layer, Service, ServiceExt};
+
+    /// A service whose first call takes 10 seconds and whose later calls take 100 milliseconds.
+    /// Responds with the number of the call.
+    fn slow_then_fast(
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<Operation<(), ()>, Response = usize, Error = Infallible, Future = impl Send> + Clone
+    {
+        tower::service_fn(move |_req: Operation<(), ()>| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let latency = if call == 0 { 10_000 } else { 100 };
+                tokio::time::sleep(Duration::from_millis(latency)).await;
+                Ok::<_, Infallible>(call)
+            }
+        })
+    }
+
+    /// A service that panics if it is called before it is ready
+    struct MustBeReady<S> {
+        inner: S,
+        ready: bool,
+    }
+
+    impl<S: Clone> Clone for MustBeReady<S> {
+        fn clone(&self) -> Self {
+            // a clone hasn't been polled, so it isn't ready
+            Self {
+                inner: self.inner.clone(),
+                ready: false,
+            }
+        }
+    }
+
+    impl<S, Req> Service<Req> for MustBeReady<S>
+    where
+        S: Service<Req>,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            let poll = self.inner.poll_ready(cx);
+            self.ready = poll.is_ready();
+            poll
+        }
+
+        fn call(&mut self, req: Req) -> Self::Future {
+            assert!(self.ready, "service was called before it was ready");
+            self.ready = false;
+            self.inner.call(req)
+        }
+    }
+
+    fn operation(body: SdkBody, policy: Option<HedgingPolicy>) -> Operation<(), ()> {
+        let mut operation = Operation::new(Request::new(http::Request::new(body)), ());
+        if let Some(policy) = policy {
+            operation.properties_mut().insert(policy);
+        }
+        operation
+    }
+
+    fn quota(initial_retry_tokens: usize) -> RetryQuota {
+        Standard::new(Config::default().with_initial_retry_tokens(initial_retry_tokens))
+            .quota()
+            .expect("standard retry has a quota")
+    }
+
+    async fn send(quota: &RetryQuota, operation: Operation<(), ()>) -> (usize, usize, Duration) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = HedgeLayer::new(Some(quota.clone()), Some(Arc::new(TokioSleep::new())))
+            .layer(slow_then_fast(calls.clone()));
+        let start = Instant::now();
+        let response = svc.oneshot(operation).await.unwrap();
+        (response, calls.load(Ordering::SeqCst), start.elapsed())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_attempt_wins() {
+        let quota = quota(500);
+        let policy = HedgingPolicy::fixed(Duration::from_secs(1));
+        let (response, calls, elapsed) =
+            send(&quota, operation(SdkBody::from("hello"), Some(policy))).await;
+        assert_eq!(response, 1);
+        assert_eq!(calls, 2);
+        assert_eq!(elapsed, Duration::from_millis(1_100));
+        // the hedged attempt succeeded, so its quota was returned
+        assert_eq!(quota.available(), 500);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_attempt_waits_for_service_to_be_ready() {
+        let quota = quota(500);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = HedgeLayer::new(Some(quota.clone()), Some(Arc::new(TokioSleep::new()))).layer(
+            MustBeReady {
+                inner: slow_then_fast(calls.clone()),
+                ready: false,
+            },
+        );
+        let policy = HedgingPolicy::fixed(Duration::from_secs(1));
+        let response = svc
+            .oneshot(operation(SdkBody::from("hello"), Some(policy)))
+            .await
+            .unwrap();
+        assert_eq!(response, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn only_first_attempt_latency_is_recorded() {
+        let quota = quota(500);
+        let policy = HedgingPolicy::percentile(95.0, Duration::from_secs(1));
+        let (response, _, _) = send(
+            &quota,
+            operation(SdkBody::from("hello"), Some(policy.clone())),
+        )
+        .await;
+        // the hedged attempt won, so no latency was recorded
+        assert_eq!(response, 1);
+        assert!(policy.latencies.lock().unwrap().is_empty());
+
+        // the first attempt wins, so its latency is recorded
+        let calls = Arc::new(AtomicUsize::new(1));
+        let svc = HedgeLayer::new(Some(quota), Some(Arc::new(TokioSleep::new())))
+            .layer(slow_then_fast(calls));
+        let response = svc
+            .oneshot(operation(SdkBody::from("hello"), Some(policy.clone())))
+            .await
+            .unwrap();
+        assert_eq!(response, 1);
+        assert_eq!(policy.latencies.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn not_hedged_without_policy() {
+        let quota = quota(500);
+        let (response, calls, elapsed) =
+            send(&quota, operation(SdkBody::from("hello"), None)).await;
+        assert_eq!((response, calls), (0, 1));
+        assert_eq!(elapsed, Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn not_hedged_when_body_cant_be_cloned() {
+        let quota = quota(500);
+        let policy = HedgingPolicy::fixed(Duration::from_secs(1));
+        let (response, calls, _) = send(&quota, operation(SdkBody::taken(), Some(policy))).await;
+        assert_eq!((response, calls), (0, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn not_hedged_without_quota() {
+        let quota = quota(4);
+        let policy = HedgingPolicy::fixed(Duration::from_secs(1));
+        let (response, calls, elapsed) =
+            send(&quota, operation(SdkBody::from("hello"), Some(policy))).await;
+        assert_eq!((response, calls), (0, 1));
+        assert_eq!(elapsed, Duration::from_secs(10));
+        assert_eq!(quota.available(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn primary_wins_when_fast() {
+        let quota = quota(500);
+        let calls = Arc::new(AtomicUsize::new(1));
+        let svc = HedgeLayer::new(Some(quota.clone()), Some(Arc::new(TokioSleep::new())))
+            .layer(slow_then_fast(calls.clone()));
+        let policy = HedgingPolicy::fixed(Duration::from_secs(1));
+        let response = svc
+            .oneshot(operation(SdkBody::from("hello"), Some(policy)))
+            .await
+            .unwrap();
+        assert_eq!(response, 1);
+        // the hedged attempt was never sent
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(quota.available(), 500);
+    }
+
+    #[test]
+    fn percentile_delay() {
+        let policy = HedgingPolicy::percentile(90.0, Duration::from_secs(1));
+        for latency in 1..10 {
+            policy.record_latency(Duration::from_millis(latency));
+        }
+        // not enough samples yet
+        assert_eq!(policy.delay(), Duration::from_secs(1));
+        for latency in 10..=100 {
+            policy
+                .clone()
+                .record_latency(Duration::from_millis(latency));
+        }
+        assert_eq!(policy.delay(), Duration::from_millis(90));
+
+        // only the most recent latencies are kept
+        for _ in 0..128 {
+            policy.record_latency(Duration::from_millis(5));
+        }
+        assert_eq!(policy.delay(), Duration::from_millis(5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_percentile() {
+        HedgingPolicy::percentile(101.0, Duration::from_secs(1));
+    }
+}
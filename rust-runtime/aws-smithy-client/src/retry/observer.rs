@@ -0,0 +1,155 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Observing request attempts
+//!
+//! A [`RetryObserver`] registered with [`Builder::retry_observer`](crate::Builder::retry_observer)
+//! is notified after every attempt of every request made by the client, along with the decision
+//! the retry policy made for it. This can be used to emit metrics for retries, throttling and time
+//! spent backing off.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use aws_smithy_http::operation::Metadata;
+use aws_smithy_types::retry::RetryKind;
+
+/// Observes the attempts made by a client
+///
+/// The observer is passed to the retry policy of each request through the operation's property
+/// bag as an `Arc<dyn RetryObserver>`. [`Standard`](super::Standard) notifies it after each
+/// attempt; custom retry policies may do the same.
+pub trait RetryObserver: Debug + Send + Sync {
+    /// Called after each attempt once the retry policy has decided whether to retry
+    fn on_attempt(&self, attempt: &Attempt);
+}
+
+/// What happened after an attempt
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// The request will be retried after the given backoff
+    Retry(Duration),
+    /// The attempt succeeded
+    Success,
+    /// The attempt failed with an error that isn't retryable
+    NotRetryable,
+    /// The attempt failed with a retryable error, but the maximum number of attempts was reached
+    MaxAttemptsReached,
+    /// The attempt failed with a retryable error, but there was not enough retry quota left
+    QuotaExhausted,
+}
+
+/// An attempt of a request, as seen by a [`RetryObserver`]
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    metadata: Option<Metadata>,
+    number: u32,
+    retry_kind: RetryKind,
+    outcome: AttemptOutcome,
+    quota_remaining: usize,
+}
+
+impl Attempt {
+    pub(super) fn new(
+        metadata: Option<Metadata>,
+        number: u32,
+        retry_kind: RetryKind,
+        outcome: AttemptOutcome,
+        quota_remaining: usize,
+    ) -> Self {
+        Self {
+            metadata,
+            number,
+            retry_kind,
+            outcome,
+            quota_remaining,
+        }
+    }
+
+    /// The operation this attempt was made for, if the operation has metadata
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
+    /// The attempt number. The initial request is attempt 1.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// How the response or error of this attempt was classified
+    pub fn retry_kind(&self) -> &RetryKind {
+        &self.retry_kind
+    }
+
+    /// What happened after this attempt
+    pub fn outcome(&self) -> &AttemptOutcome {
+        &self.outcome
+    }
+
+    /// The backoff before the next attempt, if the request will be retried
+    pub fn backoff(&self) -> Option<Duration> {
+        match self.outcome {
+            AttemptOutcome::Retry(backoff) => Some(backoff),
+            _ => None,
+        }
+    }
+
+    /// The retry quota left after this attempt
+    pub fn quota_remaining(&self) -> usize {
+        self.quota_remaining
+    }
+
+    /// Returns `true` if this was the last attempt of the request
+    pub fn is_final(&self) -> bool {
+        !matches!(self.outcome, AttemptOutcome::Retry(_))
+    }
+}
+
+/// A [`RetryObserver`] that records every attempt
+///
+/// Cloning an `AttemptRecorder` creates a handle to the same recording, so a clone can be
+/// registered with a client while the original is used to inspect the attempts.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_client::retry::AttemptRecorder;
+/// use aws_smithy_client::Builder;
+///
+/// let recorder = AttemptRecorder::new();
+/// let client = Builder::new()
+///     .https()
+/// # /*
+///     .middleware(..)
+/// # */
+/// # .middleware(tower::layer::util::Identity::new())
+///     .retry_observer(recorder.clone())
+///     .build();
+/// // make requests with `client`, then inspect `recorder.attempts()`
+/// # client.check();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AttemptRecorder {
+    attempts: Arc<Mutex<Vec<Attempt>>>,
+}
+
+impl AttemptRecorder {
+    /// Create a new, empty, recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The attempts recorded so far, in the order they were made
+    pub fn attempts(&self) -> Vec<Attempt> {
+        self.attempts.lock().unwrap().clone()
+    }
+}
+
+impl RetryObserver for AttemptRecorder {
+    fn on_attempt(&self, attempt: &Attempt) {
+        self.attempts.lock().unwrap().push(attempt.clone());
+    }
+}
@@ -0,0 +1,248 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Retry partitions
+//!
+//! A retry partition is a scope for the retry quota and the adaptive rate limiter. Requests in
+//! different partitions don't share a retry budget, so a service or region that is failing can't
+//! exhaust the retry quota of requests to other services or regions.
+//!
+//! The state of a partition lives as long as a retry policy uses it. Once the last policy of a
+//! partition is dropped, its state is discarded, and the next policy that uses the partition
+//! starts with a full retry quota.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+use aws_smithy_async::rt::time::TimeSource;
+
+use super::{ClientRateLimiter, CrossRequestRetryState};
+
+/// The name of a scope for cross-request retry state
+///
+/// Clients whose retry policies use the same partition (and the same [`RetryPartitions`]) share a
+/// retry quota and, in the adaptive retry mode, a rate limiter.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_client::retry::{Config, RetryPartition};
+///
+/// let config = Config::default()
+///     .with_retry_partition(RetryPartition::for_service_and_region("dynamodb", "us-west-2"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RetryPartition {
+    name: Cow<'static, str>,
+}
+
+impl RetryPartition {
+    /// Create a retry partition with a custom name
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Create the retry partition for requests to `service` in `region`
+    pub fn for_service_and_region(service: &str, region: &str) -> Self {
+        Self::new(format!("{}/{}", service, region))
+    }
+
+    /// Create the retry partition for requests to `service` in `region`, sent to `endpoint_url`
+    ///
+    /// Clients of the same service and region that send requests to different endpoints, e.g. a
+    /// FIPS endpoint or a local test server, don't share a partition.
+    pub fn for_endpoint(service: &str, region: &str, endpoint_url: &str) -> Self {
+        Self::new(format!("{}/{}/{}", service, region, endpoint_url))
+    }
+
+    /// The name of this partition
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for RetryPartition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The retry state of a set of [`RetryPartition`]s
+///
+/// Cloning `RetryPartitions` creates a handle to the same state. Unless another set is configured
+/// with [`Config::with_retry_partitions`](super::Config::with_retry_partitions), retry policies use
+/// the [global](RetryPartitions::global) set, so that all clients in a process that make requests
+/// to the same partition share its state.
+///
+/// The retry quota of a partition is sized by the `initial_retry_tokens` of the first retry policy
+/// that uses it. Policies that later join the partition with a different setting share the
+/// existing quota, and a warning is logged.
+///
+/// Partitions are only held while a retry policy uses them, so the set doesn't grow with the
+/// number of partitions that were ever used.
+#[derive(Clone, Default)]
+pub struct RetryPartitions {
+    partitions: Arc<Mutex<HashMap<RetryPartition, PartitionState>>>,
+}
+
+/// The state of a partition, without keeping it alive
+struct PartitionState {
+    quota_available: Weak<Mutex<usize>>,
+    initial_quota: usize,
+    rate_limiter: ClientRateLimiter,
+}
+
+impl PartitionState {
+    fn upgrade(&self) -> Option<CrossRequestRetryState> {
+        Some(CrossRequestRetryState {
+            quota_available: self.quota_available.upgrade()?,
+            rate_limiter: self.rate_limiter.clone(),
+            adaptive: false,
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref GLOBAL_PARTITIONS: RetryPartitions = RetryPartitions::new();
+}
+
+impl RetryPartitions {
+    /// Create an empty set of retry partitions, independent from the global set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set of retry partitions shared by the whole process
+    pub fn global() -> Self {
+        GLOBAL_PARTITIONS.clone()
+    }
+
+    /// Returns the state of `partition`, creating it with `initial_quota` and a rate limiter that
+    /// measures time with `time_source` if it isn't in use
+    pub(super) fn get_or_create(
+        &self,
+        partition: &RetryPartition,
+        initial_quota: usize,
+        time_source: Arc<dyn TimeSource>,
+    ) -> CrossRequestRetryState {
+        let mut partitions = self.partitions.lock().unwrap();
+        // forget the partitions that are no longer used by any retry policy
+        partitions.retain(|_, state| state.quota_available.strong_count() > 0);
+        if let Some((existing, state)) = partitions
+            .get(partition)
+            .and_then(|existing| Some((existing, existing.upgrade()?)))
+        {
+            if existing.initial_quota != initial_quota {
+                tracing::warn!(
+                    partition = %partition,
+                    retry_quota = existing.initial_quota,
+                    initial_retry_tokens = initial_quota,
+                    "the retry partition is already in use with a different retry quota, the existing quota is shared"
+                );
+            }
+            return state;
+        }
+        let state = CrossRequestRetryState::new(initial_quota, time_source);
+        partitions.insert(
+            partition.clone(),
+            PartitionState {
+                quota_available: Arc::downgrade(&state.quota_available),
+                initial_quota,
+                rate_limiter: state.rate_limiter.clone(),
+            },
+        );
+        state
+    }
+
+    fn get(&self, partition: &RetryPartition) -> Option<CrossRequestRetryState> {
+        self.partitions
+            .lock()
+            .unwrap()
+            .get(partition)
+            .and_then(PartitionState::upgrade)
+    }
+
+    /// The retry quota currently available in `partition`
+    ///
+    /// Returns `None` if no retry policy uses `partition`.
+    pub fn retry_quota(&self, partition: &RetryPartition) -> Option<usize> {
+        self.get(partition).map(|state| state.quota())
+    }
+
+    /// The rate limiter used by `partition` in the adaptive retry mode
+    ///
+    /// Returns `None` if no retry policy uses `partition`.
+    pub fn rate_limiter(&self, partition: &RetryPartition) -> Option<ClientRateLimiter> {
+        self.get(partition).map(|state| state.rate_limiter)
+    }
+
+    /// Returns `true` if both handles refer to the same set of partitions
+    pub(super) fn ptr_eq(&self, other: &RetryPartitions) -> bool {
+        Arc::ptr_eq(&self.partitions, &other.partitions)
+    }
+}
+
+impl fmt::Debug for RetryPartitions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let partitions = self.partitions.lock().unwrap();
+        let in_use = partitions
+            .iter()
+            .filter(|(_, state)| state.quota_available.strong_count() > 0)
+            .map(|(partition, _)| partition);
+        f.debug_set().entries(in_use).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RetryPartition, RetryPartitions};
+    use aws_smithy_async::rt::time::default_time_source;
+
+    #[test]
+    fn partitions_are_created_on_first_use() {
+        let partitions = RetryPartitions::new();
+        let partition = RetryPartition::for_service_and_region("dynamodb", "us-west-2");
+        assert_eq!(partition.name(), "dynamodb/us-west-2");
+        assert_eq!(partitions.retry_quota(&partition), None);
+
+        let state = partitions.get_or_create(&partition, 100, default_time_source());
+        assert_eq!(partitions.retry_quota(&partition), Some(100));
+        assert!(partitions.rate_limiter(&partition).is_some());
+
+        // the initial quota only applies to new partitions
+        let other = partitions.get_or_create(&partition, 5, default_time_source());
+        assert_eq!(other.quota(), 100);
+        assert!(std::sync::Arc::ptr_eq(
+            &state.quota_available,
+            &other.quota_available
+        ));
+    }
+
+    #[test]
+    fn unused_partitions_are_discarded() {
+        let partitions = RetryPartitions::new();
+        let partition =
+            RetryPartition::for_endpoint("dynamodb", "us-west-2", "http://localhost:8000");
+        assert_eq!(partition.name(), "dynamodb/us-west-2/http://localhost:8000");
+
+        let state = partitions.get_or_create(&partition, 100, default_time_source());
+        *state.quota_available.lock().unwrap() = 10;
+        assert_eq!(partitions.retry_quota(&partition), Some(10));
+        assert_eq!(format!("{:?}", partitions), format!("{{{:?}}}", partition));
+
+        drop(state);
+        assert_eq!(partitions.retry_quota(&partition), None);
+        assert!(partitions.rate_limiter(&partition).is_none());
+        assert_eq!(format!("{:?}", partitions), "{}");
+
+        // the partition starts over with a full quota, and other partitions are pruned
+        let _other =
+            partitions.get_or_create(&RetryPartition::new("other"), 100, default_time_source());
+        assert_eq!(partitions.partitions.lock().unwrap().len(), 1);
+        let _state = partitions.get_or_create(&partition, 100, default_time_source());
+        assert_eq!(partitions.retry_quota(&partition), Some(100));
+    }
+}
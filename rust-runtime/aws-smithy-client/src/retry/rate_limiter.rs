@@ -0,0 +1,464 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Client-side rate limiting for the adaptive retry mode
+//!
+//! The rate limiter is a token bucket whose fill rate follows the CUBIC congestion control
+//! algorithm: when a request is throttled, the sending rate is cut to a fraction of the measured
+//! rate. On success, the rate recovers along a cubic curve, slowly while it is close to the rate at
+//! which throttling last occurred and faster as the time since throttling grows.
+//!
+//! The token bucket is only enabled once the first throttling error is received. Until then,
+//! requests are sent without delay.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use aws_smithy_async::rt::sleep::{AsyncSleep, Sleep};
+use aws_smithy_async::rt::time::{default_time_source, TimeSource};
+use pin_project_lite::pin_project;
+use tower::{Layer, Service};
+
+/// How much the sending rate is reduced by when a request is throttled
+const BETA: f64 = 0.7;
+/// Scales how quickly the sending rate recovers after throttling
+const SCALE_CONSTANT: f64 = 0.4;
+/// Weight of the latest measurement when smoothing the measured sending rate
+const SMOOTH: f64 = 0.8;
+const MIN_FILL_RATE: f64 = 0.5;
+const MIN_CAPACITY: f64 = 1.0;
+
+/// A token bucket that limits the rate at which requests are sent
+///
+/// A `ClientRateLimiter` is shared by all requests made by clients using the same retry policy.
+/// Cloning a `ClientRateLimiter` creates a handle to the same token bucket.
+#[derive(Clone, Debug)]
+pub struct ClientRateLimiter {
+    time_source: Arc<dyn TimeSource>,
+    start: Instant,
+    state: Arc<Mutex<RateLimiterState>>,
+}
+
+impl Default for ClientRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientRateLimiter {
+    /// Create a new rate limiter. The token bucket is enabled when the first request is throttled.
+    pub fn new() -> Self {
+        Self::with_time_source(default_time_source())
+    }
+
+    /// Create a new rate limiter that measures time with `time_source`
+    pub fn with_time_source(time_source: Arc<dyn TimeSource>) -> Self {
+        Self {
+            start: time_source.now(),
+            time_source,
+            state: Arc::new(Mutex::new(RateLimiterState::new(0.0))),
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.time_source
+            .now()
+            .saturating_duration_since(self.start)
+            .as_secs_f64()
+    }
+
+    /// Acquire capacity to send a request
+    ///
+    /// Returns how long the request must wait before being sent. The capacity is reserved
+    /// immediately so that concurrent requests queue up behind each other.
+    pub fn acquire(&self) -> Duration {
+        let now = self.now();
+        Duration::from_secs_f64(self.state.lock().unwrap().acquire(now, 1.0))
+    }
+
+    /// Update the sending rate after a response was received
+    ///
+    /// `throttled` should be `true` when the response was a throttling error.
+    pub fn update(&self, throttled: bool) {
+        let now = self.now();
+        self.state
+            .lock()
+            .unwrap()
+            .update_sending_rate(now, throttled);
+    }
+
+    /// The rate, in requests per second, at which capacity is currently added to the token bucket
+    ///
+    /// Returns `None` when the token bucket is not enabled, i.e. no request has been throttled yet.
+    pub fn fill_rate(&self) -> Option<f64> {
+        let state = self.state.lock().unwrap();
+        if state.enabled {
+            Some(state.fill_rate)
+        } else {
+            None
+        }
+    }
+}
+
+/// The state of the token bucket. Timestamps are in seconds.
+#[derive(Debug)]
+struct RateLimiterState {
+    enabled: bool,
+    fill_rate: f64,
+    max_capacity: f64,
+    current_capacity: f64,
+    last_timestamp: Option<f64>,
+
+    measured_tx_rate: f64,
+    last_tx_rate_bucket: f64,
+    request_count: u64,
+
+    last_max_rate: f64,
+    last_throttle_time: f64,
+    time_window: f64,
+}
+
+impl RateLimiterState {
+    fn new(now: f64) -> Self {
+        Self {
+            enabled: false,
+            fill_rate: 0.0,
+            max_capacity: 0.0,
+            current_capacity: 0.0,
+            last_timestamp: None,
+            measured_tx_rate: 0.0,
+            last_tx_rate_bucket: now.floor(),
+            request_count: 0,
+            last_max_rate: 0.0,
+            last_throttle_time: now,
+            time_window: 0.0,
+        }
+    }
+
+    /// Take `amount` from the bucket, returning the number of seconds to wait for it to be available
+    fn acquire(&mut self, now: f64, amount: f64) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.refill(now);
+        let delay = if amount <= self.current_capacity {
+            0.0
+        } else {
+            (amount - self.current_capacity) / self.fill_rate
+        };
+        self.current_capacity -= amount;
+        delay
+    }
+
+    fn refill(&mut self, now: f64) {
+        // capacity doesn't build up before the bucket is enabled
+        if !self.enabled {
+            return;
+        }
+        if let Some(last_timestamp) = self.last_timestamp {
+            let fill_amount = (now - last_timestamp) * self.fill_rate;
+            self.current_capacity = self.max_capacity.min(self.current_capacity + fill_amount);
+        }
+        self.last_timestamp = Some(now);
+    }
+
+    fn update_bucket_rate(&mut self, now: f64, new_rate: f64) {
+        self.refill(now);
+        self.fill_rate = new_rate.max(MIN_FILL_RATE);
+        self.max_capacity = new_rate.max(MIN_CAPACITY);
+        self.current_capacity = self.current_capacity.min(self.max_capacity);
+    }
+
+    fn update_sending_rate(&mut self, now: f64, throttled: bool) {
+        self.update_measured_rate(now);
+        let calculated_rate = if throttled {
+            let rate_to_use = if self.enabled {
+                self.measured_tx_rate.min(self.fill_rate)
+            } else {
+                self.measured_tx_rate
+            };
+            self.last_max_rate = rate_to_use;
+            self.calculate_time_window();
+            self.last_throttle_time = now;
+            self.enabled = true;
+            cubic_throttle(rate_to_use)
+        } else {
+            self.calculate_time_window();
+            self.cubic_success(now)
+        };
+        let new_rate = calculated_rate.min(2.0 * self.measured_tx_rate);
+        self.update_bucket_rate(now, new_rate);
+    }
+
+    /// Track the rate at which requests are sent, in half-second buckets
+    fn update_measured_rate(&mut self, now: f64) {
+        let time_bucket = (now * 2.0).floor() / 2.0;
+        self.request_count += 1;
+        if time_bucket > self.last_tx_rate_bucket {
+            let current_rate = self.request_count as f64 / (time_bucket - self.last_tx_rate_bucket);
+            self.measured_tx_rate = current_rate * SMOOTH + self.measured_tx_rate * (1.0 - SMOOTH);
+            self.request_count = 0;
+            self.last_tx_rate_bucket = time_bucket;
+        }
+    }
+
+    /// The time it takes the cubic curve to recover to the rate at which throttling last occurred
+    fn calculate_time_window(&mut self) {
+        self.time_window = (self.last_max_rate * (1.0 - BETA) / SCALE_CONSTANT).cbrt();
+    }
+
+    fn cubic_success(&self, now: f64) -> f64 {
+        let dt = now - self.last_throttle_time;
+        SCALE_CONSTANT * (dt - self.time_window).powi(3) + self.last_max_rate
+    }
+}
+
+fn cubic_throttle(rate_to_use: f64) -> f64 {
+    rate_to_use * BETA
+}
+
+/// A layer that delays requests until the [`ClientRateLimiter`] has capacity for them
+#[derive(Debug)]
+pub(crate) struct RateLimitLayer {
+    rate_limiter: Option<ClientRateLimiter>,
+    sleep_impl: Option<Arc<dyn AsyncSleep>>,
+}
+
+impl RateLimitLayer {
+    pub(crate) fn new(
+        rate_limiter: Option<ClientRateLimiter>,
+        sleep_impl: Option<Arc<dyn AsyncSleep>>,
+    ) -> Self {
+        if rate_limiter.is_some() && sleep_impl.is_none() {
+            tracing::warn!(
+                "The adaptive retry mode is enabled but no async_sleep fn was passed. Requests will not be rate limited."
+            );
+        }
+        Self {
+            rate_limiter,
+            sleep_impl,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            rate_limiter: self.rate_limiter.clone(),
+            sleep_impl: self.sleep_impl.clone(),
+        }
+    }
+}
+
+/// A service that delays each request until the [`ClientRateLimiter`] has capacity for it
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimitService<S> {
+    inner: S,
+    rate_limiter: Option<ClientRateLimiter>,
+    sleep_impl: Option<Arc<dyn AsyncSleep>>,
+}
+
+impl<S, Req> Service<Req> for RateLimitService<S>
+where
+    S: Service<Req> + Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = RateLimitFuture<S, Req, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let delay = match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.acquire(),
+            None => Duration::ZERO,
+        };
+        match &self.sleep_impl {
+            Some(sleep_impl) if delay > Duration::ZERO => {
+                tracing::debug!(delay = ?delay, "rate limiting request");
+                // the service that was polled ready is the one that must be called
+                let clone = self.inner.clone();
+                let inner = std::mem::replace(&mut self.inner, clone);
+                RateLimitFuture::Waiting {
+                    sleep: sleep_impl.sleep(delay),
+                    pending: Some((inner, req)),
+                }
+            }
+            _ => RateLimitFuture::Ready {
+                future: self.inner.call(req),
+            },
+        }
+    }
+}
+
+pin_project! {
+    /// Future returned by [`RateLimitService`]
+    #[project = RateLimitFutureProj]
+    pub(crate) enum RateLimitFuture<S, Req, F> {
+        /// Waiting for the rate limiter to have capacity
+        Waiting {
+            #[pin]
+            sleep: Sleep,
+            pending: Option<(S, Req)>,
+        },
+        /// The request was sent to the inner service
+        Ready {
+            #[pin]
+            future: F,
+        },
+    }
+}
+
+impl<S, Req> Future for RateLimitFuture<S, Req, S::Future>
+where
+    S: Service<Req>,
+{
+    type Output = Result<S::Response, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project() {
+                RateLimitFutureProj::Waiting { sleep, pending } => {
+                    if sleep.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    let (mut inner, req) = pending.take().expect("polled after completion");
+                    let future = inner.call(req);
+                    self.set(RateLimitFuture::Ready { future });
+                }
+                RateLimitFutureProj::Ready { future } => return future.poll(cx),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClientRateLimiter, RateLimiterState, BETA};
+    use aws_smithy_async::rt::time::TimeSource;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// A clock that only advances when told to
+    #[derive(Debug)]
+    struct ManualTimeSource(Mutex<Instant>);
+
+    impl ManualTimeSource {
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl TimeSource for ManualTimeSource {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn approx_eq(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-6,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn disabled_until_throttled() {
+        let mut state = RateLimiterState::new(0.0);
+        for i in 0..100 {
+            approx_eq(0.0, state.acquire(i as f64 * 0.01, 1.0));
+            state.update_sending_rate(i as f64 * 0.01, false);
+        }
+        assert!(!state.enabled);
+        state.update_sending_rate(1.0, true);
+        assert!(state.enabled);
+    }
+
+    #[test]
+    fn throttling_reduces_the_sending_rate() {
+        let mut state = RateLimiterState::new(0.0);
+        // 10 requests per second for 5 seconds
+        for i in 0..50 {
+            state.update_sending_rate(i as f64 * 0.1, false);
+        }
+        assert!((state.measured_tx_rate - 10.0).abs() < 0.1);
+
+        state.update_sending_rate(5.0, true);
+        let measured = state.measured_tx_rate;
+        approx_eq(measured * BETA, state.fill_rate);
+        approx_eq(measured * BETA, state.max_capacity);
+
+        // the bucket starts empty, so the next request has to wait for a token
+        approx_eq(1.0 / state.fill_rate, state.acquire(5.0, 1.0));
+        // and the one after that waits for the next token
+        approx_eq(2.0 / state.fill_rate, state.acquire(5.0, 1.0));
+    }
+
+    #[test]
+    fn cubic_success() {
+        // test vector from the adaptive retry specification: throttled at t=5 with a max rate of 10
+        let mut state = RateLimiterState::new(0.0);
+        state.last_max_rate = 10.0;
+        state.last_throttle_time = 5.0;
+        state.calculate_time_window();
+        approx_eq(1.9574338205844317, state.time_window);
+        let expected = [
+            (5.0, 7.0),
+            (6.0, 9.64893600966),
+            (7.0, 10.000030849917364),
+            (8.0, 10.453284520772092),
+            (9.0, 13.408697022224185),
+            (10.0, 21.26626835427364),
+            (11.0, 36.425998516920465),
+        ];
+        for (now, rate) in &expected {
+            approx_eq(*rate, state.cubic_success(*now));
+        }
+    }
+
+    #[test]
+    fn time_is_read_from_the_time_source() {
+        let time_source = Arc::new(ManualTimeSource(Mutex::new(Instant::now())));
+        let rate_limiter = ClientRateLimiter::with_time_source(time_source.clone());
+        // 10 requests per second for 5 seconds
+        for _ in 0..50 {
+            rate_limiter.update(false);
+            time_source.advance(Duration::from_millis(100));
+        }
+        rate_limiter.update(true);
+        let fill_rate = rate_limiter.fill_rate().expect("throttled");
+        assert!((fill_rate - 10.0 * BETA).abs() < 0.1);
+
+        // the bucket starts empty, and nothing is refilled until the clock advances
+        approx_eq(1.0 / fill_rate, rate_limiter.acquire().as_secs_f64());
+        approx_eq(2.0 / fill_rate, rate_limiter.acquire().as_secs_f64());
+        time_source.advance(Duration::from_secs(10));
+        approx_eq(0.0, rate_limiter.acquire().as_secs_f64());
+    }
+
+    #[test]
+    fn capacity_is_refilled_over_time() {
+        let mut state = RateLimiterState::new(0.0);
+        state.enabled = true;
+        state.update_bucket_rate(0.0, 4.0);
+        approx_eq(0.25, state.acquire(0.0, 1.0));
+        // after 2 seconds, 8 tokens were added but the capacity is capped at 4
+        approx_eq(0.0, state.acquire(2.0, 1.0));
+        approx_eq(0.0, state.acquire(2.0, 1.0));
+        approx_eq(0.0, state.acquire(2.0, 1.0));
+        approx_eq(0.0, state.acquire(2.0, 1.0));
+        approx_eq(0.25, state.acquire(2.0, 1.0));
+    }
+}
@@ -0,0 +1,344 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Protection against stalled streams
+//!
+//! Timeouts only bound the time until the response headers arrive. A streaming response body that
+//! stops delivering data halfway through would otherwise hang forever. [`MinimumThroughputBody`]
+//! wraps a body and fails it with a [`StalledStreamError`] when it delivers less than a minimum
+//! throughput for a grace period.
+//!
+//! To protect every response body of a Hyper connector, use
+//! [`hyper_ext::Builder::stalled_stream_protection`](crate::hyper_ext::Builder::stalled_stream_protection).
+//! To protect a single body, use [`protect`].
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::combinators::BoxBody;
+use http_body::{Body, SizeHint};
+use pin_project_lite::pin_project;
+use tower::BoxError;
+
+use aws_smithy_async::rt::sleep::{AsyncSleep, Sleep};
+use aws_smithy_http::body::SdkBody;
+
+/// Minimum throughput that a streaming body must deliver
+///
+/// Throughput is measured over consecutive windows of the grace period, starting when the body is
+/// first polled. A window in which the body delivered fewer than
+/// `minimum_throughput * grace_period` bytes fails the body. Time that the consumer spends not
+/// polling the body counts towards the window, so bodies should be read as they are received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StalledStreamProtection {
+    minimum_throughput: u64,
+    grace_period: Duration,
+}
+
+impl StalledStreamProtection {
+    /// Require at least `minimum_throughput` bytes per second, measured over `grace_period`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `grace_period` is zero.
+    pub fn new(minimum_throughput: u64, grace_period: Duration) -> Self {
+        assert!(
+            grace_period > Duration::from_secs(0),
+            "the grace period must not be zero"
+        );
+        Self {
+            minimum_throughput,
+            grace_period,
+        }
+    }
+
+    /// The minimum throughput, in bytes per second
+    pub fn minimum_throughput(&self) -> u64 {
+        self.minimum_throughput
+    }
+
+    /// The period over which the throughput is measured
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    fn minimum_bytes_per_window(&self) -> u64 {
+        (self.minimum_throughput as f64 * self.grace_period.as_secs_f64()).ceil() as u64
+    }
+}
+
+/// A body delivered less than the minimum throughput for a grace period
+#[derive(Debug)]
+pub struct StalledStreamError {
+    bytes_received: u64,
+    protection: StalledStreamProtection,
+}
+
+impl StalledStreamError {
+    /// The number of bytes received during the grace period
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// The protection settings that were not met
+    pub fn protection(&self) -> &StalledStreamProtection {
+        &self.protection
+    }
+}
+
+impl fmt::Display for StalledStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the stream stalled: received {} bytes in {:?}, below the minimum throughput of {} bytes per second",
+            self.bytes_received, self.protection.grace_period, self.protection.minimum_throughput
+        )
+    }
+}
+
+impl Error for StalledStreamError {}
+
+pin_project! {
+    /// Body that fails with a [`StalledStreamError`] when `inner` delivers less than a minimum throughput
+    pub struct MinimumThroughputBody<B> {
+        #[pin]
+        inner: B,
+        sleep_impl: Arc<dyn AsyncSleep>,
+        protection: StalledStreamProtection,
+        // `Sleep` isn't `Sync`, but the body must be to be boxed into an `SdkBody`. The window is
+        // only accessed through `&mut self`, so the mutex is never contended.
+        window: Option<Mutex<Sleep>>,
+        bytes_in_window: u64,
+        done: bool,
+    }
+}
+
+impl<B> MinimumThroughputBody<B> {
+    /// Enforce `protection` on `inner`, using `sleep_impl` to measure the grace period
+    pub fn new(
+        inner: B,
+        protection: StalledStreamProtection,
+        sleep_impl: Arc<dyn AsyncSleep>,
+    ) -> Self {
+        Self {
+            inner,
+            sleep_impl,
+            protection,
+            window: None,
+            bytes_in_window: 0,
+            done: false,
+        }
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for MinimumThroughputBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinimumThroughputBody")
+            .field("inner", &self.inner)
+            .field("protection", &self.protection)
+            .field("bytes_in_window", &self.bytes_in_window)
+            .finish()
+    }
+}
+
+impl<B> Body for MinimumThroughputBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        if *this.done {
+            return this.inner.poll_data(cx).map_err(|err| err.into());
+        }
+        let sleep_impl = this.sleep_impl;
+        let protection = this.protection;
+        let window = this
+            .window
+            .get_or_insert_with(|| Mutex::new(sleep_impl.sleep(protection.grace_period)))
+            .get_mut()
+            .expect("the window is never accessed concurrently");
+        match this.inner.poll_data(cx) {
+            Poll::Ready(Some(Ok(data))) => {
+                *this.bytes_in_window += data.len() as u64;
+                return Poll::Ready(Some(Ok(data)));
+            }
+            Poll::Ready(None) => {
+                *this.done = true;
+                *this.window = None;
+                return Poll::Ready(None);
+            }
+            Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+            Poll::Pending => {}
+        }
+        // the body is waiting for data: check the throughput of every window that has ended
+        while Pin::new(&mut *window).poll(cx).is_ready() {
+            if *this.bytes_in_window < protection.minimum_bytes_per_window() {
+                *this.done = true;
+                return Poll::Ready(Some(Err(StalledStreamError {
+                    bytes_received: *this.bytes_in_window,
+                    protection: protection.clone(),
+                }
+                .into())));
+            }
+            *this.bytes_in_window = 0;
+            *window = sleep_impl.sleep(protection.grace_period);
+        }
+        Poll::Pending
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project()
+            .inner
+            .poll_trailers(cx)
+            .map_err(|err| err.into())
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Enforce `protection` on `body`
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_async::rt::sleep::default_async_sleep;
+/// use aws_smithy_client::stalled_stream::{protect, StalledStreamProtection};
+/// use aws_smithy_http::body::SdkBody;
+/// use aws_smithy_http::byte_stream::ByteStream;
+/// use std::time::Duration;
+///
+/// # let body = SdkBody::from("hello");
+/// // fail the download if it receives less than 1 KB/s for 30 seconds
+/// let protection = StalledStreamProtection::new(1024, Duration::from_secs(30));
+/// let stream = ByteStream::new(protect(body, protection, default_async_sleep().unwrap()));
+/// ```
+pub fn protect(
+    body: SdkBody,
+    protection: StalledStreamProtection,
+    sleep_impl: Arc<dyn AsyncSleep>,
+) -> SdkBody {
+    SdkBody::from_dyn(BoxBody::new(MinimumThroughputBody::new(
+        body, protection, sleep_impl,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{protect, StalledStreamError, StalledStreamProtection};
+    use aws_smithy_async::assert_elapsed;
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_http::body::SdkBody;
+    use bytes::Bytes;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::Instant;
+    use tower::BoxError;
+
+    /// A body that sends `chunks` chunks of `chunk_size` bytes, one every `interval`, then stalls
+    /// if `stall` is set
+    fn body(chunk_size: usize, chunks: usize, interval: Duration, stall: bool) -> SdkBody {
+        let (mut sender, body) = hyper::Body::channel();
+        tokio::spawn(async move {
+            for _ in 0..chunks {
+                tokio::time::sleep(interval).await;
+                sender
+                    .send_data(Bytes::from(vec![0; chunk_size]))
+                    .await
+                    .unwrap();
+            }
+            if stall {
+                std::future::pending::<()>().await;
+            }
+        });
+        SdkBody::from(body)
+    }
+
+    fn protection() -> StalledStreamProtection {
+        StalledStreamProtection::new(100, Duration::from_secs(2))
+    }
+
+    async fn read(body: SdkBody) -> Result<Bytes, BoxError> {
+        let body = protect(body, protection(), Arc::new(TokioSleep::new()));
+        hyper::body::to_bytes(body).await
+    }
+
+    #[tokio::test]
+    async fn fast_enough() {
+        tokio::time::pause();
+        let start = Instant::now();
+        let result = read(body(250, 4, Duration::from_secs(1), false)).await;
+        assert_eq!(result.expect("throughput is above the minimum").len(), 1000);
+        assert_elapsed!(start, Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn stalled() {
+        tokio::time::pause();
+        let start = Instant::now();
+        let result = read(body(1000, 1, Duration::from_millis(500), true)).await;
+        let err = result.expect_err("the stream stalls");
+        let err = err
+            .downcast_ref::<StalledStreamError>()
+            .expect("a stalled stream error");
+        assert_eq!(err.bytes_received(), 0);
+        assert_eq!(err.protection(), &protection());
+        // the first window received enough data, the second window didn't receive any
+        assert_elapsed!(start, Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn too_slow() {
+        tokio::time::pause();
+        let start = Instant::now();
+        let result = read(body(10, 100, Duration::from_millis(600), false)).await;
+        let err = result.expect_err("the stream is too slow");
+        let err = err
+            .downcast_ref::<StalledStreamError>()
+            .expect("a stalled stream error");
+        assert_eq!(err.bytes_received(), 30);
+        assert_eq!(
+            format!("{}", err),
+            "the stream stalled: received 30 bytes in 2s, below the minimum throughput of 100 bytes per second"
+        );
+        assert_elapsed!(start, Duration::from_secs(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_grace_period() {
+        StalledStreamProtection::new(100, Duration::from_secs(0));
+    }
+
+    #[tokio::test]
+    async fn in_memory_bodies_are_not_affected() {
+        tokio::time::pause();
+        let start = Instant::now();
+        let result = read(SdkBody::from("hello")).await;
+        assert_eq!(result.unwrap(), "hello");
+        assert_elapsed!(start, Duration::from_secs(0));
+    }
+}
@@ -48,6 +48,11 @@ impl Settings {
         self.read_timeout
     }
 
+    /// The configured TLS negotiation timeout
+    pub fn tls_negotiation(&self) -> Option<Duration> {
+        self.tls_negotiation_timeout
+    }
+
     /// Sets the connect timeout
     pub fn with_connect_timeout(self, connect_timeout: Duration) -> Self {
         Self {
@@ -63,6 +68,28 @@ impl Settings {
             ..self
         }
     }
+
+    /// Sets the TLS negotiation timeout
+    ///
+    /// The timeout only covers the TLS handshake, which starts once the TCP connection is open. It
+    /// is enforced by the connectors that
+    /// [`hyper_ext::Builder::build_https`](crate::hyper_ext::Builder::build_https) creates.
+    pub fn with_tls_negotiation_timeout(self, tls_negotiation_timeout: Duration) -> Self {
+        Self {
+            tls_negotiation_timeout: Some(tls_negotiation_timeout),
+            ..self
+        }
+    }
+}
+
+impl From<&TimeoutConfig> for Settings {
+    fn from(timeout_config: &TimeoutConfig) -> Self {
+        Self {
+            connect_timeout: timeout_config.connect_timeout(),
+            read_timeout: timeout_config.read_timeout(),
+            tls_negotiation_timeout: timeout_config.tls_negotiation_timeout(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
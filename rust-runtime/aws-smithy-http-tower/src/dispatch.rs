@@ -5,6 +5,7 @@
 
 use crate::SendOperationError;
 use aws_smithy_http::body::SdkBody;
+use aws_smithy_http::interceptor::Interceptors;
 use aws_smithy_http::operation;
 use aws_smithy_http::result::ConnectorError;
 use std::future::Future;
@@ -41,15 +42,23 @@ where
     }
 
     fn call(&mut self, req: operation::Request) -> Self::Future {
-        let (req, property_bag) = req.into_parts();
+        let (mut req, property_bag) = req.into_parts();
+        let interceptors = property_bag.acquire().get::<Interceptors>().cloned();
+        if let Some(interceptors) = &interceptors {
+            interceptors.modify_before_transmit(&mut req, &mut property_bag.acquire_mut());
+        }
         let mut inner = self.inner.clone();
         let future = async move {
             trace!(request = ?req);
-            inner
+            let mut resp = inner
                 .call(req)
                 .await
-                .map(|resp| operation::Response::from_parts(resp, property_bag))
-                .map_err(|e| SendOperationError::RequestDispatchError(e.into()))
+                .map_err(|e| SendOperationError::RequestDispatchError(e.into()))?;
+            if let Some(interceptors) = &interceptors {
+                interceptors
+                    .modify_after_response_headers(&mut resp, &mut property_bag.acquire_mut());
+            }
+            Ok(operation::Response::from_parts(resp, property_bag))
         };
         Box::pin(future)
     }
@@ -74,6 +74,11 @@ impl Endpoint {
         }
     }
 
+    /// The URI of the endpoint, without any endpoint prefix
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
     /// Sets the endpoint on `uri`, potentially applying the specified `prefix` in the process.
     pub fn set_endpoint(&self, uri: &mut http::Uri, prefix: Option<&EndpointPrefix>) {
         let prefix = prefix.map(|p| p.0.as_str()).unwrap_or("");
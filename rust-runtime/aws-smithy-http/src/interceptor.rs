@@ -0,0 +1,314 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! Interceptors for the request lifecycle
+//!
+//! An [`Interceptor`] is called at well-defined points of the lifecycle of every request:
+//!
+//! 1. [before serialization](Interceptor::modify_before_serialization), once per call, with the
+//!    operation input. This hook is only run by generated fluent clients, which serialize the
+//!    input. Operations that are built by hand and sent with `aws_smithy_client::Client::call`
+//!    are already serialized, so the hook isn't run for them.
+//! 2. [before signing](Interceptor::modify_before_signing), once per attempt. This hook is run by
+//!    [`BeforeSigningStage`], which middleware that signs requests must include before signing.
+//! 3. [before transmit](Interceptor::modify_before_transmit), once per attempt, after all
+//!    middleware has run. This hook and the next one are run by the dispatch service of
+//!    `aws-smithy-http-tower`.
+//! 4. [after response headers](Interceptor::modify_after_response_headers), once per attempt,
+//!    before the response body is read.
+//! 5. [after deserialization](Interceptor::modify_after_deserialization), once per call, with the
+//!    parsed output of the successful attempt.
+//! 6. [on error](Interceptor::on_error), once per call, if the call failed.
+//!
+//! Hooks find the interceptors to run in the property bag of the operation. Interceptors are
+//! registered for every call made by a client with `aws_smithy_client::Builder::interceptor`, or
+//! for a single call by inserting [`Interceptors`] into the property bag of the operation. Client
+//! interceptors are called before the interceptors of the call.
+
+use std::any::Any;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::body::SdkBody;
+use crate::middleware::MapRequest;
+use crate::operation;
+use crate::property_bag::PropertyBag;
+
+/// Hooks into the lifecycle of a request
+///
+/// Every hook has a default implementation that does nothing, so implementations only need to
+/// override the hooks they are interested in. Each hook has access to the property bag of the
+/// operation, which can be used to share state between hooks, or with middleware.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_http::interceptor::Interceptor;
+/// use aws_smithy_http::body::SdkBody;
+/// use aws_smithy_http::property_bag::PropertyBag;
+///
+/// /// Adds a trace ID header to every request
+/// #[derive(Debug)]
+/// struct TraceId(&'static str);
+///
+/// impl Interceptor for TraceId {
+///     fn modify_before_signing(
+///         &self,
+///         request: &mut http::Request<SdkBody>,
+///         _properties: &mut PropertyBag,
+///     ) {
+///         request
+///             .headers_mut()
+///             .insert("x-trace-id", http::HeaderValue::from_static(self.0));
+///     }
+/// }
+/// ```
+pub trait Interceptor: Debug + Send + Sync {
+    /// Called with the operation input before it is serialized into an HTTP request
+    ///
+    /// The input can be modified by downcasting it to the input type of the operation. Properties
+    /// inserted into `properties` are added to the property bag of the operation once it is
+    /// serialized, replacing properties of the same type.
+    ///
+    /// This hook is only called by the fluent builders of generated clients. An [`Operation`]
+    /// passed directly to `aws_smithy_client::Client::call` has already been serialized, so this
+    /// hook is skipped for it; the other hooks are still called. Code that serializes operations
+    /// itself can run the hook with [`Interceptors::modify_before_serialization`].
+    ///
+    /// [`Operation`]: crate::operation::Operation
+    fn modify_before_serialization(&self, input: &mut dyn Any, properties: &mut PropertyBag) {
+        let _ = (input, properties);
+    }
+
+    /// Called before each attempt is signed
+    ///
+    /// Changes made to the request here are covered by the signature.
+    fn modify_before_signing(
+        &self,
+        request: &mut http::Request<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        let _ = (request, properties);
+    }
+
+    /// Called before each attempt is sent over the wire, once all middleware has run
+    fn modify_before_transmit(
+        &self,
+        request: &mut http::Request<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        let _ = (request, properties);
+    }
+
+    /// Called when the headers of the response to an attempt are received, before its body is read
+    fn modify_after_response_headers(
+        &self,
+        response: &mut http::Response<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        let _ = (response, properties);
+    }
+
+    /// Called with the parsed output of a successful call
+    ///
+    /// The output can be modified by downcasting it to the output type of the operation.
+    fn modify_after_deserialization(&self, output: &mut dyn Any, properties: &mut PropertyBag) {
+        let _ = (output, properties);
+    }
+
+    /// Called with the error of a failed call, once all attempts have been made
+    fn on_error(&self, error: &(dyn Error + 'static), properties: &mut PropertyBag) {
+        let _ = (error, properties);
+    }
+}
+
+/// An ordered list of [`Interceptor`]s
+///
+/// Insert `Interceptors` into the property bag of an operation to register interceptors for a
+/// single call.
+///
+/// # Examples
+/// ```rust
+/// use aws_smithy_http::interceptor::{Interceptor, Interceptors};
+/// # use aws_smithy_http::body::SdkBody;
+/// # use aws_smithy_http::operation::{Operation, Request};
+/// # let mut operation = Operation::new(Request::new(http::Request::new(SdkBody::empty())), ());
+///
+/// #[derive(Debug)]
+/// struct AuditLog;
+/// impl Interceptor for AuditLog {}
+///
+/// operation
+///     .properties_mut()
+///     .insert(Interceptors::new().with(AuditLog));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interceptors {
+    interceptors: Vec<Arc<dyn Interceptor>>,
+}
+
+impl Interceptors {
+    /// Create an empty list of interceptors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interceptor to the end of the list
+    pub fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.push(interceptor);
+    }
+
+    /// Add an interceptor to the end of the list
+    pub fn with(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Add all interceptors of `other` to the end of the list
+    pub fn append(&mut self, other: &Interceptors) {
+        self.interceptors.extend(other.interceptors.iter().cloned());
+    }
+
+    /// Returns `true` if there are no interceptors in the list
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.is_empty()
+    }
+
+    /// Run [`Interceptor::modify_before_serialization`] for every interceptor
+    pub fn modify_before_serialization(&self, input: &mut dyn Any, properties: &mut PropertyBag) {
+        for interceptor in &self.interceptors {
+            interceptor.modify_before_serialization(input, properties);
+        }
+    }
+
+    /// Run [`Interceptor::modify_before_signing`] for every interceptor
+    pub fn modify_before_signing(
+        &self,
+        request: &mut http::Request<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        for interceptor in &self.interceptors {
+            interceptor.modify_before_signing(request, properties);
+        }
+    }
+
+    /// Run [`Interceptor::modify_before_transmit`] for every interceptor
+    pub fn modify_before_transmit(
+        &self,
+        request: &mut http::Request<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        for interceptor in &self.interceptors {
+            interceptor.modify_before_transmit(request, properties);
+        }
+    }
+
+    /// Run [`Interceptor::modify_after_response_headers`] for every interceptor
+    pub fn modify_after_response_headers(
+        &self,
+        response: &mut http::Response<SdkBody>,
+        properties: &mut PropertyBag,
+    ) {
+        for interceptor in &self.interceptors {
+            interceptor.modify_after_response_headers(response, properties);
+        }
+    }
+
+    /// Run [`Interceptor::modify_after_deserialization`] for every interceptor
+    pub fn modify_after_deserialization(&self, output: &mut dyn Any, properties: &mut PropertyBag) {
+        for interceptor in &self.interceptors {
+            interceptor.modify_after_deserialization(output, properties);
+        }
+    }
+
+    /// Run [`Interceptor::on_error`] for every interceptor
+    pub fn on_error(&self, error: &(dyn Error + 'static), properties: &mut PropertyBag) {
+        for interceptor in &self.interceptors {
+            interceptor.on_error(error, properties);
+        }
+    }
+}
+
+/// Middleware stage that runs [`Interceptor::modify_before_signing`]
+///
+/// Middleware that signs requests should include this stage right before the signing stage.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BeforeSigningStage;
+
+impl BeforeSigningStage {
+    /// Create a new `BeforeSigningStage`
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl MapRequest for BeforeSigningStage {
+    type Error = Infallible;
+
+    fn apply(&self, request: operation::Request) -> Result<operation::Request, Self::Error> {
+        request.augment(|mut request, properties| {
+            if let Some(interceptors) = properties.get::<Interceptors>().cloned() {
+                interceptors.modify_before_signing(&mut request, properties);
+            }
+            Ok(request)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BeforeSigningStage, Interceptor, Interceptors};
+    use crate::body::SdkBody;
+    use crate::middleware::MapRequest;
+    use crate::operation;
+    use crate::property_bag::PropertyBag;
+
+    #[derive(Debug)]
+    struct AddHeader(&'static str);
+
+    impl Interceptor for AddHeader {
+        fn modify_before_signing(
+            &self,
+            request: &mut http::Request<SdkBody>,
+            properties: &mut PropertyBag,
+        ) {
+            request
+                .headers_mut()
+                .append("x-hooks", self.0.parse().unwrap());
+            properties.insert(self.0);
+        }
+    }
+
+    fn hooks(request: &operation::Request) -> Vec<&str> {
+        request
+            .http()
+            .headers()
+            .get_all("x-hooks")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn before_signing_stage_runs_interceptors_in_order() {
+        let mut request = operation::Request::new(http::Request::new(SdkBody::empty()));
+        let mut client = Interceptors::new().with(AddHeader("client"));
+        client.append(&Interceptors::new().with(AddHeader("call")));
+        request.properties_mut().insert(client);
+
+        let request = BeforeSigningStage::new().apply(request).unwrap();
+        assert_eq!(hooks(&request), vec!["client", "call"]);
+        assert_eq!(request.properties().get::<&str>(), Some(&"call"));
+    }
+
+    #[test]
+    fn before_signing_stage_without_interceptors() {
+        let request = operation::Request::new(http::Request::new(SdkBody::empty()));
+        let request = BeforeSigningStage::new().apply(request).unwrap();
+        assert!(hooks(&request).is_empty());
+    }
+}
@@ -9,6 +9,7 @@ pub mod body;
 pub mod byte_stream;
 pub mod endpoint;
 pub mod header;
+pub mod interceptor;
 pub mod label;
 pub mod middleware;
 pub mod operation;
@@ -150,6 +150,29 @@ impl PropertyBag {
         })
     }
 
+    /// Move all properties of `other` into this `PropertyBag`.
+    ///
+    /// Values in `other` replace values of the same type in this `PropertyBag`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use aws_smithy_http::property_bag::PropertyBag;
+    /// let mut props = PropertyBag::new();
+    /// props.insert(5i32);
+    /// props.insert(String::from("Hello"));
+    ///
+    /// let mut other = PropertyBag::new();
+    /// other.insert(6i32);
+    /// props.extend(other);
+    ///
+    /// assert_eq!(props.get::<i32>(), Some(&6i32));
+    /// assert_eq!(props.get::<String>().unwrap(), "Hello");
+    /// ```
+    pub fn extend(&mut self, other: PropertyBag) {
+        self.map.extend(other.map);
+    }
+
     /// Clear the `PropertyBag` of all inserted extensions.
     ///
     /// # Examples
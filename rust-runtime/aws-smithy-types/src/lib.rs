@@ -17,6 +17,7 @@ use std::collections::HashMap;
 
 pub mod base64;
 pub mod date_time;
+pub mod pool;
 pub mod primitive;
 pub mod retry;
 pub mod timeout;
@@ -0,0 +1,114 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0.
+ */
+
+//! This module defines types that describe the connection pool of an HTTP client.
+
+use std::time::Duration;
+
+/// The HTTP version used by a connection pool
+#[non_exhaustive]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum HttpVersion {
+    /// Only use HTTP/1.1. HTTP/2 isn't offered when negotiating TLS.
+    Http1Only,
+
+    /// Only use HTTP/2, even if the connection didn't negotiate it with ALPN.
+    Http2Only,
+}
+
+/// Configuration for the connection pool of an HTTP client
+///
+/// Settings that aren't set use the defaults of the HTTP client.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+///
+/// # fn main() {
+/// use aws_smithy_types::pool::{HttpVersion, PoolConfig};
+/// let pool_config = PoolConfig::new()
+///     .with_idle_timeout(Some(Duration::from_secs(30)))
+///     .with_max_idle_per_host(Some(8))
+///     .with_http_version(Some(HttpVersion::Http1Only));
+///
+/// assert_eq!(pool_config.idle_timeout(), Some(Duration::from_secs(30)));
+/// assert_eq!(pool_config.max_idle_per_host(), Some(8));
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PoolConfig {
+    idle_timeout: Option<Duration>,
+    max_idle_per_host: Option<usize>,
+    http_version: Option<HttpVersion>,
+}
+
+impl PoolConfig {
+    /// Create a new `PoolConfig` with no settings set
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// How long an idle connection is kept in the pool before it is closed.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// The maximum number of idle connections kept in the pool for each host.
+    pub fn max_idle_per_host(&self) -> Option<usize> {
+        self.max_idle_per_host
+    }
+
+    /// The HTTP version the pool is restricted to. When unset, HTTP/2 is used if the connection
+    /// negotiates it with ALPN, and HTTP/1.1 otherwise.
+    pub fn http_version(&self) -> Option<HttpVersion> {
+        self.http_version
+    }
+
+    /// Consume a `PoolConfig` to create a new one, setting the idle timeout
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Consume a `PoolConfig` to create a new one, setting the maximum idle connections per host
+    pub fn with_max_idle_per_host(mut self, max_idle_per_host: Option<usize>) -> Self {
+        self.max_idle_per_host = max_idle_per_host;
+        self
+    }
+
+    /// Consume a `PoolConfig` to create a new one, setting the HTTP version
+    pub fn with_http_version(mut self, http_version: Option<HttpVersion>) -> Self {
+        self.http_version = http_version;
+        self
+    }
+
+    /// Merges two configs together.
+    ///
+    /// Values from `other` will only be used as a fallback for values from `self`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use aws_smithy_types::pool::PoolConfig;
+    /// let a = PoolConfig::new().with_max_idle_per_host(Some(2));
+    /// let b = PoolConfig::new()
+    ///     .with_max_idle_per_host(Some(10))
+    ///     .with_idle_timeout(Some(Duration::from_secs(30)));
+    /// let pool_config = a.take_unset_from(b);
+    /// // A's value take precedence over B's value
+    /// assert_eq!(pool_config.max_idle_per_host(), Some(2));
+    /// // A never set an idle timeout so B's value was used
+    /// assert_eq!(pool_config.idle_timeout(), Some(Duration::from_secs(30)));
+    /// ```
+    pub fn take_unset_from(self, other: Self) -> Self {
+        Self {
+            idle_timeout: self.idle_timeout.or(other.idle_timeout),
+            max_idle_per_host: self.max_idle_per_host.or(other.max_idle_per_host),
+            http_version: self.http_version.or(other.http_version),
+        }
+    }
+}
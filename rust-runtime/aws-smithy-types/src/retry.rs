@@ -61,7 +61,7 @@ pub trait ProvideErrorKind {
 /// - The required retry delay exceeds the maximum backoff configured by the client
 /// - No retry tokens are available due to service health
 #[non_exhaustive]
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum RetryKind {
     /// Retry the associated request due to a known `ErrorKind`.
     Error(ErrorKind),
@@ -85,12 +85,13 @@ pub enum RetryMode {
     Standard,
 
     /// An experimental retry mode that includes the functionality of standard mode but includes
-    /// automatic client-side throttling. Because this mode is experimental, it might change
-    /// behavior in the future.
+    /// automatic client-side throttling. Once the service starts throttling requests, the rate at
+    /// which requests are sent is limited, and it recovers as requests succeed. Because this mode
+    /// is experimental, it might change behavior in the future.
     Adaptive,
 }
 
-const VALID_RETRY_MODES: &[RetryMode] = &[RetryMode::Standard];
+const VALID_RETRY_MODES: &[RetryMode] = &[RetryMode::Standard, RetryMode::Adaptive];
 
 /// Failure to parse a `RetryMode` from string.
 #[derive(Debug)]
@@ -116,21 +117,93 @@ impl FromStr for RetryMode {
         // eq_ignore_ascii_case is OK here because the only strings we need to check for are ASCII
         if string.eq_ignore_ascii_case("standard") {
             Ok(RetryMode::Standard)
-        // TODO we can uncomment this once this issue is addressed: https://github.com/awslabs/aws-sdk-rust/issues/247
-        // } else if string.eq_ignore_ascii_case("adaptive") {
-        //     Ok(RetryMode::Adaptive)
+        } else if string.eq_ignore_ascii_case("adaptive") {
+            Ok(RetryMode::Adaptive)
         } else {
             Err(RetryModeParseErr(string.to_owned()))
         }
     }
 }
 
+/// Specifies how randomness is added to the backoff between retries.
+///
+/// For attempt `i`, the exponential backoff is `min(initial_backoff * 2^i, max_backoff)`.
+/// `random(a, b)` is a random value between `a` and `b`.
+#[non_exhaustive]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum JitterMode {
+    /// Wait for `random(0, backoff)`. This is the default.
+    Full,
+
+    /// Wait for `backoff / 2 + random(0, backoff / 2)`. Retries are never sent immediately, at the
+    /// cost of more requests being retried at the same time.
+    Equal,
+
+    /// Wait for `min(random(initial_backoff, previous_wait * 3), max_backoff)`, where the first
+    /// `previous_wait` is `initial_backoff`. The wait is based on the previous wait instead of the
+    /// number of attempts.
+    Decorrelated,
+}
+
+const VALID_JITTER_MODES: &[JitterMode] = &[
+    JitterMode::Full,
+    JitterMode::Equal,
+    JitterMode::Decorrelated,
+];
+
+/// Failure to parse a `JitterMode` from string.
+#[derive(Debug)]
+pub struct JitterModeParseErr(String);
+
+impl Display for JitterModeParseErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "error parsing string '{}' as JitterMode, valid options are: {:#?}",
+            self.0, VALID_JITTER_MODES
+        )
+    }
+}
+
+impl std::error::Error for JitterModeParseErr {}
+
+impl FromStr for JitterMode {
+    type Err = JitterModeParseErr;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        // eq_ignore_ascii_case is OK here because the only strings we need to check for are ASCII
+        if string.eq_ignore_ascii_case("full") {
+            Ok(JitterMode::Full)
+        } else if string.eq_ignore_ascii_case("equal") {
+            Ok(JitterMode::Equal)
+        } else if string.eq_ignore_ascii_case("decorrelated") {
+            Ok(JitterMode::Decorrelated)
+        } else {
+            Err(JitterModeParseErr(string.to_owned()))
+        }
+    }
+}
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(20);
+const DEFAULT_INITIAL_RETRY_TOKENS: usize = 500;
+const DEFAULT_RETRY_COST: usize = 5;
+const DEFAULT_TIMEOUT_RETRY_COST: usize = 10;
+
 /// Builder for [`RetryConfig`].
 #[non_exhaustive]
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct RetryConfigBuilder {
     mode: Option<RetryMode>,
     max_attempts: Option<u32>,
+    initial_backoff: Option<Duration>,
+    max_backoff: Option<Duration>,
+    jitter_mode: Option<JitterMode>,
+    initial_retry_tokens: Option<usize>,
+    retry_cost: Option<usize>,
+    timeout_retry_cost: Option<usize>,
 }
 
 impl RetryConfigBuilder {
@@ -163,6 +236,78 @@ impl RetryConfigBuilder {
         self
     }
 
+    /// Sets the backoff before the first retry, before jitter is applied.
+    pub fn set_initial_backoff(&mut self, initial_backoff: Option<Duration>) -> &mut Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the backoff before the first retry, before jitter is applied.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.set_initial_backoff(Some(initial_backoff));
+        self
+    }
+
+    /// Sets the maximum backoff between retries.
+    pub fn set_max_backoff(&mut self, max_backoff: Option<Duration>) -> &mut Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the maximum backoff between retries.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.set_max_backoff(Some(max_backoff));
+        self
+    }
+
+    /// Sets the jitter mode.
+    pub fn set_jitter_mode(&mut self, jitter_mode: Option<JitterMode>) -> &mut Self {
+        self.jitter_mode = jitter_mode;
+        self
+    }
+
+    /// Sets the jitter mode.
+    pub fn jitter_mode(mut self, jitter_mode: JitterMode) -> Self {
+        self.set_jitter_mode(Some(jitter_mode));
+        self
+    }
+
+    /// Sets the size of the retry quota.
+    pub fn set_initial_retry_tokens(&mut self, initial_retry_tokens: Option<usize>) -> &mut Self {
+        self.initial_retry_tokens = initial_retry_tokens;
+        self
+    }
+
+    /// Sets the size of the retry quota.
+    pub fn initial_retry_tokens(mut self, initial_retry_tokens: usize) -> Self {
+        self.set_initial_retry_tokens(Some(initial_retry_tokens));
+        self
+    }
+
+    /// Sets how much of the retry quota a retry costs.
+    pub fn set_retry_cost(&mut self, retry_cost: Option<usize>) -> &mut Self {
+        self.retry_cost = retry_cost;
+        self
+    }
+
+    /// Sets how much of the retry quota a retry costs.
+    pub fn retry_cost(mut self, retry_cost: usize) -> Self {
+        self.set_retry_cost(Some(retry_cost));
+        self
+    }
+
+    /// Sets how much of the retry quota a retry of a transient error, such as a timeout, costs.
+    pub fn set_timeout_retry_cost(&mut self, timeout_retry_cost: Option<usize>) -> &mut Self {
+        self.timeout_retry_cost = timeout_retry_cost;
+        self
+    }
+
+    /// Sets how much of the retry quota a retry of a transient error, such as a timeout, costs.
+    pub fn timeout_retry_cost(mut self, timeout_retry_cost: usize) -> Self {
+        self.set_timeout_retry_cost(Some(timeout_retry_cost));
+        self
+    }
+
     /// Merge two builders together. Values from `other` will only be used as a fallback for values
     /// from `self` Useful for merging configs from different sources together when you want to
     /// handle "precedence" per value instead of at the config level
@@ -183,6 +328,12 @@ impl RetryConfigBuilder {
         Self {
             mode: self.mode.or(other.mode),
             max_attempts: self.max_attempts.or(other.max_attempts),
+            initial_backoff: self.initial_backoff.or(other.initial_backoff),
+            max_backoff: self.max_backoff.or(other.max_backoff),
+            jitter_mode: self.jitter_mode.or(other.jitter_mode),
+            initial_retry_tokens: self.initial_retry_tokens.or(other.initial_retry_tokens),
+            retry_cost: self.retry_cost.or(other.retry_cost),
+            timeout_retry_cost: self.timeout_retry_cost.or(other.timeout_retry_cost),
         }
     }
 
@@ -190,7 +341,17 @@ impl RetryConfigBuilder {
     pub fn build(self) -> RetryConfig {
         RetryConfig {
             mode: self.mode.unwrap_or(RetryMode::Standard),
-            max_attempts: self.max_attempts.unwrap_or(3),
+            max_attempts: self.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            initial_backoff: self.initial_backoff.unwrap_or(DEFAULT_INITIAL_BACKOFF),
+            max_backoff: self.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF),
+            jitter_mode: self.jitter_mode.unwrap_or(JitterMode::Full),
+            initial_retry_tokens: self
+                .initial_retry_tokens
+                .unwrap_or(DEFAULT_INITIAL_RETRY_TOKENS),
+            retry_cost: self.retry_cost.unwrap_or(DEFAULT_RETRY_COST),
+            timeout_retry_cost: self
+                .timeout_retry_cost
+                .unwrap_or(DEFAULT_TIMEOUT_RETRY_COST),
         }
     }
 }
@@ -201,6 +362,12 @@ impl RetryConfigBuilder {
 pub struct RetryConfig {
     mode: RetryMode,
     max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter_mode: JitterMode,
+    initial_retry_tokens: usize,
+    retry_cost: usize,
+    timeout_retry_cost: usize,
 }
 
 impl RetryConfig {
@@ -226,6 +393,47 @@ impl RetryConfig {
         self
     }
 
+    /// Changes the backoff before the first retry, before jitter is applied. The backoff doubles
+    /// with every attempt. Defaults to one second.
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Changes the maximum backoff between retries. Defaults to 20 seconds.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Changes how randomness is added to the backoff. Defaults to [`JitterMode::Full`].
+    pub fn with_jitter_mode(mut self, jitter_mode: JitterMode) -> Self {
+        self.jitter_mode = jitter_mode;
+        self
+    }
+
+    /// Changes the size of the retry quota. Defaults to 500.
+    ///
+    /// Every retry takes some of the quota, and every successful response returns it. Once the
+    /// quota is used up, requests are no longer retried.
+    pub fn with_initial_retry_tokens(mut self, initial_retry_tokens: usize) -> Self {
+        self.initial_retry_tokens = initial_retry_tokens;
+        self
+    }
+
+    /// Changes how much of the retry quota a retry costs. Defaults to 5.
+    pub fn with_retry_cost(mut self, retry_cost: usize) -> Self {
+        self.retry_cost = retry_cost;
+        self
+    }
+
+    /// Changes how much of the retry quota a retry of a transient error, such as a timeout, costs.
+    /// Defaults to 10.
+    pub fn with_timeout_retry_cost(mut self, timeout_retry_cost: usize) -> Self {
+        self.timeout_retry_cost = timeout_retry_cost;
+        self
+    }
+
     /// Returns the retry mode.
     pub fn mode(&self) -> RetryMode {
         self.mode
@@ -235,14 +443,41 @@ impl RetryConfig {
     pub fn max_attempts(&self) -> u32 {
         self.max_attempts
     }
+
+    /// Returns the backoff before the first retry.
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// Returns the maximum backoff between retries.
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// Returns the jitter mode.
+    pub fn jitter_mode(&self) -> JitterMode {
+        self.jitter_mode
+    }
+
+    /// Returns the size of the retry quota.
+    pub fn initial_retry_tokens(&self) -> usize {
+        self.initial_retry_tokens
+    }
+
+    /// Returns how much of the retry quota a retry costs.
+    pub fn retry_cost(&self) -> usize {
+        self.retry_cost
+    }
+
+    /// Returns how much of the retry quota a retry of a transient error costs.
+    pub fn timeout_retry_cost(&self) -> usize {
+        self.timeout_retry_cost
+    }
 }
 
 impl Default for RetryConfig {
     fn default() -> Self {
-        Self {
-            mode: RetryMode::Standard,
-            max_attempts: 3,
-        }
+        RetryConfigBuilder::new().build()
     }
 }
 
@@ -270,6 +505,9 @@ pub enum RetryConfigErr {
         set_by: Cow<'static, str>,
     },
     /// The adaptive retry mode hasn't been implemented yet.
+    ///
+    /// This error is no longer returned now that the adaptive retry mode is supported.
+    #[deprecated(note = "the adaptive retry mode is supported, this error is never returned")]
     AdaptiveModeIsNotSupported {
         /// Where the invalid retry mode value originated from.
         set_by: Cow<'static, str>,
@@ -277,6 +515,7 @@ pub enum RetryConfigErr {
 }
 
 impl Display for RetryConfigErr {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         use RetryConfigErr::*;
         match self {
@@ -313,8 +552,9 @@ impl std::error::Error for RetryConfigErr {
 
 #[cfg(test)]
 mod tests {
-    use crate::retry::{RetryConfigBuilder, RetryMode};
+    use crate::retry::{JitterMode, RetryConfig, RetryConfigBuilder, RetryMode};
     use std::str::FromStr;
+    use std::time::Duration;
 
     #[test]
     fn retry_config_builder_merge_with_favors_self_values_over_other_values() {
@@ -330,6 +570,44 @@ mod tests {
         assert_eq!(retry_config.mode, RetryMode::Adaptive);
     }
 
+    #[test]
+    fn retry_config_builder_merges_backoff_and_quota_settings() {
+        let self_builder = RetryConfigBuilder::new()
+            .max_backoff(Duration::from_secs(60))
+            .jitter_mode(JitterMode::Decorrelated)
+            .retry_cost(1);
+        let other_builder = RetryConfigBuilder::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_secs(5))
+            .initial_retry_tokens(50);
+        let retry_config = self_builder.take_unset_from(other_builder).build();
+
+        assert_eq!(
+            retry_config,
+            RetryConfig::new()
+                .with_initial_backoff(Duration::from_millis(100))
+                .with_max_backoff(Duration::from_secs(60))
+                .with_jitter_mode(JitterMode::Decorrelated)
+                .with_initial_retry_tokens(50)
+                .with_retry_cost(1)
+        );
+        assert_eq!(retry_config.timeout_retry_cost(), 10);
+    }
+
+    #[test]
+    fn jitter_mode_from_str() {
+        assert_eq!(JitterMode::from_str("full").ok(), Some(JitterMode::Full));
+        assert_eq!(
+            JitterMode::from_str(" Equal ").ok(),
+            Some(JitterMode::Equal)
+        );
+        assert_eq!(
+            JitterMode::from_str("DECORRELATED").ok(),
+            Some(JitterMode::Decorrelated)
+        );
+        assert_eq!(JitterMode::from_str("none").ok(), None);
+    }
+
     #[test]
     fn retry_mode_from_str_parses_valid_strings_regardless_of_casing() {
         assert_eq!(
@@ -344,18 +622,18 @@ mod tests {
             RetryMode::from_str("StAnDaRd").ok(),
             Some(RetryMode::Standard)
         );
-        // assert_eq!(
-        //     RetryMode::from_str("adaptive").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
-        // assert_eq!(
-        //     RetryMode::from_str("ADAPTIVE").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
-        // assert_eq!(
-        //     RetryMode::from_str("aDaPtIvE").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
+        assert_eq!(
+            RetryMode::from_str("adaptive").ok(),
+            Some(RetryMode::Adaptive)
+        );
+        assert_eq!(
+            RetryMode::from_str("ADAPTIVE").ok(),
+            Some(RetryMode::Adaptive)
+        );
+        assert_eq!(
+            RetryMode::from_str("aDaPtIvE").ok(),
+            Some(RetryMode::Adaptive)
+        );
     }
 
     #[test]
@@ -372,18 +650,18 @@ mod tests {
             RetryMode::from_str("  StAnDaRd   ").ok(),
             Some(RetryMode::Standard)
         );
-        // assert_eq!(
-        //     RetryMode::from_str("  adaptive  ").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
-        // assert_eq!(
-        //     RetryMode::from_str("   ADAPTIVE ").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
-        // assert_eq!(
-        //     RetryMode::from_str("  aDaPtIvE    ").ok(),
-        //     Some(RetryMode::Adaptive)
-        // );
+        assert_eq!(
+            RetryMode::from_str("  adaptive  ").ok(),
+            Some(RetryMode::Adaptive)
+        );
+        assert_eq!(
+            RetryMode::from_str("   ADAPTIVE ").ok(),
+            Some(RetryMode::Adaptive)
+        );
+        assert_eq!(
+            RetryMode::from_str("  aDaPtIvE    ").ok(),
+            Some(RetryMode::Adaptive)
+        );
     }
 
     #[test]